@@ -2,10 +2,16 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use anyhow::{anyhow, ensure, format_err, Context as AnyhowContext, Result};
-use aptos_api_types::{AsConverter, BlockInfo, Error, LedgerInfo, TransactionOnChainData, U64};
+use aptos_api_types::{
+    AsConverter, BlockInfo, Error, LedgerInfo, TransactionId, TransactionOnChainData, U64,
+};
 use aptos_config::config::{NodeConfig, RoleType};
 use aptos_crypto::HashValue;
-use aptos_mempool::{MempoolClientRequest, MempoolClientSender, SubmissionStatus};
+use aptos_gas::AptosGasParameters;
+use aptos_infallible::RwLock;
+use aptos_mempool::{
+    MempoolClientRequest, MempoolClientSender, MempoolUtilization, SubmissionStatus,
+};
 use aptos_state_view::StateView;
 use aptos_types::{
     access_path::Path,
@@ -17,10 +23,13 @@ use aptos_types::{
     event::EventKey,
     ledger_info::LedgerInfoWithSignatures,
     state_store::{state_key::StateKey, state_key_prefix::StateKeyPrefix, state_value::StateValue},
-    transaction::{SignedTransaction, TransactionWithProof, Version},
+    transaction::{SignedTransaction, Transaction, TransactionWithProof, Version},
     write_set::WriteOp,
 };
-use aptos_vm::data_cache::{IntoMoveResolver, RemoteStorageOwned};
+use aptos_vm::{
+    data_cache::{IntoMoveResolver, RemoteStorageOwned},
+    AptosVM,
+};
 use futures::{channel::oneshot, SinkExt};
 use move_deps::move_core_types::ident_str;
 use serde::{Deserialize, Serialize};
@@ -31,7 +40,9 @@ use storage_interface::{
 };
 use warp::{filters::BoxedFilter, Filter, Reply};
 
-use crate::poem_backend::{AptosErrorCode, InternalError};
+use crate::poem_backend::{
+    build_not_found, build_version_pruned, AptosErrorCode, InternalError, NotFoundError,
+};
 
 // Context holds application scope context
 #[derive(Clone)]
@@ -40,6 +51,16 @@ pub struct Context {
     pub db: Arc<dyn DbReader>,
     mp_sender: MempoolClientSender,
     node_config: NodeConfig,
+    gas_unit_prices_cache: Arc<RwLock<Option<RecentGasUnitPrices>>>,
+}
+
+/// The sorted gas unit prices of the user transactions among the last `count`
+/// transactions up to and including `ledger_version`.
+#[derive(Clone)]
+struct RecentGasUnitPrices {
+    count: u64,
+    ledger_version: u64,
+    gas_unit_prices: Arc<Vec<u64>>,
 }
 
 impl Context {
@@ -54,6 +75,7 @@ impl Context {
             db,
             mp_sender,
             node_config,
+            gas_unit_prices_cache: Arc::new(RwLock::new(None)),
         }
     }
 
@@ -87,6 +109,10 @@ impl Context {
         self.node_config.api.content_length_limit()
     }
 
+    pub fn max_view_function_gas(&self) -> u64 {
+        self.node_config.api.max_view_function_gas
+    }
+
     pub fn failpoints_enabled(&self) -> bool {
         self.node_config.api.failpoints_enabled
     }
@@ -106,36 +132,65 @@ impl Context {
     }
 
     pub fn get_latest_ledger_info(&self) -> Result<LedgerInfo, Error> {
-        if let Some(oldest_version) = self.db.get_first_txn_version()? {
-            Ok(LedgerInfo::new(
-                &self.chain_id(),
-                &self.get_latest_ledger_info_with_signatures()?,
-                oldest_version,
-            ))
-        } else {
-            return Err(anyhow! {"Failed to retrieve oldest version"}.into());
-        }
+        Ok(LedgerInfo::new(
+            &self.chain_id(),
+            &self.get_latest_ledger_info_with_signatures()?,
+            self.db.get_min_readable_ledger_version()?,
+        ))
     }
 
     // TODO: Add error codes to these errors.
     pub fn get_latest_ledger_info_poem<E: InternalError>(&self) -> Result<LedgerInfo, E> {
-        if let Some(oldest_version) = self
+        let oldest_version = self
             .db
-            .get_first_txn_version()
-            .map_err(|e| E::internal(e).error_code(AptosErrorCode::ReadFromStorageError))?
-        {
-            Ok(LedgerInfo::new(
-                &self.chain_id(),
-                &self
-                    .get_latest_ledger_info_with_signatures()
-                    .map_err(E::internal)?,
-                oldest_version,
-            ))
-        } else {
-            Err(E::internal(anyhow!(
-                "Failed to retrieve latest ledger info"
-            )))
+            .get_min_readable_ledger_version()
+            .context("Failed to retrieve oldest ledger version")
+            .map_err(|e| E::internal(e).error_code(AptosErrorCode::ReadFromStorageError))?;
+        Ok(LedgerInfo::new(
+            &self.chain_id(),
+            &self
+                .get_latest_ledger_info_with_signatures()
+                .map_err(E::internal)?,
+            oldest_version,
+        ))
+    }
+
+    pub fn get_oldest_state_version(&self) -> Result<Version> {
+        self.db.get_min_readable_state_version()
+    }
+
+    pub fn get_oldest_state_version_poem<E: InternalError>(&self) -> Result<Version, E> {
+        self.get_oldest_state_version()
+            .context("Failed to retrieve oldest state version")
+            .map_err(|e| E::internal(e).error_code(AptosErrorCode::ReadFromStorageError))
+    }
+
+    /// Checks that the state at the given version can be read, i.e. that the
+    /// ledger has reached the version and that it has not been pruned yet.
+    pub fn check_state_version_poem<E: NotFoundError + InternalError>(
+        &self,
+        version: u64,
+        latest_ledger_info: &LedgerInfo,
+    ) -> Result<(), E> {
+        if version > latest_ledger_info.version() {
+            return Err(build_not_found(
+                "ledger",
+                TransactionId::Version(U64::from(version)),
+                latest_ledger_info.version(),
+            ));
         }
+
+        let oldest_state_version = self.get_oldest_state_version_poem()?;
+        if version < oldest_state_version {
+            return Err(build_version_pruned(
+                "State",
+                version,
+                oldest_state_version,
+                latest_ledger_info.version(),
+            ));
+        }
+
+        Ok(())
     }
 
     pub fn get_latest_ledger_info_with_signatures(&self) -> Result<LedgerInfoWithSignatures> {
@@ -284,6 +339,55 @@ impl Context {
         }
     }
 
+    /// Returns the gas unit prices of the user transactions among the last
+    /// `count` transactions up to and including `ledger_version`, sorted in
+    /// increasing order. They are cached until the ledger moves on, so that
+    /// estimating gas prices doesn't scan storage on every request.
+    pub fn get_recent_gas_unit_prices(
+        &self,
+        count: u64,
+        ledger_version: u64,
+    ) -> Result<Arc<Vec<u64>>> {
+        if let Some(cached) = &*self.gas_unit_prices_cache.read() {
+            if cached.count == count && cached.ledger_version == ledger_version {
+                return Ok(cached.gas_unit_prices.clone());
+            }
+        }
+
+        let oldest_version = self.db.get_min_readable_ledger_version()?;
+        let start_version = ledger_version
+            .saturating_sub(count.saturating_sub(1))
+            .max(oldest_version);
+        let txns = self.db.get_transactions(
+            start_version,
+            ledger_version - start_version + 1,
+            ledger_version,
+            false,
+        )?;
+        let mut gas_unit_prices: Vec<_> = txns
+            .transactions
+            .iter()
+            .filter_map(|txn| match txn {
+                Transaction::UserTransaction(txn) => Some(txn.gas_unit_price()),
+                _ => None,
+            })
+            .collect();
+        gas_unit_prices.sort_unstable();
+        let gas_unit_prices = Arc::new(gas_unit_prices);
+
+        *self.gas_unit_prices_cache.write() = Some(RecentGasUnitPrices {
+            count,
+            ledger_version,
+            gas_unit_prices: gas_unit_prices.clone(),
+        });
+        Ok(gas_unit_prices)
+    }
+
+    pub fn get_gas_parameters(&self) -> Result<AptosGasParameters> {
+        let state_view = self.db.latest_state_checkpoint_view()?;
+        AptosVM::get_gas_parameters_from_state(&state_view)
+    }
+
     pub fn get_transactions(
         &self,
         start_version: u64,
@@ -347,6 +451,38 @@ impl Context {
             .collect::<Result<Vec<_>>>()
     }
 
+    pub fn get_affected_account_transactions(
+        &self,
+        address: AccountAddress,
+        start_version: u64,
+        limit: u16,
+        ledger_version: u64,
+    ) -> Result<Vec<TransactionOnChainData>> {
+        self.db
+            .get_affected_account_transactions(
+                address,
+                start_version,
+                limit as u64,
+                true,
+                ledger_version,
+            )?
+            .into_iter()
+            .map(|t| self.convert_into_transaction_on_chain_data(t))
+            .collect::<Result<Vec<_>>>()
+    }
+
+    pub fn get_account_transaction(
+        &self,
+        address: AccountAddress,
+        seq_number: u64,
+        ledger_version: u64,
+    ) -> Result<Option<TransactionOnChainData>> {
+        self.db
+            .get_account_transaction(address, seq_number, true, ledger_version)?
+            .map(|t| self.convert_into_transaction_on_chain_data(t))
+            .transpose()
+    }
+
     pub fn get_transaction_by_hash(
         &self,
         hash: HashValue,
@@ -373,6 +509,18 @@ impl Context {
         callback.await.map_err(anyhow::Error::from)
     }
 
+    pub async fn get_mempool_utilization(&self) -> Result<MempoolUtilization> {
+        let (req_sender, callback) = oneshot::channel();
+
+        self.mp_sender
+            .clone()
+            .send(MempoolClientRequest::GetUtilization(req_sender))
+            .await
+            .map_err(anyhow::Error::from)?;
+
+        callback.await.map_err(anyhow::Error::from)
+    }
+
     pub fn get_transaction_by_version(
         &self,
         version: u64,
@@ -100,8 +100,9 @@ pub fn index(context: Context) -> BoxedFilter<(impl Reply,)> {
 pub async fn handle_index(context: Context) -> Result<impl Reply, Rejection> {
     fail_point("endpoint_index")?;
     let ledger_info = context.get_latest_ledger_info()?;
+    let oldest_state_version = context.get_oldest_state_version().map_err(Error::from)?;
     let node_role = context.node_role();
-    let index_response = IndexResponse::new(ledger_info.clone(), node_role);
+    let index_response = IndexResponse::new(ledger_info.clone(), oldest_state_version, node_role);
     Ok(Response::new(ledger_info, &index_response)?)
 }
 
@@ -1,7 +1,9 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use aptos_metrics_core::{register_histogram_vec, HistogramVec};
+use aptos_metrics_core::{
+    register_histogram_vec, register_int_counter_vec, HistogramVec, IntCounterVec,
+};
 
 use once_cell::sync::Lazy;
 use warp::log::{custom, Info, Log};
@@ -24,6 +26,24 @@ pub static RESPONSE_STATUS: Lazy<HistogramVec> = Lazy::new(|| {
     .unwrap()
 });
 
+pub static RATE_LIMIT_METRICS: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "aptos_api_rate_limit",
+        "API rate limiting metrics, grouped by what clients are keyed by",
+        &["key_type", "metric"]
+    )
+    .unwrap()
+});
+
+pub static RATE_LIMITED_REQUESTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_api_rate_limited_requests",
+        "Number of API requests rejected by rate limiting, grouped by what clients are keyed by",
+        &["key_type"]
+    )
+    .unwrap()
+});
+
 // Record metrics by method, operation_id and status.
 // The operation_id is the id for the request handler.
 // Should use same `operationId` defined in `openapi.yaml` whenever possible.
@@ -10,7 +10,7 @@ use super::{AptosErrorCode, BasicErrorWith404, BasicResultWith404};
 use crate::context::Context;
 use crate::failpoint::fail_point_poem;
 use anyhow::Context as AnyhowContext;
-use aptos_api_types::{AccountData, Address, AsConverter, MoveStructTag, TransactionId, U64};
+use aptos_api_types::{AccountData, Address, AsConverter, MoveStructTag, U64};
 use aptos_api_types::{LedgerInfo, MoveModuleBytecode, MoveResource};
 use aptos_types::access_path::AccessPath;
 use aptos_types::account_config::AccountResource;
@@ -124,13 +124,7 @@ impl Account {
             .map(|v| v.0)
             .unwrap_or_else(|| latest_ledger_info.version());
 
-        if ledger_version > latest_ledger_info.version() {
-            return Err(build_not_found(
-                "ledger",
-                TransactionId::Version(U64::from(ledger_version)),
-                latest_ledger_info.version(),
-            ));
-        }
+        context.check_state_version_poem(ledger_version, &latest_ledger_info)?;
 
         Ok(Self {
             context,
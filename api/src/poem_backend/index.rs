@@ -27,9 +27,11 @@ impl IndexApi {
     )]
     async fn get_ledger_info(&self, accept_type: AcceptType) -> BasicResult<IndexResponse> {
         let ledger_info = self.context.get_latest_ledger_info_poem()?;
+        let oldest_state_version = self.context.get_oldest_state_version_poem()?;
 
         let node_role = self.context.node_role();
-        let index_response = IndexResponse::new(ledger_info.clone(), node_role);
+        let index_response =
+            IndexResponse::new(ledger_info.clone(), oldest_state_version, node_role);
 
         BasicResponse::try_from_rust_value((
             index_response,
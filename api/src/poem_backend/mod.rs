@@ -13,10 +13,12 @@ mod events;
 mod index;
 mod log;
 mod page;
+mod rate_limit;
 mod response;
 mod runtime;
 mod state;
 mod transactions;
+mod view_function;
 
 #[derive(Tags)]
 pub enum ApiTags {
@@ -34,6 +36,9 @@ pub enum ApiTags {
 
     /// Access to transactions
     Transactions,
+
+    /// Execution of view functions
+    View,
 }
 
 pub use accept_type::AcceptType;
@@ -46,3 +51,4 @@ pub use response::*;
 pub use runtime::{attach_poem_to_runtime, get_api_service};
 pub use state::StateApi;
 pub use transactions::TransactionsApi;
+pub use view_function::ViewFunctionApi;
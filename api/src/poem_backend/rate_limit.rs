@@ -0,0 +1,434 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use super::{AptosError, AptosErrorCode};
+use crate::metrics::{RATE_LIMITED_REQUESTS, RATE_LIMIT_METRICS};
+use aptos_config::config::ApiRateLimitConfig;
+use aptos_infallible::Mutex;
+use aptos_rate_limiter::rate_limit::{Bucket, SharedBucket, TokenBucketRateLimiter};
+use hyper::Method;
+use poem::{
+    http::{
+        header::{HeaderValue, RETRY_AFTER},
+        StatusCode,
+    },
+    Endpoint, IntoResponse, Middleware, Request, Result,
+};
+use poem_openapi::payload::Json;
+
+/// The header clients send their API key in.
+pub const X_APTOS_API_KEY: &str = "X-Aptos-Api-Key";
+
+/// The header reverse proxies append the address of their client to. Only
+/// the addresses appended by trusted proxies are genuine, clients can set the
+/// others to anything.
+const X_FORWARDED_FOR: &str = "X-Forwarded-For";
+
+/// How often the buckets of IP addresses which are full again, i.e. have not
+/// been used for a while, are removed.
+const IP_BUCKET_GC_INTERVAL: Duration = Duration::from_secs(60);
+
+const IP_KEY_TYPE: &str = "ip";
+const API_KEY_KEY_TYPE: &str = "api_key";
+
+/// This middleware rate limits requests by API key, or by IP address for
+/// clients without a known API key. Each request costs tokens depending on
+/// how expensive it is to serve, and is rejected with a 429 once the client is
+/// out of tokens.
+pub struct RateLimit {
+    state: Arc<RateLimitState>,
+}
+
+struct RateLimitState {
+    config: ApiRateLimitConfig,
+    ip_rate_limiter: TokenBucketRateLimiter<IpAddr>,
+    api_key_buckets: HashMap<String, SharedBucket>,
+    last_ip_bucket_gc: Mutex<Instant>,
+}
+
+impl RateLimit {
+    /// Without a config, no requests are rate limited.
+    pub fn new(config: Option<ApiRateLimitConfig>) -> anyhow::Result<Self> {
+        let config = config.unwrap_or(ApiRateLimitConfig {
+            enabled: false,
+            ..Default::default()
+        });
+        config.validate()?;
+        let ip_rate_limiter = if config.enabled {
+            TokenBucketRateLimiter::new(
+                IP_KEY_TYPE,
+                String::new(),
+                100,
+                config.ip_bucket_size,
+                config.ip_bucket_rate,
+                Some(RATE_LIMIT_METRICS.clone()),
+            )
+        } else {
+            TokenBucketRateLimiter::open(IP_KEY_TYPE)
+        };
+        let api_key_buckets = config
+            .api_keys
+            .iter()
+            .map(|(name, quota)| {
+                let bucket = Bucket::new(
+                    API_KEY_KEY_TYPE.to_string(),
+                    String::new(),
+                    name.clone(),
+                    quota.bucket_size,
+                    quota.bucket_size,
+                    quota.bucket_rate,
+                    Some(RATE_LIMIT_METRICS.clone()),
+                );
+                (quota.key.clone(), Arc::new(Mutex::new(bucket)))
+            })
+            .collect();
+
+        Ok(Self {
+            state: Arc::new(RateLimitState {
+                config,
+                ip_rate_limiter,
+                api_key_buckets,
+                last_ip_bucket_gc: Mutex::new(Instant::now()),
+            }),
+        })
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for RateLimit {
+    type Output = RateLimitEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        RateLimitEndpoint {
+            inner: ep,
+            state: self.state.clone(),
+        }
+    }
+}
+
+/// Endpoint for RateLimit middleware.
+pub struct RateLimitEndpoint<E> {
+    inner: E,
+    state: Arc<RateLimitState>,
+}
+
+#[async_trait::async_trait]
+impl<E: Endpoint> Endpoint for RateLimitEndpoint<E> {
+    type Output = E::Output;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        if !self.state.config.enabled {
+            return self.inner.call(req).await;
+        }
+
+        self.state.maybe_garbage_collect_ip_buckets();
+
+        let (key_type, bucket) = match self.state.api_key_bucket(&req) {
+            Some(bucket) => (API_KEY_KEY_TYPE, bucket),
+            None => match self.state.client_ip(&req) {
+                Some(ip) => (IP_KEY_TYPE, self.state.ip_rate_limiter.bucket(ip)),
+                // There is no one to attribute the request to.
+                None => return self.inner.call(req).await,
+            },
+        };
+
+        let cost = self.state.request_cost(&req);
+        let result = bucket.lock().acquire_all_tokens(cost);
+        match result {
+            Ok(()) => self.inner.call(req).await,
+            Err(retry_at) => {
+                RATE_LIMITED_REQUESTS.with_label_values(&[key_type]).inc();
+                Err(too_many_requests(cost, retry_at))
+            }
+        }
+    }
+}
+
+impl RateLimitState {
+    /// Removes the buckets of IP addresses that have not been used for a
+    /// while, so that they don't pile up with requests from ever new addresses.
+    fn maybe_garbage_collect_ip_buckets(&self) {
+        {
+            let mut last_ip_bucket_gc = self.last_ip_bucket_gc.lock();
+            if last_ip_bucket_gc.elapsed() < IP_BUCKET_GC_INTERVAL {
+                return;
+            }
+            *last_ip_bucket_gc = Instant::now();
+        }
+        self.ip_rate_limiter.garbage_collect_full_buckets();
+    }
+
+    /// Returns the IP address of the client. That's the peer, unless the peer
+    /// is a trusted proxy, in which case it's the last address in
+    /// X-Forwarded-For which wasn't appended by a trusted proxy.
+    fn client_ip(&self, req: &Request) -> Option<IpAddr> {
+        let peer_ip = req.remote_addr().as_socket_addr()?.ip();
+        if !self.is_trusted_proxy(peer_ip) {
+            return Some(peer_ip);
+        }
+        let mut client_ip = peer_ip;
+        let forwarded_for = req
+            .headers()
+            .get_all(X_FORWARDED_FOR)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+            .collect::<Vec<_>>();
+        for forwarded_ip in forwarded_for.into_iter().rev() {
+            match forwarded_ip.trim().parse() {
+                Ok(ip) => client_ip = ip,
+                // Proxies append valid addresses, so the client of the last
+                // trusted one is unknown.
+                Err(_) => break,
+            }
+            if !self.is_trusted_proxy(client_ip) {
+                break;
+            }
+        }
+        Some(client_ip)
+    }
+
+    fn is_trusted_proxy(&self, ip: IpAddr) -> bool {
+        self.config.trusted_proxies.contains(&ip)
+    }
+
+    fn api_key_bucket(&self, req: &Request) -> Option<SharedBucket> {
+        req.headers()
+            .get(X_APTOS_API_KEY)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|key| self.api_key_buckets.get(key))
+            .cloned()
+    }
+
+    /// Returns how many tokens the request costs. Simulations and view
+    /// functions cost more than plain reads, as do large pages.
+    fn request_cost(&self, req: &Request) -> usize {
+        let path = req.uri().path().trim_end_matches('/');
+        let mut cost = if req.method() == Method::POST && path.ends_with("/transactions/simulate") {
+            self.config.simulate_transaction_cost
+        } else if req.method() == Method::POST && path.ends_with("/view") {
+            self.config.view_function_cost
+        } else {
+            1
+        };
+
+        let limit = req.uri().query().and_then(|query| {
+            url::form_urlencoded::parse(query.as_bytes())
+                .find(|(name, _)| name == "limit")
+                .and_then(|(_, value)| value.parse::<usize>().ok())
+        });
+        if let Some(limit) = limit {
+            let extra_cost = limit
+                .checked_div(self.config.page_items_per_token)
+                .unwrap_or(0);
+            cost = cost.saturating_add(extra_cost);
+        }
+        cost
+    }
+}
+
+fn too_many_requests(cost: usize, retry_at: Option<Instant>) -> poem::Error {
+    let message = match retry_at {
+        Some(_) => {
+            "Rate limit exceeded, retry after the time given in the Retry-After header".to_string()
+        }
+        None => format!(
+            "Rate limit exceeded, the request costs {} tokens which is more than the quota allows",
+            cost
+        ),
+    };
+    let mut response = Json(AptosError::new(message).error_code(AptosErrorCode::RateLimited))
+        .with_status(StatusCode::TOO_MANY_REQUESTS)
+        .into_response();
+    if let Some(retry_at) = retry_at {
+        // Round up, so that retrying at the given time doesn't come too early.
+        let wait = retry_at.saturating_duration_since(Instant::now()) + Duration::from_millis(999);
+        response
+            .headers_mut()
+            .insert(RETRY_AFTER, HeaderValue::from(wait.as_secs().max(1)));
+    }
+    poem::Error::from_response(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_config::config::ApiKeyQuota;
+    use poem::{
+        get, handler,
+        listener::{Acceptor, Listener, TcpListener},
+        EndpointExt, Route, Server,
+    };
+    use reqwest::{RequestBuilder, StatusCode};
+    use std::net::{Ipv4Addr, SocketAddr};
+
+    #[handler]
+    fn ok() -> &'static str {
+        "ok"
+    }
+
+    /// Serves an endpoint behind the middleware, returning its address.
+    async fn serve(config: ApiRateLimitConfig) -> SocketAddr {
+        let route = Route::new()
+            .at("/*path", get(ok).post(ok))
+            .with(RateLimit::new(Some(config)).unwrap());
+        let acceptor = TcpListener::bind("127.0.0.1:0")
+            .into_acceptor()
+            .await
+            .unwrap();
+        let address = *acceptor.local_addr()[0].as_socket_addr().unwrap();
+        tokio::spawn(Server::new_with_acceptor(acceptor).run(route));
+        address
+    }
+
+    fn config(ip_bucket_size: usize) -> ApiRateLimitConfig {
+        ApiRateLimitConfig {
+            ip_bucket_size,
+            ip_bucket_rate: 1,
+            trusted_proxies: vec![],
+            ..Default::default()
+        }
+    }
+
+    async fn status(request: RequestBuilder) -> StatusCode {
+        request.send().await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn test_ip_rate_limit() {
+        let address = serve(config(3)).await;
+        let client = reqwest::Client::new();
+        let url = format!("http://{}/v1/accounts/0x1", address);
+
+        for _ in 0..3 {
+            assert_eq!(status(client.get(&url)).await, StatusCode::OK);
+        }
+        let response = client.get(&url).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[RETRY_AFTER], "1");
+        let error: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(error["error_code"], "rate_limited");
+
+        // The bucket refills at a token per second.
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        assert_eq!(status(client.get(&url)).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_api_key_rate_limit() {
+        let mut config = config(3);
+        config.api_keys.insert(
+            "test".to_string(),
+            ApiKeyQuota {
+                key: "secret".to_string(),
+                bucket_size: 5,
+                bucket_rate: 1,
+            },
+        );
+        let address = serve(config).await;
+        let client = reqwest::Client::new();
+        let url = format!("http://{}/v1/accounts/0x1", address);
+        let with_key = || client.get(&url).header(X_APTOS_API_KEY, "secret");
+
+        // The API key has its own quota, separate from that of the IP address.
+        for _ in 0..5 {
+            assert_eq!(status(with_key()).await, StatusCode::OK);
+        }
+        assert_eq!(status(with_key()).await, StatusCode::TOO_MANY_REQUESTS);
+        for _ in 0..3 {
+            assert_eq!(status(client.get(&url)).await, StatusCode::OK);
+        }
+        assert_eq!(
+            status(client.get(&url)).await,
+            StatusCode::TOO_MANY_REQUESTS
+        );
+
+        // Unknown keys get the quota of the IP address.
+        let response = client
+            .get(&url)
+            .header(X_APTOS_API_KEY, "unknown")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[tokio::test]
+    async fn test_request_cost() {
+        let address = serve(config(10)).await;
+        let client = reqwest::Client::new();
+        let base_url = format!("http://{}/v1", address);
+
+        // A simulation takes the whole bucket.
+        let simulate = format!("{}/transactions/simulate", base_url);
+        assert_eq!(status(client.post(&simulate)).await, StatusCode::OK);
+        assert_eq!(
+            status(client.get(format!("{}/transactions", base_url))).await,
+            StatusCode::TOO_MANY_REQUESTS
+        );
+
+        // A page this large costs more than the bucket holds, so there is no
+        // time to retry at.
+        let response = client
+            .get(format!("{}/transactions?limit=1000", base_url))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(response.headers().get(RETRY_AFTER).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_disabled() {
+        let address = serve(ApiRateLimitConfig {
+            enabled: false,
+            ..config(1)
+        })
+        .await;
+        let client = reqwest::Client::new();
+        let url = format!("http://{}/v1/transactions/simulate", address);
+        for _ in 0..10 {
+            assert_eq!(status(client.post(&url)).await, StatusCode::OK);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_forwarded_for() {
+        let untrusted = serve(config(1)).await;
+        let trusted = serve(ApiRateLimitConfig {
+            trusted_proxies: vec![IpAddr::V4(Ipv4Addr::LOCALHOST), "10.0.0.1".parse().unwrap()],
+            ..config(1)
+        })
+        .await;
+        let client = reqwest::Client::new();
+        let request = |address: SocketAddr, forwarded_for: &str| {
+            client
+                .get(format!("http://{}/v1/accounts/0x1", address))
+                .header(X_FORWARDED_FOR, forwarded_for)
+        };
+
+        // Only trusted proxies are believed.
+        assert_eq!(status(request(untrusted, "1.1.1.1")).await, StatusCode::OK);
+        assert_eq!(
+            status(request(untrusted, "2.2.2.2")).await,
+            StatusCode::TOO_MANY_REQUESTS
+        );
+
+        // The client is the last address not appended by a trusted proxy.
+        assert_eq!(status(request(trusted, "1.1.1.1")).await, StatusCode::OK);
+        assert_eq!(
+            status(request(trusted, "2.2.2.2, 1.1.1.1, 10.0.0.1")).await,
+            StatusCode::TOO_MANY_REQUESTS
+        );
+        assert_eq!(
+            status(request(trusted, "1.1.1.1, 2.2.2.2")).await,
+            StatusCode::OK
+        );
+    }
+}
@@ -44,6 +44,10 @@ pub struct AptosError {
     message: String,
     error_code: Option<AptosErrorCode>,
     aptos_ledger_version: Option<U64>,
+    /// The oldest version the node still has data for. This is only set when
+    /// the requested version has been pruned.
+    #[oai(skip_serializing_if_is_none)]
+    oldest_available_version: Option<U64>,
 }
 
 impl AptosError {
@@ -52,6 +56,7 @@ impl AptosError {
             message,
             error_code: None,
             aptos_ledger_version: None,
+            oldest_available_version: None,
         }
     }
     pub fn error_code(mut self, error_code: AptosErrorCode) -> Self {
@@ -63,6 +68,11 @@ impl AptosError {
         self.aptos_ledger_version = Some(ledger_version.into());
         self
     }
+
+    pub fn oldest_available_version(mut self, oldest_available_version: u64) -> Self {
+        self.oldest_available_version = Some(oldest_available_version.into());
+        self
+    }
 }
 
 impl From<anyhow::Error> for AptosError {
@@ -92,6 +102,23 @@ pub enum AptosErrorCode {
 
     /// The limit param given for paging is invalid.
     InvalidLimitParam = 5,
+
+    /// The requested version has been pruned from this node. Data at this
+    /// version may still be available from a node with a larger prune window,
+    /// such as an archive node.
+    VersionPruned = 6,
+
+    /// The client has run out of its request quota. Retry after the time
+    /// given in the Retry-After header.
+    RateLimited = 7,
+
+    /// The node doesn't keep the history of state keys needed to serve this
+    /// request. It may be available from a node with the history enabled.
+    StateKeyHistoryNotEnabled = 8,
+
+    /// The node only indexes transactions by their sender, so it can't list
+    /// every transaction that affected an account.
+    AffectedAccountIndexNotEnabled = 9,
 }
 
 #[derive(ResponseContent)]
@@ -128,6 +155,14 @@ pub trait AptosErrorResponse {
         self.inner_mut().aptos_ledger_version = Some(aptos_ledger_version.into());
         self
     }
+
+    fn oldest_available_version(mut self, oldest_available_version: u64) -> Self
+    where
+        Self: Sized,
+    {
+        self.inner_mut().oldest_available_version = Some(oldest_available_version.into());
+        self
+    }
 }
 
 /// This macro defines traits for all of the given status codes. In eahc trait
@@ -382,3 +417,20 @@ pub fn build_not_found<S: Display, E: NotFoundError>(
     E::not_found_str(&format!("{} not found by {}", resource, identifier))
         .aptos_ledger_version(ledger_version)
 }
+
+// A 404 for data at a version that has been pruned, telling the client which
+// version is the oldest one it can still ask for.
+pub fn build_version_pruned<E: NotFoundError>(
+    resource: &str,
+    requested_version: u64,
+    oldest_available_version: u64,
+    ledger_version: u64,
+) -> E {
+    E::not_found_str(&format!(
+        "{} at version {} has been pruned, the oldest available version is {}",
+        resource, requested_version, oldest_available_version
+    ))
+    .error_code(AptosErrorCode::VersionPruned)
+    .oldest_available_version(oldest_available_version)
+    .aptos_ledger_version(ledger_version)
+}
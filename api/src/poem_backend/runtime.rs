@@ -8,7 +8,10 @@ use super::{middleware_log, AccountsApi, BasicApi, EventsApi, IndexApi};
 use crate::{
     context::Context,
     poem_backend::{
-        check_size::PostSizeLimit, error_converter::convert_error, StateApi, TransactionsApi,
+        check_size::PostSizeLimit,
+        error_converter::convert_error,
+        rate_limit::{RateLimit, X_APTOS_API_KEY},
+        StateApi, TransactionsApi, ViewFunctionApi,
     },
 };
 use anyhow::Context as AnyhowContext;
@@ -41,6 +44,7 @@ pub fn get_api_service(
         IndexApi,
         StateApi,
         TransactionsApi,
+        ViewFunctionApi,
     ),
     (),
 > {
@@ -61,7 +65,10 @@ pub fn get_api_service(
         StateApi {
             context: context.clone(),
         },
-        TransactionsApi { context },
+        TransactionsApi {
+            context: context.clone(),
+        },
+        ViewFunctionApi { context },
     );
 
     let version = VERSION.to_string();
@@ -87,6 +94,8 @@ pub fn attach_poem_to_runtime(
     let context = Arc::new(context);
 
     let size_limit = context.content_length_limit();
+    let rate_limit =
+        RateLimit::new(config.api.rate_limit.clone()).context("Invalid API rate limit config")?;
 
     let api_service = get_api_service(context);
 
@@ -133,13 +142,16 @@ pub fn attach_poem_to_runtime(
     runtime_handle.spawn(async move {
         let cors = Cors::new()
             .allow_methods(vec![Method::GET, Method::POST])
-            .allow_headers(vec![header::CONTENT_TYPE, header::ACCEPT]);
+            .allow_headers(vec![header::CONTENT_TYPE, header::ACCEPT])
+            .allow_header(X_APTOS_API_KEY);
         let route = Route::new()
             .nest("/", api_service)
             .at("/spec.json", spec_json)
             .at("/spec.yaml", spec_yaml)
             .with(cors)
             .with(PostSizeLimit::new(size_limit))
+            // Rate limit before doing anything else with the request.
+            .with(rate_limit)
             // NOTE: Make sure to keep this after all the `with` middleware.
             .catch_all_error(convert_error)
             .around(middleware_log);
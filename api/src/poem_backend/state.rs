@@ -2,19 +2,20 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::accept_type::AcceptType;
+use super::page::Page;
 use super::{
-    build_not_found, ApiTags, BadRequestError, BasicResponse, BasicResponseStatus, InternalError,
-    NotFoundError,
+    build_not_found, ApiTags, AptosErrorCode, AptosErrorResponse, BadRequestError, BasicResponse,
+    BasicResponseStatus, InternalError, NotFoundError,
 };
 use super::{BasicErrorWith404, BasicResultWith404};
 use crate::context::Context;
 use crate::failpoint::fail_point_poem;
 use anyhow::Context as AnyhowContext;
 use aptos_api_types::{
-    Address, AsConverter, IdentifierWrapper, MoveModuleBytecode, MoveStructTag, MoveStructTagParam,
-    MoveValue, TableItemRequest, TransactionId, U128, U64,
+    Address, AsConverter, HexEncodedBytes, IdentifierWrapper, MoveModuleBytecode, MoveStructTag,
+    MoveStructTagParam, MoveValue, RawStateValueRequest, TableItemRequest, U128, U64,
 };
-use aptos_api_types::{LedgerInfo, MoveResource};
+use aptos_api_types::{LedgerInfo, MoveResource, MoveResourceVersion};
 use aptos_state_view::StateView;
 use aptos_types::access_path::AccessPath;
 use aptos_types::state_store::state_key::StateKey;
@@ -60,6 +61,43 @@ impl StateApi {
         self.resource(&accept_type, address.0, resource_type.0, ledger_version.0)
     }
 
+    /// Get account resource history
+    ///
+    /// This endpoint returns the versions at which the resource of a specific
+    /// type residing at a given account was changed, in ascending order, along
+    /// with the resource as of each of those versions. The resource is missing
+    /// from versions at which it was deleted. Use `start` (a ledger version)
+    /// and `limit` to page through the history, up to the given ledger version.
+    ///
+    /// The history is only kept by nodes with `enable_state_key_history` set in
+    /// their storage config, and only covers changes committed since it was
+    /// set. Other nodes respond with a 404.
+    #[oai(
+        path = "/accounts/:address/resource/:resource_type/history",
+        method = "get",
+        operation_id = "get_account_resource_history",
+        tag = "ApiTags::Accounts"
+    )]
+    async fn get_account_resource_history(
+        &self,
+        accept_type: AcceptType,
+        address: Path<Address>,
+        resource_type: Path<MoveStructTagParam>,
+        start: Query<Option<U64>>,
+        limit: Query<Option<u16>>,
+        ledger_version: Query<Option<U64>>,
+    ) -> BasicResultWith404<Vec<MoveResourceVersion>> {
+        fail_point_poem("endpoint_get_account_resource_history")?;
+        let page = Page::new(start.0.map(|v| v.0), limit.0);
+        self.resource_history(
+            &accept_type,
+            address.0,
+            resource_type.0,
+            page,
+            ledger_version.0,
+        )
+    }
+
     /// Get specific account module
     ///
     /// This endpoint returns the module with a specific name residing at a given
@@ -116,6 +154,32 @@ impl StateApi {
             ledger_version.0,
         )
     }
+
+    /// Get raw state value
+    ///
+    /// Get the bytes stored under a state key at a specified ledger version
+    /// (AKA transaction version), as the Move VM reads them. If the ledger
+    /// version is not specified in the request, the latest ledger version is
+    /// used.
+    ///
+    /// This lets clients run the Move VM locally against the state of the
+    /// chain, e.g. to profile the gas a transaction would use. If nothing is
+    /// stored under the key, the server responds with a 404.
+    #[oai(
+        path = "/state_values/raw",
+        method = "post",
+        operation_id = "get_raw_state_value",
+        tag = "ApiTags::General"
+    )]
+    async fn get_raw_state_value(
+        &self,
+        accept_type: AcceptType,
+        raw_state_value_request: Json<RawStateValueRequest>,
+        ledger_version: Query<Option<U64>>,
+    ) -> BasicResultWith404<HexEncodedBytes> {
+        fail_point_poem("endpoint_get_raw_state_value")?;
+        self.raw_state_value(&accept_type, raw_state_value_request.0, ledger_version.0)
+    }
 }
 
 impl StateApi {
@@ -128,13 +192,8 @@ impl StateApi {
             .map(|v| v.0)
             .unwrap_or_else(|| latest_ledger_info.version());
 
-        if ledger_version > latest_ledger_info.version() {
-            return Err(build_not_found(
-                "ledger",
-                TransactionId::Version(U64::from(ledger_version)),
-                latest_ledger_info.version(),
-            ));
-        }
+        self.context
+            .check_state_version_poem(ledger_version, &latest_ledger_info)?;
 
         let state_view = self.context.state_view_at_version(ledger_version)
             .context(format!("Failed to get state view at version {} even after confirming the ledger has advanced past that version to {}", ledger_version, latest_ledger_info.version()))
@@ -180,6 +239,72 @@ impl StateApi {
         ))
     }
 
+    fn resource_history(
+        &self,
+        accept_type: &AcceptType,
+        address: Address,
+        resource_type: MoveStructTagParam,
+        page: Page,
+        ledger_version: Option<U64>,
+    ) -> BasicResultWith404<Vec<MoveResourceVersion>> {
+        let resource_type: MoveStructTag = resource_type.into();
+        let resource_type: StructTag = resource_type
+            .try_into()
+            .context("Failed to parse given resource type")
+            .map_err(BasicErrorWith404::bad_request)?;
+        let resource_key = ResourceKey::new(address.into(), resource_type.clone());
+        let access_path = AccessPath::resource_access_path(resource_key);
+        let state_key = StateKey::AccessPath(access_path);
+        let (ledger_info, ledger_version, state_view) = self.preprocess_request(ledger_version)?;
+        if !self.context.db.state_key_history_enabled() {
+            return Err(BasicErrorWith404::not_found_str(
+                "The history of resources is not kept by this node",
+            )
+            .error_code(AptosErrorCode::StateKeyHistoryNotEnabled));
+        }
+
+        let history = self
+            .context
+            .db
+            .get_state_value_history(
+                &state_key,
+                page.start(0, ledger_version)?,
+                page.limit()? as u64,
+                ledger_version,
+            )
+            .context(format!(
+                "Failed to query DB for the history of {:?}",
+                state_key
+            ))
+            .map_err(BasicErrorWith404::internal)
+            .map_err(|e| e.error_code(AptosErrorCode::ReadFromStorageError))?;
+
+        let resolver = state_view.as_move_resolver();
+        let converter = resolver.as_converter(self.context.db.clone());
+        let resource_versions = history
+            .into_iter()
+            .map(|(version, state_value)| {
+                let resource = state_value
+                    .maybe_bytes
+                    .map(|bytes| converter.try_into_resource(&resource_type, &bytes))
+                    .transpose()
+                    .context("Failed to deserialize resource data retrieved from DB")
+                    .map_err(BasicErrorWith404::internal)?;
+                Ok(MoveResourceVersion {
+                    version: version.into(),
+                    resource,
+                })
+            })
+            .collect::<Result<Vec<_>, BasicErrorWith404>>()?;
+
+        BasicResponse::try_from_rust_value((
+            resource_versions,
+            &ledger_info,
+            BasicResponseStatus::Ok,
+            accept_type,
+        ))
+    }
+
     pub fn module(
         &self,
         accept_type: &AcceptType,
@@ -210,6 +335,31 @@ impl StateApi {
         ))
     }
 
+    pub fn raw_state_value(
+        &self,
+        accept_type: &AcceptType,
+        raw_state_value_request: RawStateValueRequest,
+        ledger_version: Option<U64>,
+    ) -> BasicResultWith404<HexEncodedBytes> {
+        let key = raw_state_value_request.key;
+        let state_key: StateKey = bcs::from_bytes(&key.0)
+            .context("Failed to deserialize state key")
+            .map_err(BasicErrorWith404::bad_request)?;
+        let (ledger_info, ledger_version, state_view) = self.preprocess_request(ledger_version)?;
+        let bytes = state_view
+            .get_state_value(&state_key)
+            .context(format!("Failed to query DB to check for {:?}", state_key))
+            .map_err(BasicErrorWith404::internal)?
+            .ok_or_else(|| build_not_found("State value", key, ledger_version))?;
+
+        BasicResponse::try_from_rust_value((
+            HexEncodedBytes::from(bytes),
+            &ledger_info,
+            BasicResponseStatus::Ok,
+            accept_type,
+        ))
+    }
+
     pub fn table_item(
         &self,
         accept_type: &AcceptType,
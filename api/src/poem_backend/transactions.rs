@@ -10,8 +10,8 @@ use super::accept_type::AcceptType;
 use super::bcs_payload::Bcs;
 use super::page::Page;
 use super::{
-    ApiTags, AptosErrorResponse, BasicError, BasicErrorWith404, BasicResponse, BasicResponseStatus,
-    BasicResult, BasicResultWith404, InternalError, NotFoundError,
+    build_not_found, ApiTags, AptosErrorResponse, BasicError, BasicErrorWith404, BasicResponse,
+    BasicResponseStatus, BasicResult, BasicResultWith404, InternalError, NotFoundError,
 };
 use super::{AptosErrorCode, BadRequestError, InsufficientStorageError};
 use crate::context::Context;
@@ -19,11 +19,13 @@ use crate::failpoint::fail_point_poem;
 use crate::{generate_error_response, generate_success_response};
 use anyhow::Context as AnyhowContext;
 use aptos_api_types::{
-    Address, AsConverter, EncodeSubmissionRequest, HashValue, HexEncodedBytes, LedgerInfo,
+    Address, AsConverter, EncodeSubmissionRequest, GasEstimation, GasProfile, GasProfileCategory,
+    GasProfileFunction, GasProfileInstruction, HashValue, HexEncodedBytes, LedgerInfo,
     PendingTransaction, SubmitTransactionRequest, Transaction, TransactionData,
     TransactionOnChainData, UserTransaction, U64,
 };
 use aptos_crypto::signing_message;
+use aptos_mempool::MempoolUtilization;
 use aptos_types::mempool_status::MempoolStatusCode;
 use aptos_types::transaction::{
     ExecutionStatus, RawTransaction, RawTransactionWithData, SignedTransaction, TransactionStatus,
@@ -31,7 +33,35 @@ use aptos_types::transaction::{
 use aptos_vm::AptosVM;
 use poem_openapi::param::{Path, Query};
 use poem_openapi::payload::Json;
-use poem_openapi::{ApiRequest, OpenApi};
+use poem_openapi::{ApiRequest, Enum, OpenApi};
+
+// The most transactions that can be looked up in a single by_hashes request.
+const MAX_TRANSACTIONS_BY_HASHES: usize = 100;
+
+// The number of most recent transactions whose gas unit prices are sampled to
+// estimate the gas price.
+const GAS_ESTIMATION_LOOKBACK_TRANSACTIONS: u64 = 1000;
+
+// The percentile of sampled gas unit prices used as the estimate. It goes from
+// the min, when mempool is empty, to the max, when mempool is full.
+const GAS_ESTIMATION_MIN_PERCENTILE: u64 = 50;
+const GAS_ESTIMATION_MAX_PERCENTILE: u64 = 90;
+
+// How much gas, relative to what a simulation used, is added on top of it for
+// the recommended max_gas_amount.
+const SIMULATION_GAS_SAFETY_MARGIN_PERCENT: u64 = 50;
+
+/// Which transactions of an account to list
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Enum)]
+#[oai(rename_all = "snake_case")]
+pub enum AccountTransactionInvolvement {
+    /// Transactions sent by the account, paged by sequence number.
+    Sender,
+    /// Transactions that affected the account, paged by version: those sent
+    /// by it, those that wrote its state and those that emitted events from
+    /// its event handles.
+    All,
+}
 
 generate_success_response!(SubmitTransactionResponse, (202, Accepted));
 generate_error_response!(
@@ -123,6 +153,31 @@ impl TransactionsApi {
             .await
     }
 
+    /// Get transactions by hashes
+    ///
+    /// Look up many transactions by their hashes in a single request. Each
+    /// hash is looked up the same way as in /transactions/by_hash, first in
+    /// storage and then in the mempool.
+    ///
+    /// Transactions are returned in the order of the given hashes. Hashes for
+    /// which no transaction could be found are left out of the response, so
+    /// use the hash of each returned transaction to match it to the request.
+    #[oai(
+        path = "/transactions/by_hashes",
+        method = "post",
+        operation_id = "get_transactions_by_hashes",
+        tag = "ApiTags::Transactions"
+    )]
+    async fn get_transactions_by_hashes(
+        &self,
+        accept_type: AcceptType,
+        txn_hashes: Json<Vec<HashValue>>,
+    ) -> BasicResultWith404<Vec<Transaction>> {
+        fail_point_poem("endpoint_transactions_by_hashes")?;
+        self.get_transactions_by_hashes_inner(&accept_type, txn_hashes.0)
+            .await
+    }
+
     /// Get transaction by version
     ///
     /// todo
@@ -144,7 +199,13 @@ impl TransactionsApi {
 
     /// Get account transactions
     ///
-    /// todo
+    /// By default, or with `involvement=sender`, this endpoint returns the
+    /// transactions sent by the given account, and `start` is a sequence
+    /// number. With `involvement=all`, it returns every transaction that
+    /// affected the account, including coins received and resources written
+    /// by others, and `start` is a ledger version. `involvement=all` is only
+    /// served by nodes with `enable_affected_account_index` set in their
+    /// storage config. Other nodes respond with a 404.
     #[oai(
         path = "/accounts/:address/transactions",
         method = "get",
@@ -158,10 +219,42 @@ impl TransactionsApi {
         address: Path<Address>,
         start: Query<Option<U64>>,
         limit: Query<Option<u16>>,
+        involvement: Query<Option<AccountTransactionInvolvement>>,
     ) -> BasicResultWith404<Vec<Transaction>> {
         fail_point_poem("endpoint_get_accounts_transactions")?;
         let page = Page::new(start.0.map(|v| v.0), limit.0);
-        self.list_by_account(&accept_type, page, address.0)
+        match involvement
+            .0
+            .unwrap_or(AccountTransactionInvolvement::Sender)
+        {
+            AccountTransactionInvolvement::Sender => {
+                self.list_by_account(&accept_type, page, address.0)
+            }
+            AccountTransactionInvolvement::All => {
+                self.list_by_affected_account(&accept_type, page, address.0)
+            }
+        }
+    }
+
+    /// Get account transaction by sequence number
+    ///
+    /// Look up the committed transaction sent by the given account with the
+    /// given sequence number.
+    #[oai(
+        path = "/accounts/:address/transactions/:sequence_number",
+        method = "get",
+        operation_id = "get_account_transaction_by_sequence_number",
+        tag = "ApiTags::Transactions"
+    )]
+    async fn get_account_transaction_by_sequence_number(
+        &self,
+        accept_type: AcceptType,
+        address: Path<Address>,
+        sequence_number: Path<U64>,
+    ) -> BasicResultWith404<Transaction> {
+        fail_point_poem("endpoint_get_account_transaction_by_sequence_number")?;
+        self.get_by_account_and_sequence_number(&accept_type, address.0, sequence_number.0)
+            .await
     }
 
     /// Submit transaction
@@ -204,6 +297,16 @@ impl TransactionsApi {
     /// - Create a SignedTransaction with a zero-padded signature.
     /// - Submit a SubmitTransactionRequest containing a UserTransactionRequest containing that signature.
     ///
+    /// The response includes a recommended_max_gas_amount, which is the gas
+    /// the simulation used plus a safety margin. For it to be meaningful, the
+    /// simulated transaction must not have run out of gas, so simulate with a
+    /// generous max_gas_amount.
+    ///
+    /// If profile_gas is set, the response also includes a gas_profile,
+    /// breaking down the gas charged by function, by instruction and by kind
+    /// of instruction, as well as the intrinsic, native function and storage
+    /// gas.
+    ///
     /// To use this endpoint with BCS, you must submit a SignedTransaction
     /// encoded as BCS. See SignedTransaction in types/src/transaction/mod.rs.
     #[oai(
@@ -215,11 +318,35 @@ impl TransactionsApi {
     async fn simulate_transaction(
         &self,
         accept_type: AcceptType,
+        profile_gas: Query<Option<bool>>,
         data: SubmitTransactionPost,
     ) -> SimulateTransactionResult<Vec<UserTransaction>> {
         fail_point_poem("endpoint_simulate_transaction")?;
         let signed_transaction = self.get_signed_transaction(data)?;
-        self.simulate(&accept_type, signed_transaction).await
+        self.simulate(
+            &accept_type,
+            signed_transaction,
+            profile_gas.0.unwrap_or_default(),
+        )
+        .await
+    }
+
+    /// Estimate gas price
+    ///
+    /// Estimate the gas unit price a transaction should use to be committed
+    /// promptly. The estimate is based on the gas unit prices of recently
+    /// committed user transactions: it is their median when mempool is empty,
+    /// going up to their 90th percentile as mempool fills up. If there are no
+    /// recent user transactions, the minimum gas unit price is returned.
+    #[oai(
+        path = "/estimate_gas_price",
+        method = "get",
+        operation_id = "estimate_gas_price",
+        tag = "ApiTags::Transactions"
+    )]
+    async fn estimate_gas_price(&self, accept_type: AcceptType) -> BasicResult<GasEstimation> {
+        fail_point_poem("endpoint_estimate_gas_price")?;
+        self.estimate_gas_price_inner(&accept_type).await
     }
 
     /// Encode submission
@@ -330,6 +457,72 @@ impl TransactionsApi {
             .await
     }
 
+    async fn get_transactions_by_hashes_inner(
+        &self,
+        accept_type: &AcceptType,
+        hashes: Vec<HashValue>,
+    ) -> BasicResultWith404<Vec<Transaction>> {
+        if hashes.len() > MAX_TRANSACTIONS_BY_HASHES {
+            return Err(BasicErrorWith404::bad_request_str(&format!(
+                "Too many transaction hashes given ({}), at most {} are allowed per request",
+                hashes.len(),
+                MAX_TRANSACTIONS_BY_HASHES
+            )));
+        }
+
+        let ledger_info = self.context.get_latest_ledger_info_poem()?;
+        let mut transactions = Vec::with_capacity(hashes.len());
+        for hash in hashes {
+            let txn_data = self
+                .get_by_hash(hash.into(), &ledger_info)
+                .await
+                .context(format!("Failed to get transaction by hash {}", hash))
+                .map_err(BasicErrorWith404::internal)
+                .map_err(|e| e.error_code(AptosErrorCode::ReadFromStorageError))?;
+            if let Some(txn_data) = txn_data {
+                transactions.push(self.convert_transaction_data(txn_data)?);
+            }
+        }
+
+        BasicResponse::try_from_rust_value((
+            transactions,
+            &ledger_info,
+            BasicResponseStatus::Ok,
+            accept_type,
+        ))
+    }
+
+    async fn get_by_account_and_sequence_number(
+        &self,
+        accept_type: &AcceptType,
+        address: Address,
+        sequence_number: U64,
+    ) -> BasicResultWith404<Transaction> {
+        let ledger_info = self.context.get_latest_ledger_info_poem()?;
+        let txn_data = self
+            .context
+            .get_account_transaction(address.into(), sequence_number.0, ledger_info.version())
+            .context(format!(
+                "Failed to get transaction of account {} with sequence number {}",
+                address, sequence_number
+            ))
+            .map_err(BasicErrorWith404::internal)
+            .map_err(|e| e.error_code(AptosErrorCode::ReadFromStorageError))?
+            .ok_or_else(|| {
+                build_not_found(
+                    "Transaction",
+                    format!(
+                        "account {} and sequence number {}",
+                        address, sequence_number
+                    ),
+                    ledger_info.version(),
+                )
+            })?;
+
+        self.get_transaction_inner(accept_type, txn_data.into(), &ledger_info)
+            .await
+    }
+
     async fn get_transaction_by_version_inner(
         &self,
         accept_type: &AcceptType,
@@ -356,6 +549,20 @@ impl TransactionsApi {
         transaction_data: TransactionData,
         ledger_info: &LedgerInfo,
     ) -> BasicResultWith404<Transaction> {
+        let transaction = self.convert_transaction_data(transaction_data)?;
+
+        BasicResponse::try_from_rust_value((
+            transaction,
+            ledger_info,
+            BasicResponseStatus::Ok,
+            accept_type,
+        ))
+    }
+
+    fn convert_transaction_data<E: InternalError>(
+        &self,
+        transaction_data: TransactionData,
+    ) -> Result<Transaction, E> {
         let resolver = self.context.move_resolver_poem()?;
         let transaction = match transaction_data {
             TransactionData::OnChain(txn) => {
@@ -363,26 +570,20 @@ impl TransactionsApi {
                     .context
                     .get_block_timestamp(txn.version)
                     .context("Failed to get block timestamp from DB")
-                    .map_err(BasicErrorWith404::internal)?;
+                    .map_err(E::internal)?;
                 resolver
                     .as_converter(self.context.db.clone())
                     .try_into_onchain_transaction(timestamp, txn)
                     .context("Failed to convert on chain transaction to Transaction")
-                    .map_err(BasicErrorWith404::internal)?
+                    .map_err(E::internal)?
             }
             TransactionData::Pending(txn) => resolver
                 .as_converter(self.context.db.clone())
                 .try_into_pending_transaction(*txn)
                 .context("Failed to convert on pending transaction to Transaction")
-                .map_err(BasicErrorWith404::internal)?,
+                .map_err(E::internal)?,
         };
-
-        BasicResponse::try_from_rust_value((
-            transaction,
-            ledger_info,
-            BasicResponseStatus::Ok,
-            accept_type,
-        ))
+        Ok(transaction)
     }
 
     fn get_by_version(
@@ -449,6 +650,40 @@ impl TransactionsApi {
         ))
     }
 
+    fn list_by_affected_account(
+        &self,
+        accept_type: &AcceptType,
+        page: Page,
+        address: Address,
+    ) -> BasicResultWith404<Vec<Transaction>> {
+        let latest_ledger_info = self.context.get_latest_ledger_info_poem()?;
+        if !self.context.db.affected_account_index_enabled() {
+            return Err(BasicErrorWith404::not_found_str(
+                "Transactions are only indexed by their sender on this node",
+            )
+            .error_code(AptosErrorCode::AffectedAccountIndexNotEnabled));
+        }
+
+        let data = self
+            .context
+            .get_affected_account_transactions(
+                address.into(),
+                page.start(0, latest_ledger_info.version())?,
+                page.limit()?,
+                latest_ledger_info.version(),
+            )
+            .context("Failed to get the transactions that affected the given account")
+            .map_err(BasicErrorWith404::internal)
+            .map_err(|e| e.error_code(AptosErrorCode::ReadFromStorageError))?;
+
+        BasicResponse::try_from_rust_value((
+            self.render_transactions(data)?,
+            &latest_ledger_info,
+            BasicResponseStatus::Ok,
+            accept_type,
+        ))
+    }
+
     fn get_signed_transaction(
         &self,
         data: SubmitTransactionPost,
@@ -522,6 +757,7 @@ impl TransactionsApi {
         &self,
         accept_type: &AcceptType,
         txn: SignedTransaction,
+        profile_gas: bool,
     ) -> SimulateTransactionResult<Vec<UserTransaction>> {
         if txn.clone().check_signature().is_ok() {
             return Err(SubmitTransactionError::bad_request_str(
@@ -530,7 +766,12 @@ impl TransactionsApi {
         }
         let ledger_info = self.context.get_latest_ledger_info_poem()?;
         let move_resolver = self.context.move_resolver_poem()?;
-        let (status, output) = AptosVM::simulate_signed_transaction(&txn, &move_resolver);
+        let (status, output, gas_profile) = if profile_gas {
+            AptosVM::simulate_signed_transaction_with_gas_profile(&txn, &move_resolver)
+        } else {
+            let (status, output) = AptosVM::simulate_signed_transaction(&txn, &move_resolver);
+            (status, output, None)
+        };
         let version = ledger_info.version();
         let exe_status = match status.into() {
             TransactionStatus::Keep(exec_status) => exec_status,
@@ -556,12 +797,31 @@ impl TransactionsApi {
 
         let transactions = self.render_transactions(vec![simulated_txn])?;
 
+        let txn_gas_params = self
+            .context
+            .get_gas_parameters()
+            .context("Failed to load gas parameters")
+            .map_err(SubmitTransactionError::internal)?
+            .txn;
+        let max_gas_units = txn_gas_params.maximum_number_of_gas_units;
+        let gas_profile = gas_profile
+            .map(|profile| render_gas_profile(&profile, txn_gas_params.gas_unit_scaling_factor));
+        let recommended_max_gas_amount = output
+            .gas_used()
+            .saturating_mul(100 + SIMULATION_GAS_SAFETY_MARGIN_PERCENT)
+            / 100;
+        let recommended_max_gas_amount = recommended_max_gas_amount.min(max_gas_units);
+
         // Users can only make requests to simulate UserTransactions, so unpack
         // the Vec<Transaction> into Vec<UserTransaction>.
         let mut user_transactions = Vec::new();
         for transaction in transactions.into_iter() {
             match transaction {
-                Transaction::UserTransaction(user_txn) => user_transactions.push(*user_txn),
+                Transaction::UserTransaction(mut user_txn) => {
+                    user_txn.recommended_max_gas_amount = Some(recommended_max_gas_amount.into());
+                    user_txn.gas_profile = gas_profile.clone();
+                    user_transactions.push(*user_txn)
+                }
                 _ => return Err(SubmitTransactionError::internal_str(
                     "Simulation unexpectedly resulted in something other than a UserTransaction",
                 )),
@@ -575,6 +835,46 @@ impl TransactionsApi {
         ))
     }
 
+    async fn estimate_gas_price_inner(
+        &self,
+        accept_type: &AcceptType,
+    ) -> BasicResult<GasEstimation> {
+        let ledger_info = self.context.get_latest_ledger_info_poem()?;
+        let gas_params = self
+            .context
+            .get_gas_parameters()
+            .context("Failed to load gas parameters")
+            .map_err(BasicError::internal)?;
+        let gas_unit_prices = self
+            .context
+            .get_recent_gas_unit_prices(GAS_ESTIMATION_LOOKBACK_TRANSACTIONS, ledger_info.version())
+            .context("Failed to read recent transactions from storage")
+            .map_err(BasicError::internal)
+            .map_err(|e| e.error_code(AptosErrorCode::ReadFromStorageError))?;
+        let utilization = self
+            .context
+            .get_mempool_utilization()
+            .await
+            .context("Failed to get mempool utilization")
+            .map_err(BasicError::internal)?;
+
+        let gas_estimate = estimate_gas_unit_price(
+            &gas_unit_prices,
+            utilization,
+            gas_params.txn.min_price_per_gas_unit,
+            gas_params.txn.max_price_per_gas_unit,
+        );
+
+        BasicResponse::try_from_rust_value((
+            GasEstimation {
+                gas_estimate: gas_estimate.into(),
+            },
+            &ledger_info,
+            BasicResponseStatus::Ok,
+            accept_type,
+        ))
+    }
+
     pub fn get_signing_message(
         &self,
         accept_type: &AcceptType,
@@ -609,3 +909,129 @@ impl TransactionsApi {
         ))
     }
 }
+
+/// Picks a gas unit price among recently used ones, sorted in increasing order,
+/// bidding higher the fuller mempool is, and keeps it within the bounds allowed
+/// by the gas schedule.
+fn estimate_gas_unit_price(
+    gas_unit_prices: &[u64],
+    utilization: MempoolUtilization,
+    min_price: u64,
+    max_price: u64,
+) -> u64 {
+    if gas_unit_prices.is_empty() {
+        return min_price;
+    }
+
+    let fullness = if utilization.capacity == 0 {
+        100
+    } else {
+        (utilization.size * 100 / utilization.capacity).min(100) as u64
+    };
+    let percentile = GAS_ESTIMATION_MIN_PERCENTILE
+        + (GAS_ESTIMATION_MAX_PERCENTILE - GAS_ESTIMATION_MIN_PERCENTILE) * fullness / 100;
+    let index = (gas_unit_prices.len() - 1) * percentile as usize / 100;
+
+    gas_unit_prices[index].max(min_price).min(max_price)
+}
+
+fn render_gas_profile(profile: &aptos_gas::GasProfile, gas_unit_scaling_factor: u64) -> GasProfile {
+    let mut categories: Vec<_> = profile
+        .categories()
+        .into_iter()
+        .map(|(category, (count, gas))| {
+            let mut instructions: Vec<_> = profile
+                .instructions
+                .iter()
+                .filter(|(_, cost)| cost.category == category)
+                .map(|(name, cost)| GasProfileInstruction {
+                    name: name.clone(),
+                    count: cost.count.into(),
+                    gas: cost.gas.into(),
+                })
+                .collect();
+            instructions.sort_by_key(|instruction| std::cmp::Reverse(instruction.gas.0));
+            GasProfileCategory {
+                name: category.name().to_string(),
+                count: count.into(),
+                gas: gas.into(),
+                instructions,
+            }
+        })
+        .collect();
+    categories.sort_by_key(|category| std::cmp::Reverse(category.gas.0));
+    let mut functions: Vec<_> = profile
+        .functions()
+        .into_iter()
+        .map(|(name, cost)| GasProfileFunction {
+            name,
+            calls: cost.calls.into(),
+            gas: cost.gas.into(),
+            self_gas: cost.self_gas.into(),
+        })
+        .collect();
+    functions.sort_by_key(|function| std::cmp::Reverse(function.gas.0));
+
+    GasProfile {
+        gas_unit_scaling_factor: gas_unit_scaling_factor.into(),
+        intrinsic: profile.intrinsic.into(),
+        execution: profile.execution().into(),
+        natives: profile.natives.into(),
+        storage: profile.storage().into(),
+        categories,
+        functions,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::estimate_gas_unit_price;
+    use aptos_mempool::MempoolUtilization;
+
+    const EMPTY: MempoolUtilization = MempoolUtilization {
+        size: 0,
+        capacity: 100,
+    };
+    const FULL: MempoolUtilization = MempoolUtilization {
+        size: 100,
+        capacity: 100,
+    };
+
+    #[test]
+    fn test_estimate_gas_unit_price_without_samples() {
+        assert_eq!(estimate_gas_unit_price(&[], EMPTY, 1, 1000), 1);
+        assert_eq!(estimate_gas_unit_price(&[], FULL, 1, 1000), 1);
+    }
+
+    #[test]
+    fn test_estimate_gas_unit_price_single_sample() {
+        assert_eq!(estimate_gas_unit_price(&[7], EMPTY, 1, 1000), 7);
+        assert_eq!(estimate_gas_unit_price(&[7], FULL, 1, 1000), 7);
+    }
+
+    #[test]
+    fn test_estimate_gas_unit_price_equal_samples() {
+        assert_eq!(estimate_gas_unit_price(&[5; 10], EMPTY, 1, 1000), 5);
+        assert_eq!(estimate_gas_unit_price(&[5; 10], FULL, 1, 1000), 5);
+    }
+
+    #[test]
+    fn test_estimate_gas_unit_price_percentile() {
+        let prices = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11];
+        // The median when mempool is empty, the 90th percentile when it's full.
+        assert_eq!(estimate_gas_unit_price(&prices, EMPTY, 1, 1000), 6);
+        assert_eq!(estimate_gas_unit_price(&prices, FULL, 1, 1000), 10);
+        // Mempool with no capacity counts as full.
+        let no_capacity = MempoolUtilization {
+            size: 0,
+            capacity: 0,
+        };
+        assert_eq!(estimate_gas_unit_price(&prices, no_capacity, 1, 1000), 10);
+    }
+
+    #[test]
+    fn test_estimate_gas_unit_price_bounds() {
+        assert_eq!(estimate_gas_unit_price(&[0, 0, 1], EMPTY, 2, 1000), 2);
+        assert_eq!(estimate_gas_unit_price(&[5000; 3], EMPTY, 2, 1000), 1000);
+    }
+}
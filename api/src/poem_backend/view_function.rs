@@ -0,0 +1,110 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::Arc;
+
+use super::accept_type::AcceptType;
+use super::{
+    ApiTags, BadRequestError, BasicErrorWith404, BasicResponse, BasicResponseStatus,
+    BasicResultWith404, InternalError,
+};
+use crate::context::Context;
+use crate::failpoint::fail_point_poem;
+use anyhow::Context as AnyhowContext;
+use aptos_api_types::{AsConverter, MoveValue, ViewRequest, U64};
+use aptos_vm::{data_cache::AsMoveResolver, AptosVM};
+use poem_openapi::param::Query;
+use poem_openapi::payload::Json;
+use poem_openapi::OpenApi;
+
+pub struct ViewFunctionApi {
+    pub context: Arc<Context>,
+}
+
+#[OpenApi]
+impl ViewFunctionApi {
+    /// Execute view function
+    ///
+    /// Execute a public Move function with the given type arguments and
+    /// arguments against the state at a specified ledger version (AKA
+    /// transaction version), returning the values the function returns. If
+    /// the ledger version is not specified in the request, the latest ledger
+    /// version is used.
+    ///
+    /// Nothing the function writes is persisted and no transaction is created,
+    /// so this is the way to read computed state without signing anything.
+    /// Functions that take a signer cannot be executed this way.
+    #[oai(
+        path = "/view",
+        method = "post",
+        operation_id = "view",
+        tag = "ApiTags::View"
+    )]
+    async fn view_function(
+        &self,
+        accept_type: AcceptType,
+        request: Json<ViewRequest>,
+        ledger_version: Query<Option<U64>>,
+    ) -> BasicResultWith404<Vec<MoveValue>> {
+        fail_point_poem("endpoint_view_function")?;
+        self.view(&accept_type, request.0, ledger_version.0)
+    }
+}
+
+impl ViewFunctionApi {
+    fn view(
+        &self,
+        accept_type: &AcceptType,
+        request: ViewRequest,
+        ledger_version: Option<U64>,
+    ) -> BasicResultWith404<Vec<MoveValue>> {
+        let ledger_info = self.context.get_latest_ledger_info_poem()?;
+        let ledger_version = ledger_version
+            .map(|v| v.0)
+            .unwrap_or_else(|| ledger_info.version());
+        self.context
+            .check_state_version_poem(ledger_version, &ledger_info)?;
+
+        let state_view = self
+            .context
+            .state_view_at_version(ledger_version)
+            .context(format!(
+                "Failed to get state view at version {}",
+                ledger_version
+            ))
+            .map_err(BasicErrorWith404::internal)?;
+        let resolver = state_view.as_move_resolver();
+        let converter = resolver.as_converter(self.context.db.clone());
+
+        let view_function = converter
+            .try_into_view_function(request)
+            .context("The given view function request is invalid")
+            .map_err(BasicErrorWith404::bad_request)?;
+
+        let return_values = AptosVM::execute_view_function(
+            &state_view,
+            view_function.module,
+            view_function.function,
+            view_function.type_arguments,
+            view_function.arguments,
+            self.context.max_view_function_gas(),
+        )
+        .map_err(BasicErrorWith404::bad_request)?;
+
+        let move_values = view_function
+            .return_types
+            .iter()
+            .zip(return_values.iter())
+            .map(|(typ, bytes)| converter.try_into_move_value(typ, bytes))
+            .collect::<anyhow::Result<Vec<_>>>()
+            .context("Failed to convert view function return values")
+            .map_err(BasicErrorWith404::internal)?;
+
+        BasicResponse::try_from_rust_value((
+            move_values,
+            &ledger_info,
+            BasicResponseStatus::Ok,
+            accept_type,
+        ))
+    }
+}
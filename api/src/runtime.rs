@@ -9,8 +9,11 @@ use aptos_types::chain_id::ChainId;
 use std::{convert::Infallible, net::SocketAddr, sync::Arc};
 use storage_interface::DbReader;
 use tokio::runtime::{Builder, Runtime};
-use warp::{Filter, Reply};
-use warp_reverse_proxy::reverse_proxy_filter;
+use warp::{
+    http::{HeaderMap, HeaderValue},
+    Filter, Reply,
+};
+use warp_reverse_proxy::{extract_request_data_filter, proxy_to_and_forward_response};
 
 /// Creates HTTP server (warp-based) serves for both REST and JSON-RPC API.
 /// When api and json-rpc are configured with same port, both API will be served for the port.
@@ -48,10 +51,34 @@ pub fn get_routes_with_poem(
     poem_address: SocketAddr,
     context: Context,
 ) -> impl Filter<Extract = impl Reply, Error = Infallible> + Clone {
-    let proxy = warp::path!("v1" / ..).and(reverse_proxy_filter(
-        "v1".to_string(),
-        format!("http://{}", poem_address),
-    ));
+    let proxy = warp::path!("v1" / ..)
+        .and(warp::addr::remote())
+        .and(extract_request_data_filter())
+        .and_then(
+            move |remote_addr: Option<SocketAddr>,
+                  uri,
+                  params,
+                  method,
+                  mut headers: HeaderMap,
+                  body| {
+                // Poem only sees the proxy as its peer, so tell it who the
+                // client is, e.g. for rate limiting.
+                if let Some(remote_addr) = remote_addr {
+                    if let Ok(value) = HeaderValue::from_str(&remote_addr.ip().to_string()) {
+                        headers.append("X-Forwarded-For", value);
+                    }
+                }
+                proxy_to_and_forward_response(
+                    format!("http://{}", poem_address),
+                    "v1".to_string(),
+                    uri,
+                    params,
+                    method,
+                    headers,
+                    body,
+                )
+            },
+        );
     proxy.or(index::routes(context))
 }
 
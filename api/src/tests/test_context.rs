@@ -11,7 +11,8 @@ use aptos_api_types::{
     X_APTOS_LEDGER_TIMESTAMP, X_APTOS_LEDGER_VERSION,
 };
 use aptos_config::config::{
-    NodeConfig, RocksdbConfigs, NO_OP_STORAGE_PRUNER_CONFIG, TARGET_SNAPSHOT_SIZE,
+    NodeConfig, RocksdbConfigs, StoragePrunerConfig, NO_OP_STORAGE_PRUNER_CONFIG,
+    TARGET_SNAPSHOT_SIZE,
 };
 use aptos_crypto::{hash::HashValue, SigningKey};
 use aptos_mempool::mocks::MockSharedMempool;
@@ -98,6 +99,14 @@ impl ApiSpecificConfig {
 }
 
 pub fn new_test_context(test_name: String, api_version: &str) -> TestContext {
+    new_test_context_with_pruner_config(test_name, api_version, NO_OP_STORAGE_PRUNER_CONFIG)
+}
+
+pub fn new_test_context_with_pruner_config(
+    test_name: String,
+    api_version: &str,
+    pruner_config: StoragePrunerConfig,
+) -> TestContext {
     let tmp_dir = TempPath::new();
     tmp_dir.create_as_dir().unwrap();
 
@@ -119,10 +128,12 @@ pub fn new_test_context(test_name: String, api_version: &str) -> TestContext {
     let (db, db_rw) = DbReaderWriter::wrap(
         AptosDB::open(
             &tmp_dir,
-            false,                       /* readonly */
-            NO_OP_STORAGE_PRUNER_CONFIG, /* pruner */
+            false,         /* readonly */
+            pruner_config, /* pruner */
             RocksdbConfigs::default(),
             true, /* indexer */
+            true, /* state key history */
+            true, /* affected account index */
             TARGET_SNAPSHOT_SIZE,
         )
         .unwrap(),
@@ -10,6 +10,7 @@ mod state_test;
 mod string_resource_test;
 mod transaction_vector_test;
 mod transactions_test;
+mod view_function_test;
 
 use super::TestContext;
 
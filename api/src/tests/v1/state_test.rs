@@ -1,14 +1,26 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use super::super::TestContext;
-use super::new_test_context;
+use super::super::{new_test_context_with_pruner_config, TestContext};
+use super::{new_test_context, API_VERSION};
 use crate::current_function_name;
+use aptos_api_types::HexEncodedBytes;
+use aptos_config::config::{StoragePrunerConfig, NO_OP_STORAGE_PRUNER_CONFIG};
 use aptos_sdk::types::LocalAccount;
-use move_deps::{move_core_types::account_address::AccountAddress, move_package::BuildConfig};
+use aptos_types::{access_path::AccessPath, state_store::state_key::StateKey};
+use move_deps::{
+    move_core_types::{
+        account_address::AccountAddress, identifier::Identifier, language_storage::ModuleId,
+    },
+    move_package::BuildConfig,
+};
 use serde::Serialize;
 use serde_json::{json, Value};
-use std::{convert::TryInto, path::PathBuf};
+use std::{
+    convert::TryInto,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_get_account_resource() {
@@ -65,6 +77,104 @@ async fn test_get_account_resource_struct_tag_not_found() {
     context.check_golden_output(resp);
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_resource_history() {
+    let mut context = new_test_context(current_function_name!());
+    let account1 = context.gen_account();
+    let account2 = context.gen_account();
+    let txn1 = context.create_user_account(&account1);
+    let txn2 = context.create_user_account(&account2);
+    context.commit_block(&vec![txn1, txn2]).await;
+
+    // The sequence number of the root account changed at genesis and with each transaction.
+    let history = context
+        .get(&get_account_resource_history(
+            "0xA550C18",
+            "0x1::account::Account",
+        ))
+        .await;
+    let history = history.as_array().unwrap();
+    assert_eq!(history.len(), 3);
+    assert_eq!(history[0]["version"], "0");
+    for entry in history {
+        let resource = context
+            .get(&format!(
+                "{}?ledger_version={}",
+                get_account_resource("0xA550C18", "0x1::account::Account"),
+                entry["version"].as_str().unwrap()
+            ))
+            .await;
+        assert_eq!(entry["resource"], resource);
+    }
+
+    let page = context
+        .get(&format!(
+            "{}?start=1&limit=1",
+            get_account_resource_history("0xA550C18", "0x1::account::Account")
+        ))
+        .await;
+    assert_eq!(page, json!([history[1]]));
+
+    let history = context
+        .get(&get_account_resource_history(
+            &account1.address().to_hex_literal(),
+            "0x1::account::Account",
+        ))
+        .await;
+    assert_eq!(history.as_array().unwrap().len(), 1);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_resource_at_pruned_version() {
+    let mut context = new_test_context_with_pruner_config(
+        current_function_name!(),
+        API_VERSION,
+        StoragePrunerConfig {
+            state_store_prune_window: Some(1),
+            ..NO_OP_STORAGE_PRUNER_CONFIG
+        },
+    );
+    let resource = get_account_resource("0xA550C18", "0x1::account::Account");
+    assert_eq!(context.get("/").await["oldest_state_version"], "0");
+    context.get(&format!("{}?ledger_version=0", resource)).await;
+
+    for _ in 0..3 {
+        let account = context.gen_account();
+        let txn = context.create_user_account(&account);
+        context.commit_block(&vec![txn]).await;
+    }
+    // The pruner catches up in the background.
+    let latest_version = context.get_latest_ledger_info().version();
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while context.context.get_oldest_state_version().unwrap() < latest_version - 1 {
+        assert!(
+            Instant::now() < deadline,
+            "Timed out waiting for the pruner."
+        );
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    let oldest_state_version = context.get("/").await["oldest_state_version"].clone();
+    assert_eq!(oldest_state_version, (latest_version - 1).to_string());
+
+    let resp = context
+        .expect_status_code(404)
+        .get(&format!("{}?ledger_version=0", resource))
+        .await;
+    assert_eq!(resp["error_code"], "version_pruned");
+    assert_eq!(resp["oldest_available_version"], oldest_state_version);
+
+    // The versions still in the window can be read.
+    context
+        .get(&format!(
+            "{}?ledger_version={}",
+            resource,
+            oldest_state_version.as_str().unwrap()
+        ))
+        .await;
+    context.get(&resource).await;
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_get_account_module() {
     let mut context = new_test_context(current_function_name!());
@@ -174,10 +284,46 @@ async fn test_get_table_item() {
     assert_table_item(ctx, &nested_table, "u8", "u8", 2, 3).await;
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_raw_state_value() {
+    let mut context = new_test_context(current_function_name!());
+    let module_key = |name: &str| {
+        let module_id = ModuleId::new(
+            AccountAddress::from_hex_literal("0x1").unwrap(),
+            Identifier::new(name).unwrap(),
+        );
+        let state_key = StateKey::AccessPath(AccessPath::code_access_path(module_id));
+        json!({ "key": HexEncodedBytes::from(bcs::to_bytes(&state_key).unwrap()) })
+    };
+
+    // Modules are stored as their bytecode
+    let value = context.post("/state_values/raw", module_key("coin")).await;
+    let module = context.get("/accounts/0x1/module/coin").await;
+    assert_eq!(value, module["bytecode"]);
+
+    let resp = context
+        .expect_status_code(404)
+        .post("/state_values/raw", module_key("missing"))
+        .await;
+    assert!(resp["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("State value not found"));
+
+    context
+        .expect_status_code(400)
+        .post("/state_values/raw", json!({ "key": "0xff" }))
+        .await;
+}
+
 fn get_account_resource(address: &str, struct_tag: &str) -> String {
     format!("/accounts/{}/resource/{}", address, struct_tag)
 }
 
+fn get_account_resource_history(address: &str, struct_tag: &str) -> String {
+    format!("/accounts/{}/resource/{}/history", address, struct_tag)
+}
+
 fn get_account_module(address: &str, name: &str) -> String {
     format!("/accounts/{}/module/{}", address, name)
 }
@@ -430,6 +430,52 @@ async fn test_get_transaction_by_hash() {
     assert_json(resp, txns[0].clone());
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_transactions_by_hashes() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account);
+    context.commit_block(&vec![txn.clone()]).await;
+
+    let txns = context.get("/transactions?start=1&limit=2").await;
+    assert_eq!(2, txns.as_array().unwrap().len());
+
+    let resp = context
+        .post(
+            "/transactions/by_hashes",
+            json!([
+                txns[1]["hash"],
+                "0xdadfeddcca7cb6396c735e9094c76c6e4e9cb3e3ef814730693aed59bd87b31d",
+                txns[0]["hash"],
+            ]),
+        )
+        .await;
+    assert_json(resp, json!([txns[1].clone(), txns[0].clone()]));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_transactions_by_hashes_with_too_many_hashes() {
+    let mut context = new_test_context(current_function_name!());
+    let hashes = vec!["0xdadfeddcca7cb6396c735e9094c76c6e4e9cb3e3ef814730693aed59bd87b31d"; 101];
+
+    context
+        .expect_status_code(400)
+        .post("/transactions/by_hashes", json!(hashes))
+        .await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_estimate_gas_price() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account);
+    context.commit_block(&vec![txn.clone()]).await;
+
+    let txns = context.get("/transactions?start=2&limit=1").await;
+    let resp = context.get("/estimate_gas_price").await;
+    assert_eq!(resp["gas_estimate"], txns[0]["gas_unit_price"]);
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_get_transaction_by_hash_not_found() {
     let mut context = new_test_context(current_function_name!());
@@ -717,6 +763,80 @@ async fn test_get_account_transactions() {
     assert_json(txns, expected_txns);
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_transactions_by_involvement() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account);
+    context.commit_block(&vec![txn]).await;
+
+    // The new account didn't send the transaction creating it, but was affected by it.
+    let sent = context
+        .get(
+            format!(
+                "/accounts/{}/transactions?involvement=sender",
+                account.address()
+            )
+            .as_str(),
+        )
+        .await;
+    assert_json(sent, json!([]));
+
+    let affected = context
+        .get(
+            format!(
+                "/accounts/{}/transactions?involvement=all",
+                account.address()
+            )
+            .as_str(),
+        )
+        .await;
+    let expected_txns = context.get("/transactions?start=2&limit=1").await;
+    assert_json(affected, expected_txns);
+
+    let affected = context
+        .get(
+            format!(
+                "/accounts/{}/transactions?involvement=all&start=3",
+                account.address()
+            )
+            .as_str(),
+        )
+        .await;
+    assert_json(affected, json!([]));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_transaction_by_sequence_number() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account);
+    context.commit_block(&vec![txn]).await;
+
+    let resp = context
+        .get(
+            format!(
+                "/accounts/{}/transactions/0",
+                context.root_account().address()
+            )
+            .as_str(),
+        )
+        .await;
+    let expected_txns = context.get("/transactions?start=2&limit=1").await;
+    assert_json(resp, expected_txns[0].clone());
+
+    context
+        .expect_status_code(404)
+        .get(
+            format!(
+                "/accounts/{}/transactions/1",
+                context.root_account().address()
+            )
+            .as_str(),
+        )
+        .await;
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_get_account_transactions_filter_transactions_by_start_sequence_number() {
     let mut context = new_test_context(current_function_name!());
@@ -0,0 +1,108 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use super::new_test_context;
+use crate::current_function_name;
+use serde_json::json;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_view_function() {
+    let context = new_test_context(current_function_name!());
+    let resp = context
+        .post(
+            "/view",
+            json!({
+                "function": "0x1::account::get_sequence_number",
+                "type_arguments": [],
+                "arguments": [context.root_account().address().to_hex_literal()],
+            }),
+        )
+        .await;
+    assert_eq!(resp, json!(["0"]));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_view_function_with_type_arguments() {
+    let context = new_test_context(current_function_name!());
+    let resp = context
+        .post(
+            "/view",
+            json!({
+                "function": "0x1::coin::is_coin_initialized",
+                "type_arguments": ["0x1::aptos_coin::AptosCoin"],
+                "arguments": [],
+            }),
+        )
+        .await;
+    assert_eq!(resp, json!([true]));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_view_function_at_ledger_version() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account);
+    context.commit_block(&vec![txn]).await;
+
+    let request = json!({
+        "function": "0x1::account::exists_at",
+        "type_arguments": [],
+        "arguments": [account.address().to_hex_literal()],
+    });
+    let resp = context.post("/view", request.clone()).await;
+    assert_eq!(resp, json!([true]));
+    let resp = context.post("/view?ledger_version=0", request).await;
+    assert_eq!(resp, json!([false]));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_view_function_with_invalid_arguments() {
+    let context = new_test_context(current_function_name!());
+    let resp = context
+        .expect_status_code(400)
+        .post(
+            "/view",
+            json!({
+                "function": "0x1::account::get_sequence_number",
+                "type_arguments": [],
+                "arguments": [],
+            }),
+        )
+        .await;
+    assert!(resp["message"]
+        .as_str()
+        .unwrap()
+        .contains("expected 1 arguments"));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_view_function_rejects_non_public_function() {
+    let context = new_test_context(current_function_name!());
+    context
+        .expect_status_code(400)
+        .post(
+            "/view",
+            json!({
+                "function": "0x1::account::create_account_internal",
+                "type_arguments": [],
+                "arguments": ["0x1"],
+            }),
+        )
+        .await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_view_function_ledger_version_too_large() {
+    let context = new_test_context(current_function_name!());
+    context
+        .expect_status_code(404)
+        .post(
+            "/view?ledger_version=1000000000000000000",
+            json!({
+                "function": "0x1::account::exists_at",
+                "type_arguments": [],
+                "arguments": ["0x1"],
+            }),
+        )
+        .await;
+}
@@ -7,11 +7,12 @@ use crate::{
         ModuleBundlePayload, StateCheckpointTransaction, UserTransactionRequestInner, WriteModule,
         WriteResource, WriteTableItem,
     },
-    Bytecode, DirectWriteSet, Event, HexEncodedBytes, MoveFunction, MoveModuleBytecode,
-    MoveResource, MoveScriptBytecode, MoveValue, PendingTransaction, ScriptFunctionId,
-    ScriptFunctionPayload, ScriptPayload, ScriptWriteSet, SubmitTransactionRequest, Transaction,
-    TransactionInfo, TransactionOnChainData, TransactionPayload, UserTransactionRequest,
-    VersionedEvent, WriteSet, WriteSetChange, WriteSetPayload,
+    Bytecode, DirectWriteSet, Event, HexEncodedBytes, MoveFunction, MoveFunctionVisibility,
+    MoveModuleBytecode, MoveResource, MoveScriptBytecode, MoveType, MoveValue, PendingTransaction,
+    ScriptFunctionId, ScriptFunctionPayload, ScriptPayload, ScriptWriteSet,
+    SubmitTransactionRequest, Transaction, TransactionInfo, TransactionOnChainData,
+    TransactionPayload, UserTransactionRequest, VersionedEvent, ViewFunction, ViewRequest,
+    WriteSet, WriteSetChange, WriteSetPayload,
 };
 use anyhow::{bail, ensure, format_err, Context as AnyhowContext, Result};
 use aptos_crypto::{hash::CryptoHash, HashValue};
@@ -541,6 +542,62 @@ impl<'a, R: MoveResolverExt + ?Sized> MoveConverter<'a, R> {
         Ok(ret)
     }
 
+    pub fn try_into_view_function(&self, request: ViewRequest) -> Result<ViewFunction> {
+        let ViewRequest {
+            function,
+            type_arguments,
+            arguments,
+        } = request;
+
+        let module = function.module.clone();
+        let code = self.inner.get_module(&module.clone().into())? as Rc<dyn Bytecode>;
+        let func = code
+            .find_function(function.name.0.as_ident_str())
+            .ok_or_else(|| format_err!("could not find function by {}", function))?;
+        ensure!(
+            func.visibility == MoveFunctionVisibility::Public,
+            "function {} is not public, only public functions can be viewed",
+            function
+        );
+        ensure!(
+            func.generic_type_params.len() == type_arguments.len(),
+            "expect {} type arguments for function {}, but got {}",
+            func.generic_type_params.len(),
+            function,
+            type_arguments.len()
+        );
+        ensure!(
+            !func.params.iter().any(|p| p.is_signer()),
+            "function {} takes a signer, which cannot be provided to a view function",
+            function
+        );
+
+        let return_types = func
+            .return_
+            .iter()
+            .map(|typ| match typ.instantiate(&type_arguments)? {
+                MoveType::Reference { mutable: _, to } => (*to).try_into(),
+                typ => typ.try_into(),
+            })
+            .collect::<Result<_>>()?;
+        let args = self
+            .try_into_vm_values(func, arguments)?
+            .iter()
+            .map(bcs::to_bytes)
+            .collect::<Result<_, bcs::Error>>()?;
+
+        Ok(ViewFunction {
+            module: module.into(),
+            function: function.name.into(),
+            type_arguments: type_arguments
+                .into_iter()
+                .map(|v| v.try_into())
+                .collect::<Result<_>>()?,
+            arguments: args,
+            return_types,
+        })
+    }
+
     pub fn try_into_vm_values(
         &self,
         func: MoveFunction,
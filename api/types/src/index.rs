@@ -1,7 +1,7 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::LedgerInfo;
+use crate::{LedgerInfo, U64};
 use aptos_config::config::RoleType;
 use poem_openapi::Object as PoemObject;
 use serde::{Deserialize, Serialize};
@@ -16,13 +16,21 @@ pub struct IndexResponse {
     #[oai(flatten)]
     #[serde(flatten)]
     pub ledger_info: LedgerInfo,
+    /// The oldest version at which the node can still serve state, such as
+    /// account resources and table items.
+    pub oldest_state_version: U64,
     pub node_role: RoleType,
 }
 
 impl IndexResponse {
-    pub fn new(ledger_info: LedgerInfo, node_role: RoleType) -> IndexResponse {
+    pub fn new(
+        ledger_info: LedgerInfo,
+        oldest_state_version: u64,
+        node_role: RoleType,
+    ) -> IndexResponse {
         Self {
             ledger_info,
+            oldest_state_version: oldest_state_version.into(),
             node_role,
         }
     }
@@ -15,8 +15,10 @@ mod ledger_info;
 pub mod mime_types;
 mod move_types;
 mod response;
+mod state;
 mod table;
 mod transaction;
+mod view;
 mod wrappers;
 
 pub use account::AccountData;
@@ -32,16 +34,18 @@ pub use ledger_info::LedgerInfo;
 pub use move_types::{
     HexEncodedBytes, MoveAbility, MoveFunction, MoveFunctionGenericTypeParam,
     MoveFunctionVisibility, MoveModule, MoveModuleBytecode, MoveModuleId, MoveResource,
-    MoveScriptBytecode, MoveStruct, MoveStructField, MoveStructTag, MoveType, MoveValue,
-    ScriptFunctionId, U128, U64,
+    MoveResourceVersion, MoveScriptBytecode, MoveStruct, MoveStructField, MoveStructTag, MoveType,
+    MoveValue, ScriptFunctionId, U128, U64,
 };
 pub use response::{
     Response, X_APTOS_CHAIN_ID, X_APTOS_EPOCH, X_APTOS_LEDGER_TIMESTAMP, X_APTOS_LEDGER_VERSION,
 };
+pub use state::RawStateValueRequest;
 pub use table::TableItemRequest;
 pub use transaction::{
     AccountSignature, BlockMetadataTransaction, DeleteModule, DeleteResource, DeleteTableItem,
-    DirectWriteSet, Ed25519Signature, EncodeSubmissionRequest, Event, GenesisPayload,
+    DirectWriteSet, Ed25519Signature, EncodeSubmissionRequest, Event, GasEstimation, GasProfile,
+    GasProfileCategory, GasProfileFunction, GasProfileInstruction, GenesisPayload,
     GenesisTransaction, MultiEd25519Signature, PendingTransaction, ScriptFunctionPayload,
     ScriptPayload, ScriptWriteSet, SubmitTransactionRequest, Transaction, TransactionData,
     TransactionId, TransactionInfo, TransactionOnChainData, TransactionPayload,
@@ -49,4 +53,5 @@ pub use transaction::{
     UserTransaction, UserTransactionRequest, VersionedEvent, WriteModule, WriteResource, WriteSet,
     WriteSetChange, WriteSetPayload, WriteTableItem,
 };
+pub use view::{ViewFunction, ViewRequest};
 pub use wrappers::{IdentifierWrapper, MoveStructTagParam};
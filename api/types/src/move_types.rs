@@ -40,6 +40,15 @@ pub struct MoveResource {
     pub data: MoveStructValue,
 }
 
+/// A version at which a resource was changed, and the resource as of that version
+///
+/// `resource` is missing if the resource was deleted at that version.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Object)]
+pub struct MoveResourceVersion {
+    pub version: U64,
+    pub resource: Option<MoveResource>,
+}
+
 impl TryFrom<AnnotatedMoveStruct> for MoveResource {
     type Error = anyhow::Error;
 
@@ -582,6 +591,33 @@ impl MoveType {
             _ => false,
         }
     }
+
+    /// Replaces every generic type param with the matching type argument.
+    pub fn instantiate(&self, type_args: &[MoveType]) -> anyhow::Result<MoveType> {
+        Ok(match self {
+            MoveType::GenericTypeParam { index } => {
+                type_args.get(*index as usize).cloned().ok_or_else(|| {
+                    format_err!("missing type argument for generic type param T{}", index)
+                })?
+            }
+            MoveType::Vector { items } => MoveType::Vector {
+                items: Box::new(items.instantiate(type_args)?),
+            },
+            MoveType::Struct(tag) => MoveType::Struct(MoveStructTag {
+                generic_type_params: tag
+                    .generic_type_params
+                    .iter()
+                    .map(|t| t.instantiate(type_args))
+                    .collect::<anyhow::Result<_>>()?,
+                ..tag.clone()
+            }),
+            MoveType::Reference { mutable, to } => MoveType::Reference {
+                mutable: *mutable,
+                to: Box::new(to.instantiate(type_args)?),
+            },
+            _ => self.clone(),
+        })
+    }
 }
 
 impl From<TypeTag> for MoveType {
@@ -1269,6 +1305,28 @@ mod tests {
         test_serialize_deserialize(HexEncodedBytes::from(bytes), json!("0xabcd"))
     }
 
+    #[test]
+    fn test_instantiate_generic_move_type() {
+        let typ = MoveType::Vector {
+            items: Box::new(MoveType::Struct(MoveStructTag::new(
+                address("0x1").into(),
+                identifier("coin").into(),
+                identifier("CoinStore").into(),
+                vec![MoveType::GenericTypeParam { index: 0 }],
+            ))),
+        };
+        let instantiated = typ
+            .instantiate(&["0x1::aptos_coin::AptosCoin".parse().unwrap()])
+            .unwrap();
+        assert_eq!(
+            instantiated.to_string(),
+            "vector<0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>>"
+        );
+        assert!(MoveType::GenericTypeParam { index: 1 }
+            .instantiate(&[MoveType::U8])
+            .is_err());
+    }
+
     fn test_serialize_deserialize<O>(obj: O, expected: Value)
     where
         O: Serialize + DeserializeOwned + PartialEq + Debug,
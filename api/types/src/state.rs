@@ -0,0 +1,15 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::HexEncodedBytes;
+use poem_openapi::Object;
+use serde::{Deserialize, Serialize};
+
+/// Request to read the bytes stored under a state key, as the Move VM reads
+/// them.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Object)]
+pub struct RawStateValueRequest {
+    /// The state key, BCS encoded. See StateKey in
+    /// types/src/state_store/state_key.rs.
+    pub key: HexEncodedBytes,
+}
@@ -243,6 +243,8 @@ impl
             request: (txn, payload).into(),
             events,
             timestamp: timestamp.into(),
+            recommended_max_gas_amount: None,
+            gas_profile: None,
         }))
     }
 }
@@ -317,6 +319,67 @@ impl From<(SignedTransaction, TransactionPayload)> for PendingTransaction {
     }
 }
 
+/// The gas unit price the node estimates a transaction should use to be
+/// committed promptly.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Object)]
+pub struct GasEstimation {
+    pub gas_estimate: U64,
+}
+
+/// What a simulated transaction was charged gas for, broken down by
+/// function and by instruction.
+///
+/// Amounts are in internal gas units: divide them by
+/// `gas_unit_scaling_factor` to get gas units.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Object)]
+pub struct GasProfile {
+    pub gas_unit_scaling_factor: U64,
+    /// Gas charged for the size of the transaction.
+    pub intrinsic: U64,
+    /// Gas charged for executing bytecode instructions.
+    pub execution: U64,
+    /// Gas charged by native functions.
+    pub natives: U64,
+    /// The part of `execution` charged by instructions accessing global
+    /// storage.
+    pub storage: U64,
+    /// Execution gas by kind of instruction, most expensive first.
+    pub categories: Vec<GasProfileCategory>,
+    /// Execution and native function gas by function, most expensive first.
+    pub functions: Vec<GasProfileFunction>,
+}
+
+/// How often a function was called, and the gas charged while it ran.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Object)]
+pub struct GasProfileFunction {
+    /// The function, e.g. `0x1::coin::transfer`, or `script`.
+    pub name: String,
+    pub calls: U64,
+    /// Gas charged by the function and the functions it called.
+    pub gas: U64,
+    /// Gas charged by the function itself.
+    pub self_gas: U64,
+}
+
+/// How often instructions of a kind were executed, and the gas charged for
+/// them.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Object)]
+pub struct GasProfileCategory {
+    pub name: String,
+    pub count: U64,
+    pub gas: U64,
+    /// The instructions of this kind, most expensive first.
+    pub instructions: Vec<GasProfileInstruction>,
+}
+
+/// How often an instruction was executed, and the gas charged for it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Object)]
+pub struct GasProfileInstruction {
+    pub name: String,
+    pub count: U64,
+    pub gas: U64,
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Object)]
 pub struct UserTransaction {
     #[serde(flatten)]
@@ -327,6 +390,16 @@ pub struct UserTransaction {
     pub request: UserTransactionRequest,
     pub events: Vec<Event>,
     pub timestamp: U64,
+    /// The `max_gas_amount` recommended for submitting this transaction: the
+    /// gas it used plus a safety margin. This is only set for simulations.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[oai(skip_serializing_if_is_none)]
+    pub recommended_max_gas_amount: Option<U64>,
+    /// What the transaction was charged gas for. This is only set for
+    /// simulations requested with `profile_gas`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[oai(skip_serializing_if_is_none)]
+    pub gas_profile: Option<GasProfile>,
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Object)]
@@ -0,0 +1,31 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{MoveType, ScriptFunctionId};
+use move_deps::move_core_types::{
+    identifier::Identifier,
+    language_storage::{ModuleId, TypeTag},
+};
+use poem_openapi::Object;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Request to execute a public Move function without submitting a transaction.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Object)]
+pub struct ViewRequest {
+    pub function: ScriptFunctionId,
+    pub type_arguments: Vec<MoveType>,
+    pub arguments: Vec<Value>,
+}
+
+/// A `ViewRequest` that has been checked against the function ABI and
+/// converted into the types the VM consumes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ViewFunction {
+    pub module: ModuleId,
+    pub function: Identifier,
+    pub type_arguments: Vec<TypeTag>,
+    pub arguments: Vec<Vec<u8>>,
+    /// Return types of the function, with generic type params instantiated.
+    pub return_types: Vec<TypeTag>,
+}
@@ -5,9 +5,12 @@ use crate::{instr::InstructionGasParameters, transaction::TransactionGasParamete
 use move_binary_format::{
     errors::{Location, PartialVMError, PartialVMResult, VMResult},
     file_format_common::Opcodes,
+    CompiledModule,
 };
 use move_core_types::{
     gas_schedule::{AbstractMemorySize, GasAlgebra, GasCarrier},
+    identifier::IdentStr,
+    language_storage::ModuleId,
     vm_status::StatusCode,
 };
 use move_vm_types::gas::GasMeter;
@@ -168,3 +171,43 @@ impl AptosGasMeter {
             .map_err(|e| e.finish(Location::Undefined))
     }
 }
+
+/// A function the VM runs on behalf of a transaction, as opposed to one called from Move code.
+#[derive(Clone, Copy, Debug)]
+pub enum TransactionFunction<'a> {
+    /// A script function, called by the transaction payload.
+    ScriptFunction(&'a ModuleId, &'a IdentStr),
+    /// The `init_module` function of a module being published.
+    ModuleInitializer(&'a CompiledModule),
+    /// A script, by its bytecode.
+    Script(&'a [u8]),
+}
+
+/// The gas meter the VM charges a user transaction with.
+pub trait TransactionGasMeter: GasMeter {
+    /// Returns the remaining balance, in gas units.
+    fn balance(&self) -> u64;
+
+    /// Returns the remaining balance, in the internal units instructions are charged in.
+    fn internal_balance(&self) -> u64;
+
+    fn charge_intrinsic_gas_for_transaction(&mut self, txn_size: u64) -> VMResult<()>;
+
+    /// Called before the VM starts running `function`. The instructions charged until it returns
+    /// are executed by `function` and the functions it calls.
+    fn start_function(&mut self, _function: TransactionFunction) {}
+}
+
+impl TransactionGasMeter for AptosGasMeter {
+    fn balance(&self) -> u64 {
+        AptosGasMeter::balance(self)
+    }
+
+    fn internal_balance(&self) -> u64 {
+        self.balance
+    }
+
+    fn charge_intrinsic_gas_for_transaction(&mut self, txn_size: u64) -> VMResult<()> {
+        AptosGasMeter::charge_intrinsic_gas_for_transaction(self, txn_size)
+    }
+}
@@ -8,11 +8,15 @@ mod aptos_framework;
 mod gas_meter;
 mod instr;
 mod move_stdlib;
+mod profile;
+mod profiler;
 mod transaction;
 
 pub use gas_meter::{
     AptosGasMeter, AptosGasParameters, FromOnChainGasSchedule, InitialGasSchedule,
-    NativeGasParameters, ToOnChainGasSchedule,
+    NativeGasParameters, ToOnChainGasSchedule, TransactionFunction, TransactionGasMeter,
 };
 pub use instr::InstructionGasParameters;
+pub use profile::{FunctionCost, GasProfile, InstructionCategory, InstructionCost};
+pub use profiler::GasProfiler;
 pub use transaction::TransactionGasParameters;
@@ -0,0 +1,248 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use move_binary_format::file_format_common::Opcodes;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The kinds of bytecode instructions, for breaking down execution gas.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum InstructionCategory {
+    ControlFlow,
+    Call,
+    Constant,
+    Local,
+    Reference,
+    Struct,
+    Arithmetic,
+    Vector,
+    GlobalStorage,
+}
+
+impl InstructionCategory {
+    pub fn of(opcode: Opcodes) -> Self {
+        use Opcodes::*;
+
+        match opcode {
+            CALL | CALL_GENERIC => Self::Call,
+            LD_U8 | LD_U64 | LD_U128 | LD_TRUE | LD_FALSE | LD_CONST => Self::Constant,
+            COPY_LOC | MOVE_LOC | ST_LOC | IMM_BORROW_LOC | MUT_BORROW_LOC => Self::Local,
+            IMM_BORROW_FIELD
+            | MUT_BORROW_FIELD
+            | IMM_BORROW_FIELD_GENERIC
+            | MUT_BORROW_FIELD_GENERIC
+            | FREEZE_REF
+            | READ_REF
+            | WRITE_REF => Self::Reference,
+            PACK | PACK_GENERIC | UNPACK | UNPACK_GENERIC => Self::Struct,
+            CAST_U8 | CAST_U64 | CAST_U128 | ADD | SUB | MUL | MOD | DIV | BIT_OR | BIT_AND
+            | XOR | SHL | SHR | OR | AND | NOT | LT | GT | LE | GE | EQ | NEQ => Self::Arithmetic,
+            VEC_PACK | VEC_LEN | VEC_IMM_BORROW | VEC_MUT_BORROW | VEC_PUSH_BACK | VEC_POP_BACK
+            | VEC_UNPACK | VEC_SWAP => Self::Vector,
+            EXISTS
+            | EXISTS_GENERIC
+            | IMM_BORROW_GLOBAL
+            | IMM_BORROW_GLOBAL_GENERIC
+            | MUT_BORROW_GLOBAL
+            | MUT_BORROW_GLOBAL_GENERIC
+            | MOVE_FROM
+            | MOVE_FROM_GENERIC
+            | MOVE_TO
+            | MOVE_TO_GENERIC => Self::GlobalStorage,
+            _ => Self::ControlFlow,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::ControlFlow => "control_flow",
+            Self::Call => "call",
+            Self::Constant => "constant",
+            Self::Local => "local",
+            Self::Reference => "reference",
+            Self::Struct => "struct",
+            Self::Arithmetic => "arithmetic",
+            Self::Vector => "vector",
+            Self::GlobalStorage => "global_storage",
+        }
+    }
+}
+
+/// How often an instruction was executed, and the gas charged for it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct InstructionCost {
+    pub category: InstructionCategory,
+    pub count: u64,
+    pub gas: u64,
+}
+
+/// How often a function was called, and the gas charged while it ran.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct FunctionCost {
+    pub calls: u64,
+    /// Gas charged by the function and the functions it called.
+    pub gas: u64,
+    /// Gas charged by the function itself.
+    pub self_gas: u64,
+}
+
+/// The gas charged by a transaction, broken down by what it was charged for, as recorded by a
+/// `GasProfiler`.
+///
+/// All amounts are in internal gas units.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct GasProfile {
+    /// Gas charged for the size of the transaction, before execution.
+    pub intrinsic: u64,
+    /// Gas charged by native functions.
+    pub natives: u64,
+    /// Gas charged per bytecode instruction, by the name of the opcode.
+    pub instructions: BTreeMap<String, InstructionCost>,
+    /// Gas charged while running functions, by the call stack it was charged in, outermost
+    /// function first. Native functions are the last frame of the stacks they charged gas in.
+    pub call_stacks: BTreeMap<Vec<String>, u64>,
+    /// How often each function was called.
+    pub calls: BTreeMap<String, u64>,
+}
+
+impl GasProfile {
+    pub(crate) fn record_instruction(&mut self, opcode: Opcodes, gas: u64) {
+        let cost = self
+            .instructions
+            .entry(format!("{:?}", opcode))
+            .or_insert(InstructionCost {
+                category: InstructionCategory::of(opcode),
+                count: 0,
+                gas: 0,
+            });
+        cost.count += 1;
+        cost.gas += gas;
+    }
+
+    /// Returns how often instructions of each category were executed, and the gas charged.
+    pub fn categories(&self) -> BTreeMap<InstructionCategory, (u64, u64)> {
+        let mut categories = BTreeMap::new();
+        for cost in self.instructions.values() {
+            let (count, gas) = categories.entry(cost.category).or_insert((0, 0));
+            *count += cost.count;
+            *gas += cost.gas;
+        }
+        categories
+    }
+
+    /// Returns the gas charged for executing bytecode instructions.
+    pub fn execution(&self) -> u64 {
+        self.instructions.values().map(|cost| cost.gas).sum()
+    }
+
+    /// Returns the gas charged by instructions accessing global storage.
+    pub fn storage(&self) -> u64 {
+        self.instructions
+            .values()
+            .filter(|cost| cost.category == InstructionCategory::GlobalStorage)
+            .map(|cost| cost.gas)
+            .sum()
+    }
+
+    /// Returns how often each function was called, and the gas charged while it ran.
+    pub fn functions(&self) -> BTreeMap<String, FunctionCost> {
+        let mut functions: BTreeMap<String, FunctionCost> = self
+            .calls
+            .iter()
+            .map(|(name, calls)| {
+                let cost = FunctionCost {
+                    calls: *calls,
+                    ..FunctionCost::default()
+                };
+                (name.clone(), cost)
+            })
+            .collect();
+        for (stack, gas) in &self.call_stacks {
+            // Recursive functions appear on a stack more than once, but ran for its gas only once
+            let names: BTreeSet<_> = stack.iter().collect();
+            for name in names {
+                functions.entry(name.clone()).or_default().gas += gas;
+            }
+            if let Some(name) = stack.last() {
+                functions.entry(name.clone()).or_default().self_gas += gas;
+            }
+        }
+        functions
+    }
+
+    /// Returns all gas charged.
+    pub fn total(&self) -> u64 {
+        self.intrinsic + self.natives + self.execution()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn breaks_down_by_category() {
+        let mut profile = GasProfile::default();
+        profile.record_instruction(Opcodes::ADD, 3);
+        profile.record_instruction(Opcodes::ADD, 3);
+        profile.record_instruction(Opcodes::MOVE_TO, 10);
+        profile.record_instruction(Opcodes::RET, 1);
+        profile.natives = 7;
+        profile.intrinsic = 100;
+
+        assert_eq!(profile.instructions["ADD"].count, 2);
+        assert_eq!(profile.execution(), 17);
+        assert_eq!(profile.storage(), 10);
+        assert_eq!(profile.total(), 124);
+        let categories = profile.categories();
+        assert_eq!(categories[&InstructionCategory::Arithmetic], (2, 6));
+        assert_eq!(categories[&InstructionCategory::ControlFlow], (1, 1));
+    }
+
+    #[test]
+    fn breaks_down_by_function() {
+        let stack = |names: &[&str]| names.iter().map(|name| name.to_string()).collect();
+        let profile = GasProfile {
+            call_stacks: [
+                (stack(&["f"]), 5),
+                (stack(&["f", "g"]), 3),
+                (stack(&["f", "g", "g"]), 2),
+                (stack(&["f", "n"]), 7),
+            ]
+            .into_iter()
+            .collect(),
+            calls: [("f", 1), ("g", 2), ("n", 1)]
+                .into_iter()
+                .map(|(name, calls)| (name.to_string(), calls))
+                .collect(),
+            ..GasProfile::default()
+        };
+
+        let functions = profile.functions();
+        assert_eq!(functions.len(), 3);
+        assert_eq!(
+            functions["f"],
+            FunctionCost {
+                calls: 1,
+                gas: 17,
+                self_gas: 5
+            }
+        );
+        // The recursive call's gas is counted once
+        assert_eq!(
+            functions["g"],
+            FunctionCost {
+                calls: 2,
+                gas: 5,
+                self_gas: 5
+            }
+        );
+        assert_eq!(
+            functions["n"],
+            FunctionCost {
+                calls: 1,
+                gas: 7,
+                self_gas: 7
+            }
+        );
+    }
+}
@@ -0,0 +1,572 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    gas_meter::{TransactionFunction, TransactionGasMeter},
+    profile::GasProfile,
+};
+use move_binary_format::{
+    access::{ModuleAccess, ScriptAccess},
+    errors::{PartialVMResult, VMResult},
+    file_format::{Bytecode, CodeOffset, CompiledModule, CompiledScript},
+    file_format_common::{instruction_key, Opcodes},
+};
+use move_core_types::{
+    gas_schedule::{AbstractMemorySize, GasCarrier},
+    ident_str,
+    identifier::{IdentStr, Identifier},
+    language_storage::ModuleId,
+};
+use move_vm_types::gas::GasMeter;
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::Arc,
+};
+
+/// The name of the functions a call couldn't be resolved to.
+const UNKNOWN_FUNCTION: &str = "<unknown>";
+
+/// Resolves the function called by `$instruction`, in `$code`, a module or a script.
+macro_rules! callee {
+    ($code:expr, $instruction:expr) => {{
+        let code = $code;
+        let handle = match $instruction {
+            Bytecode::Call(handle) => *handle,
+            Bytecode::CallGeneric(index) => code.function_instantiation_at(*index).handle,
+            _ => return None,
+        };
+        let handle = code.function_handle_at(handle);
+        let module_handle = code.module_handle_at(handle.module);
+        let module_id = ModuleId::new(
+            *code.address_identifier_at(module_handle.address),
+            code.identifier_at(module_handle.name).to_owned(),
+        );
+        Some((module_id, code.identifier_at(handle.name).to_owned()))
+    }};
+}
+
+/// The code of a function the profiler follows the instructions of.
+enum Code {
+    /// A function, by the index of its definition in its module.
+    Function(Arc<CompiledModule>, usize),
+    Script(Arc<CompiledScript>),
+}
+
+impl Code {
+    fn instructions(&self) -> &[Bytecode] {
+        match self {
+            Code::Function(module, index) => match &module.function_defs()[*index].code {
+                Some(code) => &code.code,
+                None => &[],
+            },
+            Code::Script(script) => &script.code().code,
+        }
+    }
+
+    /// Returns the module and name of the function `instruction` calls, if it's a call.
+    fn callee(&self, instruction: &Bytecode) -> Option<(ModuleId, Identifier)> {
+        match self {
+            Code::Function(module, _) => callee!(module.as_ref(), instruction),
+            Code::Script(script) => callee!(script.as_ref(), instruction),
+        }
+    }
+}
+
+/// A function running on the call stack.
+struct Frame {
+    name: String,
+    code: Option<Code>,
+    /// The offsets in `code` the next instruction charged may be at. After a branch, there are
+    /// several until the instructions charged next tell them apart. If none of them holds the
+    /// instruction charged, the position is lost, and so are the functions called from here on.
+    pcs: BTreeSet<CodeOffset>,
+}
+
+impl Frame {
+    fn new(name: String, code: Option<Code>) -> Self {
+        Self {
+            name,
+            code,
+            pcs: [0].into_iter().collect(),
+        }
+    }
+
+    /// Moves past an instruction charged as `opcode`, and returns the functions it may call.
+    fn step(&mut self, opcode: Opcodes) -> BTreeSet<(ModuleId, Identifier)> {
+        let mut callees = BTreeSet::new();
+        let code = match &self.code {
+            Some(code) => code,
+            None => return callees,
+        };
+        let instructions = code.instructions();
+        let mut next = BTreeSet::new();
+        for pc in &self.pcs {
+            let instruction = match instructions.get(*pc as usize) {
+                Some(instruction) if instruction_key(instruction) == opcode as u8 => instruction,
+                _ => continue,
+            };
+            callees.extend(code.callee(instruction));
+            match instruction {
+                Bytecode::BrTrue(target) | Bytecode::BrFalse(target) => {
+                    next.insert(*target);
+                    next.insert(pc + 1);
+                }
+                Bytecode::Branch(target) => {
+                    next.insert(*target);
+                }
+                _ => {
+                    next.insert(pc + 1);
+                }
+            }
+        }
+        self.pcs = next;
+        callees
+    }
+}
+
+/// What a call resolves to.
+enum Callee {
+    Function(Frame),
+    Native(String),
+    /// A function without code to follow. Whether it's native is only known by what is charged
+    /// next: gas charged by natives, or an instruction of the function.
+    Unresolved(String),
+}
+
+/// A gas meter that records what `base` charges gas for, and the call stacks it charges it in.
+///
+/// The VM doesn't tell gas meters which functions it calls, so the profiler follows the bytecode
+/// of the functions it runs along with the instructions charged, to find the functions they call.
+/// This is only meant for simulations, as it loads the modules of all the functions called.
+pub struct GasProfiler<'a, G> {
+    base: G,
+    load_module: Box<dyn Fn(&ModuleId) -> Option<CompiledModule> + 'a>,
+    modules: HashMap<ModuleId, Option<Arc<CompiledModule>>>,
+    frames: Vec<Frame>,
+    /// The native function called last, which charges gas until the caller's next instruction.
+    native: Option<String>,
+    /// A call to a function without code, until it's known whether it's native.
+    unresolved_call: Option<String>,
+    profile: GasProfile,
+}
+
+impl<'a, G: TransactionGasMeter> GasProfiler<'a, G> {
+    /// Wraps `base`. `load_module` returns the modules of the functions called, or `None` if they
+    /// can't be loaded, in which case the functions they call are unknown.
+    pub fn new(base: G, load_module: impl Fn(&ModuleId) -> Option<CompiledModule> + 'a) -> Self {
+        Self {
+            base,
+            load_module: Box::new(load_module),
+            modules: HashMap::new(),
+            frames: vec![],
+            native: None,
+            unresolved_call: None,
+            profile: GasProfile::default(),
+        }
+    }
+
+    pub fn into_profile(self) -> GasProfile {
+        self.profile
+    }
+
+    fn module(&mut self, module_id: &ModuleId) -> Option<Arc<CompiledModule>> {
+        let load_module = &self.load_module;
+        self.modules
+            .entry(module_id.clone())
+            .or_insert_with(|| load_module(module_id).map(Arc::new))
+            .clone()
+    }
+
+    fn resolve(&mut self, module_id: &ModuleId, name: &IdentStr) -> Callee {
+        let display_name = format!("{}::{}", module_id.short_str_lossless(), name);
+        let module = match self.module(module_id) {
+            Some(module) => module,
+            None => return Callee::Unresolved(display_name),
+        };
+        let index = module.function_defs().iter().position(|function| {
+            module.identifier_at(module.function_handle_at(function.function).name) == name
+        });
+        match index {
+            Some(index) if module.function_defs()[index].code.is_none() => {
+                Callee::Native(display_name)
+            }
+            Some(index) => Callee::Function(Frame::new(
+                display_name,
+                Some(Code::Function(module, index)),
+            )),
+            None => Callee::Unresolved(display_name),
+        }
+    }
+
+    fn call(&mut self, callee: Callee) {
+        let name = match &callee {
+            Callee::Function(frame) => &frame.name,
+            Callee::Native(name) | Callee::Unresolved(name) => name,
+        };
+        *self.profile.calls.entry(name.clone()).or_default() += 1;
+        match callee {
+            Callee::Function(frame) => self.frames.push(frame),
+            Callee::Native(name) => self.native = Some(name),
+            Callee::Unresolved(name) => self.unresolved_call = Some(name),
+        }
+    }
+
+    fn record_call_stack(&mut self, gas: u64) {
+        let mut stack: Vec<_> = self.frames.iter().map(|frame| frame.name.clone()).collect();
+        stack.extend(self.native.clone());
+        // Gas charged outside of functions, e.g. for publishing modules, has no call stack
+        if !stack.is_empty() {
+            *self.profile.call_stacks.entry(stack).or_default() += gas;
+        }
+    }
+
+    fn record_instruction(&mut self, opcode: Opcodes, gas: u64) {
+        self.native = None;
+        if let Some(name) = self.unresolved_call.take() {
+            self.frames.push(Frame::new(name, None));
+        }
+        self.profile.record_instruction(opcode, gas);
+        self.record_call_stack(gas);
+
+        let callees = match self.frames.last_mut() {
+            Some(frame) => frame.step(opcode),
+            None => BTreeSet::new(),
+        };
+        match opcode {
+            Opcodes::RET => {
+                self.frames.pop();
+            }
+            Opcodes::CALL | Opcodes::CALL_GENERIC => {
+                let callee = match callees.len() {
+                    1 => {
+                        let (module_id, name) = callees.into_iter().next().unwrap();
+                        self.resolve(&module_id, &name)
+                    }
+                    _ => Callee::Unresolved(UNKNOWN_FUNCTION.to_string()),
+                };
+                self.call(callee);
+            }
+            _ => (),
+        }
+    }
+
+    fn record_native(&mut self, gas: u64) {
+        if let Some(name) = self.unresolved_call.take() {
+            self.native = Some(name);
+        }
+        self.profile.natives += gas;
+        self.record_call_stack(gas);
+    }
+}
+
+impl<'a, G: TransactionGasMeter> GasMeter for GasProfiler<'a, G> {
+    fn charge_instr(&mut self, opcode: Opcodes) -> PartialVMResult<()> {
+        let balance = self.base.internal_balance();
+        let result = self.base.charge_instr(opcode);
+        self.record_instruction(opcode, balance - self.base.internal_balance());
+        result
+    }
+
+    fn charge_instr_with_size(
+        &mut self,
+        opcode: Opcodes,
+        size: AbstractMemorySize<GasCarrier>,
+    ) -> PartialVMResult<()> {
+        let balance = self.base.internal_balance();
+        let result = self.base.charge_instr_with_size(opcode, size);
+        self.record_instruction(opcode, balance - self.base.internal_balance());
+        result
+    }
+
+    fn charge_in_native_unit(&mut self, amount: u64) -> PartialVMResult<()> {
+        let balance = self.base.internal_balance();
+        let result = self.base.charge_in_native_unit(amount);
+        self.record_native(balance - self.base.internal_balance());
+        result
+    }
+}
+
+impl<'a, G: TransactionGasMeter> TransactionGasMeter for GasProfiler<'a, G> {
+    fn balance(&self) -> u64 {
+        self.base.balance()
+    }
+
+    fn internal_balance(&self) -> u64 {
+        self.base.internal_balance()
+    }
+
+    fn charge_intrinsic_gas_for_transaction(&mut self, txn_size: u64) -> VMResult<()> {
+        let balance = self.base.internal_balance();
+        let result = self.base.charge_intrinsic_gas_for_transaction(txn_size);
+        self.profile.intrinsic += balance - self.base.internal_balance();
+        result
+    }
+
+    fn start_function(&mut self, function: TransactionFunction) {
+        self.base.start_function(function);
+        self.frames.clear();
+        self.native = None;
+        self.unresolved_call = None;
+
+        let callee = match function {
+            TransactionFunction::ScriptFunction(module_id, name) => self.resolve(module_id, name),
+            TransactionFunction::ModuleInitializer(module) => {
+                // The module is being published, so it can't be loaded yet
+                let module_id = module.self_id();
+                self.modules
+                    .insert(module_id.clone(), Some(Arc::new(module.clone())));
+                self.resolve(&module_id, ident_str!("init_module"))
+            }
+            TransactionFunction::Script(code) => {
+                let script = CompiledScript::deserialize(code).ok();
+                Callee::Function(Frame::new(
+                    "script".to_string(),
+                    script.map(|script| Code::Script(Arc::new(script))),
+                ))
+            }
+        };
+        // Instructions are charged next, so the function isn't native
+        let callee = match callee {
+            Callee::Native(name) | Callee::Unresolved(name) => {
+                Callee::Function(Frame::new(name, None))
+            }
+            callee => callee,
+        };
+        self.call(callee);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profile::FunctionCost;
+    use move_binary_format::file_format::{
+        empty_module, AddressIdentifierIndex, CodeUnit, FunctionDefinition, FunctionHandle,
+        FunctionHandleIndex, IdentifierIndex, ModuleHandle, ModuleHandleIndex, SignatureIndex,
+        Visibility,
+    };
+
+    /// Charges one unit per instruction.
+    struct TestMeter {
+        balance: u64,
+    }
+
+    impl GasMeter for TestMeter {
+        fn charge_instr(&mut self, _opcode: Opcodes) -> PartialVMResult<()> {
+            self.charge_in_native_unit(1)
+        }
+
+        fn charge_instr_with_size(
+            &mut self,
+            _opcode: Opcodes,
+            _size: AbstractMemorySize<GasCarrier>,
+        ) -> PartialVMResult<()> {
+            self.charge_in_native_unit(1)
+        }
+
+        fn charge_in_native_unit(&mut self, amount: u64) -> PartialVMResult<()> {
+            self.balance -= amount;
+            Ok(())
+        }
+    }
+
+    impl TransactionGasMeter for TestMeter {
+        fn balance(&self) -> u64 {
+            self.balance
+        }
+
+        fn internal_balance(&self) -> u64 {
+            self.balance
+        }
+
+        fn charge_intrinsic_gas_for_transaction(&mut self, _txn_size: u64) -> VMResult<()> {
+            self.balance -= 100;
+            Ok(())
+        }
+    }
+
+    const F: FunctionHandleIndex = FunctionHandleIndex(0);
+    const G: FunctionHandleIndex = FunctionHandleIndex(1);
+    const N: FunctionHandleIndex = FunctionHandleIndex(2);
+    const H: FunctionHandleIndex = FunctionHandleIndex(3);
+
+    /// Builds module `0x0::m`, with the functions `f`, `g` and the native `n`, and a handle to
+    /// `0x0::other::h`.
+    fn module(f: Vec<Bytecode>, g: Vec<Bytecode>) -> CompiledModule {
+        let mut module = empty_module();
+        module.identifiers = ["m", "f", "g", "n", "other", "h"]
+            .into_iter()
+            .map(|name| Identifier::new(name).unwrap())
+            .collect();
+        module.module_handles.push(ModuleHandle {
+            address: AddressIdentifierIndex(0),
+            name: IdentifierIndex(4),
+        });
+        for (module, name) in [(0, 1), (0, 2), (0, 3), (1, 5)] {
+            module.function_handles.push(FunctionHandle {
+                module: ModuleHandleIndex(module),
+                name: IdentifierIndex(name),
+                parameters: SignatureIndex(0),
+                return_: SignatureIndex(0),
+                type_parameters: vec![],
+            });
+        }
+        for (handle, code) in [(F, Some(f)), (G, Some(g)), (N, None)] {
+            module.function_defs.push(FunctionDefinition {
+                function: handle,
+                visibility: Visibility::Public,
+                is_entry: false,
+                acquires_global_resources: vec![],
+                code: code.map(|code| CodeUnit {
+                    locals: SignatureIndex(0),
+                    code,
+                }),
+            });
+        }
+        module
+    }
+
+    fn profiler(module: CompiledModule) -> GasProfiler<'static, TestMeter> {
+        GasProfiler::new(TestMeter { balance: 1000 }, move |module_id| {
+            (module_id == &module.self_id()).then(|| module.clone())
+        })
+    }
+
+    fn run(profiler: &mut GasProfiler<TestMeter>, instructions: &[Opcodes]) {
+        for opcode in instructions {
+            profiler.charge_instr(*opcode).unwrap();
+        }
+    }
+
+    fn stack(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| format!("0x0::{}", name)).collect()
+    }
+
+    #[test]
+    fn attributes_gas_to_call_stacks() {
+        use Bytecode::*;
+
+        let module = module(
+            vec![Call(G), Call(N), LdTrue, BrTrue(5), Ret, Call(G), Ret],
+            vec![LdU64(1), Pop, Ret],
+        );
+        let module_id = module.self_id();
+        let mut profiler = profiler(module);
+        profiler.charge_intrinsic_gas_for_transaction(0).unwrap();
+        profiler.start_function(TransactionFunction::ScriptFunction(
+            &module_id,
+            ident_str!("f"),
+        ));
+        run(
+            &mut profiler,
+            &[Opcodes::CALL, Opcodes::LD_U64, Opcodes::POP, Opcodes::RET],
+        );
+        profiler.charge_instr(Opcodes::CALL).unwrap();
+        profiler.charge_in_native_unit(10).unwrap();
+        run(
+            &mut profiler,
+            &[
+                Opcodes::LD_TRUE,
+                Opcodes::BR_TRUE,
+                Opcodes::CALL,
+                Opcodes::LD_U64,
+                Opcodes::POP,
+                Opcodes::RET,
+                Opcodes::RET,
+            ],
+        );
+        assert_eq!(profiler.balance(), 1000 - 100 - 12 - 10);
+
+        let profile = profiler.into_profile();
+        assert_eq!(profile.intrinsic, 100);
+        assert_eq!(profile.natives, 10);
+        assert_eq!(profile.execution(), 12);
+        assert_eq!(
+            profile.call_stacks,
+            [
+                (stack(&["m::f"]), 6),
+                (stack(&["m::f", "m::g"]), 6),
+                (stack(&["m::f", "m::n"]), 10),
+            ]
+            .into_iter()
+            .collect()
+        );
+        let functions = profile.functions();
+        assert_eq!(
+            functions["0x0::m::f"],
+            FunctionCost {
+                calls: 1,
+                gas: 22,
+                self_gas: 6
+            }
+        );
+        assert_eq!(functions["0x0::m::g"].calls, 2);
+        assert_eq!(functions["0x0::m::n"].calls, 1);
+    }
+
+    #[test]
+    fn attributes_calls_without_code_to_their_name() {
+        use Bytecode::*;
+
+        let module = module(vec![Call(H), Call(H), Ret], vec![Ret]);
+        let module_id = module.self_id();
+        let mut profiler = profiler(module);
+        profiler.start_function(TransactionFunction::ScriptFunction(
+            &module_id,
+            ident_str!("f"),
+        ));
+        // `0x0::other` can't be loaded: the first call runs instructions, the second one charges
+        // gas as a native function would
+        run(
+            &mut profiler,
+            &[Opcodes::CALL, Opcodes::LD_TRUE, Opcodes::POP, Opcodes::RET],
+        );
+        profiler.charge_instr(Opcodes::CALL).unwrap();
+        profiler.charge_in_native_unit(10).unwrap();
+        run(&mut profiler, &[Opcodes::RET]);
+
+        let profile = profiler.into_profile();
+        assert_eq!(
+            profile.call_stacks,
+            [(stack(&["m::f"]), 3), (stack(&["m::f", "other::h"]), 13),]
+                .into_iter()
+                .collect()
+        );
+        assert_eq!(profile.calls["0x0::other::h"], 2);
+    }
+
+    #[test]
+    fn loses_calls_once_the_position_is_lost() {
+        use Bytecode::*;
+
+        let module = module(vec![LdTrue, Call(G), Ret], vec![Ret]);
+        let module_id = module.self_id();
+        let mut profiler = profiler(module);
+        profiler.start_function(TransactionFunction::ScriptFunction(
+            &module_id,
+            ident_str!("f"),
+        ));
+        // The first instruction doesn't match the code, so the callee is unknown
+        run(
+            &mut profiler,
+            &[Opcodes::LD_FALSE, Opcodes::CALL, Opcodes::RET, Opcodes::RET],
+        );
+        // Gas charged after the function returned has no call stack
+        run(&mut profiler, &[Opcodes::POP]);
+
+        let profile = profiler.into_profile();
+        assert_eq!(profile.execution(), 5);
+        assert_eq!(
+            profile.call_stacks,
+            [
+                (stack(&["m::f"]), 3),
+                (
+                    vec!["0x0::m::f".to_string(), UNKNOWN_FUNCTION.to_string()],
+                    1
+                ),
+            ]
+            .into_iter()
+            .collect()
+        );
+    }
+}
@@ -26,6 +26,8 @@ impl DBDebuggerInterface {
             NO_OP_STORAGE_PRUNER_CONFIG,
             RocksdbConfigs::default(),
             false,
+            false,
+            false,
             TARGET_SNAPSHOT_SIZE,
         )?)))
     }
@@ -21,7 +21,10 @@ use crate::{
 };
 use anyhow::Result;
 use aptos_crypto::HashValue;
-use aptos_gas::AptosGasMeter;
+use aptos_gas::{
+    AptosGasMeter, AptosGasParameters, GasProfile, GasProfiler, TransactionFunction,
+    TransactionGasMeter,
+};
 use aptos_logger::prelude::*;
 use aptos_module_verifier::module_init::verify_module_init_function;
 use aptos_state_view::StateView;
@@ -49,7 +52,9 @@ use move_deps::{
     move_core_types::{
         account_address::AccountAddress,
         ident_str,
-        language_storage::ModuleId,
+        identifier::Identifier,
+        language_storage::{ModuleId, TypeTag},
+        resolver::ModuleResolver,
         transaction_argument::convert_txn_args,
         value::{serialize_values, MoveValue},
     },
@@ -139,10 +144,10 @@ impl AptosVM {
 
     /// Generates a transaction output for a transaction that encountered errors during the
     /// execution process. This is public for now only for tests.
-    pub fn failed_transaction_cleanup<S: MoveResolverExt>(
+    pub fn failed_transaction_cleanup<S: MoveResolverExt, G: TransactionGasMeter>(
         &self,
         error_code: VMStatus,
-        gas_meter: &mut AptosGasMeter,
+        gas_meter: &mut G,
         txn_data: &TransactionMetadata,
         storage: &S,
         log_context: &AdapterLogSchema,
@@ -157,10 +162,10 @@ impl AptosVM {
         .1
     }
 
-    fn failed_transaction_cleanup_and_keep_vm_status<S: MoveResolverExt>(
+    fn failed_transaction_cleanup_and_keep_vm_status<S: MoveResolverExt, G: TransactionGasMeter>(
         &self,
         error_code: VMStatus,
-        gas_meter: &mut AptosGasMeter,
+        gas_meter: &mut G,
         txn_data: &TransactionMetadata,
         storage: &S,
         log_context: &AdapterLogSchema,
@@ -194,10 +199,10 @@ impl AptosVM {
         }
     }
 
-    fn success_transaction_cleanup<S: MoveResolverExt>(
+    fn success_transaction_cleanup<S: MoveResolverExt, G: TransactionGasMeter>(
         &self,
         mut session: SessionExt<S>,
-        gas_meter: &mut AptosGasMeter,
+        gas_meter: &mut G,
         txn_data: &TransactionMetadata,
         log_context: &AdapterLogSchema,
     ) -> Result<(VMStatus, TransactionOutputExt), VMStatus> {
@@ -216,10 +221,10 @@ impl AptosVM {
         ))
     }
 
-    fn execute_script_or_script_function<S: MoveResolverExt>(
+    fn execute_script_or_script_function<S: MoveResolverExt, G: TransactionGasMeter>(
         &self,
         mut session: SessionExt<S>,
-        gas_meter: &mut AptosGasMeter,
+        gas_meter: &mut G,
         txn_data: &TransactionMetadata,
         payload: &TransactionPayload,
         log_context: &AdapterLogSchema,
@@ -248,6 +253,7 @@ impl AptosVM {
                         convert_txn_args(script.args()),
                         &loaded_func,
                     )?;
+                    gas_meter.start_function(TransactionFunction::Script(script.code()));
                     session.execute_script(
                         script.code(),
                         script.ty_args().to_vec(),
@@ -271,6 +277,10 @@ impl AptosVM {
                         script_fn.args().to_vec(),
                         &function,
                     )?;
+                    gas_meter.start_function(TransactionFunction::ScriptFunction(
+                        script_fn.module(),
+                        script_fn.function(),
+                    ));
                     session.execute_entry_function(
                         script_fn.module(),
                         script_fn.function(),
@@ -319,10 +329,10 @@ impl AptosVM {
     }
 
     /// Execute all module initializers.
-    fn execute_module_initialization<S: MoveResolverExt>(
+    fn execute_module_initialization<S: MoveResolverExt, G: TransactionGasMeter>(
         &self,
         session: &mut SessionExt<S>,
-        gas_meter: &mut AptosGasMeter,
+        gas_meter: &mut G,
         modules: &[CompiledModule],
         senders: &[AccountAddress],
     ) -> VMResult<()> {
@@ -337,6 +347,7 @@ impl AptosVM {
                         .iter()
                         .map(|s| MoveValue::Signer(*s).simple_serialize().unwrap())
                         .collect();
+                    gas_meter.start_function(TransactionFunction::ModuleInitializer(module));
                     session.execute_function_bypass_visibility(
                         &module.self_id(),
                         init_func_name,
@@ -373,10 +384,10 @@ impl AptosVM {
     /// Execute a module bundle load request.
     /// TODO: this is going to be deprecated and removed in favor of code publishing via
     /// NativeCodeContext
-    fn execute_modules<S: MoveResolverExt>(
+    fn execute_modules<S: MoveResolverExt, G: TransactionGasMeter>(
         &self,
         mut session: SessionExt<S>,
-        gas_meter: &mut AptosGasMeter,
+        gas_meter: &mut G,
         txn_data: &TransactionMetadata,
         modules: &ModuleBundle,
         log_context: &AdapterLogSchema,
@@ -408,10 +419,10 @@ impl AptosVM {
     }
 
     /// Resolve a pending code publish request registered via the NativeCodeContext.
-    fn resolve_pending_code_publish<S: MoveResolverExt>(
+    fn resolve_pending_code_publish<S: MoveResolverExt, G: TransactionGasMeter>(
         &self,
         session: &mut SessionExt<S>,
-        gas_meter: &mut AptosGasMeter,
+        gas_meter: &mut G,
     ) -> VMResult<()> {
         if let Some(PublishRequest {
             destination,
@@ -857,7 +868,79 @@ impl AptosVM {
         let vm = AptosVM::new(state_view);
         let simulation_vm = AptosSimulationVM(vm);
         let log_context = AdapterLogSchema::new(state_view.id(), 0);
-        simulation_vm.simulate_signed_transaction(&state_view.as_move_resolver(), txn, &log_context)
+        let (vm_status, output, _) = simulation_vm.simulate_signed_transaction(
+            &state_view.as_move_resolver(),
+            txn,
+            false,
+            &log_context,
+        );
+        (vm_status, output)
+    }
+
+    /// Like `simulate_signed_transaction`, but also returns what the transaction was charged gas
+    /// for, if it got far enough to be charged.
+    pub fn simulate_signed_transaction_with_gas_profile(
+        txn: &SignedTransaction,
+        state_view: &impl StateView,
+    ) -> (VMStatus, TransactionOutputExt, Option<GasProfile>) {
+        let vm = AptosVM::new(state_view);
+        let simulation_vm = AptosSimulationVM(vm);
+        let log_context = AdapterLogSchema::new(state_view.id(), 0);
+        simulation_vm.simulate_signed_transaction(
+            &state_view.as_move_resolver(),
+            txn,
+            true,
+            &log_context,
+        )
+    }
+
+    /// Returns the gas parameters of the on-chain gas schedule in `state_view`.
+    pub fn get_gas_parameters_from_state(
+        state_view: &impl StateView,
+    ) -> Result<AptosGasParameters> {
+        let vm = AptosVM::new(state_view);
+        let log_context = AdapterLogSchema::new(state_view.id(), 0);
+        vm.0.get_gas_parameters(&log_context)
+            .cloned()
+            .map_err(|status| anyhow::anyhow!("Failed to load gas parameters: {:?}", status))
+    }
+
+    /// Executes a function against `state_view` outside of a transaction and
+    /// returns its BCS serialized return values. Nothing the function writes
+    /// is persisted, execution is bounded by `gas_budget` gas units.
+    pub fn execute_view_function(
+        state_view: &impl StateView,
+        module_id: ModuleId,
+        func_name: Identifier,
+        type_args: Vec<TypeTag>,
+        arguments: Vec<Vec<u8>>,
+        gas_budget: u64,
+    ) -> Result<Vec<Vec<u8>>> {
+        let vm = AptosVM::new(state_view);
+        let log_context = AdapterLogSchema::new(state_view.id(), 0);
+        let gas_params =
+            vm.0.get_gas_parameters(&log_context)
+                .map_err(|status| anyhow::anyhow!("Failed to load gas parameters: {:?}", status))?;
+        let mut gas_meter = AptosGasMeter::new(gas_params.clone(), gas_budget);
+
+        let resolver = state_view.as_move_resolver();
+        let mut session = vm.0.new_session(&resolver, SessionId::void());
+        let return_values = session
+            .execute_function_bypass_visibility(
+                &module_id,
+                &func_name,
+                type_args,
+                arguments,
+                &mut gas_meter,
+            )
+            .map_err(|err| {
+                anyhow::anyhow!("Failed to execute function: {:?}", err.into_vm_status())
+            })?
+            .return_values
+            .into_iter()
+            .map(|(bytes, _layout)| bytes)
+            .collect();
+        Ok(return_values)
     }
 
     fn run_prologue_with_payload<S: MoveResolverExt>(
@@ -1081,12 +1164,18 @@ impl AptosSimulationVM {
         &self,
         storage: &S,
         txn: &SignedTransaction,
+        profile_gas: bool,
         log_context: &AdapterLogSchema,
-    ) -> (VMStatus, TransactionOutputExt) {
+    ) -> (VMStatus, TransactionOutputExt, Option<GasProfile>) {
+        let discard = |err: VMStatus| {
+            let (vm_status, output) = discard_error_vm_status(err);
+            (vm_status, output, None)
+        };
+
         // simulation transactions should not carry valid signatures, otherwise malicious fullnodes
         // may execute them without user's explicit permission.
         if txn.clone().check_signature().is_ok() {
-            return discard_error_vm_status(VMStatus::Error(StatusCode::INVALID_SIGNATURE));
+            return discard(VMStatus::Error(StatusCode::INVALID_SIGNATURE));
         }
 
         // Revalidate the transaction.
@@ -1095,29 +1184,65 @@ impl AptosSimulationVM {
         if let Err(err) =
             self.validate_simulated_transaction::<S>(&mut session, txn, &txn_data, log_context)
         {
-            return discard_error_vm_status(err);
+            return discard(err);
         };
 
         let gas_params = match self.0 .0.get_gas_parameters(log_context) {
-            Err(err) => return discard_error_vm_status(err),
+            Err(err) => return discard(err),
             Ok(s) => s,
         };
         let mut gas_meter = AptosGasMeter::new(gas_params.clone(), txn_data.max_gas_amount());
 
+        if profile_gas {
+            let mut gas_profiler = GasProfiler::new(gas_meter, |module_id| {
+                let bytes = storage.get_module(module_id).ok()??;
+                CompiledModule::deserialize(&bytes).ok()
+            });
+            let (vm_status, output) = self.execute_simulated_transaction(
+                storage,
+                session,
+                txn,
+                &txn_data,
+                &mut gas_profiler,
+                log_context,
+            );
+            (vm_status, output, Some(gas_profiler.into_profile()))
+        } else {
+            let (vm_status, output) = self.execute_simulated_transaction(
+                storage,
+                session,
+                txn,
+                &txn_data,
+                &mut gas_meter,
+                log_context,
+            );
+            (vm_status, output, None)
+        }
+    }
+
+    fn execute_simulated_transaction<S: MoveResolverExt, G: TransactionGasMeter>(
+        &self,
+        storage: &S,
+        session: SessionExt<S>,
+        txn: &SignedTransaction,
+        txn_data: &TransactionMetadata,
+        gas_meter: &mut G,
+        log_context: &AdapterLogSchema,
+    ) -> (VMStatus, TransactionOutputExt) {
         let result = match txn.payload() {
             payload @ TransactionPayload::Script(_)
             | payload @ TransactionPayload::ScriptFunction(_) => {
                 self.0.execute_script_or_script_function(
                     session,
-                    &mut gas_meter,
-                    &txn_data,
+                    gas_meter,
+                    txn_data,
                     payload,
                     log_context,
                 )
             }
             TransactionPayload::ModuleBundle(m) => {
                 self.0
-                    .execute_modules(session, &mut gas_meter, &txn_data, m, log_context)
+                    .execute_modules(session, gas_meter, txn_data, m, log_context)
             }
             TransactionPayload::WriteSet(_) => {
                 return discard_error_vm_status(VMStatus::Error(StatusCode::UNREACHABLE));
@@ -1131,14 +1256,13 @@ impl AptosSimulationVM {
                 if txn_status.is_discarded() {
                     discard_error_vm_status(err)
                 } else {
-                    let (vm_status, output) = self.0.failed_transaction_cleanup_and_keep_vm_status(
+                    self.0.failed_transaction_cleanup_and_keep_vm_status(
                         err,
-                        &mut gas_meter,
-                        &txn_data,
+                        gas_meter,
+                        txn_data,
                         storage,
                         log_context,
-                    );
-                    (vm_status, output)
+                    )
                 }
             }
         }
@@ -478,6 +478,8 @@ pub fn setup_environment(node_config: NodeConfig) -> anyhow::Result<AptosHandle>
             node_config.storage.storage_pruner_config,
             node_config.storage.rocksdb_configs,
             node_config.storage.enable_indexer,
+            node_config.storage.enable_state_key_history,
+            node_config.storage.enable_affected_account_index,
             node_config.storage.target_snapshot_size,
         )
         .map_err(|err| anyhow!("DB failed to open {}", err))?,
@@ -485,6 +487,7 @@ pub fn setup_environment(node_config: NodeConfig) -> anyhow::Result<AptosHandle>
     let backup_service = start_backup_service(
         node_config.storage.backup_service_address,
         Arc::clone(&aptos_db),
+        node_config.storage.backup_service_checkpoint.clone(),
     );
 
     let genesis_waypoint = node_config.base.waypoint.genesis_waypoint();
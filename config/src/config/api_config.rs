@@ -1,9 +1,15 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::utils;
+use crate::{
+    config::{invariant, Error},
+    utils,
+};
 use serde::{Deserialize, Serialize};
-use std::net::SocketAddr;
+use std::{
+    collections::{BTreeMap, HashSet},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+};
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(default, deny_unknown_fields)]
@@ -20,11 +26,19 @@ pub struct ApiConfig {
     pub content_length_limit: Option<u64>,
     #[serde(default = "default_disabled")]
     pub failpoints_enabled: bool,
+    // gas units a single view function call may consume before it is aborted
+    pub max_view_function_gas: u64,
+    // Rate limiting of requests to the v1 API, if not specified, no rate limiting
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<ApiRateLimitConfig>,
 }
 
 pub const DEFAULT_ADDRESS: &str = "127.0.0.1";
 pub const DEFAULT_PORT: u16 = 8080;
 pub const DEFAULT_REQUEST_CONTENT_LENGTH_LIMIT: u64 = 4 * 1024 * 1024; // 4mb
+pub const DEFAULT_MAX_VIEW_FUNCTION_GAS: u64 = 2_000_000;
+pub const DEFAULT_RATE_LIMIT_BUCKET_SIZE: usize = 1000;
+pub const DEFAULT_RATE_LIMIT_FILL_RATE: usize = 100;
 
 fn default_enabled() -> bool {
     true
@@ -45,6 +59,8 @@ impl Default for ApiConfig {
             tls_key_path: None,
             content_length_limit: None,
             failpoints_enabled: default_disabled(),
+            max_view_function_gas: DEFAULT_MAX_VIEW_FUNCTION_GAS,
+            rate_limit: None,
         }
     }
 }
@@ -60,4 +76,143 @@ impl ApiConfig {
             None => DEFAULT_REQUEST_CONTENT_LENGTH_LIMIT,
         }
     }
+
+    pub fn validate(&self) -> Result<(), Error> {
+        match &self.rate_limit {
+            Some(rate_limit) => rate_limit.validate(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Rate limiting of API requests. Every request costs a number of tokens, which
+/// are taken from a token bucket belonging to the client: either the bucket of
+/// its API key, or else the bucket of its IP address.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ApiRateLimitConfig {
+    /// Allow for disabling the rate limiting
+    pub enabled: bool,
+    /// Maximum burst of tokens for an IP
+    pub ip_bucket_size: usize,
+    /// Tokens/s added to the bucket of an IP
+    pub ip_bucket_rate: usize,
+    /// API keys, by name, with their own quotas. Clients send their API key in
+    /// the `X-Aptos-Api-Key` header.
+    pub api_keys: BTreeMap<String, ApiKeyQuota>,
+    /// Tokens a transaction simulation costs
+    pub simulate_transaction_cost: usize,
+    /// Tokens a view function call costs
+    pub view_function_cost: usize,
+    /// Every this many items requested via the `limit` query param cost one
+    /// more token, so that large pages cost more
+    pub page_items_per_token: usize,
+    /// Addresses of the reverse proxies in front of the API, which are trusted
+    /// to append the address of their client to `X-Forwarded-For`. The header
+    /// is ignored in requests from any other peer. The default trusts the
+    /// proxy the node runs itself in front of the v1 API.
+    pub trusted_proxies: Vec<IpAddr>,
+}
+
+impl Default for ApiRateLimitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ip_bucket_size: DEFAULT_RATE_LIMIT_BUCKET_SIZE,
+            ip_bucket_rate: DEFAULT_RATE_LIMIT_FILL_RATE,
+            api_keys: BTreeMap::new(),
+            simulate_transaction_cost: 10,
+            view_function_cost: 5,
+            page_items_per_token: 100,
+            trusted_proxies: vec![
+                IpAddr::V4(Ipv4Addr::LOCALHOST),
+                IpAddr::V6(Ipv6Addr::LOCALHOST),
+            ],
+        }
+    }
+}
+
+impl ApiRateLimitConfig {
+    pub fn validate(&self) -> Result<(), Error> {
+        validate_bucket("IP addresses", self.ip_bucket_size, self.ip_bucket_rate)?;
+        let mut keys = HashSet::new();
+        for (name, quota) in &self.api_keys {
+            validate_bucket(
+                &format!("API key {}", name),
+                quota.bucket_size,
+                quota.bucket_rate,
+            )?;
+            invariant(
+                keys.insert(&quota.key),
+                format!("API key {} is the same as another one.", name),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn validate_bucket(owner: &str, size: usize, rate: usize) -> Result<(), Error> {
+    invariant(
+        rate > 0 && size >= rate,
+        format!(
+            "The rate limit bucket of {} must fill at a positive rate no larger than its size, \
+             got a size of {} and a rate of {}.",
+            owner, size, rate
+        ),
+    )
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApiKeyQuota {
+    /// The API key itself
+    pub key: String,
+    /// Maximum burst of tokens for this API key
+    pub bucket_size: usize,
+    /// Tokens/s added to the bucket of this API key
+    pub bucket_rate: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quota(key: &str, bucket_size: usize, bucket_rate: usize) -> ApiKeyQuota {
+        ApiKeyQuota {
+            key: key.to_string(),
+            bucket_size,
+            bucket_rate,
+        }
+    }
+
+    #[test]
+    fn test_validate_rate_limit_config() {
+        let mut config = ApiRateLimitConfig::default();
+        config
+            .api_keys
+            .insert("a".to_string(), quota("key_a", 10, 10));
+        config
+            .api_keys
+            .insert("b".to_string(), quota("key_b", 100, 1));
+        config.validate().unwrap();
+
+        for (size, rate) in [(0, 0), (10, 0), (10, 11)] {
+            let mut invalid = config.clone();
+            invalid.ip_bucket_size = size;
+            invalid.ip_bucket_rate = rate;
+            invalid.validate().unwrap_err();
+
+            let mut invalid = config.clone();
+            invalid
+                .api_keys
+                .insert("c".to_string(), quota("key_c", size, rate));
+            invalid.validate().unwrap_err();
+        }
+
+        let mut invalid = config;
+        invalid
+            .api_keys
+            .insert("c".to_string(), quota("key_a", 10, 10));
+        invalid.validate().unwrap_err();
+    }
 }
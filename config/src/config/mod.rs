@@ -263,6 +263,8 @@ impl NodeConfig {
         let input_dir = RootPath::new(input_path);
         config.execution.load(&input_dir)?;
 
+        config.api.validate()?;
+        config.storage.storage_pruner_config.validate()?;
         let mut config = config.validate_network_configs()?;
         config.set_data_dir(config.data_dir().to_path_buf());
         Ok(config)
@@ -1,7 +1,10 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::utils;
+use crate::{
+    config::{invariant, Error},
+    utils,
+};
 use serde::{Deserialize, Serialize};
 use std::{
     net::{IpAddr, Ipv4Addr, SocketAddr},
@@ -83,13 +86,40 @@ pub struct StorageConfig {
     pub rocksdb_configs: RocksdbConfigs,
     /// Try to enable the internal indexer. The indexer expects to have seen all transactions
     /// since genesis. To recover operation after data loss, or to bootstrap a node in fast sync
-    /// mode, the indexer db needs to be copied in from another node.
+    /// mode, the indexer db needs to be copied in from another node, or restored from an indexer
+    /// backup with `db-restore`.
     pub enable_indexer: bool,
+    /// Index the versions at which each state key changes, to serve the change history of
+    /// resources and table items. Only changes committed while this is enabled are indexed.
+    pub enable_state_key_history: bool,
+    /// Index transactions by every account they affect (the accounts whose state they write and
+    /// whose event handles emit their events), not just by their sender. Only transactions
+    /// committed while this is enabled are indexed.
+    pub enable_affected_account_index: bool,
+    /// Lets the backup service create checkpoints of the DBs on request. `None` disables it.
+    pub backup_service_checkpoint: Option<BackupServiceCheckpointConfig>,
+}
+
+/// Where the backup service creates checkpoints of the DBs, and who may ask it to.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct BackupServiceCheckpointConfig {
+    /// Each checkpoint is created in a new directory in here. It's best on the same file system as
+    /// the DB, so that the DB files are hard linked rather than copied.
+    pub dir: PathBuf,
+    /// File holding the token requests must carry, as `Authorization: Bearer <token>`.
+    pub auth_token_file: PathBuf,
 }
 
 pub const NO_OP_STORAGE_PRUNER_CONFIG: StoragePrunerConfig = StoragePrunerConfig {
     state_store_prune_window: None,
     ledger_prune_window: None,
+    ledger_store_prune_windows: LedgerStorePruneWindows {
+        transaction: None,
+        transaction_info: None,
+        event: None,
+        write_set: None,
+    },
     ledger_pruning_batch_size: 10_000,
     state_store_pruning_batch_size: 10_000,
 };
@@ -104,6 +134,10 @@ pub struct StoragePrunerConfig {
     /// being big in size, we might want to configure a smaller window for state store vs other
     /// store.
     pub ledger_prune_window: Option<u64>,
+    /// Prune windows of individual stores pruned by the ledger pruner, overriding
+    /// `ledger_prune_window` for them.
+    #[serde(default)]
+    pub ledger_store_prune_windows: LedgerStorePruneWindows,
     /// Batch size of the versions to be sent to the ledger pruner - this is to avoid slowdown due to
     /// issuing too many DB calls and batch prune instead. For ledger pruner, this means the number
     /// of versions to prune a time.
@@ -113,6 +147,30 @@ pub struct StoragePrunerConfig {
     pub state_store_pruning_batch_size: usize,
 }
 
+/// How much history a pruner keeps.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PruneWindow {
+    /// Keeps this many latest versions.
+    Versions(u64),
+    /// Keeps the versions committed in this many latest seconds, going by the block timestamps
+    /// rather than the local clock.
+    Seconds(u64),
+}
+
+/// Prune windows of the stores pruned by the ledger pruner. `None` means the store follows
+/// `ledger_prune_window`.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LedgerStorePruneWindows {
+    /// Transactions, and their indices by hash and by account.
+    pub transaction: Option<PruneWindow>,
+    /// Transaction infos, which proofs of transactions and their outputs are built from.
+    pub transaction_info: Option<PruneWindow>,
+    pub event: Option<PruneWindow>,
+    pub write_set: Option<PruneWindow>,
+}
+
 impl StoragePrunerConfig {
     pub fn new(
         state_store_prune_window: Option<u64>,
@@ -123,10 +181,82 @@ impl StoragePrunerConfig {
         StoragePrunerConfig {
             state_store_prune_window,
             ledger_prune_window: ledger_store_prune_window,
+            ledger_store_prune_windows: LedgerStorePruneWindows::default(),
             ledger_pruning_batch_size,
             state_store_pruning_batch_size,
         }
     }
+
+    /// Returns the prune window of a ledger store given its own window, which falls back to
+    /// `ledger_prune_window`. `None` disables pruning the store.
+    pub fn ledger_store_prune_window(
+        &self,
+        store_prune_window: Option<PruneWindow>,
+    ) -> Option<PruneWindow> {
+        store_prune_window.or_else(|| self.ledger_prune_window.map(PruneWindow::Versions))
+    }
+
+    pub fn validate(&self) -> Result<(), Error> {
+        let windows = &self.ledger_store_prune_windows;
+        let max_seconds = [
+            windows.transaction,
+            windows.transaction_info,
+            windows.event,
+            windows.write_set,
+        ]
+        .iter()
+        .filter_map(|window| match self.ledger_store_prune_window(*window) {
+            Some(PruneWindow::Seconds(seconds)) => Some(seconds),
+            _ => None,
+        })
+        .max();
+        // Windows in seconds are resolved to versions by searching the block events, so those
+        // must be kept at least as long.
+        if let Some(max_seconds) = max_seconds {
+            invariant(
+                match self.ledger_store_prune_window(windows.event) {
+                    None => true,
+                    Some(PruneWindow::Seconds(seconds)) => seconds >= max_seconds,
+                    Some(PruneWindow::Versions(_)) => false,
+                },
+                format!(
+                    "Prune windows in seconds need events kept for at least as long, the event \
+                     prune window must be in seconds and at least {}.",
+                    max_seconds
+                ),
+            )?;
+        }
+        // The oldest ledger version is served as the first version of which all ledger data is
+        // readable, which the transaction infos and the events must not be pruned past.
+        let transaction = self.ledger_store_prune_window(windows.transaction);
+        for (name, window) in [
+            ("transaction_info", windows.transaction_info),
+            ("event", windows.event),
+        ] {
+            invariant(
+                prune_window_covers(self.ledger_store_prune_window(window), transaction),
+                format!(
+                    "The {} prune window must be at least the transaction prune window, and in \
+                     the same unit.",
+                    name
+                ),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether a prune window keeps at least the history another one does. `None` keeps everything.
+fn prune_window_covers(window: Option<PruneWindow>, other: Option<PruneWindow>) -> bool {
+    match (window, other) {
+        (None, _) => true,
+        (Some(_), None) => false,
+        (Some(PruneWindow::Versions(window)), Some(PruneWindow::Versions(other))) => {
+            window >= other
+        }
+        (Some(PruneWindow::Seconds(window)), Some(PruneWindow::Seconds(other))) => window >= other,
+        (Some(_), Some(_)) => false,
+    }
 }
 
 impl Default for StorageConfig {
@@ -145,6 +275,7 @@ impl Default for StorageConfig {
             storage_pruner_config: StoragePrunerConfig {
                 state_store_prune_window: Some(1_000_000),
                 ledger_prune_window: Some(10_000_000),
+                ledger_store_prune_windows: LedgerStorePruneWindows::default(),
                 ledger_pruning_batch_size: 500,
                 // A 10k transaction block (touching 60k state values, in the case of the account
                 // creation benchmark) on a 4B items DB (or 1.33B accounts) yields 300k JMT nodes
@@ -155,6 +286,9 @@ impl Default for StorageConfig {
             timeout_ms: 30_000,
             rocksdb_configs: RocksdbConfigs::default(),
             enable_indexer: false,
+            enable_state_key_history: false,
+            enable_affected_account_index: false,
+            backup_service_checkpoint: None,
             target_snapshot_size: TARGET_SNAPSHOT_SIZE,
         }
     }
@@ -179,3 +313,143 @@ impl StorageConfig {
             .set_port(utils::get_available_port());
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn config(
+        ledger_prune_window: Option<u64>,
+        ledger_store_prune_windows: LedgerStorePruneWindows,
+    ) -> StoragePrunerConfig {
+        StoragePrunerConfig {
+            ledger_prune_window,
+            ledger_store_prune_windows,
+            ..NO_OP_STORAGE_PRUNER_CONFIG
+        }
+    }
+
+    #[test]
+    fn test_validate_windows_in_versions() {
+        assert!(NO_OP_STORAGE_PRUNER_CONFIG.validate().is_ok());
+        assert!(StorageConfig::default()
+            .storage_pruner_config
+            .validate()
+            .is_ok());
+        assert!(config(
+            Some(100),
+            LedgerStorePruneWindows {
+                event: Some(PruneWindow::Versions(1_000)),
+                write_set: Some(PruneWindow::Versions(10)),
+                ..LedgerStorePruneWindows::default()
+            }
+        )
+        .validate()
+        .is_ok());
+    }
+
+    #[test]
+    fn test_validate_windows_against_transactions() {
+        // Transaction infos and events pruned sooner than transactions
+        for windows in [
+            LedgerStorePruneWindows {
+                transaction_info: Some(PruneWindow::Versions(99)),
+                ..LedgerStorePruneWindows::default()
+            },
+            LedgerStorePruneWindows {
+                event: Some(PruneWindow::Versions(99)),
+                ..LedgerStorePruneWindows::default()
+            },
+        ] {
+            assert!(config(Some(100), windows).validate().is_err());
+        }
+        // Transactions not pruned at all
+        assert!(config(
+            None,
+            LedgerStorePruneWindows {
+                event: Some(PruneWindow::Versions(1_000)),
+                ..LedgerStorePruneWindows::default()
+            }
+        )
+        .validate()
+        .is_err());
+        // Transaction infos and events not pruned at all
+        assert!(config(
+            None,
+            LedgerStorePruneWindows {
+                transaction: Some(PruneWindow::Versions(100)),
+                ..LedgerStorePruneWindows::default()
+            }
+        )
+        .validate()
+        .is_ok());
+        // Windows in different units can't be compared
+        assert!(config(
+            None,
+            LedgerStorePruneWindows {
+                transaction: Some(PruneWindow::Versions(100)),
+                transaction_info: Some(PruneWindow::Seconds(100)),
+                ..LedgerStorePruneWindows::default()
+            }
+        )
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn test_validate_windows_in_seconds() {
+        // Events kept at least as long as the longest window in seconds
+        assert!(config(
+            None,
+            LedgerStorePruneWindows {
+                transaction: Some(PruneWindow::Seconds(100)),
+                event: Some(PruneWindow::Seconds(100)),
+                ..LedgerStorePruneWindows::default()
+            }
+        )
+        .validate()
+        .is_ok());
+        // Events not pruned at all
+        assert!(config(
+            None,
+            LedgerStorePruneWindows {
+                write_set: Some(PruneWindow::Seconds(100)),
+                ..LedgerStorePruneWindows::default()
+            }
+        )
+        .validate()
+        .is_ok());
+        // Events pruned sooner than another store in seconds
+        assert!(config(
+            None,
+            LedgerStorePruneWindows {
+                transaction_info: Some(PruneWindow::Seconds(100)),
+                event: Some(PruneWindow::Seconds(99)),
+                ..LedgerStorePruneWindows::default()
+            }
+        )
+        .validate()
+        .is_err());
+        // Events falling back to the default window in versions
+        assert!(config(
+            Some(1_000),
+            LedgerStorePruneWindows {
+                transaction: Some(PruneWindow::Seconds(100)),
+                ..LedgerStorePruneWindows::default()
+            }
+        )
+        .validate()
+        .is_err());
+        // Events with their own window in versions
+        assert!(config(
+            None,
+            LedgerStorePruneWindows {
+                transaction: Some(PruneWindow::Seconds(100)),
+                event: Some(PruneWindow::Versions(1_000_000)),
+                ..LedgerStorePruneWindows::default()
+            }
+        )
+        .validate()
+        .is_err());
+    }
+}
@@ -112,6 +112,8 @@ impl GenesisInfo {
             NO_OP_STORAGE_PRUNER_CONFIG,
             RocksdbConfigs::default(),
             false,
+            false,
+            false,
             TARGET_SNAPSHOT_SIZE,
         )?;
         let db_rw = DbReaderWriter::new(aptosdb);
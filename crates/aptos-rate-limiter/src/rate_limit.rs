@@ -171,6 +171,19 @@ impl<Key: Eq + Hash + Clone + Debug> TokenBucketRateLimiter<Key> {
         }
         remove
     }
+
+    /// Garbage collects the buckets that are full and not in use, which would be created the
+    /// same again if needed, returning how many were removed. Useful when keys are not known,
+    /// e.g. when they come from untrusted clients.
+    pub fn garbage_collect_full_buckets(&self) -> usize {
+        if self.new_bucket_start_percentage < 100 {
+            return 0;
+        }
+        let mut buckets = self.buckets.write();
+        let num_buckets = buckets.len();
+        buckets.retain(|_, bucket| Arc::strong_count(bucket) > 1 || !bucket.lock().is_full());
+        num_buckets - buckets.len()
+    }
 }
 
 /// A token bucket object that keeps track of everything related to a key
@@ -370,6 +383,12 @@ impl Bucket {
         self.tokens = min(self.size, self.tokens.saturating_add(new_tokens));
     }
 
+    /// Whether the bucket is full, after refilling it.
+    pub fn is_full(&mut self) -> bool {
+        self.refill();
+        self.tokens >= self.size
+    }
+
     /// Returns tokens that were unused
     pub fn return_tokens(&mut self, new_tokens: usize) {
         self.allowed_in_period = self.allowed_in_period.saturating_sub(new_tokens);
@@ -546,4 +565,32 @@ mod tests {
         assert!(!rate_limiter.try_garbage_collect_key(&key_to_keep));
         assert_num_keys(&rate_limiter, 1);
     }
+
+    #[test]
+    fn test_garbage_collect_full_buckets() {
+        let key_in_use = "in use";
+        let key_not_full = "not full";
+        let key_full = "full";
+        let rate_limiter = TokenBucketRateLimiter::test(2, 1);
+
+        let _bucket_arc = rate_limiter.bucket(key_in_use);
+        rate_limiter
+            .bucket(key_not_full)
+            .lock()
+            .acquire_all_tokens(2)
+            .unwrap();
+        rate_limiter.bucket(key_full);
+        assert_num_keys(&rate_limiter, 3);
+
+        // Only the full bucket no one holds onto is removed
+        assert_eq!(rate_limiter.garbage_collect_full_buckets(), 1);
+        assert_num_keys(&rate_limiter, 2);
+        assert_eq!(rate_limiter.garbage_collect_full_buckets(), 0);
+        assert_num_keys(&rate_limiter, 2);
+
+        // Once refilled, the other one is removed too
+        sleep(Duration::from_secs(2));
+        assert_eq!(rate_limiter.garbage_collect_full_buckets(), 1);
+        assert_num_keys(&rate_limiter, 1);
+    }
 }
@@ -3,13 +3,17 @@
 
 use anyhow::{anyhow, Result};
 pub use aptos_api_types::{
-    self, IndexResponse, MoveModuleBytecode, PendingTransaction, Transaction,
+    self, GasEstimation, IndexResponse, MoveModuleBytecode, PendingTransaction, Transaction,
+    UserTransaction, ViewRequest,
+};
+use aptos_api_types::{
+    mime_types::BCS_SIGNED_TRANSACTION as BCS_CONTENT_TYPE, BlockInfo, HexEncodedBytes,
+    RawStateValueRequest,
 };
-use aptos_api_types::{mime_types::BCS_SIGNED_TRANSACTION as BCS_CONTENT_TYPE, BlockInfo};
 use aptos_crypto::HashValue;
 use aptos_types::{
     account_address::AccountAddress, account_config::CORE_CODE_ADDRESS,
-    transaction::SignedTransaction,
+    state_store::state_key::StateKey, transaction::SignedTransaction,
 };
 use reqwest::{header::CONTENT_TYPE, Client as ReqwestClient, StatusCode};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
@@ -26,7 +30,7 @@ pub use response::Response;
 mod state;
 pub mod types;
 use crate::aptos::{AptosVersion, Balance};
-pub use types::{Account, Resource, RestError};
+pub use types::{Account, AptosError, Resource, ResourceVersion, RestError};
 pub mod aptos;
 
 pub const USER_AGENT: &str = concat!("aptos-client-sdk-rust / ", env!("CARGO_PKG_VERSION"));
@@ -119,6 +123,27 @@ impl Client {
         self.json(response).await
     }
 
+    /// Simulates `txn`, which must not be validly signed, and returns the
+    /// simulated transaction along with what it was charged gas for.
+    pub async fn simulate_with_gas_profile(
+        &self,
+        txn: &SignedTransaction,
+    ) -> Result<Response<Vec<UserTransaction>>> {
+        let txn_payload = bcs::to_bytes(txn)?;
+        let url = self.build_v1_url("transactions/simulate")?;
+
+        let response = self
+            .inner
+            .post(url)
+            .query(&[("profile_gas", true)])
+            .header(CONTENT_TYPE, BCS_CONTENT_TYPE)
+            .body(txn_payload)
+            .send()
+            .await?;
+
+        self.json_v1(response).await
+    }
+
     pub async fn submit_and_wait(&self, txn: &SignedTransaction) -> Result<Response<Transaction>> {
         self.submit(txn).await?;
         self.wait_for_signed_transaction(txn).await
@@ -216,6 +241,32 @@ impl Client {
         .await
     }
 
+    /// Looks up many transactions by hash at once. Hashes for which no
+    /// transaction is found are left out of the response.
+    pub async fn get_transactions_by_hashes(
+        &self,
+        hashes: &[HashValue],
+    ) -> Result<Response<Vec<Transaction>>> {
+        let url = self.build_v1_url("transactions/by_hashes")?;
+        let hashes: Vec<String> = hashes.iter().map(|h| h.to_hex_literal()).collect();
+        let response = self.inner.post(url).json(&hashes).send().await?;
+        self.json_v1(response).await
+    }
+
+    pub async fn get_account_transaction_by_sequence_number(
+        &self,
+        address: AccountAddress,
+        sequence_number: u64,
+    ) -> Result<Response<Transaction>> {
+        let url = self.build_v1_url(&format!(
+            "accounts/{}/transactions/{}",
+            address.to_hex_literal(),
+            sequence_number
+        ))?;
+        let response = self.inner.get(url).send().await?;
+        self.json_v1(response).await
+    }
+
     pub async fn get_transaction_by_version(&self, version: u64) -> Result<Response<Transaction>> {
         self.json(
             self.get_transaction_by_version_or_hash(version.to_string())
@@ -259,6 +310,31 @@ impl Client {
         self.json(response).await
     }
 
+    /// Returns the transactions that affected an account, whether or not it
+    /// sent them, starting from version `start`. Only served by nodes that
+    /// index transactions by every account they affect.
+    pub async fn get_account_affected_transactions(
+        &self,
+        address: AccountAddress,
+        start: Option<u64>,
+        limit: Option<u64>,
+    ) -> Result<Response<Vec<Transaction>>> {
+        let url = self.build_v1_url(&format!("accounts/{}/transactions", address))?;
+
+        let mut request = self.inner.get(url).query(&[("involvement", "all")]);
+        if let Some(start) = start {
+            request = request.query(&[("start", start)])
+        }
+
+        if let Some(limit) = limit {
+            request = request.query(&[("limit", limit)])
+        }
+
+        let response = request.send().await?;
+
+        self.json_v1(response).await
+    }
+
     pub async fn get_account_resources(
         &self,
         address: AccountAddress,
@@ -335,6 +411,61 @@ impl Client {
         self.json(response).await
     }
 
+    /// Returns the resource as of `version`, or `None` if the account had no
+    /// such resource then. Fails with an `AptosError` for other 404s, e.g.
+    /// when the node has pruned `version`.
+    pub async fn get_account_resource_at_version_v1(
+        &self,
+        address: AccountAddress,
+        resource_type: &str,
+        version: u64,
+    ) -> Result<Option<Resource>> {
+        let url = self.build_v1_url(&format!("accounts/{}/resource/{}", address, resource_type))?;
+
+        let response = self
+            .inner
+            .get(url)
+            .query(&[("ledger_version", version)])
+            .send()
+            .await?;
+        // Other 404s, e.g. for pruned versions, come with an error code
+        if response.status() == StatusCode::NOT_FOUND {
+            let error = response.json::<AptosError>().await?;
+            if error.error_code.is_none() {
+                return Ok(None);
+            }
+            return Err(anyhow::Error::new(error).context("Request failed"));
+        }
+        Ok(Some(self.json_v1(response).await?.into_inner()))
+    }
+
+    /// Returns the versions at which a resource was changed, starting from
+    /// version `start`. Only served by nodes that keep state key history.
+    pub async fn get_account_resource_history(
+        &self,
+        address: AccountAddress,
+        resource_type: &str,
+        start: Option<u64>,
+        limit: Option<u16>,
+    ) -> Result<Response<Vec<ResourceVersion>>> {
+        let url = self.build_v1_url(&format!(
+            "accounts/{}/resource/{}/history",
+            address, resource_type
+        ))?;
+
+        let mut request = self.inner.get(url);
+        if let Some(start) = start {
+            request = request.query(&[("start", start)])
+        }
+
+        if let Some(limit) = limit {
+            request = request.query(&[("limit", limit)])
+        }
+
+        let response = request.send().await?;
+        self.json_v1(response).await
+    }
+
     pub async fn get_account_modules(
         &self,
         address: AccountAddress,
@@ -367,6 +498,63 @@ impl Client {
         self.json(response).await
     }
 
+    /// Executes a view function at `version`, or the latest version if none is
+    /// given, and returns its return values as JSON.
+    pub async fn view(
+        &self,
+        request: &ViewRequest,
+        version: Option<u64>,
+    ) -> Result<Response<Vec<Value>>> {
+        let url = self.build_v1_url("view")?;
+
+        let mut request = self.inner.post(url).json(request);
+        if let Some(version) = version {
+            request = request.query(&[("ledger_version", version)])
+        }
+
+        let response = request.send().await?;
+        self.json_v1(response).await
+    }
+
+    /// Returns the bytes stored under `state_key` at `version`, as the Move VM
+    /// reads them, or `None` if nothing is stored under it.
+    pub async fn get_raw_state_value(
+        &self,
+        state_key: &StateKey,
+        version: u64,
+    ) -> Result<Option<Vec<u8>>> {
+        let url = self.build_v1_url("state_values/raw")?;
+        let request = RawStateValueRequest {
+            key: bcs::to_bytes(state_key)?.into(),
+        };
+
+        let response = self
+            .inner
+            .post(url)
+            .query(&[("ledger_version", version)])
+            .json(&request)
+            .send()
+            .await?;
+        // Other 404s, e.g. for pruned versions, come with an error code
+        if response.status() == StatusCode::NOT_FOUND {
+            let error = response.json::<AptosError>().await?;
+            if error.error_code.is_none() {
+                return Ok(None);
+            }
+            return Err(anyhow::Error::new(error).context("Request failed"));
+        }
+        let bytes: HexEncodedBytes = self.json_v1(response).await?.into_inner();
+        Ok(Some(bytes.into()))
+    }
+
+    /// Asks the node which gas unit price to use for a transaction to be
+    /// committed promptly.
+    pub async fn estimate_gas_price(&self) -> Result<Response<GasEstimation>> {
+        let url = self.build_v1_url("estimate_gas_price")?;
+        let response = self.inner.get(url).send().await?;
+        self.json_v1(response).await
+    }
+
     pub async fn get_account(&self, address: AccountAddress) -> Result<Response<Account>> {
         let url = self.base_url.join(&format!("accounts/{}", address))?;
         let response = self.inner.get(url).send().await?;
@@ -415,6 +603,36 @@ impl Client {
         Ok(Response::new(json, state))
     }
 
+    async fn check_v1_response(
+        &self,
+        response: reqwest::Response,
+    ) -> Result<(reqwest::Response, State)> {
+        if !response.status().is_success() {
+            // Keep the error body typed so callers can downcast to AptosError
+            // and inspect the error code, e.g. to detect pruned versions.
+            let error_response = response.json::<AptosError>().await?;
+            return Err(anyhow::Error::new(error_response).context("Request failed"));
+        }
+        let state = State::from_headers(response.headers())?;
+
+        Ok((response, state))
+    }
+
+    async fn json_v1<T: serde::de::DeserializeOwned>(
+        &self,
+        response: reqwest::Response,
+    ) -> Result<Response<T>> {
+        let (response, state) = self.check_v1_response(response).await?;
+        let json = response.json().await?;
+        Ok(Response::new(json, state))
+    }
+
+    /// Endpoints that only exist in the v1 API are served under `/v1`,
+    /// relative to the node URL the client is built with.
+    fn build_v1_url(&self, path: &str) -> Result<Url> {
+        Ok(self.base_url.join("v1/")?.join(path)?)
+    }
+
     pub async fn health_check(&self, seconds: u64) -> Result<()> {
         let url = self.base_url.join("-/healthy")?;
         let response = self
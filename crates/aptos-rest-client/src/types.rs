@@ -14,6 +14,33 @@ pub struct RestError {
     pub aptos_ledger_version: Option<U64>,
 }
 
+/// The error body returned by endpoints of the v1 API.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct AptosError {
+    pub message: String,
+    pub error_code: Option<String>,
+    pub aptos_ledger_version: Option<U64>,
+    /// Set when the requested version has been pruned by the node.
+    pub oldest_available_version: Option<U64>,
+}
+
+impl AptosError {
+    /// Whether the request failed because the node no longer has data at the
+    /// requested version. Such requests can be retried against a node that
+    /// keeps more history, e.g. an archive node.
+    pub fn is_version_pruned(&self) -> bool {
+        self.error_code.as_deref() == Some("version_pruned")
+    }
+}
+
+impl std::fmt::Display for AptosError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for AptosError {}
+
 #[derive(Clone, Debug, PartialEq, Deserialize)]
 pub struct Resource {
     #[serde(rename = "type", deserialize_with = "deserialize_resource_type")]
@@ -21,6 +48,14 @@ pub struct Resource {
     pub data: serde_json::Value,
 }
 
+/// A version at which a resource was changed, and the resource as of that version, or `None` if it
+/// was deleted at that version
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct ResourceVersion {
+    pub version: U64,
+    pub resource: Option<Resource>,
+}
+
 pub fn deserialize_from_string<'de, D, T>(deserializer: D) -> Result<T, D::Error>
 where
     D: Deserializer<'de>,
@@ -2,7 +2,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::common::{
-    types::{CliCommand, CliTypedResult, FaucetOptions, TransactionOptions},
+    types::{
+        CliCommand, CliError, CliTypedResult, FaucetOptions, TransactionOptions, TransactionOutcome,
+    },
     utils::fund_account,
 };
 use aptos_transaction_builder::aptos_stdlib;
@@ -33,13 +35,22 @@ pub struct CreateAccount {
 }
 
 #[async_trait]
-impl CliCommand<String> for CreateAccount {
+impl CliCommand<TransactionOutcome<String>> for CreateAccount {
     fn command_name(&self) -> &'static str {
         "CreateAccount"
     }
 
-    async fn execute(self) -> CliTypedResult<String> {
+    async fn execute(self) -> CliTypedResult<TransactionOutcome<String>> {
         let address = self.account;
+        if self.use_faucet
+            && (self.txn_options.build_only.is_some() || self.txn_options.profile_gas.is_some())
+        {
+            return Err(CliError::CommandArgumentError(
+                "--use-faucet can't be given with --build-only or --profile-gas, the faucet \
+                 submits a transaction of its own"
+                    .to_string(),
+            ));
+        }
         if self.use_faucet {
             fund_account(
                 self.faucet_options
@@ -48,18 +59,66 @@ impl CliCommand<String> for CreateAccount {
                 self.account,
             )
             .await
+            .map(TransactionOutcome::Submitted)
         } else {
             self.create_account_with_key(address).await
         }
-        .map(|_| format!("Account Created at {}", address))
+        .map(|outcome| outcome.map(|_| format!("Account Created at {}", address)))
     }
 }
 
 impl CreateAccount {
-    async fn create_account_with_key(self, address: AccountAddress) -> CliTypedResult<()> {
+    async fn create_account_with_key(
+        self,
+        address: AccountAddress,
+    ) -> CliTypedResult<TransactionOutcome<()>> {
         self.txn_options
             .submit_transaction(aptos_stdlib::account_create_account(address))
-            .await?;
-        Ok(())
+            .await
+            .map(|outcome| outcome.map(|_| ()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn create_account(txn_options: TransactionOptions, use_faucet: bool) -> CreateAccount {
+        CreateAccount {
+            txn_options,
+            account: AccountAddress::ONE,
+            use_faucet,
+            faucet_options: Default::default(),
+            initial_coins: DEFAULT_FUNDED_COINS,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reject_faucet_with_build_only() {
+        let result = create_account(
+            TransactionOptions {
+                build_only: Some(PathBuf::from("create.txn")),
+                ..Default::default()
+            },
+            true,
+        )
+        .execute()
+        .await;
+        assert!(matches!(result, Err(CliError::CommandArgumentError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_reject_sender_account_without_build_only() {
+        let result = create_account(
+            TransactionOptions {
+                sender_account: Some(AccountAddress::from_hex_literal("0x2").unwrap()),
+                ..Default::default()
+            },
+            false,
+        )
+        .execute()
+        .await;
+        assert!(matches!(result, Err(CliError::CommandArgumentError(_))));
     }
 }
@@ -0,0 +1,99 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::common::types::{
+    CliCommand, CliError, CliTypedResult, EncodingOptions, SaveFile, TransactionOptions,
+    TransactionOutcome, TransactionSummary,
+};
+use aptos_crypto::{
+    ed25519::Ed25519PublicKey, multi_ed25519::MultiEd25519PublicKey, ValidCryptoMaterialStringExt,
+};
+use aptos_transaction_builder::aptos_stdlib;
+use aptos_types::{account_address::AccountAddress, transaction::authenticator::AuthenticationKey};
+use async_trait::async_trait;
+use clap::Parser;
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// Command to create a K-of-N multisig account on-chain
+///
+/// The account is owned by a MultiEd25519 key made of the given public keys, and transactions
+/// from it need signatures from `threshold` of them.  The multisig public key is written to the
+/// output file, for use with `aptos transaction sign-multisig` and `combine-multisig`.
+#[derive(Debug, Parser)]
+pub struct CreateMultisigAccount {
+    #[clap(flatten)]
+    pub(crate) txn_options: TransactionOptions,
+
+    /// Hex encoded Ed25519 public keys of the owners, separated by spaces
+    ///
+    /// The order matters, as it changes the multisig authentication key
+    #[clap(long, multiple_values = true, parse(try_from_str = Ed25519PublicKey::from_encoded_string))]
+    pub(crate) public_keys: Vec<Ed25519PublicKey>,
+
+    /// Number of owners that need to sign a transaction
+    #[clap(long)]
+    pub(crate) threshold: u8,
+
+    #[clap(flatten)]
+    pub(crate) encoding_options: EncodingOptions,
+
+    #[clap(flatten)]
+    pub(crate) save_file: SaveFile,
+}
+
+/// A summary of a created multisig account
+#[derive(Debug, Serialize)]
+pub struct CreateMultisigAccountSummary {
+    pub account: AccountAddress,
+    pub authentication_key: String,
+    pub threshold: u8,
+    pub num_public_keys: usize,
+    pub multisig_public_key_file: PathBuf,
+    pub transaction: TransactionOutcome<TransactionSummary>,
+}
+
+#[async_trait]
+impl CliCommand<CreateMultisigAccountSummary> for CreateMultisigAccount {
+    fn command_name(&self) -> &'static str {
+        "CreateMultisigAccount"
+    }
+
+    async fn execute(self) -> CliTypedResult<CreateMultisigAccountSummary> {
+        self.save_file.check_file()?;
+        let threshold = self.threshold;
+        let num_public_keys = self.public_keys.len();
+        let multisig_public_key =
+            MultiEd25519PublicKey::new(self.public_keys, threshold).map_err(|err| {
+                CliError::CommandArgumentError(format!(
+                    "Invalid {}-of-{} multisig key: {}",
+                    threshold, num_public_keys, err
+                ))
+            })?;
+        let authentication_key = AuthenticationKey::multi_ed25519(&multisig_public_key);
+        let account = authentication_key.derived_address();
+
+        self.save_file.save_to_file(
+            "Multisig public key",
+            &self
+                .encoding_options
+                .encoding
+                .encode_key("--multisig-public-key", &multisig_public_key)?,
+        )?;
+
+        let transaction = self
+            .txn_options
+            .submit_transaction(aptos_stdlib::account_create_account(account))
+            .await?
+            .map(TransactionSummary::from);
+
+        Ok(CreateMultisigAccountSummary {
+            account,
+            authentication_key: authentication_key.to_string(),
+            threshold,
+            num_public_keys,
+            multisig_public_key_file: self.save_file.output_file,
+            transaction,
+        })
+    }
+}
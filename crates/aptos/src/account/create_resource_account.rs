@@ -1,7 +1,7 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::common::types::{CliCommand, CliTypedResult, TransactionOptions};
+use crate::common::types::{CliCommand, CliTypedResult, TransactionOptions, TransactionOutcome};
 use aptos_rest_client::{
     aptos_api_types::{WriteResource, WriteSetChange},
     Transaction,
@@ -77,12 +77,12 @@ impl From<Transaction> for CreateResourceAccountSummary {
 }
 
 #[async_trait]
-impl CliCommand<CreateResourceAccountSummary> for CreateResourceAccount {
+impl CliCommand<TransactionOutcome<CreateResourceAccountSummary>> for CreateResourceAccount {
     fn command_name(&self) -> &'static str {
         "CreateResourceAccount"
     }
 
-    async fn execute(self) -> CliTypedResult<CreateResourceAccountSummary> {
+    async fn execute(self) -> CliTypedResult<TransactionOutcome<CreateResourceAccountSummary>> {
         let authentication_key: Vec<u8> = if let Some(key) = self.authentication_key {
             bcs::to_bytes(&key)?
         } else {
@@ -94,6 +94,6 @@ impl CliCommand<CreateResourceAccountSummary> for CreateResourceAccount {
                 authentication_key,
             ))
             .await
-            .map(CreateResourceAccountSummary::from)
+            .map(|outcome| outcome.map(CreateResourceAccountSummary::from))
     }
 }
@@ -0,0 +1,301 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    account::snapshot::{account_or_profile, AccountSnapshot},
+    common::{
+        diff::{diff_values, FieldChange},
+        types::{CliCommand, CliError, CliTypedResult, ProfileOptions, RestOptions},
+    },
+};
+use aptos_rest_client::Client;
+use aptos_types::account_address::AccountAddress;
+use async_trait::async_trait;
+use clap::Parser;
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// Compare the resources of an account at two versions
+///
+/// Each side is either a version of the account on chain, or a file saved by
+/// `aptos account snapshot`.  The newer side defaults to the latest version.  Every resource that
+/// was added, removed or modified is listed with the fields that changed, down to fields of
+/// nested structs.
+#[derive(Debug, Parser)]
+pub struct DiffAccount {
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+    /// Address of the account to compare
+    ///
+    /// Defaults to the account of the snapshots if any are given, otherwise to the account of the
+    /// profile.  It must match the account of the snapshots.
+    #[clap(long, parse(try_from_str=crate::common::types::load_account_arg))]
+    pub(crate) account: Option<AccountAddress>,
+    /// Version to compare from
+    #[clap(long, conflicts_with = "from-snapshot")]
+    pub(crate) from_version: Option<u64>,
+    /// Snapshot file to compare from
+    #[clap(long, parse(from_os_str))]
+    pub(crate) from_snapshot: Option<PathBuf>,
+    /// Version to compare to, defaults to the latest version
+    #[clap(long, conflicts_with = "to-snapshot")]
+    pub(crate) to_version: Option<u64>,
+    /// Snapshot file to compare to
+    #[clap(long, parse(from_os_str))]
+    pub(crate) to_snapshot: Option<PathBuf>,
+}
+
+/// The resources that differ between two snapshots of an account
+#[derive(Debug, Serialize)]
+pub struct AccountDiff {
+    pub from: SnapshotId,
+    pub to: SnapshotId,
+    pub changes: Vec<ResourceChange>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SnapshotId {
+    pub account: AccountAddress,
+    pub version: u64,
+}
+
+impl From<&AccountSnapshot> for SnapshotId {
+    fn from(snapshot: &AccountSnapshot) -> Self {
+        SnapshotId {
+            account: snapshot.account,
+            version: snapshot.version,
+        }
+    }
+}
+
+/// A resource that was added, removed or modified, with the fields that changed
+#[derive(Debug, Serialize)]
+pub struct ResourceChange {
+    pub change: &'static str,
+    pub resource: String,
+    pub diff: Vec<FieldChange>,
+}
+
+impl DiffAccount {
+    /// Loads the snapshot file of a side, if it's given as one
+    fn load_snapshot(snapshot: &Option<PathBuf>) -> CliTypedResult<Option<AccountSnapshot>> {
+        snapshot.as_deref().map(AccountSnapshot::load).transpose()
+    }
+
+    /// Returns the snapshot of a side, fetching it from chain if it isn't given as a file
+    async fn fetch_side(
+        client: &Client,
+        snapshot: Option<AccountSnapshot>,
+        account: AccountAddress,
+        version: Option<u64>,
+    ) -> CliTypedResult<AccountSnapshot> {
+        match snapshot {
+            Some(snapshot) => Ok(snapshot),
+            None => AccountSnapshot::fetch(client, account, version).await,
+        }
+    }
+}
+
+#[async_trait]
+impl CliCommand<AccountDiff> for DiffAccount {
+    fn command_name(&self) -> &'static str {
+        "DiffAccount"
+    }
+
+    async fn execute(self) -> CliTypedResult<AccountDiff> {
+        if self.from_version.is_none() && self.from_snapshot.is_none() {
+            return Err(CliError::CommandArgumentError(
+                "Please provide a version or snapshot to compare from using --from-version or \
+                 --from-snapshot"
+                    .to_string(),
+            ));
+        }
+
+        let from = Self::load_snapshot(&self.from_snapshot)?;
+        let to = Self::load_snapshot(&self.to_snapshot)?;
+        // Sides fetched from chain are of the account of the snapshots, if any
+        let account = match snapshots_account(self.account, from.as_ref(), to.as_ref())? {
+            Some(account) => account,
+            None => account_or_profile(None, &self.profile_options)?,
+        };
+
+        let client = self.rest_options.client(&self.profile_options.profile)?;
+        let from = Self::fetch_side(&client, from, account, self.from_version).await?;
+        let to = Self::fetch_side(&client, to, account, self.to_version).await?;
+
+        Ok(AccountDiff {
+            from: SnapshotId::from(&from),
+            to: SnapshotId::from(&to),
+            changes: diff_snapshots(&from, &to),
+        })
+    }
+}
+
+/// Returns the account both sides are of, from `--account` or the snapshots, and fails if they
+/// name different accounts
+fn snapshots_account(
+    account: Option<AccountAddress>,
+    from: Option<&AccountSnapshot>,
+    to: Option<&AccountSnapshot>,
+) -> CliTypedResult<Option<AccountAddress>> {
+    let mut account = account.map(|account| (account, "--account".to_string()));
+    for (side, snapshot) in [("from", from), ("to", to)] {
+        let snapshot = match snapshot {
+            Some(snapshot) => snapshot,
+            None => continue,
+        };
+        let source = format!("the account of the {} snapshot", side);
+        match &account {
+            Some((expected, expected_source)) if *expected != snapshot.account => {
+                return Err(CliError::CommandArgumentError(format!(
+                    "Can't compare different accounts: {} is {}, but {} is {}",
+                    source, snapshot.account, expected_source, expected
+                )));
+            }
+            Some(_) => {}
+            None => account = Some((snapshot.account, source)),
+        }
+    }
+    Ok(account.map(|(account, _)| account))
+}
+
+fn diff_snapshots(from: &AccountSnapshot, to: &AccountSnapshot) -> Vec<ResourceChange> {
+    let mut resources: Vec<&String> = from.resources.keys().chain(to.resources.keys()).collect();
+    resources.sort();
+    resources.dedup();
+
+    resources
+        .into_iter()
+        .filter_map(|resource| {
+            let before = from.resources.get(resource);
+            let after = to.resources.get(resource);
+            let diff = diff_values(before, after);
+            if diff.is_empty() {
+                return None;
+            }
+            let change = match (before, after) {
+                (None, _) => "added",
+                (_, None) => "removed",
+                _ => "modified",
+            };
+            Some(ResourceChange {
+                change,
+                resource: resource.clone(),
+                diff,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    fn snapshot(account: AccountAddress, resources: Vec<(&str, Value)>) -> AccountSnapshot {
+        AccountSnapshot {
+            account,
+            version: 0,
+            resources: resources
+                .into_iter()
+                .map(|(resource, value)| (resource.to_string(), value))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_diff_snapshots() {
+        let from = snapshot(
+            AccountAddress::ONE,
+            vec![
+                ("0x1::m::Removed", json!({ "value": "1" })),
+                ("0x1::m::Modified", json!({ "value": "1", "same": true })),
+                ("0x1::m::Unchanged", json!({ "value": "1" })),
+            ],
+        );
+        let to = snapshot(
+            AccountAddress::ONE,
+            vec![
+                ("0x1::m::Added", json!({ "value": "2" })),
+                ("0x1::m::Modified", json!({ "value": "2", "same": true })),
+                ("0x1::m::Unchanged", json!({ "value": "1" })),
+            ],
+        );
+
+        let changes: Vec<_> = diff_snapshots(&from, &to)
+            .into_iter()
+            .map(|change| (change.change, change.resource, change.diff))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                (
+                    "added",
+                    "0x1::m::Added".to_string(),
+                    vec![FieldChange {
+                        field: String::new(),
+                        before: None,
+                        after: Some(json!({ "value": "2" })),
+                    }]
+                ),
+                (
+                    "modified",
+                    "0x1::m::Modified".to_string(),
+                    vec![FieldChange {
+                        field: "value".to_string(),
+                        before: Some(json!("1")),
+                        after: Some(json!("2")),
+                    }]
+                ),
+                (
+                    "removed",
+                    "0x1::m::Removed".to_string(),
+                    vec![FieldChange {
+                        field: String::new(),
+                        before: Some(json!({ "value": "1" })),
+                        after: None,
+                    }]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_snapshots_account() {
+        let two_address = AccountAddress::from_hex_literal("0x2").unwrap();
+        let one = snapshot(AccountAddress::ONE, vec![]);
+        let two = snapshot(two_address, vec![]);
+
+        assert_eq!(snapshots_account(None, None, None).unwrap(), None);
+        assert_eq!(
+            snapshots_account(Some(two_address), None, None).unwrap(),
+            Some(two_address)
+        );
+        // A side fetched from chain is of the account of the other side's snapshot
+        assert_eq!(
+            snapshots_account(None, Some(&one), None).unwrap(),
+            Some(AccountAddress::ONE)
+        );
+        assert_eq!(
+            snapshots_account(Some(AccountAddress::ONE), Some(&one), Some(&one)).unwrap(),
+            Some(AccountAddress::ONE)
+        );
+
+        // --account must match the snapshots
+        assert!(matches!(
+            snapshots_account(Some(two_address), Some(&one), None),
+            Err(CliError::CommandArgumentError(_))
+        ));
+        assert!(matches!(
+            snapshots_account(Some(two_address), None, Some(&one)),
+            Err(CliError::CommandArgumentError(_))
+        ));
+        // And so must the snapshots themselves
+        assert!(matches!(
+            snapshots_account(None, Some(&one), Some(&two)),
+            Err(CliError::CommandArgumentError(_))
+        ));
+    }
+}
@@ -5,9 +5,12 @@ use crate::common::types::{CliCommand, CliResult};
 use clap::Subcommand;
 
 pub mod create;
+pub mod create_multisig;
 pub mod create_resource_account;
+pub mod diff;
 pub mod fund;
 pub mod list;
+pub mod snapshot;
 pub mod transfer;
 
 /// CLI tool for interacting with accounts
@@ -15,9 +18,12 @@ pub mod transfer;
 #[derive(Debug, Subcommand)]
 pub enum AccountTool {
     Create(create::CreateAccount),
+    CreateMultisig(create_multisig::CreateMultisigAccount),
     CreateResourceAccount(create_resource_account::CreateResourceAccount),
+    Diff(diff::DiffAccount),
     Fund(fund::FundAccount),
     List(list::ListAccount),
+    Snapshot(snapshot::SnapshotAccount),
     Transfer(transfer::TransferCoins),
 }
 
@@ -25,9 +31,12 @@ impl AccountTool {
     pub async fn execute(self) -> CliResult {
         match self {
             AccountTool::Create(tool) => tool.execute_serialized().await,
+            AccountTool::CreateMultisig(tool) => tool.execute_serialized().await,
             AccountTool::CreateResourceAccount(tool) => tool.execute_serialized().await,
+            AccountTool::Diff(tool) => tool.execute_serialized().await,
             AccountTool::Fund(tool) => tool.execute_serialized().await,
             AccountTool::List(tool) => tool.execute_serialized().await,
+            AccountTool::Snapshot(tool) => tool.execute_serialized().await,
             AccountTool::Transfer(tool) => tool.execute_serialized().await,
         }
     }
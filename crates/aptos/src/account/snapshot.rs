@@ -0,0 +1,127 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::common::{
+    types::{CliCommand, CliError, CliTypedResult, ProfileOptions, RestOptions, SaveFile},
+    utils::read_from_file,
+};
+use aptos_rest_client::Client;
+use aptos_types::account_address::AccountAddress;
+use async_trait::async_trait;
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{collections::BTreeMap, path::Path};
+
+/// Save all resources of an account to a file
+///
+/// The snapshot can be compared with another version of the account with `aptos account diff`.
+#[derive(Debug, Parser)]
+pub struct SnapshotAccount {
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+    #[clap(flatten)]
+    pub(crate) save_file: SaveFile,
+    /// Address of the account to snapshot, defaults to the account of the profile
+    #[clap(long, parse(try_from_str=crate::common::types::load_account_arg))]
+    pub(crate) account: Option<AccountAddress>,
+    /// Version to snapshot the account at, defaults to the latest version
+    #[clap(long)]
+    pub(crate) at_version: Option<u64>,
+}
+
+/// All resources of an account at a version, by resource type
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AccountSnapshot {
+    pub account: AccountAddress,
+    pub version: u64,
+    pub resources: BTreeMap<String, Value>,
+}
+
+impl AccountSnapshot {
+    /// Fetches the resources of `account` at `version`, or at the latest version
+    pub async fn fetch(
+        client: &Client,
+        account: AccountAddress,
+        version: Option<u64>,
+    ) -> CliTypedResult<AccountSnapshot> {
+        let response = match version {
+            Some(version) => {
+                client
+                    .get_account_resources_at_version(account, version)
+                    .await
+            }
+            None => client.get_account_resources(account).await,
+        }
+        .map_err(|err| CliError::ApiError(err.to_string()))?;
+        let version = version.unwrap_or(response.state().version);
+
+        let resources = response
+            .into_inner()
+            .into_iter()
+            .map(|resource| (resource.resource_type.to_string(), resource.data))
+            .collect();
+        Ok(AccountSnapshot {
+            account,
+            version,
+            resources,
+        })
+    }
+
+    /// Loads a snapshot saved by `aptos account snapshot`
+    pub fn load(path: &Path) -> CliTypedResult<AccountSnapshot> {
+        serde_json::from_slice(&read_from_file(path)?).map_err(|err| {
+            CliError::UnableToParse("account snapshot", format!("{}: {}", path.display(), err))
+        })
+    }
+}
+
+/// What was saved by `aptos account snapshot`
+#[derive(Debug, Serialize)]
+pub struct SnapshotSummary {
+    pub account: AccountAddress,
+    pub version: u64,
+    pub num_resources: usize,
+}
+
+/// Resolves `--account`, falling back to the account of the profile
+pub(crate) fn account_or_profile(
+    account: Option<AccountAddress>,
+    profile_options: &ProfileOptions,
+) -> CliTypedResult<AccountAddress> {
+    if let Some(account) = account {
+        Ok(account)
+    } else {
+        profile_options.account_address().map_err(|_| {
+            CliError::CommandArgumentError(
+                "Please provide an account using --account or run aptos init".to_string(),
+            )
+        })
+    }
+}
+
+#[async_trait]
+impl CliCommand<SnapshotSummary> for SnapshotAccount {
+    fn command_name(&self) -> &'static str {
+        "SnapshotAccount"
+    }
+
+    async fn execute(self) -> CliTypedResult<SnapshotSummary> {
+        self.save_file.check_file()?;
+        let account = account_or_profile(self.account, &self.profile_options)?;
+        let client = self.rest_options.client(&self.profile_options.profile)?;
+
+        let snapshot = AccountSnapshot::fetch(&client, account, self.at_version).await?;
+        let bytes = serde_json::to_vec_pretty(&snapshot)
+            .map_err(|err| CliError::UnexpectedError(err.to_string()))?;
+        self.save_file.save_to_file("Account snapshot", &bytes)?;
+
+        Ok(SnapshotSummary {
+            account,
+            version: snapshot.version,
+            num_resources: snapshot.resources.len(),
+        })
+    }
+}
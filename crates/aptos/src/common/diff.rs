@@ -0,0 +1,121 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! Structural diffs of JSON encoded Move values
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A field that differs between two values
+///
+/// Fields are named by their path in the value, e.g. `coin.value` or `items[2].amount`.  When a
+/// whole value is added or removed, the field name is empty and the missing side has no value.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub field: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub before: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after: Option<Value>,
+}
+
+/// Lists the fields that differ between two values, recursing into structs
+///
+/// Vectors of the same length are compared element by element, other vectors are shown as a
+/// single change.
+pub fn diff_values(before: Option<&Value>, after: Option<&Value>) -> Vec<FieldChange> {
+    let mut changes = Vec::new();
+    diff_field(String::new(), before, after, &mut changes);
+    changes
+}
+
+fn diff_field(
+    field: String,
+    before: Option<&Value>,
+    after: Option<&Value>,
+    changes: &mut Vec<FieldChange>,
+) {
+    match (before, after) {
+        (Some(Value::Object(before)), Some(Value::Object(after))) => {
+            let mut fields: Vec<&String> = before.keys().chain(after.keys()).collect();
+            fields.sort();
+            fields.dedup();
+            for name in fields {
+                let path = if field.is_empty() {
+                    name.clone()
+                } else {
+                    format!("{}.{}", field, name)
+                };
+                diff_field(path, before.get(name), after.get(name), changes);
+            }
+        }
+        (Some(Value::Array(before)), Some(Value::Array(after))) if before.len() == after.len() => {
+            for (index, (before, after)) in before.iter().zip(after).enumerate() {
+                diff_field(
+                    format!("{}[{}]", field, index),
+                    Some(before),
+                    Some(after),
+                    changes,
+                );
+            }
+        }
+        (before, after) if before != after => changes.push(FieldChange {
+            field,
+            before: before.cloned(),
+            after: after.cloned(),
+        }),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn diffs_nested_fields() {
+        let before = json!({
+            "coin": { "value": "10" },
+            "frozen": false,
+            "events": [{ "counter": "1" }, { "counter": "2" }],
+            "tags": ["a"],
+        });
+        let after = json!({
+            "coin": { "value": "15" },
+            "frozen": false,
+            "events": [{ "counter": "1" }, { "counter": "3" }],
+            "tags": ["a", "b"],
+        });
+
+        assert_eq!(
+            diff_values(Some(&before), Some(&after)),
+            vec![
+                FieldChange {
+                    field: "coin.value".to_string(),
+                    before: Some(json!("10")),
+                    after: Some(json!("15")),
+                },
+                FieldChange {
+                    field: "events[1].counter".to_string(),
+                    before: Some(json!("2")),
+                    after: Some(json!("3")),
+                },
+                FieldChange {
+                    field: "tags".to_string(),
+                    before: Some(json!(["a"])),
+                    after: Some(json!(["a", "b"])),
+                },
+            ]
+        );
+        assert!(diff_values(Some(&before), Some(&before)).is_empty());
+        assert_eq!(
+            diff_values(None, Some(&after)),
+            vec![FieldChange {
+                field: String::new(),
+                before: None,
+                after: Some(after),
+            }]
+        );
+    }
+}
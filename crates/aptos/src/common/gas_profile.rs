@@ -0,0 +1,620 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::common::{
+    types::{CliError, CliTypedResult},
+    utils::write_to_file,
+};
+use aptos_gas::GasProfile;
+use aptos_rest_client::Client;
+use aptos_state_view::StateView;
+use aptos_types::{
+    state_store::state_key::StateKey,
+    transaction::{ExecutionStatus, SignedTransaction, TransactionStatus},
+};
+use aptos_vm::AptosVM;
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Write,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+use tokio::runtime::Handle;
+
+/// Reads the state of a node over its REST API, as of one ledger version
+///
+/// The VM reads state synchronously, so this must be used off the async runtime, e.g. in
+/// `spawn_blocking`.
+struct RestStateView {
+    client: Client,
+    version: u64,
+    runtime: Handle,
+    values: Mutex<HashMap<StateKey, Option<Vec<u8>>>>,
+    /// The first request that failed, as the VM only sees that storage failed
+    error: Mutex<Option<String>>,
+}
+
+impl StateView for RestStateView {
+    fn get_state_value(&self, state_key: &StateKey) -> anyhow::Result<Option<Vec<u8>>> {
+        if let Some(value) = self.values.lock().unwrap().get(state_key) {
+            return Ok(value.clone());
+        }
+        match self
+            .runtime
+            .block_on(self.client.get_raw_state_value(state_key, self.version))
+        {
+            Ok(value) => {
+                self.values
+                    .lock()
+                    .unwrap()
+                    .insert(state_key.clone(), value.clone());
+                Ok(value)
+            }
+            Err(err) => {
+                self.error
+                    .lock()
+                    .unwrap()
+                    .get_or_insert_with(|| format!("{:#}", err));
+                Err(err)
+            }
+        }
+    }
+
+    fn is_genesis(&self) -> bool {
+        false
+    }
+}
+
+/// A transaction simulated locally, with what it was charged gas for
+#[derive(Clone, Debug)]
+pub struct ProfiledTransaction {
+    pub success: bool,
+    pub vm_status: String,
+    pub gas_used: u64,
+    pub profile: GasProfile,
+    pub gas_unit_scaling_factor: u64,
+}
+
+impl ProfiledTransaction {
+    /// Simulates `txn` locally against the latest state of the node behind `client`, with the
+    /// on-chain gas schedule, profiling the gas it's charged
+    pub async fn simulate(client: &Client, txn: SignedTransaction) -> CliTypedResult<Self> {
+        let version = client
+            .get_ledger_information()
+            .await
+            .map_err(|err| CliError::ApiError(err.to_string()))?
+            .into_inner()
+            .version;
+        let state_view = RestStateView {
+            client: client.clone(),
+            version,
+            runtime: Handle::current(),
+            values: Mutex::new(HashMap::new()),
+            error: Mutex::new(None),
+        };
+
+        tokio::task::spawn_blocking(move || {
+            let request_error = |state_view: &RestStateView| {
+                state_view
+                    .error
+                    .lock()
+                    .unwrap()
+                    .take()
+                    .map(CliError::ApiError)
+            };
+
+            let gas_params =
+                AptosVM::get_gas_parameters_from_state(&state_view).map_err(|err| {
+                    request_error(&state_view)
+                        .unwrap_or_else(|| CliError::UnexpectedError(err.to_string()))
+                })?;
+            let (vm_status, output, profile) =
+                AptosVM::simulate_signed_transaction_with_gas_profile(&txn, &state_view);
+            if let Some(err) = request_error(&state_view) {
+                return Err(err);
+            }
+            let profile = profile.ok_or_else(|| {
+                CliError::UnexpectedError(format!(
+                    "Transaction was discarded before it was charged gas: {:?}",
+                    vm_status
+                ))
+            })?;
+
+            let success = matches!(
+                output.status(),
+                TransactionStatus::Keep(ExecutionStatus::Success)
+            );
+            Ok(ProfiledTransaction {
+                success,
+                vm_status: if success {
+                    "Executed successfully".to_string()
+                } else {
+                    format!("{:?}", vm_status)
+                },
+                gas_used: output.gas_used(),
+                profile,
+                gas_unit_scaling_factor: gas_params.txn.gas_unit_scaling_factor,
+            })
+        })
+        .await
+        .map_err(|err| CliError::UnexpectedError(err.to_string()))?
+    }
+}
+
+/// A summary of a transaction simulated with `--profile-gas`
+///
+/// Gas amounts are in gas units, and can be fractional as the VM charges in smaller units.
+#[derive(Clone, Debug, Serialize)]
+pub struct GasProfileSummary {
+    pub gas_profile_file: PathBuf,
+    pub success: bool,
+    pub vm_status: String,
+    pub gas_used: u64,
+    pub intrinsic_gas: f64,
+    pub execution_gas: f64,
+    pub native_gas: f64,
+    pub storage_gas: f64,
+    pub functions: Vec<GasProfileFunction>,
+    pub categories: Vec<GasProfileItem>,
+    pub instructions: Vec<GasProfileItem>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct GasProfileFunction {
+    pub name: String,
+    pub calls: u64,
+    pub gas: f64,
+    pub self_gas: f64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct GasProfileItem {
+    pub name: String,
+    pub count: u64,
+    pub gas: f64,
+}
+
+impl GasProfileSummary {
+    /// Summarizes the gas profile of `txn`, and writes it as an HTML report to `gas_profile_file`
+    ///
+    /// `label` names what the transaction runs, e.g. its entry function.
+    pub fn write_report(
+        txn: &ProfiledTransaction,
+        label: &str,
+        gas_profile_file: &Path,
+    ) -> CliTypedResult<GasProfileSummary> {
+        let profile = &txn.profile;
+        let scale = txn.gas_unit_scaling_factor.max(1) as f64;
+        let functions = functions_by_gas(profile)
+            .into_iter()
+            .map(|(name, cost)| GasProfileFunction {
+                name,
+                calls: cost.calls,
+                gas: cost.gas as f64 / scale,
+                self_gas: cost.self_gas as f64 / scale,
+            })
+            .collect();
+        let categories = profile
+            .categories()
+            .into_iter()
+            .map(|(category, (count, gas))| GasProfileItem {
+                name: category.name().to_string(),
+                count,
+                gas: gas as f64 / scale,
+            })
+            .collect();
+        let instructions = instructions_by_gas(profile)
+            .into_iter()
+            .map(|(name, cost)| GasProfileItem {
+                name: name.to_string(),
+                count: cost.count,
+                gas: cost.gas as f64 / scale,
+            })
+            .collect();
+
+        write_to_file(
+            gas_profile_file,
+            "Gas profile",
+            render_html(txn, label).as_bytes(),
+        )?;
+
+        Ok(GasProfileSummary {
+            gas_profile_file: gas_profile_file.to_path_buf(),
+            success: txn.success,
+            vm_status: txn.vm_status.clone(),
+            gas_used: txn.gas_used,
+            intrinsic_gas: profile.intrinsic as f64 / scale,
+            execution_gas: profile.execution() as f64 / scale,
+            native_gas: profile.natives as f64 / scale,
+            storage_gas: profile.storage() as f64 / scale,
+            functions,
+            categories,
+            instructions,
+        })
+    }
+}
+
+/// Returns the functions called, most expensive first
+fn functions_by_gas(profile: &GasProfile) -> Vec<(String, aptos_gas::FunctionCost)> {
+    let mut functions: Vec<_> = profile.functions().into_iter().collect();
+    functions.sort_by_key(|(_, cost)| std::cmp::Reverse(cost.gas));
+    functions
+}
+
+/// Returns the instructions executed, most expensive first
+fn instructions_by_gas(profile: &GasProfile) -> Vec<(&str, aptos_gas::InstructionCost)> {
+    let mut instructions: Vec<_> = profile
+        .instructions
+        .iter()
+        .map(|(name, cost)| (name.as_str(), *cost))
+        .collect();
+    instructions.sort_by_key(|(_, cost)| std::cmp::Reverse(cost.gas));
+    instructions
+}
+
+/// The gas charged in a function called along some call stack, and in the functions it called
+#[derive(Default)]
+struct CallTree {
+    gas: u64,
+    callees: BTreeMap<String, CallTree>,
+}
+
+impl CallTree {
+    fn new(call_stacks: &BTreeMap<Vec<String>, u64>) -> Self {
+        let mut root = CallTree::default();
+        for (stack, gas) in call_stacks {
+            let mut node = &mut root;
+            node.gas += gas;
+            for name in stack {
+                node = node.callees.entry(name.clone()).or_default();
+                node.gas += gas;
+            }
+        }
+        root
+    }
+}
+
+/// A bar in the report, spanning `[start, start + gas)` of the total gas on its row
+struct Frame {
+    row: usize,
+    start: u64,
+    gas: u64,
+    name: String,
+}
+
+fn push_callees(frames: &mut Vec<Frame>, tree: &CallTree, row: usize, mut start: u64) {
+    for (name, callee) in &tree.callees {
+        frames.push(Frame {
+            row,
+            start,
+            gas: callee.gas,
+            name: name.clone(),
+        });
+        push_callees(frames, callee, row + 1, start);
+        start += callee.gas;
+    }
+}
+
+/// Lays out the profile as an icicle graph: the transaction running `label` on top, split into
+/// intrinsic gas and the functions it ran, with each function split further into the functions
+/// it called.
+///
+/// Gas charged outside of any function, e.g. when publishing modules, is shown as "other".
+fn frames(profile: &GasProfile, label: &str) -> Vec<Frame> {
+    let total = profile.total();
+    let intrinsic = profile.intrinsic;
+    let mut frames = vec![
+        Frame {
+            row: 0,
+            start: 0,
+            gas: total,
+            name: label.to_string(),
+        },
+        Frame {
+            row: 1,
+            start: 0,
+            gas: intrinsic,
+            name: "intrinsic".to_string(),
+        },
+    ];
+
+    let calls = CallTree::new(&profile.call_stacks);
+    push_callees(&mut frames, &calls, 1, intrinsic);
+    frames.push(Frame {
+        row: 1,
+        start: intrinsic + calls.gas,
+        gas: total.saturating_sub(intrinsic + calls.gas),
+        name: "other".to_string(),
+    });
+    frames
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn render_html(txn: &ProfiledTransaction, label: &str) -> String {
+    let profile = &txn.profile;
+    let scale = txn.gas_unit_scaling_factor.max(1) as f64;
+    let frames = frames(profile, label);
+    let total = frames[0].gas.max(1) as f64;
+    let rows = frames.iter().map(|frame| frame.row + 1).max().unwrap_or(1);
+    let label = escape_html(label);
+
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Gas profile: {label}</title>
+<style>
+body {{ font-family: sans-serif; margin: 2em; }}
+.graph {{ position: relative; height: {height}px; }}
+.frame {{ position: absolute; height: 22px; box-sizing: border-box; border: 1px solid #fff;
+  overflow: hidden; white-space: nowrap; font-size: 12px; line-height: 20px; padding: 0 4px; }}
+table {{ border-collapse: collapse; margin-top: 1em; }}
+td, th {{ padding: 2px 12px; text-align: right; }}
+td:first-child, th:first-child {{ text-align: left; }}
+</style>
+</head>
+<body>
+<h1>Gas profile: {label}</h1>
+<p>Status: {vm_status}. Gas used: {gas_used} gas units
+(intrinsic {intrinsic:.3}, execution {execution:.3} including {storage:.3} for global storage,
+natives {natives:.3}).</p>
+<div class="graph">
+"#,
+        label = label,
+        height = rows * 24,
+        vm_status = escape_html(&txn.vm_status),
+        gas_used = txn.gas_used,
+        intrinsic = profile.intrinsic as f64 / scale,
+        execution = profile.execution() as f64 / scale,
+        natives = profile.natives as f64 / scale,
+        storage = profile.storage() as f64 / scale,
+    );
+
+    for frame in frames.iter().filter(|frame| frame.gas > 0) {
+        let left = frame.start as f64 / total * 100.0;
+        let width = frame.gas as f64 / total * 100.0;
+        // Warm colors, varied by name so neighbouring frames stand apart
+        let hue = frame
+            .name
+            .bytes()
+            .fold(0u32, |acc, b| acc.wrapping_add(b as u32))
+            % 50;
+        let title = format!(
+            "{}: {:.3} gas units ({:.2}%)",
+            frame.name,
+            frame.gas as f64 / scale,
+            width
+        );
+        let _ = writeln!(
+            html,
+            r#"<div class="frame" style="top: {top}px; left: {left:.4}%; width: {width:.4}%; background: hsl({hue}, 80%, 60%);" title="{title}">{name}</div>"#,
+            top = frame.row * 24,
+            left = left,
+            width = width,
+            hue = hue,
+            title = escape_html(&title),
+            name = escape_html(&frame.name),
+        );
+    }
+    html.push_str("</div>\n");
+
+    html.push_str(
+        "<h2>Functions</h2>\n<table>\n\
+         <tr><th>Name</th><th>Calls</th><th>Gas units</th><th>Self gas units</th><th>Share</th></tr>\n",
+    );
+    for (name, cost) in functions_by_gas(profile) {
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td>{:.3}</td><td>{:.3}</td><td>{:.2}%</td></tr>",
+            escape_html(&name),
+            cost.calls,
+            cost.gas as f64 / scale,
+            cost.self_gas as f64 / scale,
+            cost.gas as f64 / total * 100.0
+        );
+    }
+    html.push_str("</table>\n");
+
+    html.push_str(
+        "<h2>Instructions</h2>\n<table>\n\
+         <tr><th>Name</th><th>Count</th><th>Gas units</th><th>Share</th></tr>\n",
+    );
+    let instructions = instructions_by_gas(profile);
+    for (category, (count, gas)) in profile.categories() {
+        let _ = writeln!(
+            html,
+            "<tr><th>{}</th><th>{}</th><th>{:.3}</th><th>{:.2}%</th></tr>",
+            category.name(),
+            count,
+            gas as f64 / scale,
+            gas as f64 / total * 100.0
+        );
+        for (name, cost) in instructions
+            .iter()
+            .filter(|(_, cost)| cost.category == category)
+        {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td>{:.3}</td><td>{:.2}%</td></tr>",
+                escape_html(name),
+                cost.count,
+                cost.gas as f64 / scale,
+                cost.gas as f64 / total * 100.0
+            );
+        }
+    }
+    html.push_str("</table>\n");
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_gas::{InstructionCategory, InstructionCost};
+
+    fn instruction(category: InstructionCategory, count: u64, gas: u64) -> InstructionCost {
+        InstructionCost {
+            category,
+            count,
+            gas,
+        }
+    }
+
+    fn stack(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn profile() -> GasProfile {
+        GasProfile {
+            intrinsic: 300,
+            natives: 100,
+            instructions: vec![
+                (
+                    "MOVE_TO",
+                    instruction(InstructionCategory::GlobalStorage, 1, 400),
+                ),
+                ("ADD", instruction(InstructionCategory::Arithmetic, 3, 150)),
+                ("LT", instruction(InstructionCategory::Arithmetic, 1, 50)),
+            ]
+            .into_iter()
+            .map(|(name, cost)| (name.to_string(), cost))
+            .collect(),
+            call_stacks: vec![
+                (stack(&["0x1::m::f"]), 150),
+                (stack(&["0x1::m::f", "0x1::m::<g>"]), 400),
+                (stack(&["0x1::m::f", "0x1::m::n"]), 100),
+            ]
+            .into_iter()
+            .collect(),
+            calls: vec![("0x1::m::f", 1), ("0x1::m::<g>", 2), ("0x1::m::n", 1)]
+                .into_iter()
+                .map(|(name, calls)| (name.to_string(), calls))
+                .collect(),
+        }
+    }
+
+    fn profiled_transaction(
+        profile: GasProfile,
+        gas_unit_scaling_factor: u64,
+    ) -> ProfiledTransaction {
+        ProfiledTransaction {
+            success: true,
+            vm_status: "Executed successfully".to_string(),
+            gas_used: 1,
+            profile,
+            gas_unit_scaling_factor,
+        }
+    }
+
+    #[test]
+    fn test_frames() {
+        let frames = frames(&profile(), "0x1::m::f");
+        let layout: Vec<_> = frames
+            .iter()
+            .map(|frame| (frame.row, frame.start, frame.gas, frame.name.as_str()))
+            .collect();
+        assert_eq!(
+            layout,
+            vec![
+                (0, 0, 1000, "0x1::m::f"),
+                (1, 0, 300, "intrinsic"),
+                (1, 300, 650, "0x1::m::f"),
+                (2, 300, 400, "0x1::m::<g>"),
+                (2, 700, 100, "0x1::m::n"),
+                // 50 of the execution gas was charged outside of functions
+                (1, 950, 50, "other"),
+            ]
+        );
+
+        // Children never overflow their parent
+        for child in frames.iter().filter(|frame| frame.row > 0) {
+            assert!(frames.iter().any(|parent| parent.row + 1 == child.row
+                && parent.start <= child.start
+                && child.start + child.gas <= parent.start + parent.gas));
+        }
+    }
+
+    #[test]
+    fn test_write_report() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("profile.html");
+        let summary =
+            GasProfileSummary::write_report(&profiled_transaction(profile(), 1000), "f", &file)
+                .unwrap();
+
+        assert_eq!(summary.execution_gas, 0.6);
+        assert_eq!(summary.storage_gas, 0.4);
+        let functions: Vec<_> = summary
+            .functions
+            .iter()
+            .map(|function| (function.name.as_str(), function.calls, function.self_gas))
+            .collect();
+        assert_eq!(
+            functions,
+            vec![
+                ("0x1::m::f", 1, 0.15),
+                ("0x1::m::<g>", 2, 0.4),
+                ("0x1::m::n", 1, 0.1)
+            ]
+        );
+        let instructions: Vec<_> = summary
+            .instructions
+            .iter()
+            .map(|instruction| instruction.name.as_str())
+            .collect();
+        assert_eq!(instructions, vec!["MOVE_TO", "ADD", "LT"]);
+        assert!(std::fs::read_to_string(&file)
+            .unwrap()
+            .contains("<h1>Gas profile: f</h1>"));
+    }
+
+    #[test]
+    fn test_render_html() {
+        let html = render_html(&profiled_transaction(profile(), 1000), "0x1::m::<f>");
+
+        // Names are escaped
+        assert!(html.contains("<title>Gas profile: 0x1::m::&lt;f&gt;</title>"));
+        assert!(!html.contains("<f>"));
+        assert!(!html.contains("<g>"));
+        assert!(html.contains("Status: Executed successfully. Gas used: 1 gas units"));
+        assert!(html.contains(
+            "(intrinsic 0.300, execution 0.600 including 0.400 for global storage,\nnatives 0.100)"
+        ));
+
+        // One bar per frame, scaled to the total gas, on as many rows as the deepest call
+        assert_eq!(html.matches(r#"<div class="frame""#).count(), 6);
+        assert!(html.contains(".graph { position: relative; height: 72px; }"));
+        assert!(html.contains("top: 48px; left: 30.0000%; width: 40.0000%;"));
+        assert!(html.contains(
+            r#"title="0x1::m::&lt;g&gt;: 0.400 gas units (40.00%)">0x1::m::&lt;g&gt;</div>"#
+        ));
+
+        // One table row per function, and per category and instruction
+        assert!(html.contains(
+            "<tr><td>0x1::m::f</td><td>1</td><td>0.650</td><td>0.150</td><td>65.00%</td></tr>"
+        ));
+        assert!(
+            html.contains("<tr><th>arithmetic</th><th>4</th><th>0.200</th><th>20.00%</th></tr>")
+        );
+        assert!(html.contains("<tr><td>LT</td><td>1</td><td>0.050</td><td>5.00%</td></tr>"));
+    }
+
+    #[test]
+    fn test_render_html_empty_profile() {
+        // Frames without gas aren't drawn, and nothing divides by zero
+        let html = render_html(&profiled_transaction(GasProfile::default(), 0), "script");
+        assert_eq!(html.matches(r#"<div class="frame""#).count(), 0);
+        assert!(!html.contains("NaN"));
+    }
+}
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::common::{
+    keystore::read_password,
     types::{
         account_address_from_public_key, CliCommand, CliConfig, CliError, CliTypedResult,
         EncodingOptions, PrivateKeyInputOptions, ProfileConfig, ProfileOptions, PromptOptions,
@@ -132,6 +133,7 @@ impl CliCommand<()> for InitTool {
         profile_config.faucet_url = faucet_url.clone().map(|inner| inner.to_string());
 
         // Private key
+        let mut keep_encrypted_key = false;
         let private_key = if let Some(private_key) = self
             .private_key_options
             .extract_private_key_cli(self.encoding_options.encoding)?
@@ -139,13 +141,25 @@ impl CliCommand<()> for InitTool {
             eprintln!("Using command line argument for private key");
             private_key
         } else {
-            eprintln!("Enter your private key as a hex literal (0x...) [Current: {} | No input: Generate new key (or keep one if present)]", profile_config.private_key.as_ref().map(|_| "Redacted").unwrap_or("None"));
+            let current_key = if profile_config.encrypted_private_key.is_some() {
+                "Encrypted"
+            } else if profile_config.private_key.is_some() {
+                "Redacted"
+            } else {
+                "None"
+            };
+            eprintln!("Enter your private key as a hex literal (0x...) [Current: {} | No input: Generate new key (or keep one if present)]", current_key);
             let input = read_line("Private key")?;
             let input = input.trim();
             if input.is_empty() {
-                if let Some(private_key) = profile_config.private_key {
+                if let Some(private_key) = profile_config.private_key.take() {
                     eprintln!("No key given, keeping existing key...");
                     private_key
+                } else if let Some(ref encrypted_private_key) = profile_config.encrypted_private_key
+                {
+                    eprintln!("No key given, keeping existing encrypted key...");
+                    keep_encrypted_key = true;
+                    encrypted_private_key.decrypt(&read_password(&self.profile_options.profile)?)?
                 } else {
                     eprintln!("No key given, generating key...");
                     self.rng_args
@@ -159,7 +173,10 @@ impl CliCommand<()> for InitTool {
         };
         let public_key = private_key.public_key();
         let address = account_address_from_public_key(&public_key);
-        profile_config.private_key = Some(private_key);
+        if !keep_encrypted_key {
+            profile_config.private_key = Some(private_key);
+            profile_config.encrypted_private_key = None;
+        }
         profile_config.public_key = Some(public_key);
         profile_config.account = Some(address);
 
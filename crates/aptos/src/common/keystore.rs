@@ -0,0 +1,218 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! Encryption of the private keys stored in profiles
+//!
+//! Keys are encrypted with AES-256-GCM, under a key derived from a password with Argon2id.  The
+//! password is read from `APTOS_KEY_PASSWORD`, or from the file named by
+//! `APTOS_KEY_PASSWORD_FILE`, and otherwise prompted for.
+
+use crate::common::{
+    types::{CliError, CliTypedResult},
+    utils::read_from_file,
+};
+use aes_gcm::{
+    aead::{Aead, NewAead},
+    Aes256Gcm, Key, Nonce,
+};
+use aptos_crypto::{ed25519::Ed25519PrivateKey, ValidCryptoMaterial};
+use argon2::{Algorithm, Argon2, Params, Version};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::{convert::TryFrom, path::Path};
+
+/// Environment variable holding the password of encrypted private keys
+pub const PASSWORD_ENV: &str = "APTOS_KEY_PASSWORD";
+/// Environment variable naming a file holding the password of encrypted private keys
+pub const PASSWORD_FILE_ENV: &str = "APTOS_KEY_PASSWORD_FILE";
+
+const KDF_ARGON2ID: &str = "argon2id";
+const CIPHER_AES_256_GCM: &str = "aes-256-gcm";
+/// Argon2id costs, as recommended by OWASP for password storage
+const KDF_MEMORY_KIB: u32 = 19 * 1024;
+const KDF_ITERATIONS: u32 = 2;
+const KDF_PARALLELISM: u32 = 1;
+const SALT_LENGTH: usize = 16;
+const NONCE_LENGTH: usize = 12;
+const KEY_LENGTH: usize = 32;
+
+/// A private key encrypted with a password, as stored in a profile
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct EncryptedPrivateKey {
+    pub kdf: KdfParams,
+    pub cipher: String,
+    /// Hex encoded nonce
+    pub nonce: String,
+    /// Hex encoded private key encrypted with its authentication tag
+    pub ciphertext: String,
+}
+
+/// How the encryption key is derived from the password
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct KdfParams {
+    pub algorithm: String,
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+    /// Hex encoded salt
+    pub salt: String,
+}
+
+impl KdfParams {
+    fn derive_key(&self, password: &str) -> CliTypedResult<[u8; KEY_LENGTH]> {
+        if self.algorithm != KDF_ARGON2ID {
+            return Err(CliError::UnexpectedError(format!(
+                "Unsupported key derivation function '{}'",
+                self.algorithm
+            )));
+        }
+        let salt = hex::decode(&self.salt)?;
+        let params = Params::new(
+            self.memory_kib,
+            self.iterations,
+            self.parallelism,
+            Some(KEY_LENGTH),
+        )
+        .map_err(|err| CliError::UnexpectedError(format!("Invalid Argon2 parameters: {}", err)))?;
+
+        let mut key = [0u8; KEY_LENGTH];
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(password.as_bytes(), &salt, &mut key)
+            .map_err(|err| CliError::UnexpectedError(format!("Failed to derive key: {}", err)))?;
+        Ok(key)
+    }
+}
+
+impl EncryptedPrivateKey {
+    /// Encrypts `private_key` with a key derived from `password`
+    pub fn encrypt(private_key: &Ed25519PrivateKey, password: &str) -> CliTypedResult<Self> {
+        let mut salt = [0u8; SALT_LENGTH];
+        let mut nonce = [0u8; NONCE_LENGTH];
+        OsRng.fill_bytes(&mut salt);
+        OsRng.fill_bytes(&mut nonce);
+
+        let kdf = KdfParams {
+            algorithm: KDF_ARGON2ID.to_string(),
+            memory_kib: KDF_MEMORY_KIB,
+            iterations: KDF_ITERATIONS,
+            parallelism: KDF_PARALLELISM,
+            salt: hex::encode(salt),
+        };
+        let key = kdf.derive_key(password)?;
+        let ciphertext = Aes256Gcm::new(Key::from_slice(&key))
+            .encrypt(Nonce::from_slice(&nonce), private_key.to_bytes().as_ref())
+            .map_err(|_| CliError::UnexpectedError("Failed to encrypt private key".to_string()))?;
+
+        Ok(EncryptedPrivateKey {
+            kdf,
+            cipher: CIPHER_AES_256_GCM.to_string(),
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+        })
+    }
+
+    /// Decrypts the private key, failing if `password` is wrong
+    pub fn decrypt(&self, password: &str) -> CliTypedResult<Ed25519PrivateKey> {
+        if self.cipher != CIPHER_AES_256_GCM {
+            return Err(CliError::UnexpectedError(format!(
+                "Unsupported cipher '{}'",
+                self.cipher
+            )));
+        }
+        let nonce = hex::decode(&self.nonce)?;
+        if nonce.len() != NONCE_LENGTH {
+            return Err(CliError::UnexpectedError(
+                "Invalid nonce for encrypted private key".to_string(),
+            ));
+        }
+        let ciphertext = hex::decode(&self.ciphertext)?;
+
+        let key = self.kdf.derive_key(password)?;
+        let plaintext = Aes256Gcm::new(Key::from_slice(&key))
+            .decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref())
+            .map_err(|_| {
+                CliError::CommandArgumentError(
+                    "Wrong password for encrypted private key".to_string(),
+                )
+            })?;
+        Ok(Ed25519PrivateKey::try_from(plaintext.as_slice())?)
+    }
+}
+
+/// Reads the password from `APTOS_KEY_PASSWORD` or `APTOS_KEY_PASSWORD_FILE`, if either is set
+fn password_from_env() -> CliTypedResult<Option<String>> {
+    if let Ok(password) = std::env::var(PASSWORD_ENV) {
+        return Ok(Some(password));
+    }
+    if let Ok(password_file) = std::env::var(PASSWORD_FILE_ENV) {
+        let password = String::from_utf8(read_from_file(Path::new(&password_file))?)?;
+        // Files usually end with a newline, which isn't part of the password
+        return Ok(Some(
+            password.trim_end_matches(&['\r', '\n'][..]).to_string(),
+        ));
+    }
+    Ok(None)
+}
+
+fn prompt_password(prompt: &str) -> CliTypedResult<String> {
+    rpassword::prompt_password(prompt).map_err(|err| CliError::IO("Password".to_string(), err))
+}
+
+/// Reads the password to unlock the private key of `profile`
+pub fn read_password(profile: &str) -> CliTypedResult<String> {
+    if let Some(password) = password_from_env()? {
+        return Ok(password);
+    }
+    prompt_password(&format!(
+        "Enter the password for the private key of profile '{}': ",
+        profile
+    ))
+}
+
+/// Reads a new password to encrypt the private key of `profile` with, asking for it twice
+pub fn read_new_password(profile: &str) -> CliTypedResult<String> {
+    if let Some(password) = password_from_env()? {
+        return Ok(password);
+    }
+    let password = prompt_password(&format!(
+        "Enter a new password for the private key of profile '{}': ",
+        profile
+    ))?;
+    if password.is_empty() {
+        return Err(CliError::CommandArgumentError(
+            "Password must not be empty".to_string(),
+        ));
+    }
+    if password != prompt_password("Confirm the password: ")? {
+        return Err(CliError::CommandArgumentError(
+            "Passwords don't match".to_string(),
+        ));
+    }
+    Ok(password)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_keygen::KeyGen;
+
+    #[test]
+    fn encrypt_and_decrypt() {
+        let private_key = KeyGen::from_os_rng().generate_ed25519_private_key();
+        let encrypted = EncryptedPrivateKey::encrypt(&private_key, "password").unwrap();
+
+        assert_eq!(
+            encrypted.decrypt("password").unwrap().to_bytes(),
+            private_key.to_bytes()
+        );
+        assert!(encrypted.decrypt("wrong password").is_err());
+
+        let mut ciphertext = hex::decode(&encrypted.ciphertext).unwrap();
+        ciphertext[0] ^= 1;
+        let tampered = EncryptedPrivateKey {
+            ciphertext: hex::encode(ciphertext),
+            ..encrypted
+        };
+        assert!(tampered.decrypt("password").is_err());
+    }
+}
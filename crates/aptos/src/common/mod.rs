@@ -1,6 +1,9 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod diff;
+pub mod gas_profile;
 pub mod init;
+pub mod keystore;
 pub mod types;
 pub mod utils;
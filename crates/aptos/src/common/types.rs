@@ -5,17 +5,19 @@ use crate::common::utils::{create_dir_if_not_exist, dir_default_to_current, star
 use crate::config::GlobalConfig;
 use crate::{
     common::{
+        gas_profile::{GasProfileSummary, ProfiledTransaction},
         init::{DEFAULT_FAUCET_URL, DEFAULT_REST_URL},
+        keystore::{read_password, EncryptedPrivateKey},
         utils::{
-            chain_id, check_if_file_exists, get_sequence_number, read_from_file, to_common_result,
-            to_common_success_result, write_to_file, write_to_file_with_opts,
-            write_to_user_only_file,
+            append_file_extension, chain_id, check_if_file_exists, get_sequence_number,
+            read_from_file, to_common_result, to_common_success_result, write_to_file,
+            write_to_file_with_opts, write_to_user_only_file,
         },
     },
     genesis::git::from_yaml,
 };
 use aptos_crypto::{
-    ed25519::{Ed25519PrivateKey, Ed25519PublicKey},
+    ed25519::{Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature},
     x25519, PrivateKey, ValidCryptoMaterial, ValidCryptoMaterialStringExt,
 };
 use aptos_keygen::KeyGen;
@@ -32,7 +34,7 @@ use aptos_sdk::{
     types::LocalAccount,
 };
 use aptos_types::transaction::{
-    authenticator::AuthenticationKey, ScriptFunction, TransactionPayload,
+    authenticator::AuthenticationKey, ScriptFunction, SignedTransaction, TransactionPayload,
 };
 use async_trait::async_trait;
 use clap::{ArgEnum, Parser};
@@ -43,6 +45,7 @@ use serde::{Deserialize, Serialize};
 use std::os::unix::fs::OpenOptionsExt;
 use std::{
     collections::{BTreeMap, HashMap},
+    convert::TryFrom,
     fmt::{Debug, Display, Formatter},
     fs::OpenOptions,
     path::{Path, PathBuf},
@@ -178,6 +181,9 @@ pub const CONFIG_FOLDER: &str = ".aptos";
 pub struct ProfileConfig {
     /// Private key for commands.
     pub private_key: Option<Ed25519PrivateKey>,
+    /// Private key for commands, encrypted with a password by `aptos key encrypt`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encrypted_private_key: Option<EncryptedPrivateKey>,
     /// Public key for commands
     pub public_key: Option<Ed25519PublicKey>,
     /// Account for commands
@@ -188,6 +194,21 @@ pub struct ProfileConfig {
     pub faucet_url: Option<String>,
 }
 
+impl ProfileConfig {
+    /// Returns the private key, asking for the password of `profile` if it's encrypted
+    pub fn unlock_private_key(self, profile: &str) -> CliTypedResult<Option<Ed25519PrivateKey>> {
+        if let Some(private_key) = self.private_key {
+            Ok(Some(private_key))
+        } else if let Some(encrypted_private_key) = self.encrypted_private_key {
+            encrypted_private_key
+                .decrypt(&read_password(profile)?)
+                .map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+}
+
 impl Default for CliConfig {
     fn default() -> Self {
         CliConfig {
@@ -540,8 +561,10 @@ impl PrivateKeyInputOptions {
     ) -> CliTypedResult<Ed25519PrivateKey> {
         if let Some(key) = self.extract_private_key_cli(encoding)? {
             Ok(key)
-        } else if let Some(Some(private_key)) =
-            CliConfig::load_profile(profile)?.map(|p| p.private_key)
+        } else if let Some(private_key) = CliConfig::load_profile(profile)?
+            .map(|p| p.unlock_private_key(profile))
+            .transpose()?
+            .flatten()
         {
             Ok(private_key)
         } else {
@@ -735,8 +758,12 @@ pub fn load_account_arg(str: &str) -> Result<AccountAddress, CliError> {
         })
     } else if let Ok(account_address) = AccountAddress::from_str(str) {
         Ok(account_address)
-    } else if let Some(Some(private_key)) = CliConfig::load_profile(str)?.map(|p| p.private_key) {
-        let public_key = private_key.public_key();
+    } else if let Some(public_key) = CliConfig::load_profile(str)?.and_then(|p| {
+        // Encrypted keys are stored with their public key, so there's no need to unlock them
+        p.private_key
+            .map(|private_key| private_key.public_key())
+            .or(p.public_key)
+    }) {
         Ok(account_address_from_public_key(&public_key))
     } else {
         Err(CliError::CommandArgumentError(
@@ -932,6 +959,11 @@ impl Default for GasOptions {
     }
 }
 
+/// Seconds until a transaction expires, when it is submitted right away
+pub const DEFAULT_EXPIRATION_SECS: u64 = 30;
+/// Seconds until a transaction built with `--build-only` expires, leaving time to sign it offline
+pub const DEFAULT_BUILD_ONLY_EXPIRATION_SECS: u64 = 600;
+
 /// Common options for interacting with an account for a validator
 #[derive(Debug, Default, Parser)]
 pub struct TransactionOptions {
@@ -945,6 +977,36 @@ pub struct TransactionOptions {
     pub(crate) rest_options: RestOptions,
     #[clap(flatten)]
     pub(crate) gas_options: GasOptions,
+    /// Only build the transaction, and write it unsigned to this file instead of submitting it
+    ///
+    /// The transaction is written as BCS, along with a JSON summary in a `.json` file next to it.
+    /// It can then be signed offline with `aptos transaction sign`, and submitted with
+    /// `aptos transaction submit`.  No private key is needed to build it.
+    #[clap(long, parse(from_os_str))]
+    pub(crate) build_only: Option<PathBuf>,
+    /// Account to send a transaction built with `--build-only` from
+    ///
+    /// Defaults to the account of the profile
+    #[clap(long, parse(try_from_str = crate::common::types::load_account_arg))]
+    pub(crate) sender_account: Option<AccountAddress>,
+    /// Sequence number to use for the transaction
+    ///
+    /// Defaults to the current sequence number of the sender on chain
+    #[clap(long)]
+    pub(crate) sequence_number: Option<u64>,
+    /// Number of seconds until the transaction expires
+    ///
+    /// Defaults to 30 seconds, or 600 seconds with `--build-only`
+    #[clap(long)]
+    pub(crate) expiration_secs: Option<u64>,
+    /// Simulate the transaction instead of submitting it, and write a gas profile to this file
+    ///
+    /// The simulation runs locally, against the state of the node and its on-chain gas schedule.
+    /// The profile breaks down the gas charged by function, by kind of instruction and by
+    /// instruction, and into intrinsic, native function and storage gas.  It is written as an
+    /// HTML report, and summarized in the output.
+    #[clap(long, parse(from_os_str))]
+    pub(crate) profile_gas: Option<PathBuf>,
 }
 
 impl TransactionOptions {
@@ -969,7 +1031,7 @@ impl TransactionOptions {
         function: &'static str,
         type_args: Vec<TypeTag>,
         args: Vec<Vec<u8>>,
-    ) -> CliTypedResult<Transaction> {
+    ) -> CliTypedResult<TransactionOutcome<Transaction>> {
         let txn = TransactionPayload::ScriptFunction(ScriptFunction::new(
             ModuleId::new(address, ident_str!(module).to_owned()),
             ident_str!(function).to_owned(),
@@ -979,11 +1041,37 @@ impl TransactionOptions {
         self.submit_transaction(txn).await
     }
 
-    /// Submit a transaction
+    /// Submit a transaction, or only build it if `--build-only` is given, or only simulate it if
+    /// `--profile-gas` is given
     pub async fn submit_transaction(
         &self,
         payload: TransactionPayload,
-    ) -> CliTypedResult<Transaction> {
+    ) -> CliTypedResult<TransactionOutcome<Transaction>> {
+        if self.build_only.is_some() && self.profile_gas.is_some() {
+            return Err(CliError::CommandArgumentError(
+                "Only one of --build-only and --profile-gas can be given".to_string(),
+            ));
+        }
+        if self.sender_account.is_some() && self.build_only.is_none() {
+            return Err(CliError::CommandArgumentError(
+                "--sender-account can only be given with --build-only, otherwise the transaction \
+                 is sent from the account of the private key"
+                    .to_string(),
+            ));
+        }
+        if let Some(ref unsigned_txn_file) = self.build_only {
+            return self
+                .build_transaction(payload, unsigned_txn_file)
+                .await
+                .map(TransactionOutcome::Built);
+        }
+        if let Some(ref gas_profile_file) = self.profile_gas {
+            return self
+                .profile_gas(payload, gas_profile_file)
+                .await
+                .map(TransactionOutcome::Simulated);
+        }
+
         let sender_key = self.private_key()?;
         let client = self.rest_client()?;
 
@@ -992,12 +1080,12 @@ impl TransactionOptions {
         let sender_address = AccountAddress::new(*sender_address);
 
         // Get sequence number for account
-        let sequence_number = get_sequence_number(&client, sender_address).await?;
+        let sequence_number = self.sequence_number(&client, sender_address).await?;
 
         // Sign and submit transaction
-        let transaction_factory = TransactionFactory::new(chain_id(&client).await?)
-            .with_gas_unit_price(self.gas_options.gas_unit_price)
-            .with_max_gas_amount(self.gas_options.max_gas);
+        let transaction_factory = self
+            .transaction_factory(&client, DEFAULT_EXPIRATION_SECS)
+            .await?;
         let sender_account = &mut LocalAccount::new(sender_address, sender_key, sequence_number);
         let transaction =
             sender_account.sign_with_transaction_builder(transaction_factory.payload(payload));
@@ -1006,6 +1094,161 @@ impl TransactionOptions {
             .await
             .map_err(|err| CliError::ApiError(err.to_string()))?;
 
-        Ok(response.into_inner())
+        Ok(TransactionOutcome::Submitted(response.into_inner()))
     }
+
+    /// Builds an unsigned transaction, and writes it to `unsigned_txn_file` for offline signing
+    async fn build_transaction(
+        &self,
+        payload: TransactionPayload,
+        unsigned_txn_file: &Path,
+    ) -> CliTypedResult<UnsignedTransactionSummary> {
+        let client = self.rest_client()?;
+        let sender_address = match self.sender_account {
+            Some(sender_address) => sender_address,
+            None => self.profile_options.account_address()?,
+        };
+        let sequence_number = self.sequence_number(&client, sender_address).await?;
+
+        let raw_txn = self
+            .transaction_factory(&client, DEFAULT_BUILD_ONLY_EXPIRATION_SECS)
+            .await?
+            .payload(payload)
+            .sender(sender_address)
+            .sequence_number(sequence_number)
+            .build();
+
+        let summary_file = append_file_extension(unsigned_txn_file, "json")?;
+        write_to_file(
+            unsigned_txn_file,
+            "Unsigned transaction",
+            &bcs::to_bytes(&raw_txn).map_err(|err| CliError::BCS("RawTransaction", err))?,
+        )?;
+        write_to_file(
+            &summary_file,
+            "Unsigned transaction summary",
+            serde_json::to_string_pretty(&raw_txn)
+                .map_err(|err| CliError::UnexpectedError(err.to_string()))?
+                .as_bytes(),
+        )?;
+
+        Ok(UnsignedTransactionSummary {
+            unsigned_txn_file: unsigned_txn_file.to_path_buf(),
+            summary_file,
+            sender: sender_address,
+            sequence_number,
+        })
+    }
+
+    /// Simulates a transaction, and writes what it was charged gas for to `gas_profile_file`
+    async fn profile_gas(
+        &self,
+        payload: TransactionPayload,
+        gas_profile_file: &Path,
+    ) -> CliTypedResult<GasProfileSummary> {
+        let label = match &payload {
+            TransactionPayload::ScriptFunction(function) => format!(
+                "{}::{}",
+                function.module().short_str_lossless(),
+                function.function()
+            ),
+            TransactionPayload::Script(_) => "script".to_string(),
+            TransactionPayload::ModuleBundle(_) => "module publishing".to_string(),
+            TransactionPayload::WriteSet(_) => "write set".to_string(),
+        };
+
+        let public_key = self.private_key()?.public_key();
+        let client = self.rest_client()?;
+        let sender_address = AuthenticationKey::ed25519(&public_key).derived_address();
+        let sender_address = AccountAddress::new(*sender_address);
+        let sequence_number = self.sequence_number(&client, sender_address).await?;
+
+        let raw_txn = self
+            .transaction_factory(&client, DEFAULT_EXPIRATION_SECS)
+            .await?
+            .payload(payload)
+            .sender(sender_address)
+            .sequence_number(sequence_number)
+            .build();
+        // The VM refuses to simulate validly signed transactions, so sign with zeroes
+        let signature = Ed25519Signature::try_from(&[0u8; 64][..])
+            .map_err(|err| CliError::UnexpectedError(err.to_string()))?;
+        let txn = SignedTransaction::new(raw_txn, public_key, signature);
+
+        let simulated = ProfiledTransaction::simulate(&client, txn).await?;
+        GasProfileSummary::write_report(&simulated, &label, gas_profile_file)
+    }
+
+    /// Uses the given sequence number, or else retrieves the sender's current one
+    async fn sequence_number(
+        &self,
+        client: &Client,
+        sender_address: AccountAddress,
+    ) -> CliTypedResult<u64> {
+        match self.sequence_number {
+            Some(sequence_number) => Ok(sequence_number),
+            None => get_sequence_number(client, sender_address).await,
+        }
+    }
+
+    async fn transaction_factory(
+        &self,
+        client: &Client,
+        default_expiration_secs: u64,
+    ) -> CliTypedResult<TransactionFactory> {
+        Ok(TransactionFactory::new(chain_id(client).await?)
+            .with_gas_unit_price(self.gas_options.gas_unit_price)
+            .with_max_gas_amount(self.gas_options.max_gas)
+            .with_transaction_expiration_time(
+                self.expiration_secs.unwrap_or(default_expiration_secs),
+            ))
+    }
+}
+
+/// The outcome of a command that sends a transaction
+///
+/// With `--build-only`, the transaction is only built and written to a file, and with
+/// `--profile-gas` it is only simulated, so the command has nothing else to show.  Otherwise,
+/// this serializes the same as the submitted result.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum TransactionOutcome<T> {
+    Submitted(T),
+    Built(UnsignedTransactionSummary),
+    Simulated(GasProfileSummary),
+}
+
+impl<T> TransactionOutcome<T> {
+    /// Maps the submitted result, leaving a built or simulated transaction untouched
+    pub fn map<U, F: FnOnce(T) -> U>(self, f: F) -> TransactionOutcome<U> {
+        match self {
+            TransactionOutcome::Submitted(inner) => TransactionOutcome::Submitted(f(inner)),
+            TransactionOutcome::Built(summary) => TransactionOutcome::Built(summary),
+            TransactionOutcome::Simulated(summary) => TransactionOutcome::Simulated(summary),
+        }
+    }
+
+    /// Returns the submitted result, or an error if the transaction was only built or simulated
+    pub fn into_submitted(self) -> CliTypedResult<T> {
+        match self {
+            TransactionOutcome::Submitted(inner) => Ok(inner),
+            TransactionOutcome::Built(summary) => Err(CliError::UnexpectedError(format!(
+                "Transaction was only built and written to {}",
+                summary.unsigned_txn_file.display()
+            ))),
+            TransactionOutcome::Simulated(summary) => Err(CliError::UnexpectedError(format!(
+                "Transaction was only simulated, with its gas profile written to {}",
+                summary.gas_profile_file.display()
+            ))),
+        }
+    }
+}
+
+/// A summary of a transaction built with `--build-only`
+#[derive(Clone, Debug, Serialize)]
+pub struct UnsignedTransactionSummary {
+    pub unsigned_txn_file: PathBuf,
+    pub summary_file: PathBuf,
+    pub sender: AccountAddress,
+    pub sequence_number: u64,
 }
@@ -2,26 +2,39 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::common::types::{
-    AccountAddressWrapper, CliError, CliTypedResult, PromptOptions, TransactionOptions,
+    AccountAddressWrapper, CliError, CliTypedResult, MovePackageDir, ProfileOptions, PromptOptions,
+    RestOptions, TransactionOptions, TransactionOutcome,
 };
-use crate::common::utils::prompt_yes_with_override;
+use crate::common::utils::{prompt_yes_with_override, read_from_file};
+use crate::move_tool::BuiltPackage;
 use crate::{CliCommand, CliResult};
 use aptos_crypto::HashValue;
-use aptos_rest_client::Transaction;
+use aptos_rest_client::aptos_api_types::{Address, HexEncodedBytes, U128, U64};
+use aptos_rest_client::{Client, Transaction};
 use aptos_types::account_address::AccountAddress;
+use aptos_types::transaction::{Script, TransactionArgument, TransactionPayload};
 use async_trait::async_trait;
 use clap::Parser;
+use futures::{stream, StreamExt, TryStreamExt};
 use reqwest::Url;
 use serde::Deserialize;
 use serde::Serialize;
 use std::fmt::Formatter;
+use std::path::PathBuf;
 
 /// Tool for on-chain governance
 ///
+/// Proposals can be submitted, listed and voted on, and once passed executed.  Before voting, the
+/// script a proposal executes can be checked with `verify-proposal`.
+///
 #[derive(Parser)]
 pub enum GovernanceTool {
     Propose(SubmitProposal),
     Vote(SubmitVote),
+    ListProposals(ListProposals),
+    ShowProposal(ShowProposal),
+    VerifyProposal(VerifyProposal),
+    ExecuteProposal(ExecuteProposal),
 }
 
 impl GovernanceTool {
@@ -30,6 +43,10 @@ impl GovernanceTool {
         match self {
             Propose(tool) => tool.execute_serialized().await,
             Vote(tool) => tool.execute_serialized().await,
+            ListProposals(tool) => tool.execute_serialized().await,
+            ShowProposal(tool) => tool.execute_serialized().await,
+            VerifyProposal(tool) => tool.execute_serialized().await,
+            ExecuteProposal(tool) => tool.execute_serialized().await,
         }
     }
 }
@@ -54,12 +71,12 @@ pub struct SubmitProposal {
 }
 
 #[async_trait]
-impl CliCommand<Transaction> for SubmitProposal {
+impl CliCommand<TransactionOutcome<Transaction>> for SubmitProposal {
     fn command_name(&self) -> &'static str {
         "SubmitProposal"
     }
 
-    async fn execute(mut self) -> CliTypedResult<Transaction> {
+    async fn execute(mut self) -> CliTypedResult<TransactionOutcome<Transaction>> {
         // Validate the proposal metadata
         let client = reqwest::ClientBuilder::default()
             .tls_built_in_root_certs(true)
@@ -137,12 +154,12 @@ pub struct SubmitVote {
 }
 
 #[async_trait]
-impl CliCommand<Transaction> for SubmitVote {
+impl CliCommand<TransactionOutcome<Transaction>> for SubmitVote {
     fn command_name(&self) -> &'static str {
         "SubmitVote"
     }
 
-    async fn execute(mut self) -> CliTypedResult<Transaction> {
+    async fn execute(mut self) -> CliTypedResult<TransactionOutcome<Transaction>> {
         // TODO: Display details of proposal
         let vote = if self.should_pass { "Yes" } else { "No" };
         prompt_yes_with_override(
@@ -183,3 +200,480 @@ impl std::fmt::Display for ProposalMetadata {
         )
     }
 }
+
+/// The voting forum holding on-chain governance proposals
+const VOTING_FORUM_TYPE: &str =
+    "0x1::voting::VotingForum<0x1::governance_proposal::GovernanceProposal>";
+/// A proposal in the voting forum
+const PROPOSAL_TYPE: &str = "0x1::voting::Proposal<0x1::governance_proposal::GovernanceProposal>";
+
+#[derive(Deserialize)]
+struct VotingForum {
+    proposals: TableHandle,
+    next_proposal_id: U64,
+}
+
+#[derive(Deserialize)]
+struct TableHandle {
+    handle: U128,
+}
+
+/// A Move `Option`, which is encoded as a vector of zero or one element
+#[derive(Deserialize)]
+struct MoveOption<T> {
+    vec: Vec<T>,
+}
+
+impl<T> MoveOption<T> {
+    fn into_option(self) -> Option<T> {
+        self.vec.into_iter().next()
+    }
+}
+
+#[derive(Deserialize)]
+struct OnChainProposal {
+    proposer: Address,
+    execution_content: MoveOption<GovernanceProposal>,
+    creation_time_secs: U64,
+    execution_hash: HexEncodedBytes,
+    min_vote_threshold: U128,
+    expiration_secs: U64,
+    early_resolution_vote_threshold: MoveOption<U128>,
+    yes_votes: U128,
+    no_votes: U128,
+    is_resolved: bool,
+}
+
+#[derive(Deserialize)]
+struct GovernanceProposal {
+    metadata_location: String,
+    metadata_hash: String,
+}
+
+/// The state of a proposal, as computed by `voting::get_proposal_state`
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProposalState {
+    /// Voting is still open
+    Pending,
+    /// Voting closed with enough votes, and more yes than no votes, it can be executed
+    Succeeded,
+    /// Voting closed without enough votes, or with at least as many no as yes votes
+    Failed,
+    /// The proposal has been executed
+    Resolved,
+}
+
+/// An on-chain governance proposal, with its votes
+#[derive(Debug, Serialize)]
+pub struct ProposalDetails {
+    pub proposal_id: u64,
+    pub proposer: AccountAddress,
+    pub state: ProposalState,
+    /// Hash of the script that executes the proposal
+    pub execution_hash: String,
+    /// Metadata of the proposal, which is removed once it is executed
+    pub metadata_location: Option<String>,
+    pub metadata_hash: Option<String>,
+    pub creation_time_secs: u64,
+    /// Time at which voting closes, unless the early resolution threshold is reached before
+    pub expiration_secs: u64,
+    /// Total votes needed for the proposal to pass
+    pub min_vote_threshold: u128,
+    /// Yes or no votes that close voting before the expiration
+    pub early_resolution_vote_threshold: Option<u128>,
+    pub yes_votes: u128,
+    pub no_votes: u128,
+}
+
+impl ProposalDetails {
+    fn new(proposal_id: u64, proposal: OnChainProposal, now_secs: u64) -> Self {
+        let early_resolution_vote_threshold = proposal
+            .early_resolution_vote_threshold
+            .into_option()
+            .map(|threshold| threshold.0);
+        let yes_votes = proposal.yes_votes.0;
+        let no_votes = proposal.no_votes.0;
+        let min_vote_threshold = proposal.min_vote_threshold.0;
+        let expiration_secs = proposal.expiration_secs.0;
+
+        let resolvable_early = early_resolution_vote_threshold
+            .map(|threshold| yes_votes >= threshold || no_votes >= threshold)
+            .unwrap_or(false);
+        let state = if proposal.is_resolved {
+            ProposalState::Resolved
+        } else if !resolvable_early && now_secs < expiration_secs {
+            ProposalState::Pending
+        } else if yes_votes > no_votes && yes_votes + no_votes >= min_vote_threshold {
+            ProposalState::Succeeded
+        } else {
+            ProposalState::Failed
+        };
+
+        let content = proposal.execution_content.into_option();
+        ProposalDetails {
+            proposal_id,
+            proposer: *proposal.proposer.inner(),
+            state,
+            execution_hash: format!("0x{}", hex::encode(&proposal.execution_hash.0)),
+            metadata_location: content
+                .as_ref()
+                .map(|content| content.metadata_location.clone()),
+            metadata_hash: content.map(|content| content.metadata_hash),
+            creation_time_secs: proposal.creation_time_secs.0,
+            expiration_secs,
+            min_vote_threshold,
+            early_resolution_vote_threshold,
+            yes_votes,
+            no_votes,
+        }
+    }
+}
+
+/// Reads proposals from the voting forum
+struct ProposalReader {
+    client: Client,
+    proposals_handle: u128,
+    next_proposal_id: u64,
+    /// Ledger time of the forum read, to tell whether voting has closed
+    now_secs: u64,
+}
+
+impl ProposalReader {
+    async fn new(client: Client) -> CliTypedResult<Self> {
+        let (forum, state) = client
+            .get_resource::<VotingForum>(AccountAddress::ONE, VOTING_FORUM_TYPE)
+            .await
+            .map_err(|err| CliError::ApiError(err.to_string()))?
+            .into_parts();
+        Ok(ProposalReader {
+            client,
+            proposals_handle: forum.proposals.handle.0,
+            next_proposal_id: forum.next_proposal_id.0,
+            now_secs: state.timestamp_usecs / 1_000_000,
+        })
+    }
+
+    async fn get_proposal(&self, proposal_id: u64) -> CliTypedResult<ProposalDetails> {
+        if proposal_id >= self.next_proposal_id {
+            return Err(CliError::CommandArgumentError(format!(
+                "Proposal {} doesn't exist, there are {} proposals",
+                proposal_id, self.next_proposal_id
+            )));
+        }
+        let proposal = self
+            .client
+            .get_table_item(
+                self.proposals_handle,
+                "u64",
+                PROPOSAL_TYPE,
+                proposal_id.to_string(),
+            )
+            .await
+            .map_err(|err| CliError::ApiError(err.to_string()))?
+            .into_inner();
+        let proposal: OnChainProposal = serde_json::from_value(proposal).map_err(|err| {
+            CliError::UnexpectedError(format!("Failed to parse proposal {}: {}", proposal_id, err))
+        })?;
+        Ok(ProposalDetails::new(proposal_id, proposal, self.now_secs))
+    }
+}
+
+/// How many proposals `ListProposals` fetches at once
+const MAX_CONCURRENT_PROPOSAL_FETCHES: usize = 16;
+
+/// List all on-chain governance proposals
+#[derive(Parser)]
+pub struct ListProposals {
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+}
+
+#[async_trait]
+impl CliCommand<Vec<ProposalDetails>> for ListProposals {
+    fn command_name(&self) -> &'static str {
+        "ListProposals"
+    }
+
+    async fn execute(self) -> CliTypedResult<Vec<ProposalDetails>> {
+        let client = self.rest_options.client(&self.profile_options.profile)?;
+        let reader = ProposalReader::new(client).await?;
+        stream::iter(0..reader.next_proposal_id)
+            .map(|proposal_id| reader.get_proposal(proposal_id))
+            .buffered(MAX_CONCURRENT_PROPOSAL_FETCHES)
+            .try_collect()
+            .await
+    }
+}
+
+/// Show an on-chain governance proposal, with its votes and when voting closes
+#[derive(Parser)]
+pub struct ShowProposal {
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+    /// Id of the proposal to show
+    #[clap(long)]
+    pub(crate) proposal_id: u64,
+}
+
+#[async_trait]
+impl CliCommand<ProposalDetails> for ShowProposal {
+    fn command_name(&self) -> &'static str {
+        "ShowProposal"
+    }
+
+    async fn execute(self) -> CliTypedResult<ProposalDetails> {
+        let client = self.rest_options.client(&self.profile_options.profile)?;
+        ProposalReader::new(client)
+            .await?
+            .get_proposal(self.proposal_id)
+            .await
+    }
+}
+
+/// The resolution script of a proposal
+///
+/// The script is either compiled from a Move package, or given already compiled.  It's passed the
+/// proposal id as its only argument when it's executed.
+#[derive(Parser)]
+pub struct ResolutionScript {
+    /// Path to the compiled script, instead of compiling it from the Move package
+    #[clap(long, parse(from_os_str))]
+    pub(crate) compiled_script_path: Option<PathBuf>,
+    /// Name of the script in the Move package, needed if the package has more than one script
+    #[clap(long)]
+    pub(crate) script_name: Option<String>,
+    #[clap(flatten)]
+    pub(crate) move_options: MovePackageDir,
+}
+
+impl ResolutionScript {
+    /// Returns the bytecode of the script
+    fn bytecode(self) -> CliTypedResult<Vec<u8>> {
+        if let Some(path) = self.compiled_script_path {
+            return read_from_file(&path);
+        }
+
+        let package = BuiltPackage::build(self.move_options, false, false)?;
+        let mut scripts = package.extract_scripts();
+        match self.script_name {
+            Some(script_name) => scripts
+                .into_iter()
+                .find(|(name, _)| *name == script_name)
+                .map(|(_, code)| code)
+                .ok_or_else(|| {
+                    CliError::CommandArgumentError(format!(
+                        "No script named '{}' in the package",
+                        script_name
+                    ))
+                }),
+            None if scripts.len() == 1 => Ok(scripts.remove(0).1),
+            None => Err(CliError::CommandArgumentError(format!(
+                "The package must have exactly one script, or one must be chosen with \
+                 --script-name, found {}",
+                scripts.len()
+            ))),
+        }
+    }
+}
+
+/// Verify that a script is the one a proposal will execute
+///
+/// The hash of the script is compared with the execution hash of the proposal, and the command
+/// fails if they differ.  Only the script with that hash can execute the proposal, so this shows
+/// what a vote would approve.
+#[derive(Parser)]
+pub struct VerifyProposal {
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+    /// Id of the proposal to verify
+    #[clap(long)]
+    pub(crate) proposal_id: u64,
+    #[clap(flatten)]
+    pub(crate) script: ResolutionScript,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VerifiedProposal {
+    /// Hash of the script
+    pub computed_hash: String,
+    /// Execution hash of the proposal
+    pub onchain_hash: String,
+}
+
+#[async_trait]
+impl CliCommand<VerifiedProposal> for VerifyProposal {
+    fn command_name(&self) -> &'static str {
+        "VerifyProposal"
+    }
+
+    async fn execute(self) -> CliTypedResult<VerifiedProposal> {
+        let client = self.rest_options.client(&self.profile_options.profile)?;
+        let proposal = ProposalReader::new(client)
+            .await?
+            .get_proposal(self.proposal_id)
+            .await?;
+        let computed_hash = HashValue::sha3_256_of(&self.script.bytecode()?).to_hex_literal();
+        if computed_hash != proposal.execution_hash {
+            return Err(CliError::UnexpectedError(format!(
+                "Script hash {} doesn't match the execution hash {} of proposal {}",
+                computed_hash, proposal.execution_hash, self.proposal_id
+            )));
+        }
+
+        Ok(VerifiedProposal {
+            computed_hash,
+            onchain_hash: proposal.execution_hash,
+        })
+    }
+}
+
+/// Execute a proposal that passed, by running its resolution script
+#[derive(Parser)]
+pub struct ExecuteProposal {
+    #[clap(flatten)]
+    pub(crate) txn_options: TransactionOptions,
+    /// Id of the proposal to execute
+    #[clap(long)]
+    pub(crate) proposal_id: u64,
+    #[clap(flatten)]
+    pub(crate) script: ResolutionScript,
+}
+
+#[async_trait]
+impl CliCommand<TransactionOutcome<Transaction>> for ExecuteProposal {
+    fn command_name(&self) -> &'static str {
+        "ExecuteProposal"
+    }
+
+    async fn execute(self) -> CliTypedResult<TransactionOutcome<Transaction>> {
+        let client = self
+            .txn_options
+            .rest_options
+            .client(&self.txn_options.profile_options.profile)?;
+        let proposal = ProposalReader::new(client)
+            .await?
+            .get_proposal(self.proposal_id)
+            .await?;
+        if proposal.state != ProposalState::Succeeded {
+            return Err(CliError::CommandArgumentError(format!(
+                "Proposal {} can't be executed, its state is {:?}",
+                self.proposal_id, proposal.state
+            )));
+        }
+
+        // Check the hash here, rather than paying gas for the transaction to abort
+        let code = self.script.bytecode()?;
+        let hash = HashValue::sha3_256_of(&code).to_hex_literal();
+        if hash != proposal.execution_hash {
+            return Err(CliError::CommandArgumentError(format!(
+                "Script hash {} doesn't match the execution hash {} of proposal {}",
+                hash, proposal.execution_hash, self.proposal_id
+            )));
+        }
+
+        self.txn_options
+            .submit_transaction(TransactionPayload::Script(Script::new(
+                code,
+                vec![],
+                vec![TransactionArgument::U64(self.proposal_id)],
+            )))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW_SECS: u64 = 1_000;
+
+    fn proposal(yes_votes: u128, no_votes: u128, expiration_secs: u64) -> OnChainProposal {
+        OnChainProposal {
+            proposer: AccountAddress::ONE.into(),
+            execution_content: MoveOption {
+                vec: vec![GovernanceProposal {
+                    metadata_location: "https://example.com/proposal.json".to_string(),
+                    metadata_hash: "abcd".to_string(),
+                }],
+            },
+            creation_time_secs: U64(0),
+            execution_hash: HexEncodedBytes(vec![0xab, 0xcd]),
+            min_vote_threshold: U128(100),
+            expiration_secs: U64(expiration_secs),
+            early_resolution_vote_threshold: MoveOption { vec: vec![] },
+            yes_votes: U128(yes_votes),
+            no_votes: U128(no_votes),
+            is_resolved: false,
+        }
+    }
+
+    fn state(proposal: OnChainProposal) -> ProposalState {
+        ProposalDetails::new(0, proposal, NOW_SECS).state
+    }
+
+    #[test]
+    fn test_pending_proposal() {
+        let details = ProposalDetails::new(7, proposal(60, 50, NOW_SECS + 1), NOW_SECS);
+        assert_eq!(details.state, ProposalState::Pending);
+        assert_eq!(details.proposal_id, 7);
+        assert_eq!(details.proposer, AccountAddress::ONE);
+        assert_eq!(details.execution_hash, "0xabcd");
+        assert_eq!(
+            details.metadata_location.as_deref(),
+            Some("https://example.com/proposal.json")
+        );
+        assert_eq!(details.early_resolution_vote_threshold, None);
+    }
+
+    #[test]
+    fn test_succeeded_proposal() {
+        // Voting closes at the expiration time
+        assert_eq!(state(proposal(60, 40, NOW_SECS)), ProposalState::Succeeded);
+    }
+
+    #[test]
+    fn test_failed_proposal() {
+        assert_eq!(state(proposal(40, 60, NOW_SECS)), ProposalState::Failed);
+        // A tie fails
+        assert_eq!(state(proposal(50, 50, NOW_SECS)), ProposalState::Failed);
+    }
+
+    #[test]
+    fn test_proposal_below_min_vote_threshold() {
+        // More yes than no votes, but not enough votes in total
+        assert_eq!(state(proposal(60, 39, NOW_SECS)), ProposalState::Failed);
+    }
+
+    #[test]
+    fn test_proposal_resolved_early() {
+        let mut early = proposal(80, 10, NOW_SECS + 1);
+        early.early_resolution_vote_threshold = MoveOption {
+            vec: vec![U128(80)],
+        };
+        assert_eq!(state(early), ProposalState::Succeeded);
+
+        let mut below_early = proposal(79, 10, NOW_SECS + 1);
+        below_early.early_resolution_vote_threshold = MoveOption {
+            vec: vec![U128(80)],
+        };
+        assert_eq!(state(below_early), ProposalState::Pending);
+    }
+
+    #[test]
+    fn test_resolved_proposal() {
+        let mut resolved = proposal(60, 40, NOW_SECS);
+        resolved.is_resolved = true;
+        resolved.execution_content = MoveOption { vec: vec![] };
+        let details = ProposalDetails::new(0, resolved, NOW_SECS);
+        assert_eq!(details.state, ProposalState::Resolved);
+        assert_eq!(details.metadata_location, None);
+        assert_eq!(details.metadata_hash, None);
+    }
+}
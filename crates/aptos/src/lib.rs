@@ -12,6 +12,7 @@ pub mod move_tool;
 pub mod node;
 pub mod op;
 pub mod test;
+pub mod transaction;
 
 use crate::common::types::{CliCommand, CliResult, CliTypedResult};
 use aptos_telemetry::collect_build_information;
@@ -40,6 +41,8 @@ pub enum Tool {
     Move(move_tool::MoveTool),
     #[clap(subcommand)]
     Node(node::NodeTool),
+    #[clap(subcommand)]
+    Transaction(transaction::TransactionTool),
 }
 
 impl Tool {
@@ -56,6 +59,7 @@ impl Tool {
             Key(tool) => tool.execute().await,
             Move(tool) => tool.execute().await,
             Node(tool) => tool.execute().await,
+            Transaction(tool) => tool.execute().await,
         }
     }
 }
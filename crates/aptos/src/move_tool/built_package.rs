@@ -4,6 +4,8 @@
 use crate::common::types::{CliError, MovePackageDir};
 use crate::CliTypedResult;
 use framework::natives::code::{ModuleMetadata, PackageMetadata, UpgradePolicy};
+use move_deps::move_binary_format::CompiledModule;
+use move_deps::move_compiler::compiled_unit::{CompiledUnit, NamedCompiledModule};
 use move_deps::move_package::compilation::compiled_package::CompiledPackage;
 use move_deps::move_package::BuildConfig;
 
@@ -51,6 +53,35 @@ impl BuiltPackage {
             .collect()
     }
 
+    /// Extracts the modules from the built package, along with their bytecode as published.
+    pub fn extract_modules(&self) -> Vec<(&CompiledModule, Vec<u8>)> {
+        self.package
+            .root_compiled_units
+            .iter()
+            .filter_map(|unit_with_source| match &unit_with_source.unit {
+                CompiledUnit::Module(NamedCompiledModule { module, .. }) => {
+                    Some((module, unit_with_source.unit.serialize(None)))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Extracts the scripts from the built package, by name, along with their bytecode.
+    pub fn extract_scripts(&self) -> Vec<(String, Vec<u8>)> {
+        self.package
+            .root_compiled_units
+            .iter()
+            .filter_map(|unit_with_source| match &unit_with_source.unit {
+                CompiledUnit::Script(_) => Some((
+                    unit_with_source.unit.name().to_string(),
+                    unit_with_source.unit.serialize(None),
+                )),
+                _ => None,
+            })
+            .collect()
+    }
+
     /// Extracts metadata, as needed for publishing a package, from the built package.
     pub fn extract_metadata(
         &self,
@@ -6,12 +6,13 @@ mod built_package;
 pub use built_package::*;
 mod transactional_tests_runner;
 
-use crate::common::utils::{create_dir_if_not_exist, dir_default_to_current};
+use crate::common::utils::{create_dir_if_not_exist, dir_default_to_current, write_to_file};
 use crate::{
     common::{
         types::{
             load_account_arg, AccountAddressWrapper, CliError, CliTypedResult, MovePackageDir,
-            PromptOptions, TransactionOptions, TransactionSummary,
+            ProfileOptions, PromptOptions, RestOptions, TransactionOptions, TransactionOutcome,
+            TransactionSummary,
         },
         utils::check_if_file_exists,
     },
@@ -19,19 +20,25 @@ use crate::{
 };
 use aptos_gas::NativeGasParameters;
 use aptos_module_verifier::module_init::verify_module_init_function;
-use aptos_rest_client::aptos_api_types::MoveType;
+use aptos_rest_client::aptos_api_types::{MoveModule, MoveModuleBytecode, MoveType};
 use aptos_transactional_test_harness::run_aptos_test;
-use aptos_types::transaction::{ModuleBundle, ScriptFunction, TransactionPayload};
+use aptos_types::{
+    account_address::AccountAddress,
+    transaction::{ModuleBundle, ScriptFunction, TransactionPayload},
+};
 use async_trait::async_trait;
 use clap::{Parser, Subcommand};
 use framework::natives::code::UpgradePolicy;
 use move_deps::move_cli::base::test::UnitTestResult;
 use move_deps::{
+    move_binary_format::CompiledModule,
     move_cli,
     move_core_types::{
         identifier::Identifier,
         language_storage::{ModuleId, TypeTag},
     },
+    move_disassembler::disassembler::Disassembler,
+    move_ir_types::location::Spanned,
     move_package::{
         compilation::compiled_package::CompiledPackage,
         source_package::layout::SourcePackageLayout, BuildConfig,
@@ -39,11 +46,12 @@ use move_deps::{
     move_prover,
     move_unit_test::UnitTestingConfig,
 };
+use serde::{Deserialize, Serialize};
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, BTreeSet},
     convert::TryFrom,
     io::Write,
-    path::{Path, PathBuf},
+    path::{Component, Path, PathBuf},
     str::FromStr,
 };
 use tokio::task;
@@ -54,24 +62,28 @@ use transactional_tests_runner::TransactionalTestOpts;
 #[derive(Subcommand)]
 pub enum MoveTool {
     Compile(CompilePackage),
+    Download(DownloadPackage),
     Init(InitPackage),
     Publish(PublishPackage),
     Run(RunFunction),
     Test(TestPackage),
     Prove(ProvePackage),
     TransactionalTest(TransactionalTestOpts),
+    VerifyPackage(VerifyPackage),
 }
 
 impl MoveTool {
     pub async fn execute(self) -> CliResult {
         match self {
             MoveTool::Compile(tool) => tool.execute_serialized().await,
+            MoveTool::Download(tool) => tool.execute_serialized().await,
             MoveTool::Init(tool) => tool.execute_serialized_success().await,
             MoveTool::Publish(tool) => tool.execute_serialized().await,
             MoveTool::Run(tool) => tool.execute_serialized().await,
             MoveTool::Test(tool) => tool.execute_serialized().await,
             MoveTool::Prove(tool) => tool.execute_serialized().await,
             MoveTool::TransactionalTest(tool) => tool.execute_serialized_success().await,
+            MoveTool::VerifyPackage(tool) => tool.execute_serialized().await,
         }
     }
 }
@@ -187,6 +199,240 @@ impl CliCommand<Vec<String>> for CompilePackage {
     }
 }
 
+/// Downloads the modules and package metadata published at an account
+///
+/// Each package of the account is written to its own directory, with its manifest, its sources
+/// if they were published, and its modules as `.mv` files in the `build` layout of the package.
+/// Modules published outside of a package are written to a package named `Modules`.
+#[derive(Parser)]
+pub struct DownloadPackage {
+    /// Address of the account to download the modules of
+    #[clap(long, parse(try_from_str = load_account_arg))]
+    account: AccountAddress,
+    /// Directory to write the packages into
+    ///
+    /// Defaults to the current directory
+    #[clap(long, parse(from_os_str))]
+    output_dir: Option<PathBuf>,
+    /// Also write a disassembly of each module into the `disassembly` directory of the build
+    #[clap(long)]
+    disassemble: bool,
+    #[clap(flatten)]
+    rest_options: RestOptions,
+    #[clap(flatten)]
+    profile_options: ProfileOptions,
+    #[clap(flatten)]
+    prompt_options: PromptOptions,
+}
+
+/// Name of the package for modules published outside of a package
+const UNPACKAGED_MODULES: &str = "Modules";
+
+/// The fields of `0x1::code::PackageRegistry` needed to lay out the downloaded packages
+#[derive(Deserialize)]
+struct PackageRegistry {
+    packages: Vec<PackageRegistryEntry>,
+}
+
+#[derive(Deserialize)]
+struct PackageRegistryEntry {
+    name: String,
+    manifest: String,
+    modules: Vec<PackageRegistryModule>,
+}
+
+#[derive(Deserialize)]
+struct PackageRegistryModule {
+    name: String,
+    source: String,
+}
+
+impl PackageRegistry {
+    /// Checks that the names of the packages and modules are safe to use as file names
+    ///
+    /// The registry is read from chain, so its names must not be able to escape the output
+    /// directory, nor collide with the package the unpackaged modules are written to.
+    fn validate(&self) -> CliTypedResult<()> {
+        for package in &self.packages {
+            check_file_name("package", &package.name)?;
+            if package.name == UNPACKAGED_MODULES {
+                return Err(CliError::UnexpectedError(format!(
+                    "Package name '{}' is reserved for modules published outside of a package",
+                    UNPACKAGED_MODULES
+                )));
+            }
+            for module in &package.modules {
+                check_file_name("module", &module.name)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Checks that a name from chain is a valid identifier that is a single normal path component
+fn check_file_name(kind: &str, name: &str) -> CliTypedResult<()> {
+    let mut components = Path::new(name).components();
+    let is_single_component = matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(_)), None)
+    );
+    if !is_single_component || name.contains(['/', '\\']) || !Identifier::is_valid(name) {
+        return Err(CliError::UnexpectedError(format!(
+            "Invalid {} name '{}' in the package registry",
+            kind, name
+        )));
+    }
+    Ok(())
+}
+
+/// A summary of a downloaded package
+#[derive(Debug, Serialize)]
+pub struct DownloadedPackage {
+    pub name: String,
+    pub package_dir: PathBuf,
+    pub modules: Vec<String>,
+    pub with_sources: bool,
+}
+
+#[async_trait]
+impl CliCommand<Vec<DownloadedPackage>> for DownloadPackage {
+    fn command_name(&self) -> &'static str {
+        "DownloadPackage"
+    }
+
+    async fn execute(self) -> CliTypedResult<Vec<DownloadedPackage>> {
+        let client = self.rest_options.client(&self.profile_options.profile)?;
+        let output_dir = dir_default_to_current(self.output_dir.clone())?;
+
+        let mut modules: BTreeMap<String, Vec<u8>> = BTreeMap::new();
+        for bytecode in client
+            .get_account_modules(self.account)
+            .await
+            .map_err(|err| CliError::ApiError(err.to_string()))?
+            .into_inner()
+        {
+            let module = CompiledModule::deserialize(bytecode.bytecode.inner())
+                .map_err(|err| CliError::UnableToParse("module bytecode", err.to_string()))?;
+            modules.insert(
+                module.self_id().name().to_string(),
+                bytecode.bytecode.inner().to_vec(),
+            );
+        }
+
+        let registry = client
+            .get_account_resources(self.account)
+            .await
+            .map_err(|err| CliError::ApiError(err.to_string()))?
+            .into_inner()
+            .into_iter()
+            .find(|resource| {
+                resource.resource_type.address == AccountAddress::ONE
+                    && resource.resource_type.module.as_str() == "code"
+                    && resource.resource_type.name.as_str() == "PackageRegistry"
+            })
+            .map(|resource| serde_json::from_value::<PackageRegistry>(resource.data))
+            .transpose()
+            .map_err(|err| CliError::UnableToParse("PackageRegistry", err.to_string()))?;
+
+        let registry = registry.unwrap_or(PackageRegistry { packages: vec![] });
+        registry.validate()?;
+
+        let mut packages = Vec::new();
+        for package in registry.packages {
+            let package_dir = output_dir.join(&package.name);
+            let mut package_modules = Vec::new();
+            let mut with_sources = false;
+            for module in package.modules {
+                if let Some(bytecode) = modules.remove(&module.name) {
+                    if !module.source.is_empty() {
+                        let sources_dir = package_dir.join(SourcePackageLayout::Sources.path());
+                        create_dir_if_not_exist(&sources_dir)?;
+                        let source_file = sources_dir.join(format!("{}.move", module.name));
+                        write_to_file(&source_file, "Module source", module.source.as_bytes())?;
+                        with_sources = true;
+                    }
+                    package_modules.push((module.name, bytecode));
+                }
+            }
+            packages.push(self.write_package(
+                package.name,
+                package_dir,
+                &package.manifest,
+                package_modules,
+                with_sources,
+            )?);
+        }
+
+        // Whatever is left was published without package metadata
+        if !modules.is_empty() {
+            let manifest = format!(
+                "[package]\nname = \"{}\"\nversion = \"0.0.0\"\n",
+                UNPACKAGED_MODULES
+            );
+            packages.push(self.write_package(
+                UNPACKAGED_MODULES.to_string(),
+                output_dir.join(UNPACKAGED_MODULES),
+                &manifest,
+                modules.into_iter().collect(),
+                false,
+            )?);
+        }
+
+        Ok(packages)
+    }
+}
+
+impl DownloadPackage {
+    /// Writes the manifest and compiled modules of a package, in the layout `BuildConfig` uses
+    fn write_package(
+        &self,
+        name: String,
+        package_dir: PathBuf,
+        manifest: &str,
+        modules: Vec<(String, Vec<u8>)>,
+        with_sources: bool,
+    ) -> CliTypedResult<DownloadedPackage> {
+        let manifest_file = package_dir.join(SourcePackageLayout::Manifest.path());
+        check_if_file_exists(&manifest_file, self.prompt_options)?;
+        create_dir_if_not_exist(&package_dir)?;
+        write_to_file(&manifest_file, "Move.toml", manifest.as_bytes())?;
+
+        let build_dir = package_dir.join("build").join(&name);
+        let bytecode_dir = build_dir.join("bytecode_modules");
+        create_dir_if_not_exist(&bytecode_dir)?;
+        for (module_name, bytecode) in &modules {
+            let bytecode_file = bytecode_dir.join(format!("{}.mv", module_name));
+            write_to_file(&bytecode_file, "Module bytecode", bytecode)?;
+
+            if self.disassemble {
+                let disassembly_dir = build_dir.join("disassembly");
+                create_dir_if_not_exist(&disassembly_dir)?;
+                let disassembly_file = disassembly_dir.join(format!("{}.asm", module_name));
+                write_to_file(
+                    &disassembly_file,
+                    "Module disassembly",
+                    disassemble(bytecode)?.as_bytes(),
+                )?;
+            }
+        }
+
+        Ok(DownloadedPackage {
+            name,
+            package_dir,
+            modules: modules.into_iter().map(|(name, _)| name).collect(),
+            with_sources,
+        })
+    }
+}
+
+fn disassemble(bytecode: &[u8]) -> CliTypedResult<String> {
+    let module = CompiledModule::deserialize(bytecode)
+        .map_err(|err| CliError::UnableToParse("module bytecode", err.to_string()))?;
+    Disassembler::from_module(&module, Spanned::unsafe_no_loc(()).loc)
+        .and_then(|disassembler| disassembler.disassemble())
+        .map_err(|err| CliError::UnexpectedError(format!("Failed to disassemble module: {}", err)))
+}
+
 /// Run Move unit tests against a package path
 #[derive(Parser)]
 pub struct TestPackage {
@@ -321,12 +567,12 @@ pub struct PublishPackage {
 }
 
 #[async_trait]
-impl CliCommand<TransactionSummary> for PublishPackage {
+impl CliCommand<TransactionOutcome<TransactionSummary>> for PublishPackage {
     fn command_name(&self) -> &'static str {
         "PublishPackage"
     }
 
-    async fn execute(self) -> CliTypedResult<TransactionSummary> {
+    async fn execute(self) -> CliTypedResult<TransactionOutcome<TransactionSummary>> {
         let PublishPackage {
             move_options,
             txn_options,
@@ -348,7 +594,7 @@ impl CliCommand<TransactionSummary> for PublishPackage {
                     compiled_units,
                 )))
                 .await
-                .map(TransactionSummary::from)
+                .map(|outcome| outcome.map(TransactionSummary::from))
         } else {
             // Send the compiled module and metadata using the code::publish_package_txn.
             let metadata =
@@ -360,11 +606,190 @@ impl CliCommand<TransactionSummary> for PublishPackage {
             txn_options
                 .submit_transaction(payload)
                 .await
-                .map(TransactionSummary::from)
+                .map(|outcome| outcome.map(TransactionSummary::from))
         }
     }
 }
 
+/// Verifies that the modules of a Move package match the ones published on chain
+///
+/// The package is built with the same options as `publish`, and the bytecode of each module is
+/// compared with the module of the same name on chain.  The command fails if any module doesn't
+/// match, listing for modules that differ the functions and structs whose signatures differ.
+#[derive(Parser)]
+pub struct VerifyPackage {
+    #[clap(flatten)]
+    move_options: MovePackageDir,
+    #[clap(flatten)]
+    rest_options: RestOptions,
+    #[clap(flatten)]
+    profile_options: ProfileOptions,
+}
+
+/// Whether a module of a package matches the one on chain
+#[derive(Debug, Serialize)]
+pub enum ModuleStatus {
+    Matches,
+    Differs,
+    NotOnChain,
+}
+
+/// The result of verifying a module against the one on chain
+#[derive(Debug, Serialize)]
+pub struct ModuleVerification {
+    pub module: String,
+    pub status: ModuleStatus,
+    /// Functions that are only on one side, or whose signatures differ
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub differing_functions: Vec<String>,
+    /// Structs that are only on one side, or whose definitions differ
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub differing_structs: Vec<String>,
+}
+
+#[async_trait]
+impl CliCommand<Vec<ModuleVerification>> for VerifyPackage {
+    fn command_name(&self) -> &'static str {
+        "VerifyPackage"
+    }
+
+    async fn execute(self) -> CliTypedResult<Vec<ModuleVerification>> {
+        let client = self.rest_options.client(&self.profile_options.profile)?;
+        let package = BuiltPackage::build(self.move_options, true, true)?;
+
+        // Fetch the on chain modules of every address the package publishes to
+        let mut on_chain_modules: BTreeMap<AccountAddress, BTreeMap<String, MoveModuleBytecode>> =
+            BTreeMap::new();
+        for (module, _) in package.extract_modules() {
+            let address = *module.self_id().address();
+            if on_chain_modules.contains_key(&address) {
+                continue;
+            }
+            let modules = client
+                .get_account_modules(address)
+                .await
+                .map_err(|err| CliError::ApiError(err.to_string()))?
+                .into_inner()
+                .into_iter()
+                .map(|bytecode| {
+                    let bytecode = bytecode.try_parse_abi()?;
+                    let name = bytecode
+                        .abi
+                        .as_ref()
+                        .map(|abi| abi.name.to_string())
+                        .unwrap_or_default();
+                    Ok((name, bytecode))
+                })
+                .collect::<anyhow::Result<_>>()?;
+            on_chain_modules.insert(address, modules);
+        }
+
+        check_verified(verify_modules(package.extract_modules(), &on_chain_modules))
+    }
+}
+
+/// Compares each local module with the on chain module of the same id
+fn verify_modules(
+    local_modules: Vec<(&CompiledModule, Vec<u8>)>,
+    on_chain_modules: &BTreeMap<AccountAddress, BTreeMap<String, MoveModuleBytecode>>,
+) -> Vec<ModuleVerification> {
+    local_modules
+        .into_iter()
+        .map(|(module, bytecode)| {
+            let module_id = module.self_id();
+            let on_chain = on_chain_modules
+                .get(module_id.address())
+                .and_then(|modules| modules.get(module_id.name().as_str()));
+            let mut verification = ModuleVerification {
+                module: module_id.to_string(),
+                status: ModuleStatus::NotOnChain,
+                differing_functions: vec![],
+                differing_structs: vec![],
+            };
+            if let Some(on_chain) = on_chain {
+                if on_chain.bytecode.inner() == bytecode.as_slice() {
+                    verification.status = ModuleStatus::Matches;
+                } else {
+                    verification.status = ModuleStatus::Differs;
+                    if let Some(ref on_chain_abi) = on_chain.abi {
+                        let local_abi = MoveModule::from(module.clone());
+                        verification.differing_functions = differing_members(
+                            &local_abi.exposed_functions,
+                            &on_chain_abi.exposed_functions,
+                            |function| function.name.to_string(),
+                        );
+                        verification.differing_structs = differing_members(
+                            &local_abi.structs,
+                            &on_chain_abi.structs,
+                            |member| member.name.to_string(),
+                        );
+                    }
+                }
+            }
+            verification
+        })
+        .collect()
+}
+
+/// Fails with the details of every module that doesn't match the one on chain
+fn check_verified(modules: Vec<ModuleVerification>) -> CliTypedResult<Vec<ModuleVerification>> {
+    let mismatches: Vec<_> = modules
+        .iter()
+        .filter_map(|module| match module.status {
+            ModuleStatus::Matches => None,
+            ModuleStatus::NotOnChain => {
+                Some(format!("{} is not published on chain", module.module))
+            }
+            ModuleStatus::Differs => {
+                let mut details = format!("{} differs from the module on chain", module.module);
+                if !module.differing_functions.is_empty() {
+                    details.push_str(&format!(
+                        ", differing functions: [{}]",
+                        module.differing_functions.join(", ")
+                    ));
+                }
+                if !module.differing_structs.is_empty() {
+                    details.push_str(&format!(
+                        ", differing structs: [{}]",
+                        module.differing_structs.join(", ")
+                    ));
+                }
+                Some(details)
+            }
+        })
+        .collect();
+
+    if mismatches.is_empty() {
+        Ok(modules)
+    } else {
+        Err(CliError::UnexpectedError(format!(
+            "Package does not match the modules on chain: {}",
+            mismatches.join("; ")
+        )))
+    }
+}
+
+/// Returns the names of members that are only on one side, or that differ between both sides
+fn differing_members<T: PartialEq, F: Fn(&T) -> String>(
+    local: &[T],
+    on_chain: &[T],
+    name: F,
+) -> Vec<String> {
+    let local: BTreeMap<_, _> = local.iter().map(|member| (name(member), member)).collect();
+    let on_chain: BTreeMap<_, _> = on_chain
+        .iter()
+        .map(|member| (name(member), member))
+        .collect();
+    local
+        .keys()
+        .chain(on_chain.keys())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .filter(|name| local.get(*name) != on_chain.get(*name))
+        .cloned()
+        .collect()
+}
+
 /// Run a Move function
 #[derive(Parser)]
 pub struct RunFunction {
@@ -388,12 +813,12 @@ pub struct RunFunction {
 }
 
 #[async_trait]
-impl CliCommand<TransactionSummary> for RunFunction {
+impl CliCommand<TransactionOutcome<TransactionSummary>> for RunFunction {
     fn command_name(&self) -> &'static str {
         "RunFunction"
     }
 
-    async fn execute(self) -> CliTypedResult<TransactionSummary> {
+    async fn execute(self) -> CliTypedResult<TransactionOutcome<TransactionSummary>> {
         let args: Vec<Vec<u8>> = self
             .args
             .iter()
@@ -416,7 +841,7 @@ impl CliCommand<TransactionSummary> for RunFunction {
                 args,
             )))
             .await
-            .map(TransactionSummary::from)
+            .map(|outcome| outcome.map(TransactionSummary::from))
     }
 }
 
@@ -537,3 +962,136 @@ impl FromStr for MemberId {
         parse_member_id(s)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_temppath::TempPath;
+    use move_deps::move_binary_format::file_format::{basic_test_module, empty_module, Visibility};
+
+    fn serialize(module: &CompiledModule) -> Vec<u8> {
+        let mut bytecode = vec![];
+        module.serialize(&mut bytecode).unwrap();
+        bytecode
+    }
+
+    fn on_chain(
+        modules: &[&CompiledModule],
+    ) -> BTreeMap<AccountAddress, BTreeMap<String, MoveModuleBytecode>> {
+        let mut on_chain_modules: BTreeMap<_, BTreeMap<_, _>> = BTreeMap::new();
+        for module in modules {
+            let module_id = module.self_id();
+            let bytecode = MoveModuleBytecode::new(serialize(module))
+                .try_parse_abi()
+                .unwrap();
+            on_chain_modules
+                .entry(*module_id.address())
+                .or_default()
+                .insert(module_id.name().to_string(), bytecode);
+        }
+        on_chain_modules
+    }
+
+    fn registry(package: &str, module: &str) -> PackageRegistry {
+        serde_json::from_value(serde_json::json!({
+            "packages": [{
+                "name": package,
+                "manifest": "",
+                "modules": [{ "name": module, "source": "" }],
+            }]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_registry_names() {
+        assert!(registry("MyPackage", "my_module").validate().is_ok());
+
+        for name in [
+            "..", ".", "", "/", "/etc", "a/b", "..\\a", "a\\b", "../a", "a b",
+        ] {
+            assert!(registry(name, "my_module").validate().is_err(), "{}", name);
+            assert!(registry("MyPackage", name).validate().is_err(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_registry_reserved_package_name() {
+        let err = registry(UNPACKAGED_MODULES, "my_module")
+            .validate()
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("reserved"));
+    }
+
+    #[test]
+    fn test_verify_matching_package() {
+        let module = basic_test_module();
+        let bytecode = serialize(&module);
+        let on_chain_modules = on_chain(&[&module]);
+
+        let modules =
+            check_verified(verify_modules(vec![(&module, bytecode)], &on_chain_modules)).unwrap();
+        assert_eq!(modules.len(), 1);
+        assert!(matches!(modules[0].status, ModuleStatus::Matches));
+        assert!(modules[0].differing_functions.is_empty());
+        assert!(modules[0].differing_structs.is_empty());
+    }
+
+    #[test]
+    fn test_verify_mismatching_package() {
+        // Both modules have the same id, but the local one has a function and a struct more
+        let mut local = basic_test_module();
+        local.function_defs[0].visibility = Visibility::Public;
+        let local_bytecode = serialize(&local);
+        let on_chain_modules = on_chain(&[&empty_module()]);
+
+        let modules = verify_modules(vec![(&local, local_bytecode)], &on_chain_modules);
+        assert_eq!(modules.len(), 1);
+        assert!(matches!(modules[0].status, ModuleStatus::Differs));
+        assert_eq!(modules[0].differing_functions, vec!["foo".to_string()]);
+        assert_eq!(modules[0].differing_structs, vec!["Bar".to_string()]);
+
+        let err = check_verified(modules).unwrap_err().to_string();
+        assert!(err.contains(&local.self_id().to_string()));
+        assert!(err.contains("differing functions: [foo]"));
+        assert!(err.contains("differing structs: [Bar]"));
+
+        // A module that isn't published at all doesn't match either
+        let modules = verify_modules(vec![(&local, serialize(&local))], &BTreeMap::new());
+        assert!(matches!(modules[0].status, ModuleStatus::NotOnChain));
+        let err = check_verified(modules).unwrap_err().to_string();
+        assert!(err.contains("is not published on chain"));
+    }
+
+    #[test]
+    fn test_verify_built_package() {
+        let dir = TempPath::new();
+        dir.create_as_dir().unwrap();
+        std::fs::create_dir(dir.path().join("sources")).unwrap();
+        std::fs::write(
+            dir.path().join("Move.toml"),
+            "[package]\nname = \"Verify\"\nversion = \"0.0.0\"\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("sources").join("verify.move"),
+            "module 0xcafe::verify { public fun one(): u64 { 1 } }\n",
+        )
+        .unwrap();
+        let package =
+            BuiltPackage::build(MovePackageDir::new(dir.path().to_path_buf()), false, false)
+                .unwrap();
+
+        let modules = package.extract_modules();
+        assert_eq!(modules.len(), 1);
+        let on_chain_modules = on_chain(&[modules[0].0]);
+        let modules = check_verified(verify_modules(modules, &on_chain_modules)).unwrap();
+        assert_eq!(modules[0].module, "0xcafe::verify");
+        assert!(matches!(modules[0].status, ModuleStatus::Matches));
+
+        // The same package before it's published
+        let modules = verify_modules(package.extract_modules(), &BTreeMap::new());
+        assert!(matches!(modules[0].status, ModuleStatus::NotOnChain));
+    }
+}
@@ -3,9 +3,10 @@
 
 use crate::{
     common::{
+        keystore::{read_new_password, read_password, EncryptedPrivateKey},
         types::{
-            CliError, CliTypedResult, EncodingOptions, EncodingType, ExtractPublicKey, KeyType,
-            PrivateKeyInputOptions, ProfileOptions, RngArgs, SaveFile,
+            CliConfig, CliError, CliTypedResult, EncodingOptions, EncodingType, ExtractPublicKey,
+            KeyType, PrivateKeyInputOptions, ProfileConfig, ProfileOptions, RngArgs, SaveFile,
         },
         utils::{append_file_extension, check_if_file_exists, write_to_file},
     },
@@ -28,6 +29,8 @@ pub const PUBLIC_KEY_EXTENSION: &str = "pub";
 pub enum KeyTool {
     Generate(GenerateKey),
     ExtractPeer(ExtractPeer),
+    Encrypt(EncryptKey),
+    Decrypt(DecryptKey),
 }
 
 impl KeyTool {
@@ -35,6 +38,8 @@ impl KeyTool {
         match self {
             KeyTool::Generate(tool) => tool.execute_serialized().await,
             KeyTool::ExtractPeer(tool) => tool.execute_serialized().await,
+            KeyTool::Encrypt(tool) => tool.execute_serialized().await,
+            KeyTool::Decrypt(tool) => tool.execute_serialized().await,
         }
     }
 }
@@ -91,6 +96,92 @@ impl CliCommand<HashMap<AccountAddress, Peer>> for ExtractPeer {
     }
 }
 
+/// Encrypts the private key of a profile with a password
+///
+/// The key is encrypted with AES-256-GCM, under a key derived from the password with Argon2id,
+/// and replaces the plaintext key in `.aptos/config.yaml`.  Commands using the profile then
+/// prompt for the password.  To run them non-interactively, set the password in the
+/// `APTOS_KEY_PASSWORD` environment variable, or put it in a file named by the
+/// `APTOS_KEY_PASSWORD_FILE` environment variable.
+#[derive(Debug, Parser)]
+pub struct EncryptKey {
+    #[clap(flatten)]
+    profile_options: ProfileOptions,
+}
+
+#[async_trait]
+impl CliCommand<String> for EncryptKey {
+    fn command_name(&self) -> &'static str {
+        "EncryptKey"
+    }
+
+    async fn execute(self) -> CliTypedResult<String> {
+        let profile = self.profile_options.profile;
+        let mut config = CliConfig::load()?;
+        let profile_config = profile_config_mut(&mut config, &profile)?;
+        if profile_config.encrypted_private_key.is_some() {
+            return Err(CliError::CommandArgumentError(format!(
+                "Private key of profile '{}' is already encrypted",
+                profile
+            )));
+        }
+        let private_key = profile_config.private_key.take().ok_or_else(|| {
+            CliError::CommandArgumentError(format!("Profile '{}' has no private key", profile))
+        })?;
+
+        let password = read_new_password(&profile)?;
+        profile_config.encrypted_private_key =
+            Some(EncryptedPrivateKey::encrypt(&private_key, &password)?);
+        // The public key stays in plaintext, so the account can be looked up without the password
+        profile_config.public_key = Some(private_key.public_key());
+        config.save()?;
+        Ok(format!("Encrypted private key of profile '{}'", profile))
+    }
+}
+
+/// Decrypts the private key of a profile, storing it in plaintext again
+#[derive(Debug, Parser)]
+pub struct DecryptKey {
+    #[clap(flatten)]
+    profile_options: ProfileOptions,
+}
+
+#[async_trait]
+impl CliCommand<String> for DecryptKey {
+    fn command_name(&self) -> &'static str {
+        "DecryptKey"
+    }
+
+    async fn execute(self) -> CliTypedResult<String> {
+        let profile = self.profile_options.profile;
+        let mut config = CliConfig::load()?;
+        let profile_config = profile_config_mut(&mut config, &profile)?;
+        let encrypted_private_key =
+            profile_config.encrypted_private_key.take().ok_or_else(|| {
+                CliError::CommandArgumentError(format!(
+                    "Profile '{}' has no encrypted private key",
+                    profile
+                ))
+            })?;
+
+        let private_key = encrypted_private_key.decrypt(&read_password(&profile)?)?;
+        profile_config.private_key = Some(private_key);
+        config.save()?;
+        Ok(format!("Decrypted private key of profile '{}'", profile))
+    }
+}
+
+fn profile_config_mut<'a>(
+    config: &'a mut CliConfig,
+    profile: &str,
+) -> CliTypedResult<&'a mut ProfileConfig> {
+    config
+        .profiles
+        .as_mut()
+        .and_then(|profiles| profiles.get_mut(profile))
+        .ok_or_else(|| CliError::ConfigNotFoundError(profile.to_string()))
+}
+
 /// Generates a `x25519` or `ed25519` key.
 ///
 /// This can be used for generating an identity.  Two files will be created
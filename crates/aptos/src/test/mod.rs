@@ -10,6 +10,7 @@ use crate::node::{
 use crate::{
     account::{
         create::{CreateAccount, DEFAULT_FUNDED_COINS},
+        create_multisig::{CreateMultisigAccount, CreateMultisigAccountSummary},
         fund::FundAccount,
         list::{ListAccount, ListQuery},
         transfer::{TransferCoins, TransferSummary},
@@ -17,10 +18,15 @@ use crate::{
     common::{
         init::InitTool,
         types::{
-            CliTypedResult, EncodingOptions, PrivateKeyInputOptions, PromptOptions, RestOptions,
-            RngArgs, TransactionOptions,
+            CliError, CliTypedResult, EncodingOptions, PrivateKeyInputOptions, PromptOptions,
+            RestOptions, RngArgs, SaveFile, TransactionOptions, TransactionOutcome,
+            TransactionSummary, UnsignedTransactionSummary,
         },
     },
+    transaction::{
+        CombineMultisigTransaction, PartialSignatureSummary, SignMultisigTransaction,
+        SignTransaction, SignedTransactionSummary, SubmitTransaction,
+    },
     CliCommand,
 };
 use aptos_crypto::ed25519::Ed25519PrivateKey;
@@ -34,7 +40,11 @@ use aptos_types::validator_info::ValidatorInfo;
 use aptos_types::{on_chain_config::ConsensusScheme, validator_config::ValidatorConfig};
 use reqwest::Url;
 use serde_json::Value;
-use std::{str::FromStr, time::Duration};
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
 use tokio::time::{sleep, Instant};
 
 pub const INVALID_ACCOUNT: &str = "0xDEADBEEFCAFEBABE";
@@ -101,6 +111,7 @@ impl CliTestFramework {
                 profile_options: Default::default(),
                 rest_options: self.rest_options(),
                 gas_options: Default::default(),
+                ..Default::default()
             },
             account: self.account_id(index),
             use_faucet: false,
@@ -109,6 +120,7 @@ impl CliTestFramework {
         }
         .execute()
         .await
+        .and_then(TransactionOutcome::into_submitted)
     }
 
     pub async fn create_account_with_faucet(&self, index: usize) -> CliTypedResult<String> {
@@ -121,12 +133,22 @@ impl CliTestFramework {
         }
         .execute()
         .await
+        .and_then(TransactionOutcome::into_submitted)
     }
 
     pub async fn fund_account(&self, index: usize, amount: Option<u64>) -> CliTypedResult<String> {
+        self.fund_address(self.account_id(index), amount).await
+    }
+
+    /// Funds an account the framework has no key for, like a multisig account
+    pub async fn fund_address(
+        &self,
+        account: AccountAddress,
+        amount: Option<u64>,
+    ) -> CliTypedResult<String> {
         FundAccount {
             profile_options: Default::default(),
-            account: self.account_id(index),
+            account,
             faucet_options: self.faucet_options(),
             num_coins: amount.unwrap_or(DEFAULT_FUNDED_COINS),
         }
@@ -134,6 +156,32 @@ impl CliTestFramework {
         .await
     }
 
+    /// Creates a multisig account owned by the keys of `owner_indices`, paid for by
+    /// `payer_index`, and writes its public key to `multisig_public_key_file`
+    pub async fn create_multisig_account(
+        &self,
+        payer_index: usize,
+        owner_indices: &[usize],
+        threshold: u8,
+        multisig_public_key_file: &Path,
+    ) -> CliTypedResult<CreateMultisigAccountSummary> {
+        CreateMultisigAccount {
+            txn_options: self.transaction_options(payer_index, None),
+            public_keys: owner_indices
+                .iter()
+                .map(|index| self.private_key(*index).public_key())
+                .collect(),
+            threshold,
+            encoding_options: Default::default(),
+            save_file: SaveFile {
+                output_file: multisig_public_key_file.to_path_buf(),
+                prompt_options: PromptOptions::yes(),
+            },
+        }
+        .execute()
+        .await
+    }
+
     pub async fn list_account(&self, index: usize, query: ListQuery) -> CliTypedResult<Vec<Value>> {
         ListAccount {
             rest_options: self.rest_options(),
@@ -159,6 +207,7 @@ impl CliTestFramework {
         }
         .execute()
         .await
+        .and_then(TransactionOutcome::into_submitted)
     }
 
     pub async fn transfer_invalid_addr(
@@ -174,6 +223,112 @@ impl CliTestFramework {
         }
         .execute()
         .await
+        .and_then(TransactionOutcome::into_submitted)
+    }
+
+    /// Builds a transfer with `--build-only`, from `sender` but without its private key, and
+    /// writes it unsigned to `unsigned_txn_file`
+    pub async fn build_transfer_coins(
+        &self,
+        sender: AccountAddress,
+        receiver_index: usize,
+        amount: u64,
+        unsigned_txn_file: &Path,
+    ) -> CliTypedResult<UnsignedTransactionSummary> {
+        let outcome = TransferCoins {
+            txn_options: TransactionOptions {
+                rest_options: self.rest_options(),
+                build_only: Some(unsigned_txn_file.to_path_buf()),
+                sender_account: Some(sender),
+                ..Default::default()
+            },
+            account: self.account_id(receiver_index),
+            amount,
+        }
+        .execute()
+        .await?;
+        match outcome {
+            TransactionOutcome::Built(summary) => Ok(summary),
+            _ => Err(CliError::UnexpectedError(
+                "Transaction was not only built".to_string(),
+            )),
+        }
+    }
+
+    pub async fn sign_transaction(
+        &self,
+        index: usize,
+        unsigned_txn_file: &Path,
+        signed_txn_file: &Path,
+    ) -> CliTypedResult<SignedTransactionSummary> {
+        SignTransaction {
+            unsigned_txn_file: unsigned_txn_file.to_path_buf(),
+            private_key_options: PrivateKeyInputOptions::from_private_key(self.private_key(index))?,
+            encoding_options: Default::default(),
+            profile_options: Default::default(),
+            save_file: SaveFile {
+                output_file: signed_txn_file.to_path_buf(),
+                prompt_options: PromptOptions::yes(),
+            },
+        }
+        .execute()
+        .await
+    }
+
+    pub async fn sign_multisig_transaction(
+        &self,
+        index: usize,
+        unsigned_txn_file: &Path,
+        multisig_public_key_file: &Path,
+        partial_signature_file: &Path,
+    ) -> CliTypedResult<PartialSignatureSummary> {
+        SignMultisigTransaction {
+            unsigned_txn_file: unsigned_txn_file.to_path_buf(),
+            multisig_public_key_file: multisig_public_key_file.to_path_buf(),
+            private_key_options: PrivateKeyInputOptions::from_private_key(self.private_key(index))?,
+            encoding_options: Default::default(),
+            profile_options: Default::default(),
+            save_file: SaveFile {
+                output_file: partial_signature_file.to_path_buf(),
+                prompt_options: PromptOptions::yes(),
+            },
+        }
+        .execute()
+        .await
+    }
+
+    pub async fn combine_multisig_transaction(
+        &self,
+        unsigned_txn_file: &Path,
+        multisig_public_key_file: &Path,
+        partial_signature_files: Vec<PathBuf>,
+        signed_txn_file: &Path,
+    ) -> CliTypedResult<SignedTransactionSummary> {
+        CombineMultisigTransaction {
+            unsigned_txn_file: unsigned_txn_file.to_path_buf(),
+            multisig_public_key_file: multisig_public_key_file.to_path_buf(),
+            partial_signature_files,
+            encoding_options: Default::default(),
+            save_file: SaveFile {
+                output_file: signed_txn_file.to_path_buf(),
+                prompt_options: PromptOptions::yes(),
+            },
+        }
+        .execute()
+        .await
+    }
+
+    pub async fn submit_transaction(
+        &self,
+        signed_txn_file: &Path,
+    ) -> CliTypedResult<TransactionSummary> {
+        SubmitTransaction {
+            signed_txn_file: signed_txn_file.to_path_buf(),
+            rest_options: self.rest_options(),
+            profile_options: Default::default(),
+        }
+        .execute()
+        .await
     }
 
     pub async fn show_validator_config(&self, index: usize) -> CliTypedResult<ValidatorConfig> {
@@ -229,6 +384,7 @@ impl CliTestFramework {
         }
         .execute()
         .await
+        .and_then(TransactionOutcome::into_submitted)
     }
 
     pub async fn add_stake(&self, index: usize, amount: u64) -> CliTypedResult<Transaction> {
@@ -238,6 +394,7 @@ impl CliTestFramework {
         }
         .execute()
         .await
+        .and_then(TransactionOutcome::into_submitted)
     }
 
     pub async fn unlock_stake(&self, index: usize, amount: u64) -> CliTypedResult<Transaction> {
@@ -247,6 +404,7 @@ impl CliTestFramework {
         }
         .execute()
         .await
+        .and_then(TransactionOutcome::into_submitted)
     }
 
     pub async fn withdraw_stake(&self, index: usize, amount: u64) -> CliTypedResult<Transaction> {
@@ -256,6 +414,7 @@ impl CliTestFramework {
         }
         .execute()
         .await
+        .and_then(TransactionOutcome::into_submitted)
     }
 
     pub async fn increase_lockup(&self, index: usize) -> CliTypedResult<Transaction> {
@@ -264,6 +423,7 @@ impl CliTestFramework {
         }
         .execute()
         .await
+        .and_then(TransactionOutcome::into_submitted)
     }
 
     pub async fn join_validator_set(&self, index: usize) -> CliTypedResult<Transaction> {
@@ -273,6 +433,7 @@ impl CliTestFramework {
         }
         .execute()
         .await
+        .and_then(TransactionOutcome::into_submitted)
     }
 
     pub async fn leave_validator_set(&self, index: usize) -> CliTypedResult<Transaction> {
@@ -282,6 +443,7 @@ impl CliTestFramework {
         }
         .execute()
         .await
+        .and_then(TransactionOutcome::into_submitted)
     }
 
     pub async fn update_validator_network_addresses(
@@ -305,6 +467,7 @@ impl CliTestFramework {
         }
         .execute()
         .await
+        .and_then(TransactionOutcome::into_submitted)
     }
 
     pub async fn init(&self, private_key: &Ed25519PrivateKey) -> CliTypedResult<()> {
@@ -0,0 +1,549 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::common::{
+    types::{
+        account_address_from_public_key, CliCommand, CliError, CliResult, CliTypedResult,
+        EncodingOptions, EncodingType, PrivateKeyInputOptions, ProfileOptions, PromptOptions,
+        RestOptions, SaveFile, TransactionSummary,
+    },
+    utils::{prompt_yes_with_override, read_from_file},
+};
+use aptos_crypto::{
+    ed25519::{Ed25519PublicKey, Ed25519Signature},
+    multi_ed25519::{MultiEd25519PublicKey, MultiEd25519Signature},
+    HashValue, PrivateKey, SigningKey,
+};
+use aptos_types::{
+    account_address::AccountAddress,
+    transaction::{authenticator::AuthenticationKey, RawTransaction, SignedTransaction},
+};
+use async_trait::async_trait;
+use clap::{Parser, Subcommand};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+pub mod show;
+
+/// Tool for signing, submitting and inspecting transactions
+///
+/// Any command that sends a transaction can instead write it unsigned to a file with
+/// `--build-only`.  It can then be signed on an offline machine with `sign`, and the signed
+/// transaction brought back to an online machine to `submit` it.  Transactions from multisig
+/// accounts are signed by each owner with `sign-multisig`, and the partial signatures are then
+/// put together with `combine-multisig`.  Committed transactions can be inspected with `show`.
+#[derive(Debug, Subcommand)]
+pub enum TransactionTool {
+    CombineMultisig(CombineMultisigTransaction),
+    Sign(SignTransaction),
+    Show(show::ShowTransaction),
+    SignMultisig(SignMultisigTransaction),
+    Submit(SubmitTransaction),
+}
+
+impl TransactionTool {
+    pub async fn execute(self) -> CliResult {
+        match self {
+            TransactionTool::CombineMultisig(tool) => tool.execute_serialized().await,
+            TransactionTool::Sign(tool) => tool.execute_serialized().await,
+            TransactionTool::Show(tool) => tool.execute_serialized().await,
+            TransactionTool::SignMultisig(tool) => tool.execute_serialized().await,
+            TransactionTool::Submit(tool) => tool.execute_serialized().await,
+        }
+    }
+}
+
+/// Sign a transaction built with `--build-only`
+///
+/// This doesn't need network access, so it can be run on an air-gapped machine holding the
+/// private key.
+#[derive(Debug, Parser)]
+pub struct SignTransaction {
+    /// Unsigned transaction file, as written by `--build-only`
+    #[clap(long, parse(from_os_str))]
+    pub(crate) unsigned_txn_file: PathBuf,
+    #[clap(flatten)]
+    pub(crate) private_key_options: PrivateKeyInputOptions,
+    #[clap(flatten)]
+    pub(crate) encoding_options: EncodingOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+    #[clap(flatten)]
+    pub(crate) save_file: SaveFile,
+}
+
+#[async_trait]
+impl CliCommand<SignedTransactionSummary> for SignTransaction {
+    fn command_name(&self) -> &'static str {
+        "SignTransaction"
+    }
+
+    async fn execute(self) -> CliTypedResult<SignedTransactionSummary> {
+        self.save_file.check_file()?;
+        let raw_txn = load_raw_txn(&self.unsigned_txn_file)?;
+
+        let private_key = self.private_key_options.extract_private_key(
+            self.encoding_options.encoding,
+            &self.profile_options.profile,
+        )?;
+        let public_key = private_key.public_key();
+        let signer = account_address_from_public_key(&public_key);
+        if signer != raw_txn.sender() {
+            return Err(CliError::CommandArgumentError(format!(
+                "Private key is for account {}, but the transaction is sent by {}",
+                signer,
+                raw_txn.sender()
+            )));
+        }
+
+        confirm_signing(&raw_txn, self.save_file.prompt_options)?;
+
+        let signed_txn = raw_txn.sign(&private_key, public_key)?.into_inner();
+        self.save_file.save_to_file(
+            "Signed transaction",
+            &bcs::to_bytes(&signed_txn).map_err(|err| CliError::BCS("SignedTransaction", err))?,
+        )?;
+
+        Ok(SignedTransactionSummary {
+            signed_txn_file: self.save_file.output_file,
+            sender: signed_txn.sender(),
+            sequence_number: signed_txn.sequence_number(),
+            hash: signed_txn.committed_hash(),
+        })
+    }
+}
+
+/// Sign a transaction built with `--build-only` as one of the owners of a multisig account
+///
+/// This writes a partial signature, which is combined with those of the other owners by
+/// `aptos transaction combine-multisig`.  Like `sign`, it doesn't need network access.
+#[derive(Debug, Parser)]
+pub struct SignMultisigTransaction {
+    /// Unsigned transaction file, as written by `--build-only`
+    #[clap(long, parse(from_os_str))]
+    pub(crate) unsigned_txn_file: PathBuf,
+    /// Multisig public key file, as written by `aptos account create-multisig`
+    #[clap(long, parse(from_os_str))]
+    pub(crate) multisig_public_key_file: PathBuf,
+    #[clap(flatten)]
+    pub(crate) private_key_options: PrivateKeyInputOptions,
+    #[clap(flatten)]
+    pub(crate) encoding_options: EncodingOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+    #[clap(flatten)]
+    pub(crate) save_file: SaveFile,
+}
+
+#[async_trait]
+impl CliCommand<PartialSignatureSummary> for SignMultisigTransaction {
+    fn command_name(&self) -> &'static str {
+        "SignMultisigTransaction"
+    }
+
+    async fn execute(self) -> CliTypedResult<PartialSignatureSummary> {
+        self.save_file.check_file()?;
+        let raw_txn = load_raw_txn(&self.unsigned_txn_file)?;
+        let multisig_public_key = load_multisig_public_key(
+            &self.multisig_public_key_file,
+            self.encoding_options.encoding,
+            &raw_txn,
+        )?;
+
+        let private_key = self.private_key_options.extract_private_key(
+            self.encoding_options.encoding,
+            &self.profile_options.profile,
+        )?;
+        let public_key = private_key.public_key();
+        let index = multisig_public_key
+            .public_keys()
+            .iter()
+            .position(|key| *key == public_key)
+            .ok_or_else(|| {
+                CliError::CommandArgumentError(format!(
+                    "Private key is not one of the keys of multisig account {}",
+                    raw_txn.sender()
+                ))
+            })?;
+
+        confirm_signing(&raw_txn, self.save_file.prompt_options)?;
+
+        let partial_signature = PartialSignature {
+            index: index as u8,
+            public_key,
+            signature: private_key.sign(&raw_txn),
+        };
+        self.save_file.save_to_file(
+            "Partial signature",
+            &bcs::to_bytes(&partial_signature)
+                .map_err(|err| CliError::BCS("PartialSignature", err))?,
+        )?;
+
+        Ok(PartialSignatureSummary {
+            partial_signature_file: self.save_file.output_file,
+            sender: raw_txn.sender(),
+            index: partial_signature.index,
+            threshold: *multisig_public_key.threshold(),
+        })
+    }
+}
+
+/// A signature of one of the owners of a multisig account
+#[derive(Debug, Deserialize, Serialize)]
+struct PartialSignature {
+    /// Position of the owner's key in the multisig public key
+    index: u8,
+    public_key: Ed25519PublicKey,
+    signature: Ed25519Signature,
+}
+
+/// A summary of a partial signature written by `aptos transaction sign-multisig`
+#[derive(Clone, Debug, Serialize)]
+pub struct PartialSignatureSummary {
+    pub partial_signature_file: PathBuf,
+    pub sender: AccountAddress,
+    pub index: u8,
+    pub threshold: u8,
+}
+
+/// Combine the partial signatures of a multisig transaction into a signed transaction
+///
+/// The signed transaction can then be submitted with `aptos transaction submit`.
+#[derive(Debug, Parser)]
+pub struct CombineMultisigTransaction {
+    /// Unsigned transaction file, as written by `--build-only`
+    #[clap(long, parse(from_os_str))]
+    pub(crate) unsigned_txn_file: PathBuf,
+    /// Multisig public key file, as written by `aptos account create-multisig`
+    #[clap(long, parse(from_os_str))]
+    pub(crate) multisig_public_key_file: PathBuf,
+    /// Partial signature files, as written by `aptos transaction sign-multisig`
+    #[clap(long, multiple_values = true, parse(from_os_str))]
+    pub(crate) partial_signature_files: Vec<PathBuf>,
+    #[clap(flatten)]
+    pub(crate) encoding_options: EncodingOptions,
+    #[clap(flatten)]
+    pub(crate) save_file: SaveFile,
+}
+
+#[async_trait]
+impl CliCommand<SignedTransactionSummary> for CombineMultisigTransaction {
+    fn command_name(&self) -> &'static str {
+        "CombineMultisigTransaction"
+    }
+
+    async fn execute(self) -> CliTypedResult<SignedTransactionSummary> {
+        self.save_file.check_file()?;
+        let raw_txn = load_raw_txn(&self.unsigned_txn_file)?;
+        let multisig_public_key = load_multisig_public_key(
+            &self.multisig_public_key_file,
+            self.encoding_options.encoding,
+            &raw_txn,
+        )?;
+
+        let mut signatures = Vec::new();
+        for file in &self.partial_signature_files {
+            let partial_signature: PartialSignature = bcs::from_bytes(&read_from_file(file)?)
+                .map_err(|err| CliError::BCS("PartialSignature", err))?;
+            if multisig_public_key
+                .public_keys()
+                .get(partial_signature.index as usize)
+                != Some(&partial_signature.public_key)
+            {
+                return Err(CliError::CommandArgumentError(format!(
+                    "Partial signature {} is not from an owner of multisig account {}",
+                    file.display(),
+                    raw_txn.sender()
+                )));
+            }
+            signatures.push((partial_signature.signature, partial_signature.index));
+        }
+
+        let threshold = *multisig_public_key.threshold() as usize;
+        if signatures.len() < threshold {
+            return Err(CliError::CommandArgumentError(format!(
+                "Only {} of the {} required signatures were given",
+                signatures.len(),
+                threshold
+            )));
+        }
+
+        let signature = MultiEd25519Signature::new(signatures)
+            .map_err(|err| CliError::CommandArgumentError(err.to_string()))?;
+        let signed_txn = SignedTransaction::new_multisig(raw_txn, multisig_public_key, signature)
+            .check_signature()
+            .map_err(|err| {
+                CliError::CommandArgumentError(format!(
+                    "Partial signatures don't sign this transaction: {}",
+                    err
+                ))
+            })?
+            .into_inner();
+        self.save_file.save_to_file(
+            "Signed transaction",
+            &bcs::to_bytes(&signed_txn).map_err(|err| CliError::BCS("SignedTransaction", err))?,
+        )?;
+
+        Ok(SignedTransactionSummary {
+            signed_txn_file: self.save_file.output_file,
+            sender: signed_txn.sender(),
+            sequence_number: signed_txn.sequence_number(),
+            hash: signed_txn.committed_hash(),
+        })
+    }
+}
+
+/// A summary of a transaction signed with `aptos transaction sign`
+#[derive(Clone, Debug, Serialize)]
+pub struct SignedTransactionSummary {
+    pub signed_txn_file: PathBuf,
+    pub sender: AccountAddress,
+    pub sequence_number: u64,
+    pub hash: HashValue,
+}
+
+/// Submit a transaction signed with `aptos transaction sign`
+#[derive(Debug, Parser)]
+pub struct SubmitTransaction {
+    /// Signed transaction file, as written by `aptos transaction sign`
+    #[clap(long, parse(from_os_str))]
+    pub(crate) signed_txn_file: PathBuf,
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+}
+
+#[async_trait]
+impl CliCommand<TransactionSummary> for SubmitTransaction {
+    fn command_name(&self) -> &'static str {
+        "SubmitTransaction"
+    }
+
+    async fn execute(self) -> CliTypedResult<TransactionSummary> {
+        let signed_txn: SignedTransaction =
+            bcs::from_bytes(&read_from_file(&self.signed_txn_file)?)
+                .map_err(|err| CliError::BCS("SignedTransaction", err))?;
+
+        self.rest_options
+            .client(&self.profile_options.profile)?
+            .submit_and_wait(&signed_txn)
+            .await
+            .map(|response| TransactionSummary::from(response.into_inner()))
+            .map_err(|err| CliError::ApiError(err.to_string()))
+    }
+}
+
+fn load_raw_txn(unsigned_txn_file: &Path) -> CliTypedResult<RawTransaction> {
+    bcs::from_bytes(&read_from_file(unsigned_txn_file)?)
+        .map_err(|err| CliError::BCS("RawTransaction", err))
+}
+
+/// Loads the multisig public key, and checks that it belongs to the sender of the transaction
+fn load_multisig_public_key(
+    multisig_public_key_file: &Path,
+    encoding: EncodingType,
+    raw_txn: &RawTransaction,
+) -> CliTypedResult<MultiEd25519PublicKey> {
+    let multisig_public_key: MultiEd25519PublicKey =
+        encoding.load_key("--multisig-public-key-file", multisig_public_key_file)?;
+    let account = AuthenticationKey::multi_ed25519(&multisig_public_key).derived_address();
+    if account != raw_txn.sender() {
+        return Err(CliError::CommandArgumentError(format!(
+            "Multisig public key is for account {}, but the transaction is sent by {}",
+            account,
+            raw_txn.sender()
+        )));
+    }
+    Ok(multisig_public_key)
+}
+
+/// Shows the transaction about to be signed, and asks for confirmation
+fn confirm_signing(raw_txn: &RawTransaction, prompt_options: PromptOptions) -> CliTypedResult<()> {
+    println!(
+        "{}",
+        serde_json::to_string_pretty(raw_txn)
+            .map_err(|err| CliError::UnexpectedError(err.to_string()))?
+    );
+    prompt_yes_with_override("Do you want to sign this transaction?", prompt_options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::types::SaveFile;
+    use aptos_crypto::{ed25519::Ed25519PrivateKey, Uniform};
+    use aptos_temppath::TempPath;
+    use aptos_types::{
+        chain_id::ChainId,
+        transaction::{authenticator::TransactionAuthenticator, Script, TransactionPayload},
+    };
+    use rand::{rngs::StdRng, SeedableRng};
+
+    /// The keys of a 2-of-3 multisig account, with its public key written to a file
+    struct Multisig {
+        dir: TempPath,
+        private_keys: Vec<Ed25519PrivateKey>,
+        public_key: MultiEd25519PublicKey,
+        public_key_file: PathBuf,
+    }
+
+    impl Multisig {
+        fn new() -> Self {
+            let dir = TempPath::new();
+            dir.create_as_dir().unwrap();
+            let mut rng = StdRng::from_seed([0; 32]);
+            let private_keys: Vec<_> = (0..3)
+                .map(|_| Ed25519PrivateKey::generate(&mut rng))
+                .collect();
+            let public_key = MultiEd25519PublicKey::new(
+                private_keys.iter().map(|key| key.public_key()).collect(),
+                2,
+            )
+            .unwrap();
+            let public_key_file = dir.path().join("multisig.pub");
+            std::fs::write(
+                &public_key_file,
+                EncodingType::Hex
+                    .encode_key("multisig public key", &public_key)
+                    .unwrap(),
+            )
+            .unwrap();
+            Self {
+                dir,
+                private_keys,
+                public_key,
+                public_key_file,
+            }
+        }
+
+        fn account(&self) -> AccountAddress {
+            AuthenticationKey::multi_ed25519(&self.public_key).derived_address()
+        }
+
+        /// Writes an unsigned transaction from `sender` to a file
+        fn write_raw_txn(&self, name: &str, sender: AccountAddress) -> PathBuf {
+            let raw_txn = RawTransaction::new(
+                sender,
+                0,
+                TransactionPayload::Script(Script::new(vec![], vec![], vec![])),
+                1000,
+                1,
+                u64::MAX,
+                ChainId::test(),
+            );
+            let file = self.dir.path().join(name);
+            std::fs::write(&file, bcs::to_bytes(&raw_txn).unwrap()).unwrap();
+            file
+        }
+
+        fn save_file(&self, name: &str) -> SaveFile {
+            SaveFile {
+                output_file: self.dir.path().join(name),
+                prompt_options: PromptOptions::yes(),
+            }
+        }
+
+        async fn sign(
+            &self,
+            unsigned_txn_file: &Path,
+            key_index: usize,
+        ) -> CliTypedResult<PathBuf> {
+            SignMultisigTransaction {
+                unsigned_txn_file: unsigned_txn_file.to_path_buf(),
+                multisig_public_key_file: self.public_key_file.clone(),
+                private_key_options: PrivateKeyInputOptions::from_private_key(
+                    &self.private_keys[key_index],
+                )?,
+                encoding_options: Default::default(),
+                profile_options: Default::default(),
+                save_file: self.save_file(&format!("partial-{}.sig", key_index)),
+            }
+            .execute()
+            .await
+            .map(|summary| summary.partial_signature_file)
+        }
+
+        async fn combine(
+            &self,
+            unsigned_txn_file: &Path,
+            partial_signature_files: Vec<PathBuf>,
+        ) -> CliTypedResult<SignedTransactionSummary> {
+            CombineMultisigTransaction {
+                unsigned_txn_file: unsigned_txn_file.to_path_buf(),
+                multisig_public_key_file: self.public_key_file.clone(),
+                partial_signature_files,
+                encoding_options: Default::default(),
+                save_file: self.save_file("signed.txn"),
+            }
+            .execute()
+            .await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_multisig_round_trip() {
+        let multisig = Multisig::new();
+        let unsigned_txn_file = multisig.write_raw_txn("unsigned.txn", multisig.account());
+
+        let partial_signature_files = vec![
+            multisig.sign(&unsigned_txn_file, 0).await.unwrap(),
+            multisig.sign(&unsigned_txn_file, 2).await.unwrap(),
+        ];
+        let summary = multisig
+            .combine(&unsigned_txn_file, partial_signature_files)
+            .await
+            .unwrap();
+        assert_eq!(summary.sender, multisig.account());
+
+        let signed_txn: SignedTransaction =
+            bcs::from_bytes(&read_from_file(&summary.signed_txn_file).unwrap()).unwrap();
+        assert_eq!(signed_txn.sender(), multisig.account());
+        match signed_txn.authenticator() {
+            TransactionAuthenticator::MultiEd25519 {
+                public_key,
+                signature,
+            } => {
+                assert_eq!(public_key, multisig.public_key);
+                assert_eq!(signature.signatures().len(), 2);
+                // Signed by the first and the third owner
+                assert_eq!(signature.bitmap(), &[0b1010_0000, 0, 0, 0]);
+            }
+            authenticator => panic!("Unexpected authenticator {:?}", authenticator),
+        }
+        signed_txn.check_signature().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_multisig_below_threshold() {
+        let multisig = Multisig::new();
+        let unsigned_txn_file = multisig.write_raw_txn("unsigned.txn", multisig.account());
+
+        let partial_signature_file = multisig.sign(&unsigned_txn_file, 1).await.unwrap();
+        let result = multisig
+            .combine(&unsigned_txn_file, vec![partial_signature_file])
+            .await;
+        assert!(matches!(result, Err(CliError::CommandArgumentError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_multisig_wrong_sender() {
+        let multisig = Multisig::new();
+        let unsigned_txn_file = multisig.write_raw_txn("unsigned.txn", multisig.account());
+        let other_txn_file = multisig.write_raw_txn("other.txn", AccountAddress::ONE);
+
+        // Neither signing nor combining takes a transaction from another account
+        assert!(matches!(
+            multisig.sign(&other_txn_file, 0).await,
+            Err(CliError::CommandArgumentError(_))
+        ));
+        let partial_signature_files = vec![
+            multisig.sign(&unsigned_txn_file, 0).await.unwrap(),
+            multisig.sign(&unsigned_txn_file, 1).await.unwrap(),
+        ];
+        let result = multisig
+            .combine(&other_txn_file, partial_signature_files)
+            .await;
+        assert!(matches!(result, Err(CliError::CommandArgumentError(_))));
+    }
+}
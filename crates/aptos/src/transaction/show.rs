@@ -0,0 +1,414 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::common::{
+    diff::{diff_values, FieldChange},
+    types::{CliCommand, CliError, CliTypedResult, ProfileOptions, RestOptions},
+};
+use aptos_crypto::HashValue;
+use aptos_rest_client::{
+    aptos_api_types::{
+        DeleteModule, DeleteResource, DeleteTableItem, Event, TransactionPayload, WriteModule,
+        WriteResource, WriteSetChange, WriteTableItem,
+    },
+    AptosError, Client, Transaction,
+};
+use aptos_types::account_address::AccountAddress;
+use async_trait::async_trait;
+use clap::Parser;
+use serde::Serialize;
+use serde_json::Value;
+use std::str::FromStr;
+
+/// Show a committed transaction in a readable form
+///
+/// The transaction is looked up by hash (`0x...`) or by version.  Its entry function call and
+/// events are decoded, and each resource it changed is shown with the fields that changed, from
+/// their value before the transaction (at the previous version) to their value after it.
+#[derive(Debug, Parser)]
+pub struct ShowTransaction {
+    /// Hash (as a `0x` hex literal) or version of the transaction
+    pub(crate) txn_id: TransactionId,
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+}
+
+/// A transaction, either by hash or by version
+#[derive(Clone, Copy, Debug)]
+pub enum TransactionId {
+    Hash(HashValue),
+    Version(u64),
+}
+
+impl FromStr for TransactionId {
+    type Err = CliError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.starts_with("0x") {
+            HashValue::from_hex_literal(s)
+                .map(TransactionId::Hash)
+                .map_err(|err| CliError::UnableToParse("transaction hash", err.to_string()))
+        } else {
+            u64::from_str(s)
+                .map(TransactionId::Version)
+                .map_err(|err| CliError::UnableToParse("transaction version", err.to_string()))
+        }
+    }
+}
+
+/// A readable view of a committed transaction
+#[derive(Debug, Serialize)]
+pub struct TransactionDetails {
+    #[serde(rename = "type")]
+    pub txn_type: &'static str,
+    pub version: Option<u64>,
+    pub hash: String,
+    pub success: bool,
+    pub vm_status: String,
+    pub gas_used: u64,
+    pub timestamp_usecs: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sender: Option<AccountAddress>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sequence_number: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payload: Option<PayloadDetails>,
+    pub events: Vec<EventDetails>,
+    pub changes: Vec<ChangeDetails>,
+}
+
+/// What a user transaction runs
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum PayloadDetails {
+    EntryFunction {
+        function: String,
+        type_arguments: Vec<String>,
+        arguments: Vec<Value>,
+    },
+    Script {
+        type_arguments: Vec<String>,
+        arguments: Vec<Value>,
+    },
+    ModuleBundle {
+        num_modules: usize,
+    },
+    WriteSet,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EventDetails {
+    #[serde(rename = "type")]
+    pub event_type: String,
+    pub key: String,
+    pub sequence_number: u64,
+    pub data: Value,
+}
+
+/// A change to on-chain state
+///
+/// For resources, `diff` lists the fields that changed.  When a resource is created or deleted,
+/// the whole resource is shown as a single change.
+#[derive(Debug, Default, Serialize)]
+pub struct ChangeDetails {
+    pub change: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<AccountAddress>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resource: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub module: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub handle: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub diff: Vec<FieldChange>,
+}
+
+#[async_trait]
+impl CliCommand<TransactionDetails> for ShowTransaction {
+    fn command_name(&self) -> &'static str {
+        "ShowTransaction"
+    }
+
+    async fn execute(self) -> CliTypedResult<TransactionDetails> {
+        let client = self.rest_options.client(&self.profile_options.profile)?;
+        let txn = match self.txn_id {
+            TransactionId::Hash(hash) => client.get_transaction(hash).await,
+            TransactionId::Version(version) => client.get_transaction_by_version(version).await,
+        }
+        .map_err(|err| CliError::ApiError(err.to_string()))?
+        .into_inner();
+
+        let info = txn.transaction_info().map_err(|_| {
+            CliError::CommandArgumentError(
+                "Transaction is still pending, it has no outcome to show yet".to_string(),
+            )
+        })?;
+        let version = info.version.0;
+
+        let (sender, sequence_number, payload, events) = match &txn {
+            Transaction::UserTransaction(user_txn) => (
+                Some(*user_txn.request.sender.inner()),
+                Some(user_txn.request.sequence_number.0),
+                Some(payload_details(&user_txn.request.payload)),
+                user_txn.events.as_slice(),
+            ),
+            Transaction::GenesisTransaction(genesis_txn) => {
+                (None, None, None, genesis_txn.events.as_slice())
+            }
+            Transaction::BlockMetadataTransaction(block_txn) => {
+                (None, None, None, block_txn.events.as_slice())
+            }
+            _ => (None, None, None, &[][..]),
+        };
+
+        let mut changes = Vec::new();
+        for change in &info.changes {
+            changes.push(change_details(&client, change, version).await?);
+        }
+
+        Ok(TransactionDetails {
+            txn_type: txn.type_str(),
+            version: txn.version(),
+            hash: info.hash.to_string(),
+            success: txn.success(),
+            vm_status: txn.vm_status(),
+            gas_used: info.gas_used.0,
+            timestamp_usecs: txn.timestamp(),
+            sender,
+            sequence_number,
+            payload,
+            events: events.iter().map(event_details).collect(),
+            changes,
+        })
+    }
+}
+
+fn payload_details(payload: &TransactionPayload) -> PayloadDetails {
+    match payload {
+        TransactionPayload::ScriptFunctionPayload(payload) => PayloadDetails::EntryFunction {
+            function: payload.function.to_string(),
+            type_arguments: payload
+                .type_arguments
+                .iter()
+                .map(|ty| ty.to_string())
+                .collect(),
+            arguments: payload.arguments.clone(),
+        },
+        TransactionPayload::ScriptPayload(payload) => PayloadDetails::Script {
+            type_arguments: payload
+                .type_arguments
+                .iter()
+                .map(|ty| ty.to_string())
+                .collect(),
+            arguments: payload.arguments.clone(),
+        },
+        TransactionPayload::ModuleBundlePayload(payload) => PayloadDetails::ModuleBundle {
+            num_modules: payload.modules.len(),
+        },
+        TransactionPayload::WriteSetPayload(_) => PayloadDetails::WriteSet,
+    }
+}
+
+fn event_details(event: &Event) -> EventDetails {
+    EventDetails {
+        event_type: event.typ.to_string(),
+        key: event.key.to_string(),
+        sequence_number: event.sequence_number.0,
+        data: event.data.clone(),
+    }
+}
+
+async fn change_details(
+    client: &Client,
+    change: &WriteSetChange,
+    version: u64,
+) -> CliTypedResult<ChangeDetails> {
+    let details = match change {
+        WriteSetChange::WriteResource(WriteResource { address, data, .. }) => {
+            let resource = data.typ.to_string();
+            let before = resource_before(client, *address.inner(), &resource, version).await?;
+            let after = serde_json::to_value(&data.data)
+                .map_err(|err| CliError::UnexpectedError(err.to_string()))?;
+            resource_change(*address.inner(), resource, before, Some(after))
+        }
+        WriteSetChange::DeleteResource(DeleteResource {
+            address, resource, ..
+        }) => {
+            let resource = resource.to_string();
+            let before = resource_before(client, *address.inner(), &resource, version).await?;
+            resource_change(*address.inner(), resource, before, None)
+        }
+        WriteSetChange::WriteModule(WriteModule { address, data, .. }) => ChangeDetails {
+            address: Some(*address.inner()),
+            module: data
+                .clone()
+                .try_parse_abi()
+                .ok()
+                .and_then(|module| module.abi)
+                .map(|abi| abi.name.to_string()),
+            ..Default::default()
+        },
+        WriteSetChange::DeleteModule(DeleteModule {
+            address, module, ..
+        }) => ChangeDetails {
+            address: Some(*address.inner()),
+            module: Some(module.to_string()),
+            ..Default::default()
+        },
+        WriteSetChange::WriteTableItem(WriteTableItem {
+            handle, key, value, ..
+        }) => ChangeDetails {
+            handle: Some(handle.to_string()),
+            key: Some(key.to_string()),
+            value: Some(value.to_string()),
+            ..Default::default()
+        },
+        WriteSetChange::DeleteTableItem(DeleteTableItem { handle, key, .. }) => ChangeDetails {
+            handle: Some(handle.to_string()),
+            key: Some(key.to_string()),
+            ..Default::default()
+        },
+    };
+    Ok(ChangeDetails {
+        change: change.type_str(),
+        ..details
+    })
+}
+
+/// Shows a change to a resource by the fields that changed, `before` and `after` being `None`
+/// when the resource didn't exist
+fn resource_change(
+    address: AccountAddress,
+    resource: String,
+    before: Option<Value>,
+    after: Option<Value>,
+) -> ChangeDetails {
+    ChangeDetails {
+        address: Some(address),
+        diff: diff_values(before.as_ref(), after.as_ref()),
+        resource: Some(resource),
+        ..Default::default()
+    }
+}
+
+/// Looks up a resource as it was before the transaction at `version`
+///
+/// Returns `None` if the account didn't have the resource then.  If the node has pruned the
+/// previous version, it answers with the `version_pruned` error code, and as the change can't be
+/// shown that fails rather than showing the resource as created.
+async fn resource_before(
+    client: &Client,
+    address: AccountAddress,
+    resource: &str,
+    version: u64,
+) -> CliTypedResult<Option<Value>> {
+    let previous_version = match version.checked_sub(1) {
+        Some(previous_version) => previous_version,
+        None => return Ok(None),
+    };
+    let before = client
+        .get_account_resource_at_version_v1(address, resource, previous_version)
+        .await
+        .map_err(|err| match err.downcast_ref::<AptosError>() {
+            Some(error) if error.is_version_pruned() => CliError::ApiError(format!(
+                "Unable to show the change to {} of {}, as the node has pruned version {} \
+                 before the transaction. Use a node that keeps more history, e.g. an archive node",
+                resource, address, previous_version
+            )),
+            _ => CliError::ApiError(err.to_string()),
+        })?;
+    Ok(before.map(|resource| resource.data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn render(details: &ChangeDetails) -> Value {
+        serde_json::to_value(details).unwrap()
+    }
+
+    #[test]
+    fn renders_changed_fields() {
+        let details = resource_change(
+            AccountAddress::ONE,
+            "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>".to_string(),
+            Some(json!({
+                "coin": { "value": "100" },
+                "frozen": false,
+                "deposit_events": { "counter": "1" }
+            })),
+            Some(json!({
+                "coin": { "value": "90" },
+                "frozen": false,
+                "deposit_events": { "counter": "2" }
+            })),
+        );
+        let rendered = render(&ChangeDetails {
+            change: "write_resource",
+            ..details
+        });
+        assert_eq!(rendered["change"], "write_resource");
+        assert_eq!(
+            rendered["resource"],
+            "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>"
+        );
+        // Only the fields that changed are shown
+        assert_eq!(
+            rendered["diff"],
+            json!([
+                { "field": "coin.value", "before": "100", "after": "90" },
+                { "field": "deposit_events.counter", "before": "1", "after": "2" },
+            ])
+        );
+        // Table item fields don't apply to resources
+        assert!(rendered.get("handle").is_none());
+    }
+
+    #[test]
+    fn renders_created_and_deleted_resources_whole() {
+        let resource = json!({ "value": "1" });
+        let created = resource_change(
+            AccountAddress::ONE,
+            "0x1::m::R".to_string(),
+            None,
+            Some(resource.clone()),
+        );
+        assert_eq!(
+            render(&created)["diff"],
+            json!([{ "field": "", "after": { "value": "1" } }])
+        );
+
+        let deleted = resource_change(
+            AccountAddress::ONE,
+            "0x1::m::R".to_string(),
+            Some(resource),
+            None,
+        );
+        assert_eq!(
+            render(&deleted)["diff"],
+            json!([{ "field": "", "before": { "value": "1" } }])
+        );
+    }
+
+    #[test]
+    fn omits_diff_of_unchanged_resources() {
+        let resource = json!({ "value": "1" });
+        let details = resource_change(
+            AccountAddress::ONE,
+            "0x1::m::R".to_string(),
+            Some(resource.clone()),
+            Some(resource),
+        );
+        assert!(details.diff.is_empty());
+        assert!(render(&details).get("diff").is_none());
+    }
+}
@@ -13,6 +13,7 @@ RUSTFLAGS="--cfg tokio_unstable" cargo build --release \
         -p aptos-openapi-spec-generator \
         -p backup-cli \
         -p db-bootstrapper \
+        -p db-tool \
         -p forge-cli \
         -p transaction-emitter \
         "$@"
@@ -29,6 +30,7 @@ BINS=(
     db-backup-verify
     db-bootstrapper
     db-restore
+    db-tool
     forge
     transaction-emitter
 )
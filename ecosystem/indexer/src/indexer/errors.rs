@@ -22,3 +22,22 @@ impl TransactionProcessingError {
         }
     }
 }
+
+/// The database already has data indexed from another chain than the one of the node
+#[derive(Debug)]
+pub struct ChainIdMismatchError {
+    pub existing_chain_id: i64,
+    pub new_chain_id: i64,
+}
+
+impl std::fmt::Display for ChainIdMismatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Wrong chain detected! Trying to index chain {} now but existing data is for chain {}",
+            self.new_chain_id, self.existing_chain_id
+        )
+    }
+}
+
+impl std::error::Error for ChainIdMismatchError {}
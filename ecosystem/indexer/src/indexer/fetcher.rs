@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::counters::{FETCHED_TRANSACTION, UNABLE_TO_FETCH_TRANSACTION};
+use anyhow::Context;
 use aptos_rest_client::{Client as RestClient, State, Transaction};
 use std::time::Duration;
 use tokio::sync::Mutex;
@@ -36,12 +37,13 @@ impl TransactionFetcherTrait for TransactionFetcher {
         self.version = version;
     }
 
-    async fn fetch_ledger_info(&mut self) -> State {
-        self.client
+    async fn fetch_ledger_info(&mut self) -> anyhow::Result<State> {
+        Ok(self
+            .client
             .get_ledger_information()
             .await
-            .expect("ledger info must be present")
-            .into_inner()
+            .context("Failed to fetch ledger info")?
+            .into_inner())
     }
 
     /// Fetches the next version based on its internal version counter
@@ -126,7 +128,7 @@ impl TransactionFetcherTrait for TransactionFetcher {
 pub trait TransactionFetcherTrait: Send + Sync {
     fn set_version(&mut self, version: u64);
 
-    async fn fetch_ledger_info(&mut self) -> State;
+    async fn fetch_ledger_info(&mut self) -> anyhow::Result<State>;
 
     async fn fetch_next(&mut self) -> Transaction;
 
@@ -3,7 +3,7 @@
 use crate::{
     database::{execute_with_better_error, PgDbPool},
     indexer::{
-        errors::TransactionProcessingError,
+        errors::{ChainIdMismatchError, TransactionProcessingError},
         fetcher::{TransactionFetcher, TransactionFetcherTrait},
         processing_result::ProcessingResult,
         transaction_processor::TransactionProcessor,
@@ -11,7 +11,7 @@ use crate::{
     models::ledger_info::LedgerInfo,
     schema::ledger_infos::{self, dsl},
 };
-use anyhow::{ensure, Context, Result};
+use anyhow::{Context, Result};
 use aptos_logger::info;
 use aptos_rest_client::Transaction;
 use diesel::{prelude::*, RunQueryDsl};
@@ -74,30 +74,37 @@ impl Tailer {
     }
 
     pub fn run_migrations(&self) {
+        self.try_run_migrations(&mut std::io::stdout())
+            .expect("migrations failed!");
+    }
+
+    /// Like `run_migrations`, but returns an error instead of panicking, and writes the output of
+    /// the migrations to `output`
+    pub fn try_run_migrations(&self, output: &mut dyn std::io::Write) -> anyhow::Result<()> {
         info!("Running migrations...");
-        embedded_migrations::run_with_output(
-            &self
-                .connection_pool
-                .get()
-                .expect("Could not get connection for migrations"),
-            &mut std::io::stdout(),
-        )
-        .expect("migrations failed!");
+        let conn = self
+            .connection_pool
+            .get()
+            .context("Could not get connection for migrations")?;
+        embedded_migrations::run_with_output(&conn, output).context("migrations failed!")?;
         info!("Migrations complete!");
+        Ok(())
     }
 
     /// If chain id doesn't exist, save it. Otherwise make sure that we're indexing the same chain
+    ///
+    /// Fails with a `ChainIdMismatchError` if the database has data from another chain.
     pub async fn check_or_update_chain_id(&self) -> anyhow::Result<usize> {
         info!("Checking if chain id is correct");
         let conn = self
             .connection_pool
             .get()
-            .expect("DB connection should be available at this stage");
+            .context("Could not get connection to check chain id")?;
 
         let query_chain = dsl::ledger_infos
             .select(dsl::chain_id)
             .load::<i64>(&conn)
-            .expect("Error loading chain id from db");
+            .context("Error loading chain id from db")?;
         let maybe_existing_chain_id = query_chain.first();
 
         let new_chain_id = self
@@ -105,12 +112,18 @@ impl Tailer {
             .lock()
             .await
             .fetch_ledger_info()
-            .await
+            .await?
             .chain_id as i64;
 
         match maybe_existing_chain_id {
             Some(chain_id) => {
-                ensure!(*chain_id == new_chain_id, "Wrong chain detected! Trying to index chain {} now but existing data is for chain {}", new_chain_id, chain_id);
+                if *chain_id != new_chain_id {
+                    return Err(ChainIdMismatchError {
+                        existing_chain_id: *chain_id,
+                        new_chain_id,
+                    }
+                    .into());
+                }
                 info!("Chain id matches! Continuing to index chain {}", chain_id);
                 Ok(0)
             }
@@ -311,14 +324,14 @@ mod test {
             self.chain_id = version as u8;
         }
 
-        async fn fetch_ledger_info(&mut self) -> State {
-            State {
+        async fn fetch_ledger_info(&mut self) -> anyhow::Result<State> {
+            Ok(State {
                 chain_id: self.chain_id,
                 epoch: 0,
                 version: 0,
                 timestamp_usecs: 0,
                 oldest_ledger_version: None,
-            }
+            })
         }
 
         async fn fetch_next(&mut self) -> Transaction {
@@ -867,7 +880,10 @@ mod test {
         assert!(tailer.check_or_update_chain_id().await.is_ok());
 
         tailer.set_fetcher_version(10).await;
-        assert!(tailer.check_or_update_chain_id().await.is_err());
+        let err = tailer.check_or_update_chain_id().await.unwrap_err();
+        let mismatch = err.downcast_ref::<ChainIdMismatchError>().unwrap();
+        assert_eq!(mismatch.existing_chain_id, 4);
+        assert_eq!(mismatch.new_chain_id, 10);
 
         tailer.set_fetcher_version(4).await;
         assert!(tailer.check_or_update_chain_id().await.is_ok());
@@ -54,6 +54,8 @@ fn main() -> Result<()> {
             NO_OP_STORAGE_PRUNER_CONFIG, /* pruner */
             RocksdbConfigs::default(),
             false, /* indexer */
+            false, /* state key history */
+            false, /* affected account index */
             TARGET_SNAPSHOT_SIZE,
         )
     } else {
@@ -60,6 +60,8 @@ fn bootstrap_with_genesis(db_dir: impl AsRef<Path>) {
             NO_OP_STORAGE_PRUNER_CONFIG,
             rocksdb_configs,
             false, /* indexer */
+            false, /* state key history */
+            false, /* affected account index */
             TARGET_SNAPSHOT_SIZE,
         )
         .expect("DB should open."),
@@ -36,6 +36,8 @@ pub fn init_db_and_executor(config: &NodeConfig) -> (DbReaderWriter, BlockExecut
             config.storage.storage_pruner_config,
             RocksdbConfigs::default(),
             false,
+            false,
+            false,
             config.storage.target_snapshot_size,
         )
         .expect("DB should open."),
@@ -59,6 +61,8 @@ fn create_checkpoint(source_dir: impl AsRef<Path>, checkpoint_dir: impl AsRef<Pa
         NO_OP_STORAGE_PRUNER_CONFIG, /* pruner */
         RocksdbConfigs::default(),
         false,
+        false,
+        false,
         TARGET_SNAPSHOT_SIZE,
     )
     .expect("db open failure.")
@@ -1,7 +1,7 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use aptos_config::config::StoragePrunerConfig;
+use aptos_config::config::{LedgerStorePruneWindows, StoragePrunerConfig};
 use aptos_secure_push_metrics::MetricsPusher;
 use aptos_vm::AptosVM;
 use std::path::PathBuf;
@@ -38,6 +38,7 @@ impl PrunerOpt {
             } else {
                 Some(self.ledger_prune_window as u64)
             },
+            ledger_store_prune_windows: LedgerStorePruneWindows::default(),
             ledger_pruning_batch_size: self.ledger_pruning_batch_size,
             state_store_pruning_batch_size: self.state_store_pruning_batch_size,
         }
@@ -1,16 +1,20 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{bootstrap_genesis, gen_block_id, gen_ledger_info_with_sigs};
+use crate::{
+    bootstrap_genesis, gen_block_id, gen_ledger_info_with_sigs, get_test_signed_transaction,
+};
 use anyhow::{anyhow, ensure, Result};
+use aptos_crypto::PrivateKey;
 use aptos_sdk::{
-    transaction_builder::TransactionFactory,
+    transaction_builder::{aptos_stdlib, TransactionFactory},
     types::{AccountKey, LocalAccount},
 };
 use aptos_state_view::account_with_state_view::{AccountWithStateView, AsAccountWithStateView};
 use aptos_types::{
     account_config::aptos_root_address,
     account_view::AccountView,
+    block_metadata::BlockMetadata,
     chain_id::ChainId,
     event::EventKey,
     test_helpers::transaction_test_helpers::block,
@@ -440,6 +444,72 @@ pub fn test_execution_with_storage_impl() -> Arc<AptosDB> {
     aptos_db
 }
 
+/// Creates a DB with the genesis, a block ending epoch 1 with a reconfiguration, and a block in
+/// epoch 2 ending with a state checkpoint.
+pub fn test_execution_with_reconfiguration_impl() -> Arc<AptosDB> {
+    let (genesis, validators) = vm_genesis::test_genesis_change_set_and_validators(Some(1));
+    let genesis_key = &vm_genesis::GENESIS_KEYPAIR.0;
+    let genesis_txn = Transaction::GenesisTransaction(WriteSetPayload::Direct(genesis));
+
+    let path = aptos_temppath::TempPath::new();
+    path.create_as_dir().unwrap();
+    let (aptos_db, _db, executor, _waypoint) = create_db_and_executor(path.path(), &genesis_txn);
+
+    let signer = aptos_types::validator_signer::ValidatorSigner::new(
+        validators[0].data.address,
+        validators[0].consensus_key.clone(),
+    );
+    let root_txn = |sequence_number, payload| {
+        get_test_signed_transaction(
+            aptos_root_address(),
+            sequence_number,
+            genesis_key.clone(),
+            genesis_key.public_key(),
+            Some(payload),
+        )
+    };
+
+    // The block prologue bumps the timer, so that setting the version reconfigures.
+    let block1 = vec![
+        root_txn(0, aptos_stdlib::aptos_coin_mint(signer.author(), 1_000_000)),
+        Transaction::BlockMetadata(BlockMetadata::new(
+            gen_block_id(1),
+            0,
+            1,
+            signer.author(),
+            Some(0),
+            vec![false],
+            vec![],
+            300000001,
+        )),
+        root_txn(1, aptos_stdlib::version_set_version(42)),
+    ];
+    let block1_id = gen_block_id(1);
+    let output1 = executor
+        .execute_block((block1_id, block1), executor.committed_block_id())
+        .unwrap();
+    assert!(output1.has_reconfiguration());
+    let ledger_info_with_sigs = gen_ledger_info_with_sigs(1, &output1, block1_id, vec![&signer]);
+    executor
+        .commit_blocks(vec![block1_id], ledger_info_with_sigs)
+        .unwrap();
+
+    let block2 = block(vec![root_txn(
+        2,
+        aptos_stdlib::aptos_coin_mint(signer.author(), 1_000_000),
+    )]);
+    let block2_id = gen_block_id(2);
+    let output2 = executor
+        .execute_block((block2_id, block2), executor.committed_block_id())
+        .unwrap();
+    let ledger_info_with_sigs = gen_ledger_info_with_sigs(2, &output2, block2_id, vec![&signer]);
+    executor
+        .commit_blocks(vec![block2_id], ledger_info_with_sigs)
+        .unwrap();
+
+    aptos_db
+}
+
 pub fn create_db_and_executor<P: AsRef<std::path::Path>>(
     path: P,
     genesis: &Transaction,
@@ -12,6 +12,7 @@ use crate::{
     },
     counters,
     logging::{LogEntry, LogSchema, TxnsLog},
+    shared_mempool::types::MempoolUtilization,
 };
 use aptos_config::config::NodeConfig;
 use aptos_crypto::HashValue;
@@ -294,6 +295,11 @@ impl Mempool {
         self.transactions.timeline_range(start_id, end_id)
     }
 
+    pub(crate) fn utilization(&self) -> MempoolUtilization {
+        let (size, capacity) = self.transactions.size_and_capacity();
+        MempoolUtilization { size, capacity }
+    }
+
     pub fn gen_snapshot(&self) -> TxnsLog {
         self.transactions.gen_snapshot(&self.metrics_cache)
     }
@@ -579,6 +579,11 @@ impl TransactionStore {
         txns_log
     }
 
+    /// Returns the number of transactions in the store and its capacity.
+    pub(crate) fn size_and_capacity(&self) -> (usize, usize) {
+        (self.system_ttl_index.size(), self.capacity)
+    }
+
     #[cfg(test)]
     pub(crate) fn get_parking_lot_size(&self) -> usize {
         self.parking_lot_index.size()
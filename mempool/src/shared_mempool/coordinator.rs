@@ -148,6 +148,18 @@ async fn handle_client_request<V>(
                 ))
                 .await;
         }
+        MempoolClientRequest::GetUtilization(callback) => {
+            // Reading the utilization only takes the mempool lock briefly, so
+            // answer right away rather than spawning a task.
+            let utilization = smp.mempool.lock().utilization();
+            if callback.send(utilization).is_err() {
+                error!(LogSchema::event_log(
+                    LogEntry::GetUtilization,
+                    LogEvent::CallbackFail
+                ));
+                counters::CLIENT_CALLBACK_FAIL.inc();
+            }
+        }
     }
 }
 
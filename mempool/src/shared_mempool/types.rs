@@ -209,6 +209,16 @@ pub type SubmissionStatusBundle = (SignedTransaction, SubmissionStatus);
 pub enum MempoolClientRequest {
     SubmitTransaction(SignedTransaction, oneshot::Sender<Result<SubmissionStatus>>),
     GetTransactionByHash(HashValue, oneshot::Sender<Option<SignedTransaction>>),
+    GetUtilization(oneshot::Sender<MempoolUtilization>),
+}
+
+/// How full mempool is, reported to clients such as the API.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MempoolUtilization {
+    /// Number of transactions currently in mempool.
+    pub size: usize,
+    /// Maximum number of transactions mempool can hold.
+    pub capacity: usize,
 }
 
 pub type MempoolClientSender = mpsc::Sender<MempoolClientRequest>;
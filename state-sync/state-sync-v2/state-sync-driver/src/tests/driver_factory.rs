@@ -37,6 +37,8 @@ fn test_new_initialized_configs() {
         NO_OP_STORAGE_PRUNER_CONFIG,
         RocksdbConfigs::default(),
         false,
+        false,
+        false,
         TARGET_SNAPSHOT_SIZE,
     )
     .unwrap();
@@ -990,18 +990,16 @@ impl StorageReader {
         &self,
         latest_version: Version,
     ) -> Result<Option<CompleteDataRange<Version>>, Error> {
+        // Transactions are only served with their infos and events, so the range starts at the
+        // first version of which all ledger data is readable.
         let first_transaction_version = self
             .storage
-            .get_first_txn_version()
+            .get_min_readable_ledger_version()
             .map_err(|error| Error::StorageErrorEncountered(error.to_string()))?;
-        if let Some(first_transaction_version) = first_transaction_version {
-            let transaction_range =
-                CompleteDataRange::new(first_transaction_version, latest_version)
-                    .map_err(|error| Error::UnexpectedErrorEncountered(error.to_string()))?;
-            Ok(Some(transaction_range))
-        } else {
-            Ok(None)
-        }
+        let transaction_range =
+            CompleteDataRange::new(first_transaction_version, latest_version)
+                .map_err(|error| Error::UnexpectedErrorEncountered(error.to_string()))?;
+        Ok(Some(transaction_range))
     }
 
     /// Returns the transaction output range held in the database (lowest to highest).
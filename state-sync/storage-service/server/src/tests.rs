@@ -810,9 +810,9 @@ async fn test_get_storage_server_summary() {
         .times(1)
         .return_once(move || Ok(highest_ledger_info_clone));
     db_reader
-        .expect_get_first_txn_version()
+        .expect_get_min_readable_ledger_version()
         .times(1)
-        .return_once(move || Ok(Some(lowest_version)));
+        .return_once(move || Ok(lowest_version));
     db_reader
         .expect_get_first_write_set_version()
         .times(1)
@@ -1302,8 +1302,8 @@ fn create_mock_db_for_subscription(
         .expect_get_latest_ledger_info()
         .return_once(move || Ok(highest_ledger_info_clone));
     db_reader
-        .expect_get_first_txn_version()
-        .return_once(move || Ok(Some(lowest_version)));
+        .expect_get_min_readable_ledger_version()
+        .return_once(move || Ok(lowest_version));
     db_reader
         .expect_get_first_write_set_version()
         .return_once(move || Ok(Some(lowest_version)));
@@ -1670,5 +1670,7 @@ mock! {
         ) -> Result<StateValueChunkWithProof>;
 
         fn get_state_prune_window(&self) -> Result<Option<usize>>;
+
+        fn get_min_readable_ledger_version(&self) -> Result<Version>;
     }
 }
@@ -14,7 +14,7 @@ use crate::{
     test_helper::{arb_blocks_to_commit, put_as_state_root, put_transaction_info},
     AptosDB, ROCKSDB_PROPERTIES,
 };
-use aptos_config::config::StoragePrunerConfig;
+use aptos_config::config::{LedgerStorePruneWindows, StoragePrunerConfig};
 use aptos_crypto::{hash::CryptoHash, HashValue};
 use aptos_temppath::TempPath;
 use aptos_types::{
@@ -90,6 +90,7 @@ fn test_error_if_version_is_pruned() {
         StoragePrunerConfig {
             state_store_prune_window: Some(0),
             ledger_prune_window: Some(0),
+            ledger_store_prune_windows: LedgerStorePruneWindows::default(),
             ledger_pruning_batch_size: 1,
             state_store_pruning_batch_size: 1,
         },
@@ -100,6 +101,7 @@ fn test_error_if_version_is_pruned() {
         StoragePrunerConfig {
             state_store_prune_window: Some(0),
             ledger_prune_window: Some(0),
+            ledger_store_prune_windows: LedgerStorePruneWindows::default(),
             ledger_pruning_batch_size: 1,
             state_store_pruning_batch_size: 1,
         },
@@ -6,20 +6,25 @@ use crate::{
     ledger_store::LedgerStore,
     metrics::{
         BACKUP_EPOCH_ENDING_EPOCH, BACKUP_STATE_SNAPSHOT_LEAF_IDX, BACKUP_STATE_SNAPSHOT_VERSION,
-        BACKUP_TXN_VERSION,
+        BACKUP_TABLE_INFO_IDX, BACKUP_TXN_VERSION,
     },
     state_store::StateStore,
     transaction_store::TransactionStore,
 };
-use anyhow::{ensure, Result};
+use anyhow::{anyhow, ensure, Result};
 use aptos_crypto::hash::HashValue;
 use aptos_types::{
     contract_event::ContractEvent,
     ledger_info::LedgerInfoWithSignatures,
     proof::{SparseMerkleRangeProof, TransactionAccumulatorRangeProof, TransactionInfoWithProof},
-    state_store::{state_key::StateKey, state_value::StateValue},
+    state_store::{
+        state_key::StateKey,
+        state_value::StateValue,
+        table::{TableHandle, TableInfo},
+    },
     transaction::{Transaction, TransactionInfo, Version},
 };
+use aptosdb_indexer::Indexer;
 use itertools::zip_eq;
 use serde::{Deserialize, Serialize};
 use std::{fmt, sync::Arc};
@@ -31,6 +36,7 @@ pub struct BackupHandler {
     transaction_store: Arc<TransactionStore>,
     state_store: Arc<StateStore>,
     event_store: Arc<EventStore>,
+    indexer: Option<Arc<Indexer>>,
 }
 
 impl BackupHandler {
@@ -39,12 +45,14 @@ impl BackupHandler {
         transaction_store: Arc<TransactionStore>,
         state_store: Arc<StateStore>,
         event_store: Arc<EventStore>,
+        indexer: Option<Arc<Indexer>>,
     ) -> Self {
         Self {
             ledger_store,
             transaction_store,
             state_store,
             event_store,
+            indexer,
         }
     }
 
@@ -165,6 +173,34 @@ impl BackupHandler {
                 li
             }))
     }
+
+    /// Gets the last version processed by the indexer, if the indexer is enabled and has
+    /// processed any.
+    pub fn get_indexer_latest_version(&self) -> Result<Option<Version>> {
+        Ok(self.indexer()?.latest_version())
+    }
+
+    /// Gets an iterator which yields the info of all tables known to the indexer.
+    /// N.B. table infos are never removed, so all tables known at the version returned by a
+    /// previous `get_indexer_latest_version()` call are yielded, possibly with some newer ones.
+    pub fn get_table_info_iter(
+        &self,
+    ) -> Result<impl Iterator<Item = Result<(TableHandle, TableInfo)>> + '_> {
+        Ok(self
+            .indexer()?
+            .get_table_info_iter()?
+            .enumerate()
+            .map(move |(idx, res)| {
+                BACKUP_TABLE_INFO_IDX.set(idx as i64);
+                res
+            }))
+    }
+
+    fn indexer(&self) -> Result<&Indexer> {
+        self.indexer
+            .as_deref()
+            .ok_or_else(|| anyhow!("Indexer not enabled."))
+    }
 }
 
 #[derive(Clone, Copy, Debug, Serialize, Deserialize)]
@@ -13,6 +13,7 @@ const VERSION_SIZE: usize = std::mem::size_of::<Version>();
 pub(super) fn ledger_db_column_families() -> Vec<ColumnFamilyName> {
     vec![
         /* empty cf */ DEFAULT_COLUMN_FAMILY_NAME,
+        AFFECTED_ACCOUNTS_BY_VERSION_CF_NAME,
         EPOCH_BY_VERSION_CF_NAME,
         EVENT_ACCUMULATOR_CF_NAME,
         EVENT_BY_KEY_CF_NAME,
@@ -21,10 +22,12 @@ pub(super) fn ledger_db_column_families() -> Vec<ColumnFamilyName> {
         LEDGER_COUNTERS_CF_NAME,
         LEDGER_INFO_CF_NAME,
         STALE_NODE_INDEX_CF_NAME,
+        STATE_KEY_HISTORY_CF_NAME,
         STATE_VALUE_CF_NAME,
         TRANSACTION_CF_NAME,
         TRANSACTION_ACCUMULATOR_CF_NAME,
         TRANSACTION_BY_ACCOUNT_CF_NAME,
+        TRANSACTION_BY_AFFECTED_ACCOUNT_CF_NAME,
         TRANSACTION_BY_HASH_CF_NAME,
         TRANSACTION_INFO_CF_NAME,
         WRITE_SET_CF_NAME,
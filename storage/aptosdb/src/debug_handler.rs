@@ -0,0 +1,235 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    db_options::{
+        gen_ledger_cfds, gen_state_merkle_cfds, ledger_db_column_families,
+        state_merkle_db_column_families,
+    },
+    event_store::EventStore,
+    ledger_store::LedgerStore,
+    schema::{
+        event::EventSchema, jellyfish_merkle_node::JellyfishMerkleNodeSchema,
+        ledger_info::LedgerInfoSchema, stale_node_index::StaleNodeIndexSchema,
+        transaction::TransactionSchema, transaction_info::TransactionInfoSchema,
+        write_set::WriteSetSchema,
+    },
+    state_merkle_db::{Node, StateMerkleDb},
+    transaction_store::TransactionStore,
+    LEDGER_DB_NAME, STATE_MERKLE_DB_NAME,
+};
+use anyhow::Result;
+use aptos_config::config::RocksdbConfigs;
+use aptos_crypto::HashValue;
+use aptos_jellyfish_merkle::{node_type::NodeKey, StaleNodeIndex};
+use aptos_types::{
+    contract_event::ContractEvent,
+    ledger_info::LedgerInfoWithSignatures,
+    proof::TransactionAccumulatorProof,
+    transaction::{Transaction, TransactionInfo, Version},
+    write_set::WriteSet,
+};
+use schemadb::{
+    db_options::gen_rocksdb_options,
+    schema::{Schema, SeekKeyCodec},
+    ReadOptions, SchemaIterator, DB,
+};
+use std::{path::Path, sync::Arc};
+
+/// `DebugHandler` gives offline inspection tools direct access to the ledger DB and the state
+/// merkle DB of a DB directory, without the pruners and the in-memory state `AptosDB::open` sets
+/// up, so that a DB that fails to open as usual can still be looked into.
+pub struct DebugHandler {
+    ledger_db: Arc<DB>,
+    ledger_store: LedgerStore,
+    transaction_store: TransactionStore,
+    event_store: EventStore,
+    state_merkle_db: StateMerkleDb,
+}
+
+impl DebugHandler {
+    pub fn open(db_root_path: impl AsRef<Path>, readonly: bool) -> Result<Self> {
+        let rocksdb_configs = RocksdbConfigs::default();
+        let ledger_db_path = db_root_path.as_ref().join(LEDGER_DB_NAME);
+        let state_merkle_db_path = db_root_path.as_ref().join(STATE_MERKLE_DB_NAME);
+        let (ledger_db, state_merkle_db) = if readonly {
+            (
+                DB::open_cf_readonly(
+                    &gen_rocksdb_options(&rocksdb_configs.ledger_db_config, true),
+                    ledger_db_path,
+                    "ledger_db_ro",
+                    ledger_db_column_families(),
+                )?,
+                DB::open_cf_readonly(
+                    &gen_rocksdb_options(&rocksdb_configs.state_merkle_db_config, true),
+                    state_merkle_db_path,
+                    "state_merkle_db_ro",
+                    state_merkle_db_column_families(),
+                )?,
+            )
+        } else {
+            (
+                DB::open_cf(
+                    &gen_rocksdb_options(&rocksdb_configs.ledger_db_config, false),
+                    ledger_db_path,
+                    "ledger_db",
+                    gen_ledger_cfds(),
+                )?,
+                DB::open_cf(
+                    &gen_rocksdb_options(&rocksdb_configs.state_merkle_db_config, false),
+                    state_merkle_db_path,
+                    "state_merkle_db",
+                    gen_state_merkle_cfds(),
+                )?,
+            )
+        };
+
+        let ledger_db = Arc::new(ledger_db);
+        Ok(Self {
+            ledger_store: LedgerStore::new(Arc::clone(&ledger_db)),
+            transaction_store: TransactionStore::new(Arc::clone(&ledger_db)),
+            event_store: EventStore::new(Arc::clone(&ledger_db)),
+            state_merkle_db: StateMerkleDb::new(Arc::new(state_merkle_db)),
+            ledger_db,
+        })
+    }
+
+    pub fn get_latest_ledger_info_option(&self) -> Option<LedgerInfoWithSignatures> {
+        self.ledger_store.get_latest_ledger_info_option()
+    }
+
+    pub fn get_latest_transaction_info_option(&self) -> Result<Option<(Version, TransactionInfo)>> {
+        self.ledger_store.get_latest_transaction_info_option()
+    }
+
+    pub fn get_transaction_info(&self, version: Version) -> Result<TransactionInfo> {
+        self.ledger_store.get_transaction_info(version)
+    }
+
+    /// Gets the root hash of the transaction accumulator at `version`.
+    pub fn get_accumulator_root_hash(&self, version: Version) -> Result<HashValue> {
+        self.ledger_store.get_root_hash(version)
+    }
+
+    /// Gets the proof of the transaction info at `version` towards the accumulator root at
+    /// `ledger_version`.
+    pub fn get_transaction_proof(
+        &self,
+        version: Version,
+        ledger_version: Version,
+    ) -> Result<TransactionAccumulatorProof> {
+        self.ledger_store
+            .get_transaction_proof(version, ledger_version)
+    }
+
+    pub fn get_transaction(&self, version: Version) -> Result<Transaction> {
+        self.transaction_store.get_transaction(version)
+    }
+
+    pub fn get_write_set(&self, version: Version) -> Result<WriteSet> {
+        self.transaction_store.get_write_set(version)
+    }
+
+    pub fn get_events_by_version(&self, version: Version) -> Result<Vec<ContractEvent>> {
+        self.event_store.get_events_by_version(version)
+    }
+
+    /// Gets the version of the latest state snapshot before `next_version`.
+    pub fn get_state_snapshot_version_before(
+        &self,
+        next_version: Version,
+    ) -> Result<Option<Version>> {
+        self.state_merkle_db
+            .get_state_snapshot_version_before(next_version)
+    }
+
+    /// Gets the root hash of the state tree at `version`.
+    pub fn get_state_root_hash(&self, version: Version) -> Result<HashValue> {
+        self.state_merkle_db.get_root_hash(version)
+    }
+
+    /// Gets an iterator over the transactions from `start_version` on.
+    pub fn get_transaction_iter(
+        &self,
+        start_version: Version,
+    ) -> Result<impl Iterator<Item = Result<(Version, Transaction)>> + '_> {
+        iter_from::<TransactionSchema, _>(&self.ledger_db, &start_version)
+    }
+
+    /// Gets an iterator over the transaction infos from `start_version` on.
+    pub fn get_transaction_info_iter(
+        &self,
+        start_version: Version,
+    ) -> Result<impl Iterator<Item = Result<(Version, TransactionInfo)>> + '_> {
+        iter_from::<TransactionInfoSchema, _>(&self.ledger_db, &start_version)
+    }
+
+    /// Gets an iterator over the events from `start_version` on, keyed by version and index.
+    pub fn get_event_iter(
+        &self,
+        start_version: Version,
+    ) -> Result<impl Iterator<Item = Result<((Version, u64), ContractEvent)>> + '_> {
+        iter_from::<EventSchema, _>(&self.ledger_db, &start_version)
+    }
+
+    /// Gets an iterator over the write sets from `start_version` on.
+    pub fn get_write_set_iter(
+        &self,
+        start_version: Version,
+    ) -> Result<impl Iterator<Item = Result<(Version, WriteSet)>> + '_> {
+        iter_from::<WriteSetSchema, _>(&self.ledger_db, &start_version)
+    }
+
+    /// Gets an iterator over all the ledger infos, by epoch.
+    pub fn get_ledger_info_iter(
+        &self,
+    ) -> Result<impl Iterator<Item = Result<LedgerInfoWithSignatures>> + '_> {
+        Ok(iter_from::<LedgerInfoSchema, _>(&self.ledger_db, &0u64)?
+            .map(|res| res.map(|(_epoch, ledger_info)| ledger_info)))
+    }
+
+    /// Gets an iterator over the stale node index from `start_version` on, by the version the
+    /// nodes became stale at.
+    pub fn get_stale_node_index_iter(
+        &self,
+        start_version: Version,
+    ) -> Result<impl Iterator<Item = Result<StaleNodeIndex>> + '_> {
+        Ok(
+            iter_from::<StaleNodeIndexSchema, _>(&self.state_merkle_db, &start_version)?
+                .map(|res| res.map(|(index, ())| index)),
+        )
+    }
+
+    /// Gets an iterator over the state tree nodes written from `start_version` on.
+    pub fn get_jmt_node_iter(
+        &self,
+        start_version: Version,
+    ) -> Result<impl Iterator<Item = Result<(NodeKey, Node)>> + '_> {
+        iter_from::<JellyfishMerkleNodeSchema, _>(&self.state_merkle_db, &(start_version, 0u8))
+    }
+
+    pub fn get_jmt_node(&self, node_key: &NodeKey) -> Result<Option<Node>> {
+        self.state_merkle_db
+            .get::<JellyfishMerkleNodeSchema>(node_key)
+    }
+
+    #[cfg(any(test, feature = "fuzzing"))]
+    pub fn put_jmt_node(&self, node_key: &NodeKey, node: &Node) -> Result<()> {
+        self.state_merkle_db
+            .put::<JellyfishMerkleNodeSchema>(node_key, node)
+    }
+
+    #[cfg(any(test, feature = "fuzzing"))]
+    pub fn put_stale_node_index(&self, index: &StaleNodeIndex) -> Result<()> {
+        self.state_merkle_db.put::<StaleNodeIndexSchema>(index, &())
+    }
+}
+
+fn iter_from<'a, S: Schema, K: SeekKeyCodec<S>>(
+    db: &'a DB,
+    seek_key: &K,
+) -> Result<SchemaIterator<'a, S>> {
+    let mut iter = db.iter::<S>(ReadOptions::default())?;
+    iter.seek(seek_key)?;
+    Ok(iter)
+}
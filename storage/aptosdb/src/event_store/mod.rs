@@ -130,6 +130,17 @@ impl EventStore {
         ))
     }
 
+    /// Get the first sequence number on `event_key` that's not pruned, if any.
+    fn get_first_sequence_number(&self, event_key: &EventKey) -> Result<Option<u64>> {
+        let mut iter = self.db.iter::<EventByKeySchema>(ReadOptions::default())?;
+        iter.seek(&(*event_key, 0))?;
+
+        Ok(iter
+            .next()
+            .transpose()?
+            .and_then(|((key, seq), _)| if &key == event_key { Some(seq) } else { None }))
+    }
+
     /// Get the next sequence number for specified event key.
     /// Returns 0 if there's no events already in the event stream.
     pub fn get_next_sequence_number(
@@ -272,7 +283,12 @@ impl EventStore {
     where
         C: FnMut(&ContractEvent) -> Result<bool>,
     {
-        let mut begin = 0u64;
+        // Events before the first one left are pruned, and assumed to be all in the first
+        // partition.
+        let mut begin = match self.get_first_sequence_number(event_key)? {
+            Some(s) => s,
+            None => return Ok(None),
+        };
         let mut end = match self.get_latest_sequence_number(ledger_version, event_key)? {
             Some(s) => s
                 .checked_add(1)
@@ -312,6 +328,17 @@ impl EventStore {
         timestamp: u64,
         ledger_version: Version,
     ) -> Result<Version> {
+        self.find_last_version_before_timestamp(timestamp, ledger_version)?
+            .ok_or_else(|| format_err!("First block started at or after timestamp {}.", timestamp))
+    }
+
+    /// Like `get_last_version_before_timestamp`, but returns `None` rather than failing when no
+    /// block started before `timestamp`.
+    pub(crate) fn find_last_version_before_timestamp(
+        &self,
+        timestamp: u64,
+        ledger_version: Version,
+    ) -> Result<Option<Version>> {
         let event_key = new_block_event_key();
         let seq_at_or_after_ts = self.search_for_event_lower_bound(
             &event_key,
@@ -325,20 +352,35 @@ impl EventStore {
             timestamp,
         ))?;
 
-        ensure!(
-            seq_at_or_after_ts > 0,
-            "First block started at or after timestamp {}.",
-            timestamp,
-        );
+        if seq_at_or_after_ts == 0 {
+            return Ok(None);
+        }
 
         let (version, _idx) =
             self.lookup_event_by_key(&event_key, seq_at_or_after_ts, ledger_version)?;
 
         version
             .checked_sub(1)
+            .map(Some)
             .ok_or_else(|| format_err!("A block with non-zero seq num started at version 0."))
     }
 
+    /// Gets the timestamp of the latest block committed at or before `ledger_version`, if any.
+    pub(crate) fn get_latest_block_timestamp(
+        &self,
+        ledger_version: Version,
+    ) -> Result<Option<u64>> {
+        let event_key = new_block_event_key();
+        match self.get_latest_sequence_number(ledger_version, &event_key)? {
+            Some(seq) => {
+                let event = self.get_event_by_key(&event_key, seq, ledger_version)?;
+                let new_block_event: NewBlockEvent = (&event).try_into()?;
+                Ok(Some(new_block_event.proposed_time()))
+            }
+            None => Ok(None),
+        }
+    }
+
     /// Prunes the events by key store for a set of events
     pub fn prune_events_by_key(
         &self,
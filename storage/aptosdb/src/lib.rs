@@ -14,6 +14,7 @@
 pub mod test_helper;
 
 pub mod backup;
+pub mod debug_handler;
 pub mod errors;
 pub mod metrics;
 pub mod schema;
@@ -108,7 +109,7 @@ use std::{
 };
 
 use crate::pruner::ledger_pruner_manager::LedgerPrunerManager;
-use crate::pruner::ledger_store::ledger_store_pruner::LedgerPruner;
+use crate::pruner::ledger_store::ledger_store_pruner::{LedgerPruner, LedgerSubStore};
 use crate::pruner::state_pruner_manager::StatePrunerManager;
 use crate::pruner::state_store::StateStorePruner;
 use storage_interface::state_view::DbStateView;
@@ -188,6 +189,25 @@ fn error_if_version_is_pruned(
     Ok(())
 }
 
+/// Like `error_if_version_is_pruned`, for one of the stores pruned by the ledger pruner, which can
+/// be pruned to different versions.
+fn error_if_ledger_version_is_pruned(
+    ledger_pruner: &LedgerPrunerManager,
+    store: LedgerSubStore,
+    data_type: &str,
+    version: Version,
+) -> Result<()> {
+    let min_readable_version = ledger_pruner.get_min_readable_version_of(store);
+    ensure!(
+        version >= min_readable_version,
+        "{} version {} is pruned, min available version is {}.",
+        data_type,
+        version,
+        min_readable_version
+    );
+    Ok(())
+}
+
 fn update_rocksdb_properties(ledger_rocksdb: &DB, state_merkle_rocksdb: &DB) -> Result<()> {
     let _timer = OTHER_TIMERS_SECONDS
         .with_label_values(&["update_rocksdb_properties"])
@@ -268,7 +288,9 @@ pub struct AptosDB {
     ledger_pruner: LedgerPrunerManager,
     _rocksdb_property_reporter: RocksdbPropertyReporter,
     ledger_commit_lock: std::sync::Mutex<()>,
-    indexer: Option<Indexer>,
+    indexer: Option<Arc<Indexer>>,
+    // Whether transactions are indexed by every account they affect, not just by their sender.
+    enable_affected_account_index: bool,
 }
 
 impl AptosDB {
@@ -278,6 +300,8 @@ impl AptosDB {
         storage_pruner_config: StoragePrunerConfig,
         target_snapshot_size: usize,
         hack_for_tests: bool,
+        enable_state_key_history: bool,
+        enable_affected_account_index: bool,
     ) -> Self {
         let arc_ledger_rocksdb = Arc::new(ledger_rocksdb);
         let arc_state_merkle_rocksdb = Arc::new(state_merkle_rocksdb);
@@ -296,6 +320,7 @@ impl AptosDB {
                 Arc::clone(&arc_state_merkle_rocksdb),
                 target_snapshot_size,
                 hack_for_tests,
+                enable_state_key_history,
             )),
             system_store: Arc::new(SystemStore::new(Arc::clone(&arc_ledger_rocksdb))),
             transaction_store: Arc::new(TransactionStore::new(Arc::clone(&arc_ledger_rocksdb))),
@@ -307,6 +332,7 @@ impl AptosDB {
             ),
             ledger_commit_lock: std::sync::Mutex::new(()),
             indexer: None,
+            enable_affected_account_index,
         }
     }
 
@@ -316,6 +342,8 @@ impl AptosDB {
         storage_pruner_config: StoragePrunerConfig,
         rocksdb_configs: RocksdbConfigs,
         enable_indexer: bool,
+        enable_state_key_history: bool,
+        enable_affected_account_index: bool,
         target_snapshot_size: usize,
     ) -> Result<Self> {
         ensure!(
@@ -365,6 +393,8 @@ impl AptosDB {
             storage_pruner_config,
             target_snapshot_size,
             readonly,
+            enable_state_key_history,
+            enable_affected_account_index,
         );
 
         if !readonly && enable_indexer {
@@ -417,7 +447,7 @@ impl AptosDB {
         }
         info!("AptosDB Indexer caught up.");
 
-        self.indexer = Some(indexer);
+        self.indexer = Some(Arc::new(indexer));
         Ok(())
     }
 
@@ -454,6 +484,8 @@ impl AptosDB {
             NO_OP_STORAGE_PRUNER_CONFIG,
             TARGET_SNAPSHOT_SIZE,
             true,
+            false,
+            false,
         ))
     }
 
@@ -463,6 +495,8 @@ impl AptosDB {
         readonly: bool,
         target_snapshot_size: usize,
         enable_indexer: bool,
+        enable_state_key_history: bool,
+        enable_affected_account_index: bool,
     ) -> Self {
         Self::open(
             db_root_path,
@@ -470,6 +504,8 @@ impl AptosDB {
             NO_OP_STORAGE_PRUNER_CONFIG, /* pruner */
             RocksdbConfigs::default(),
             enable_indexer,
+            enable_state_key_history,
+            enable_affected_account_index,
             target_snapshot_size,
         )
         .expect("Unable to open AptosDB")
@@ -478,13 +514,55 @@ impl AptosDB {
     /// This opens db in non-readonly mode, without the pruner.
     #[cfg(any(test, feature = "fuzzing"))]
     pub fn new_for_test<P: AsRef<Path> + Clone>(db_root_path: P) -> Self {
-        Self::new_without_pruner(db_root_path, false, TARGET_SNAPSHOT_SIZE, false)
+        Self::new_without_pruner(
+            db_root_path,
+            false,
+            TARGET_SNAPSHOT_SIZE,
+            false,
+            false,
+            false,
+        )
     }
 
     /// This opens db in non-readonly mode, without the pruner, and with the indexer
     #[cfg(any(test, feature = "fuzzing"))]
     pub fn new_for_test_with_indexer<P: AsRef<Path> + Clone>(db_root_path: P) -> Self {
-        Self::new_without_pruner(db_root_path, false, TARGET_SNAPSHOT_SIZE, true)
+        Self::new_without_pruner(
+            db_root_path,
+            false,
+            TARGET_SNAPSHOT_SIZE,
+            true,
+            false,
+            false,
+        )
+    }
+
+    /// This opens db in non-readonly mode, without the pruner, and with the state key history
+    #[cfg(any(test, feature = "fuzzing"))]
+    pub fn new_for_test_with_state_key_history<P: AsRef<Path> + Clone>(db_root_path: P) -> Self {
+        Self::new_without_pruner(
+            db_root_path,
+            false,
+            TARGET_SNAPSHOT_SIZE,
+            false,
+            true,
+            false,
+        )
+    }
+
+    /// This opens db in non-readonly mode, without the pruner, and with the affected account index
+    #[cfg(any(test, feature = "fuzzing"))]
+    pub fn new_for_test_with_affected_account_index<P: AsRef<Path> + Clone>(
+        db_root_path: P,
+    ) -> Self {
+        Self::new_without_pruner(
+            db_root_path,
+            false,
+            TARGET_SNAPSHOT_SIZE,
+            false,
+            false,
+            true,
+        )
     }
 
     /// This opens db in non-readonly mode, without the pruner.
@@ -493,13 +571,27 @@ impl AptosDB {
         db_root_path: P,
         target_snapshot_size: usize,
     ) -> Self {
-        Self::new_without_pruner(db_root_path, false, target_snapshot_size, false)
+        Self::new_without_pruner(
+            db_root_path,
+            false,
+            target_snapshot_size,
+            false,
+            false,
+            false,
+        )
     }
 
     /// This opens db in non-readonly mode, without the pruner.
     #[cfg(any(test, feature = "fuzzing"))]
     pub fn new_readonly_for_test<P: AsRef<Path> + Clone>(db_root_path: P) -> Self {
-        Self::new_without_pruner(db_root_path, true, TARGET_SNAPSHOT_SIZE, false)
+        Self::new_without_pruner(
+            db_root_path,
+            true,
+            TARGET_SNAPSHOT_SIZE,
+            false,
+            false,
+            false,
+        )
     }
 
     /// This gets the current buffered_state in StateStore.
@@ -575,6 +667,31 @@ impl AptosDB {
         Ok((lis, more))
     }
 
+    /// Errors if the transactions from `version` on, with proofs and optionally events, are pruned.
+    fn error_if_transactions_are_pruned(&self, version: Version, fetch_events: bool) -> Result<()> {
+        error_if_ledger_version_is_pruned(
+            &self.ledger_pruner,
+            LedgerSubStore::Transaction,
+            "Transaction",
+            version,
+        )?;
+        error_if_ledger_version_is_pruned(
+            &self.ledger_pruner,
+            LedgerSubStore::TransactionInfo,
+            "Transaction info",
+            version,
+        )?;
+        if fetch_events {
+            error_if_ledger_version_is_pruned(
+                &self.ledger_pruner,
+                LedgerSubStore::Event,
+                "Event",
+                version,
+            )?;
+        }
+        Ok(())
+    }
+
     /// Returns the transaction with proof for a given version, or error if the transaction is not
     /// found.
     fn get_transaction_with_proof(
@@ -583,7 +700,7 @@ impl AptosDB {
         ledger_version: Version,
         fetch_events: bool,
     ) -> Result<TransactionWithProof> {
-        error_if_version_is_pruned(&self.ledger_pruner, "Transaction", version)?;
+        self.error_if_transactions_are_pruned(version, fetch_events)?;
 
         let proof = self
             .ledger_store
@@ -614,23 +731,34 @@ impl AptosDB {
             Arc::clone(&self.transaction_store),
             Arc::clone(&self.state_store),
             Arc::clone(&self.event_store),
+            self.indexer.clone(),
         )
     }
 
-    /// Creates new physical DB checkpoint in directory specified by `path`.
-    pub fn create_checkpoint<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+    /// Creates new physical DB checkpoint in directory specified by `path`, which can be opened as
+    /// an `AptosDB` itself. The indexer DB is included when it's enabled. Commits are held off
+    /// meanwhile so that the ledger DB and the indexer DB are checkpointed at the same version.
+    ///
+    /// Returns the latest version in the checkpoint.
+    pub fn create_checkpoint<P: AsRef<Path>>(&self, path: P) -> Result<Option<Version>> {
+        let _lock = self.ledger_commit_lock.lock();
         let start = Instant::now();
         let ledger_db_path = path.as_ref().join(LEDGER_DB_NAME);
         let state_merkle_db_path = path.as_ref().join(STATE_MERKLE_DB_NAME);
         self.ledger_db.create_checkpoint(&ledger_db_path)?;
         self.state_merkle_db
             .create_checkpoint(&state_merkle_db_path)?;
+        if let Some(indexer) = &self.indexer {
+            indexer.create_checkpoint(&path)?;
+        }
+        let latest_version = self.get_latest_version_option()?;
         info!(
             path = path.as_ref(),
+            latest_version = latest_version,
             time_ms = %start.elapsed().as_millis(),
             "Made AptosDB checkpoint."
         );
-        Ok(())
+        Ok(latest_version)
     }
 
     // ================================== Private APIs ==================================
@@ -767,6 +895,15 @@ impl AptosDB {
                     // Transaction updates. Gather transaction hashes.
                     self.transaction_store
                         .put_transaction(ver, txn_to_commit.transaction(), cs)?;
+                    if self.enable_affected_account_index {
+                        self.transaction_store.put_affected_accounts(
+                            ver,
+                            txn_to_commit.transaction(),
+                            txn_to_commit.write_set(),
+                            txn_to_commit.events(),
+                            cs,
+                        )?;
+                    }
                     self.transaction_store
                         .put_write_set(ver, txn_to_commit.write_set(), cs)
                 },
@@ -893,6 +1030,41 @@ impl DbReader for AptosDB {
         })
     }
 
+    fn get_affected_account_transactions(
+        &self,
+        address: AccountAddress,
+        start_version: Version,
+        limit: u64,
+        include_events: bool,
+        ledger_version: Version,
+    ) -> Result<Vec<TransactionWithProof>> {
+        gauged_api("get_affected_account_transactions", || {
+            ensure!(
+                self.enable_affected_account_index,
+                "Affected account index not enabled."
+            );
+            error_if_too_many_requested(limit, MAX_LIMIT)?;
+
+            self.transaction_store
+                .get_affected_account_transaction_versions(
+                    address,
+                    start_version,
+                    limit,
+                    ledger_version,
+                )?
+                .into_iter()
+                .map(|txn_version| {
+                    self.get_transaction_with_proof(txn_version, ledger_version, include_events)
+                })
+                .collect()
+        })
+    }
+
+    /// Returns whether the affected account index has been enabled or not
+    fn affected_account_index_enabled(&self) -> bool {
+        self.enable_affected_account_index
+    }
+
     /// This API is best-effort in that it CANNOT provide absense proof.
     fn get_transaction_by_hash(
         &self,
@@ -941,7 +1113,7 @@ impl DbReader for AptosDB {
             if start_version > ledger_version || limit == 0 {
                 return Ok(TransactionListWithProof::new_empty());
             }
-            error_if_version_is_pruned(&self.ledger_pruner, "Transaction", start_version)?;
+            self.error_if_transactions_are_pruned(start_version, fetch_events)?;
 
             let limit = std::cmp::min(limit, ledger_version - start_version + 1);
 
@@ -1059,7 +1231,12 @@ impl DbReader for AptosDB {
         end_version: Version,
     ) -> Result<Vec<WriteSet>> {
         gauged_api("get_write_sets", || {
-            error_if_version_is_pruned(&self.ledger_pruner, "Write set", begin_version)?;
+            error_if_ledger_version_is_pruned(
+                &self.ledger_pruner,
+                LedgerSubStore::WriteSet,
+                "Write set",
+                begin_version,
+            )?;
 
             self.transaction_store
                 .get_write_sets(begin_version, end_version)
@@ -1291,6 +1468,18 @@ impl DbReader for AptosDB {
         })
     }
 
+    fn get_min_readable_state_version(&self) -> Result<Version> {
+        gauged_api("get_min_readable_state_version", || {
+            Ok(self.state_pruner.get_min_readable_version())
+        })
+    }
+
+    fn get_min_readable_ledger_version(&self) -> Result<Version> {
+        gauged_api("get_min_readable_ledger_version", || {
+            Ok(self.ledger_pruner.get_min_readable_version())
+        })
+    }
+
     fn get_table_info(&self, handle: TableHandle) -> Result<TableInfo> {
         gauged_api("get_table_info", || {
             self.get_table_info_option(handle)?
@@ -1302,6 +1491,30 @@ impl DbReader for AptosDB {
     fn indexer_enabled(&self) -> bool {
         self.indexer.is_some()
     }
+
+    fn get_state_value_history(
+        &self,
+        state_key: &StateKey,
+        start_version: Version,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<Vec<(Version, StateValue)>> {
+        gauged_api("get_state_value_history", || {
+            error_if_too_many_requested(limit, MAX_LIMIT)?;
+
+            self.state_store.get_state_value_history(
+                state_key,
+                start_version,
+                limit as usize,
+                ledger_version,
+            )
+        })
+    }
+
+    /// Returns whether the state key history has been enabled or not
+    fn state_key_history_enabled(&self) -> bool {
+        self.state_store.state_key_history_enabled()
+    }
 }
 
 impl DbWriter for AptosDB {
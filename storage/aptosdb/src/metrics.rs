@@ -170,3 +170,11 @@ pub(crate) static BACKUP_STATE_SNAPSHOT_LEAF_IDX: Lazy<IntGauge> = Lazy::new(||
     )
     .unwrap()
 });
+
+pub(crate) static BACKUP_TABLE_INFO_IDX: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_backup_handler_table_info_index",
+        "Index of current table info returned in an indexer backup."
+    )
+    .unwrap()
+});
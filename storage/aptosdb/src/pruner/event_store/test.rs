@@ -1,17 +1,27 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{AptosDB, ChangeSet, EventStore, LedgerPrunerManager, PrunerManager};
-use aptos_config::config::StoragePrunerConfig;
+use crate::{
+    pruner::{ledger_store::ledger_store_pruner::LedgerSubStore, utils::create_ledger_pruner},
+    AptosDB, ChangeSet, EventStore, LedgerPrunerManager, PrunerManager,
+};
+use aptos_config::config::{LedgerStorePruneWindows, PruneWindow, StoragePrunerConfig};
 use aptos_proptest_helpers::Index;
 use aptos_temppath::TempPath;
 use aptos_types::transaction::Version;
 use aptos_types::{
+    account_address::AccountAddress,
+    account_config::{new_block_event_key, NewBlockEvent},
     contract_event::ContractEvent,
     proptest_types::{AccountInfoUniverse, ContractEventGen},
 };
+use move_deps::move_core_types::{language_storage::TypeTag, move_resource::MoveStructType};
 use proptest::{collection::vec, prelude::*, proptest};
-use std::sync::Arc;
+use std::{
+    sync::Arc,
+    thread::sleep,
+    time::{Duration, Instant},
+};
 
 proptest! {
     #![proptest_config(ProptestConfig::with_cases(10))]
@@ -50,6 +60,160 @@ proptest! {
         verify_event_store_pruner_disabled(event_batches);
     }
 
+    #[test]
+    fn test_event_store_pruner_own_window(
+        mut universe in any_with::<AccountInfoUniverse>(3),
+        gen_batches in vec(vec((any::<Index>(), any::<ContractEventGen>()), 0..=2), 0..100),
+    ) {
+        let event_batches = gen_batches
+            .into_iter()
+            .map(|gens| {
+                gens.into_iter()
+                    .map(|(index, gen)| gen.materialize(*index, &mut universe))
+                    .collect()
+            })
+            .collect();
+
+        verify_event_store_pruner_own_window(event_batches, 4);
+    }
+
+}
+
+/// Events of 10 versions, with a block starting every 3 versions, a second after the previous one.
+fn events_with_blocks() -> Vec<Vec<ContractEvent>> {
+    (0..10u64)
+        .map(|version| {
+            if version % 3 != 0 {
+                return vec![];
+            }
+            let seq = version / 3;
+            let new_block_event = NewBlockEvent::new(
+                0,                     // epoch
+                seq,                   // round
+                seq,                   // height
+                vec![],                // prev block voters
+                AccountAddress::ZERO,  // proposer
+                Vec::new(),            // failed_proposers
+                (seq + 1) * 1_000_000, // timestamp
+            );
+            vec![ContractEvent::new(
+                new_block_event_key(),
+                seq,
+                TypeTag::Struct(NewBlockEvent::struct_tag()),
+                bcs::to_bytes(&new_block_event).unwrap(),
+            )]
+        })
+        .collect()
+}
+
+fn put_events(aptos_db: &AptosDB, events: &[Vec<ContractEvent>]) {
+    let mut cs = ChangeSet::new();
+    for (version, events_for_version) in events.iter().enumerate() {
+        aptos_db
+            .event_store
+            .put_events(version as u64, events_for_version, &mut cs)
+            .unwrap();
+    }
+    aptos_db.ledger_db.write_schemas(cs.batch).unwrap();
+}
+
+#[test]
+fn test_resolve_prune_window_in_seconds() {
+    let tmp_dir = TempPath::new();
+    let aptos_db = AptosDB::new_for_test(&tmp_dir);
+    put_events(&aptos_db, &events_with_blocks());
+    let pruner = create_ledger_pruner(Arc::clone(&aptos_db.ledger_db));
+
+    // The latest block started at 4s, so a window of 1s keeps the blocks started from 3s on, which
+    // is the block at version 6.
+    assert_eq!(
+        pruner
+            .resolve_target_version(PruneWindow::Seconds(1), 9)
+            .unwrap(),
+        Some(6)
+    );
+    // As of version 8, the latest block started at 3s
+    assert_eq!(
+        pruner
+            .resolve_target_version(PruneWindow::Seconds(1), 8)
+            .unwrap(),
+        Some(3)
+    );
+    // Every block is younger than the window
+    assert_eq!(
+        pruner
+            .resolve_target_version(PruneWindow::Seconds(3), 9)
+            .unwrap(),
+        None
+    );
+    // The window goes back before the epoch
+    assert_eq!(
+        pruner
+            .resolve_target_version(PruneWindow::Seconds(10), 9)
+            .unwrap(),
+        None
+    );
+}
+
+#[test]
+fn test_resolve_prune_window_in_seconds_error() {
+    let tmp_dir = TempPath::new();
+    let aptos_db = AptosDB::new_for_test(&tmp_dir);
+    let mut events = events_with_blocks();
+    // A block event that doesn't deserialize
+    events[6] = vec![ContractEvent::new(
+        new_block_event_key(),
+        2,
+        TypeTag::Struct(NewBlockEvent::struct_tag()),
+        vec![],
+    )];
+    put_events(&aptos_db, &events);
+    let pruner = create_ledger_pruner(Arc::clone(&aptos_db.ledger_db));
+
+    assert!(pruner
+        .resolve_target_version(PruneWindow::Seconds(1), 9)
+        .is_err());
+}
+
+#[test]
+fn test_event_store_pruner_window_in_seconds() {
+    let tmp_dir = TempPath::new();
+    let aptos_db = AptosDB::new_for_test(&tmp_dir);
+    let event_store = &aptos_db.event_store;
+    let events = events_with_blocks();
+    put_events(&aptos_db, &events);
+    let pruner = LedgerPrunerManager::new(
+        Arc::clone(&aptos_db.ledger_db),
+        StoragePrunerConfig {
+            state_store_prune_window: Some(0),
+            ledger_prune_window: Some(0),
+            ledger_store_prune_windows: LedgerStorePruneWindows {
+                event: Some(PruneWindow::Seconds(1)),
+                ..LedgerStorePruneWindows::default()
+            },
+            ledger_pruning_batch_size: 1,
+            state_store_pruning_batch_size: 100,
+        },
+    );
+
+    pruner.wake_and_wait_pruner(9 /* latest_version */).unwrap();
+    // Windows in seconds aren't waited for
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while pruner.get_min_readable_version_of(LedgerSubStore::Event) < 6 {
+        assert!(
+            Instant::now() < deadline,
+            "Timeout waiting for pruner worker."
+        );
+        sleep(Duration::from_millis(1));
+    }
+
+    assert_eq!(pruner.get_min_readable_version_of(LedgerSubStore::Event), 6);
+    for version in 0..6 {
+        verify_events_not_in_store(version, event_store);
+    }
+    for version in 6..10 {
+        verify_events_in_store(&events, version, event_store);
+    }
 }
 
 fn verify_event_store_pruner(events: Vec<Vec<ContractEvent>>) {
@@ -63,6 +227,7 @@ fn verify_event_store_pruner(events: Vec<Vec<ContractEvent>>) {
         StoragePrunerConfig {
             state_store_prune_window: Some(0),
             ledger_prune_window: Some(0),
+            ledger_store_prune_windows: LedgerStorePruneWindows::default(),
             ledger_pruning_batch_size: 1,
             state_store_pruning_batch_size: 100,
         },
@@ -96,6 +261,57 @@ fn verify_event_store_pruner(events: Vec<Vec<ContractEvent>>) {
     }
 }
 
+fn verify_event_store_pruner_own_window(events: Vec<Vec<ContractEvent>>, event_window: u64) {
+    let tmp_dir = TempPath::new();
+    let aptos_db = AptosDB::new_for_test(&tmp_dir);
+    let event_store = &aptos_db.event_store;
+    let mut cs = ChangeSet::new();
+    let num_versions = events.len();
+    let pruner = LedgerPrunerManager::new(
+        Arc::clone(&aptos_db.ledger_db),
+        StoragePrunerConfig {
+            state_store_prune_window: Some(0),
+            ledger_prune_window: Some(0),
+            ledger_store_prune_windows: LedgerStorePruneWindows {
+                event: Some(PruneWindow::Versions(event_window)),
+                ..LedgerStorePruneWindows::default()
+            },
+            ledger_pruning_batch_size: 1,
+            state_store_pruning_batch_size: 100,
+        },
+    );
+
+    // Write events to DB
+    for (version, events_for_version) in events.iter().enumerate() {
+        event_store
+            .put_events(version as u64, events_for_version, &mut cs)
+            .unwrap();
+    }
+    aptos_db.ledger_db.write_schemas(cs.batch).unwrap();
+
+    // The other stores are pruned up to the latest version, while events are kept for longer.
+    for i in (0..=num_versions).step_by(2) {
+        pruner
+            .wake_and_wait_pruner(i as u64 /* latest_version */)
+            .unwrap();
+        assert_eq!(
+            pruner.get_min_readable_version_of(LedgerSubStore::Transaction),
+            i as u64
+        );
+        // Overall, ledger data is only readable from the store pruned the furthest.
+        assert_eq!(pruner.get_min_readable_version(), i as u64);
+        let min_readable_event_version = i.saturating_sub(event_window as usize);
+        for j in 0..min_readable_event_version {
+            verify_events_not_in_store(j as u64, event_store);
+            verify_event_by_key_not_in_store(&events, j as u64, event_store);
+        }
+        for j in min_readable_event_version..num_versions {
+            verify_events_in_store(&events, j as u64, event_store);
+            verify_event_by_key_in_store(&events, j as u64, event_store);
+        }
+    }
+}
+
 fn verify_event_store_pruner_disabled(events: Vec<Vec<ContractEvent>>) {
     let tmp_dir = TempPath::new();
     let aptos_db = AptosDB::new_for_test(&tmp_dir);
@@ -3,13 +3,12 @@
 
 use crate::metrics::{PRUNER_BATCH_SIZE, PRUNER_WINDOW};
 
-use aptos_config::config::StoragePrunerConfig;
+use aptos_config::config::{PruneWindow, StoragePrunerConfig};
 use aptos_infallible::Mutex;
 
-use crate::pruner::db_pruner;
 use crate::pruner::db_pruner::DBPruner;
-use crate::pruner::ledger_pruner_worker::LedgerPrunerWorker;
-use crate::pruner::ledger_store::ledger_store_pruner::LedgerPruner;
+use crate::pruner::ledger_pruner_worker::{Command, LedgerPrunerWorker};
+use crate::pruner::ledger_store::ledger_store_pruner::{LedgerPruner, LedgerSubStore};
 use crate::pruner::pruner_manager::PrunerManager;
 use crate::utils;
 use aptos_types::transaction::Version;
@@ -26,8 +25,10 @@ use std::{
 #[derive(Debug)]
 pub struct LedgerPrunerManager {
     /// DB version window, which dictates how many version of other stores like transaction, ledger
-    /// info, events etc to keep.
+    /// info, events etc to keep, unless they have their own window in `prune_windows`.
     prune_window: Option<Version>,
+    /// The stores that are pruned, with their prune windows.
+    prune_windows: Vec<(LedgerSubStore, PruneWindow)>,
     /// Ledger pruner. Is always initialized regardless if the pruner is enabled to keep tracks
     /// of the min_readable_version.
     pruner: Arc<LedgerPruner>,
@@ -37,7 +38,7 @@ pub struct LedgerPrunerManager {
     worker_thread: Option<JoinHandle<()>>,
     /// The sender side of the channel talking to the ledger pruner worker thread. Is `None` when
     /// the ledger pruner is not enabled.
-    command_sender: Option<Mutex<Sender<db_pruner::Command>>>,
+    command_sender: Option<Mutex<Sender<Command>>>,
     /// We send a batch of version to the underlying pruners for performance reason. This tracks the
     /// last version we sent to the pruners. Will only be set if the pruner is enabled.
     pub(crate) last_version_sent_to_pruner: Arc<Mutex<Version>>,
@@ -62,7 +63,7 @@ impl PrunerManager for LedgerPrunerManager {
 
         // Only wake up the ledger pruner if there are `ledger_pruner_pruning_batch_size` pending
         // versions.
-        if self.prune_window.is_some()
+        if !self.prune_windows.is_empty()
             && latest_version
                 >= *self.last_version_sent_to_pruner.as_ref().lock()
                     + self.pruning_batch_size as u64
@@ -72,15 +73,13 @@ impl PrunerManager for LedgerPrunerManager {
         }
     }
     fn wake_pruner(&self, latest_version: Version) {
-        assert!(self.prune_window.is_some());
+        assert!(!self.prune_windows.is_empty());
         assert!(self.command_sender.is_some());
         self.command_sender
             .as_ref()
             .unwrap()
             .lock()
-            .send(db_pruner::Command::Prune {
-                target_db_version: latest_version.saturating_sub(self.prune_window.unwrap()),
-            })
+            .send(Command::Prune { latest_version })
             .expect("Receiver should not destruct prematurely.");
     }
 
@@ -100,20 +99,22 @@ impl PrunerManager for LedgerPrunerManager {
             *self.last_version_sent_to_pruner.as_ref().lock() = latest_version;
         }
 
-        if self.prune_window.is_some() && latest_version > self.prune_window.unwrap() {
-            let min_readable_ledger_version = latest_version - self.prune_window.unwrap_or(0);
-
-            // Assuming no big pruning chunks will be issued by a test.
-            const TIMEOUT: Duration = Duration::from_secs(10);
-            let end = Instant::now() + TIMEOUT;
-
-            while Instant::now() < end {
-                if self.get_min_readable_version() >= min_readable_ledger_version {
-                    return Ok(());
+        // Assuming no big pruning chunks will be issued by a test, and only windows in versions
+        // are used in tests.
+        const TIMEOUT: Duration = Duration::from_secs(10);
+        let end = Instant::now() + TIMEOUT;
+        for (store, prune_window) in &self.prune_windows {
+            if let PruneWindow::Versions(versions) = prune_window {
+                if latest_version > *versions {
+                    let min_readable_version = latest_version - versions;
+                    while self.pruner.min_readable_version_of(*store) < min_readable_version {
+                        if Instant::now() >= end {
+                            anyhow::bail!("Timeout waiting for pruner worker.");
+                        }
+                        sleep(Duration::from_millis(1));
+                    }
                 }
-                sleep(Duration::from_millis(1));
             }
-            anyhow::bail!("Timeout waiting for pruner worker.");
         }
         Ok(())
     }
@@ -134,14 +135,31 @@ impl LedgerPrunerManager {
             .with_label_values(&["ledger_pruner"])
             .set(storage_pruner_config.ledger_pruning_batch_size as i64);
 
+        let windows = &storage_pruner_config.ledger_store_prune_windows;
+        let prune_windows: Vec<_> = LedgerSubStore::ALL
+            .iter()
+            .filter_map(|store| {
+                let store_prune_window = match store {
+                    LedgerSubStore::Transaction => windows.transaction,
+                    LedgerSubStore::TransactionInfo => windows.transaction_info,
+                    LedgerSubStore::Event => windows.event,
+                    LedgerSubStore::WriteSet => windows.write_set,
+                };
+                storage_pruner_config
+                    .ledger_store_prune_window(store_prune_window)
+                    .map(|prune_window| (*store, prune_window))
+            })
+            .collect();
+
         let mut command_sender = None;
-        let ledger_pruner_worker_thread = if storage_pruner_config.ledger_prune_window.is_some() {
+        let ledger_pruner_worker_thread = if !prune_windows.is_empty() {
             let (ledger_pruner_command_sender, ledger_pruner_command_receiver) = channel();
             command_sender = Some(Mutex::new(ledger_pruner_command_sender));
             let ledger_pruner_worker = LedgerPrunerWorker::new(
                 Arc::clone(&ledger_pruner),
                 ledger_pruner_command_receiver,
                 storage_pruner_config,
+                prune_windows.clone(),
             );
             Some(
                 std::thread::Builder::new()
@@ -157,6 +175,7 @@ impl LedgerPrunerManager {
 
         Self {
             prune_window: storage_pruner_config.ledger_prune_window,
+            prune_windows,
             pruner: ledger_pruner,
             worker_thread: ledger_pruner_worker_thread,
             command_sender,
@@ -166,6 +185,11 @@ impl LedgerPrunerManager {
         }
     }
 
+    /// Returns the least readable version of one of the ledger stores.
+    pub fn get_min_readable_version_of(&self, store: LedgerSubStore) -> Version {
+        self.pruner.min_readable_version_of(store)
+    }
+
     #[cfg(test)]
     pub fn testonly_update_min_version(&self, version: Version) {
         self.pruner.testonly_update_min_version(version);
@@ -177,7 +201,7 @@ impl Drop for LedgerPrunerManager {
         if let Some(command_sender) = &self.command_sender {
             command_sender
                 .lock()
-                .send(db_pruner::Command::Quit)
+                .send(Command::Quit)
                 .expect("Ledger pruner receiver should not destruct.");
         }
         if self.worker_thread.is_some() {
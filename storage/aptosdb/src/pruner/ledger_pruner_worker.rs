@@ -1,14 +1,25 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
-use crate::pruner::ledger_store::ledger_store_pruner::LedgerPruner;
-use crate::pruner::{db_pruner, db_pruner::DBPruner};
-use aptos_config::config::StoragePrunerConfig;
+use crate::pruner::db_pruner::DBPruner;
+use crate::pruner::ledger_store::ledger_store_pruner::{LedgerPruner, LedgerSubStore};
+use aptos_config::config::{PruneWindow, StoragePrunerConfig};
+use aptos_logger::warn;
+use aptos_types::transaction::Version;
 use std::sync::{mpsc::Receiver, Arc};
 
+pub enum Command {
+    Quit,
+    /// Prunes each ledger store to keep its prune window.
+    Prune {
+        /// The latest version in the DB, which the prune windows count back from.
+        latest_version: Version,
+    },
+}
+
 /// Maintains the ledger pruner and periodically calls the db_pruner's prune method to prune the DB.
 /// This also exposes API to report the progress to the parent thread.
 pub struct LedgerPrunerWorker {
-    command_receiver: Receiver<db_pruner::Command>,
+    command_receiver: Receiver<Command>,
     /// Ledger pruner.
     pruner: Arc<LedgerPruner>,
     /// Indicates if there's NOT any pending work to do currently, to hint
@@ -17,13 +28,16 @@ pub struct LedgerPrunerWorker {
     /// Max items to prune per batch. For the ledger pruner, this means the max versions to prune
     /// and for the state pruner, this means the max stale nodes to prune.
     ledger_store_max_versions_to_prune_per_batch: u64,
+    /// The stores that are pruned, with their prune windows.
+    prune_windows: Vec<(LedgerSubStore, PruneWindow)>,
 }
 
 impl LedgerPrunerWorker {
     pub(crate) fn new(
         ledger_pruner: Arc<LedgerPruner>,
-        command_receiver: Receiver<db_pruner::Command>,
+        command_receiver: Receiver<Command>,
         storage_pruner_config: StoragePrunerConfig,
+        prune_windows: Vec<(LedgerSubStore, PruneWindow)>,
     ) -> Self {
         Self {
             pruner: ledger_pruner,
@@ -32,6 +46,7 @@ impl LedgerPrunerWorker {
             ledger_store_max_versions_to_prune_per_batch: storage_pruner_config
                 .ledger_pruning_batch_size
                 as u64,
+            prune_windows,
         }
     }
 
@@ -78,16 +93,42 @@ impl LedgerPrunerWorker {
 
             match command {
                 // On `Command::Quit` inform the outer loop to quit by returning `false`.
-                db_pruner::Command::Quit => return false,
-                db_pruner::Command::Prune { target_db_version } => {
-                    if target_db_version > self.pruner.target_version() {
+                Command::Quit => return false,
+                Command::Prune { latest_version } => {
+                    if self.set_target_versions(latest_version) {
                         // Switch to non-blocking to allow some work to be done after the
                         // channel has drained.
                         self.blocking_recv = false;
                     }
-                    self.pruner.set_target_version(target_db_version);
                 }
             }
         }
     }
+
+    /// Sets the target version of each store from its prune window, returning whether any of
+    /// them advanced.
+    fn set_target_versions(&self, latest_version: Version) -> bool {
+        let mut advanced = false;
+        for (store, prune_window) in &self.prune_windows {
+            match self
+                .pruner
+                .resolve_target_version(*prune_window, latest_version)
+            {
+                Ok(Some(target_version)) => {
+                    if target_version > self.pruner.target_version_of(*store) {
+                        self.pruner.set_target_version_of(*store, target_version);
+                        advanced = true;
+                    }
+                }
+                // The ledger doesn't go back as far as the window yet.
+                Ok(None) => (),
+                Err(e) => warn!(
+                    error = ?e,
+                    store = ?store,
+                    "Failed resolving the prune window, will retry on the next command."
+                ),
+            }
+        }
+        advanced
+    }
 }
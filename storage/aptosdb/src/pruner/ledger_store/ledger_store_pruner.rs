@@ -8,29 +8,104 @@ use crate::{
         event_store::event_store_pruner::EventStorePruner,
         ledger_store::ledger_counter_pruner::LedgerCounterPruner,
         transaction_store::{
+            transaction_info_pruner::TransactionInfoPruner,
             transaction_store_pruner::TransactionStorePruner, write_set_pruner::WriteSetPruner,
         },
     },
-    transaction::TransactionSchema,
+    schema::{
+        event::EventSchema, transaction::TransactionSchema,
+        transaction_info::TransactionInfoSchema, write_set::WriteSetSchema,
+    },
     EventStore, LedgerStore, TransactionStore,
 };
+use aptos_config::config::PruneWindow;
 use aptos_types::transaction::{AtomicVersion, Version};
-use schemadb::{ReadOptions, SchemaBatch, DB};
-use std::sync::{atomic::Ordering, Arc};
+use schemadb::{schema::Schema, ReadOptions, SchemaBatch, DB};
+use std::{
+    cmp::min,
+    sync::{atomic::Ordering, Arc},
+};
 
 pub const LEDGER_PRUNER_NAME: &str = "ledger pruner";
 
+/// The stores pruned by the ledger pruner, each of which is pruned to its own prune window.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LedgerSubStore {
+    /// Transactions, their indices by hash and by account, and the ledger counters.
+    Transaction,
+    TransactionInfo,
+    Event,
+    WriteSet,
+}
+
+impl LedgerSubStore {
+    pub const ALL: [LedgerSubStore; 4] = [
+        LedgerSubStore::Transaction,
+        LedgerSubStore::TransactionInfo,
+        LedgerSubStore::Event,
+        LedgerSubStore::WriteSet,
+    ];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    fn metric_label(self) -> &'static str {
+        match self {
+            LedgerSubStore::Transaction => "ledger_pruner_transaction",
+            LedgerSubStore::TransactionInfo => "ledger_pruner_transaction_info",
+            LedgerSubStore::Event => "ledger_pruner_event",
+            LedgerSubStore::WriteSet => "ledger_pruner_write_set",
+        }
+    }
+}
+
+/// A store pruned by the ledger pruner, and its pruning progress.
+#[derive(Debug)]
+struct SubStorePruner {
+    store: LedgerSubStore,
+    sub_pruners: Vec<Arc<dyn DBSubPruner + Send + Sync>>,
+    target_version: AtomicVersion,
+    min_readable_version: AtomicVersion,
+}
+
+impl SubStorePruner {
+    fn new(store: LedgerSubStore, sub_pruners: Vec<Arc<dyn DBSubPruner + Send + Sync>>) -> Self {
+        Self {
+            store,
+            sub_pruners,
+            target_version: AtomicVersion::new(0),
+            min_readable_version: AtomicVersion::new(0),
+        }
+    }
+
+    fn target_version(&self) -> Version {
+        self.target_version.load(Ordering::Relaxed)
+    }
+
+    fn min_readable_version(&self) -> Version {
+        self.min_readable_version.load(Ordering::Relaxed)
+    }
+
+    fn record_progress(&self, min_readable_version: Version) {
+        self.min_readable_version
+            .store(min_readable_version, Ordering::Relaxed);
+        PRUNER_LEAST_READABLE_VERSION
+            .with_label_values(&[self.store.metric_label()])
+            .set(min_readable_version as i64);
+    }
+}
+
 #[derive(Debug)]
 /// Responsible for pruning everything except for the state tree.
+///
+/// Each store has its own target and progress, so stores can keep different windows of history.
+/// The overall min readable version is the first version of which all ledger data is readable.
 pub struct LedgerPruner {
     db: Arc<DB>,
-    /// Keeps track of the target version that the pruner needs to achieve.
-    target_version: AtomicVersion,
-    min_readable_version: AtomicVersion,
-    transaction_store_pruner: Arc<dyn DBSubPruner + Send + Sync>,
-    event_store_pruner: Arc<dyn DBSubPruner + Send + Sync>,
-    write_set_pruner: Arc<dyn DBSubPruner + Send + Sync>,
-    ledger_counter_pruner: Arc<dyn DBSubPruner + Send + Sync>,
+    event_store: Arc<EventStore>,
+    /// Indexed by `LedgerSubStore::index()`.
+    sub_stores: Vec<SubStorePruner>,
 }
 
 impl DBPruner for LedgerPruner {
@@ -43,29 +118,23 @@ impl DBPruner for LedgerPruner {
             return Ok(self.min_readable_version());
         }
         let mut db_batch = SchemaBatch::new();
-        let min_readable_version = self.min_readable_version();
-        // Current target version might be less than the target version to ensure we don't prune
-        // more than max_version in one go.
-        let current_target_version = self.get_currrent_batch_target(max_versions as Version);
-
-        self.transaction_store_pruner.prune(
-            &mut db_batch,
-            min_readable_version,
-            current_target_version,
-        )?;
-        self.write_set_pruner
-            .prune(&mut db_batch, min_readable_version, current_target_version)?;
-        self.ledger_counter_pruner.prune(
-            &mut db_batch,
-            min_readable_version,
-            current_target_version,
-        )?;
-
-        self.event_store_pruner.prune(
-            &mut db_batch,
-            min_readable_version,
-            current_target_version,
-        )?;
+        let mut progress = Vec::new();
+        for sub_store in &self.sub_stores {
+            let min_readable_version = sub_store.min_readable_version();
+            // Current target version might be less than the target version to ensure we don't
+            // prune more than max_version in one go.
+            let current_target_version = min(
+                min_readable_version + max_versions as Version,
+                sub_store.target_version(),
+            );
+            if current_target_version <= min_readable_version {
+                continue;
+            }
+            for sub_pruner in &sub_store.sub_pruners {
+                sub_pruner.prune(&mut db_batch, min_readable_version, current_target_version)?;
+            }
+            progress.push((sub_store, current_target_version));
+        }
 
         // Commit all the changes to DB atomically
         self.db.write_schemas(db_batch)?;
@@ -73,40 +142,71 @@ impl DBPruner for LedgerPruner {
         // TODO(zcc): recording progress after writing schemas might provide wrong answers to
         // API calls when they query min_readable_version while the write_schemas are still in
         // progress.
-        self.record_progress(current_target_version);
-        Ok(current_target_version)
+        for (sub_store, min_readable_version) in progress {
+            sub_store.record_progress(min_readable_version);
+        }
+        self.record_overall_progress();
+        Ok(self.min_readable_version())
     }
 
+    /// Initializes the least readable version of each store, and returns the overall one.
     fn initialize_min_readable_version(&self) -> anyhow::Result<Version> {
-        let mut iter = self.db.iter::<TransactionSchema>(ReadOptions::default())?;
-        iter.seek_to_first();
-        let version = iter.next().transpose()?.map_or(0, |(version, _)| version);
-        Ok(version)
+        for sub_store in &self.sub_stores {
+            let version = match sub_store.store {
+                LedgerSubStore::Transaction => self.first_version::<TransactionSchema>()?,
+                LedgerSubStore::TransactionInfo => self.first_version::<TransactionInfoSchema>()?,
+                // Can be a bit ahead of where the events are pruned to, if the versions right
+                // after it emitted no events.
+                LedgerSubStore::Event => self
+                    .first_version::<EventSchema>()?
+                    .map(|(version, _index)| version),
+                LedgerSubStore::WriteSet => self.first_version::<WriteSetSchema>()?,
+            };
+            sub_store.record_progress(version.unwrap_or(0));
+        }
+        Ok(self.overall_min_readable_version())
     }
 
     fn min_readable_version(&self) -> Version {
-        self.min_readable_version.load(Ordering::Relaxed)
+        self.overall_min_readable_version()
     }
 
+    /// Sets the target version of all the stores.
     fn set_target_version(&self, target_version: Version) {
-        self.target_version.store(target_version, Ordering::Relaxed)
+        for sub_store in &self.sub_stores {
+            sub_store
+                .target_version
+                .store(target_version, Ordering::Relaxed)
+        }
     }
 
+    /// Returns the furthest target version of the stores.
     fn target_version(&self) -> Version {
-        self.target_version.load(Ordering::Relaxed)
+        self.sub_stores
+            .iter()
+            .map(SubStorePruner::target_version)
+            .max()
+            .unwrap_or(0)
     }
 
-    fn record_progress(&self, min_readable_version: Version) {
-        self.min_readable_version
-            .store(min_readable_version, Ordering::Relaxed);
-        PRUNER_LEAST_READABLE_VERSION
-            .with_label_values(&["ledger_pruner"])
-            .set(min_readable_version as i64);
+    /// Progress is recorded per store as it's pruned, so this only reports the overall progress.
+    fn record_progress(&self, _min_readable_version: Version) {
+        self.record_overall_progress();
+    }
+
+    fn is_pruning_pending(&self) -> bool {
+        self.sub_stores
+            .iter()
+            .any(|sub_store| sub_store.target_version() > sub_store.min_readable_version())
     }
 
     /// (For tests only.) Updates the minimal readable version kept by pruner.
     fn testonly_update_min_version(&self, version: Version) {
-        self.min_readable_version.store(version, Ordering::Relaxed)
+        for sub_store in &self.sub_stores {
+            sub_store
+                .min_readable_version
+                .store(version, Ordering::Relaxed)
+        }
     }
 }
 
@@ -117,18 +217,104 @@ impl LedgerPruner {
         event_store: Arc<EventStore>,
         ledger_store: Arc<LedgerStore>,
     ) -> Self {
+        let sub_stores = LedgerSubStore::ALL
+            .iter()
+            .map(|store| {
+                let sub_pruners: Vec<Arc<dyn DBSubPruner + Send + Sync>> = match store {
+                    LedgerSubStore::Transaction => vec![
+                        Arc::new(TransactionStorePruner::new(Arc::clone(&transaction_store))),
+                        Arc::new(LedgerCounterPruner::new(Arc::clone(&ledger_store))),
+                    ],
+                    LedgerSubStore::TransactionInfo => vec![Arc::new(TransactionInfoPruner::new(
+                        Arc::clone(&transaction_store),
+                    ))],
+                    LedgerSubStore::Event => {
+                        vec![Arc::new(EventStorePruner::new(Arc::clone(&event_store)))]
+                    }
+                    LedgerSubStore::WriteSet => vec![Arc::new(WriteSetPruner::new(Arc::clone(
+                        &transaction_store,
+                    )))],
+                };
+                SubStorePruner::new(*store, sub_pruners)
+            })
+            .collect();
         let pruner = LedgerPruner {
             db,
-            target_version: AtomicVersion::new(0),
-            min_readable_version: AtomicVersion::new(0),
-            ledger_counter_pruner: Arc::new(LedgerCounterPruner::new(ledger_store)),
-            transaction_store_pruner: Arc::new(TransactionStorePruner::new(
-                transaction_store.clone(),
-            )),
-            event_store_pruner: Arc::new(EventStorePruner::new(event_store)),
-            write_set_pruner: Arc::new(WriteSetPruner::new(transaction_store)),
+            event_store,
+            sub_stores,
         };
         pruner.initialize();
         pruner
     }
+
+    fn sub_store(&self, store: LedgerSubStore) -> &SubStorePruner {
+        &self.sub_stores[store.index()]
+    }
+
+    /// Returns the least readable version of a store.
+    pub fn min_readable_version_of(&self, store: LedgerSubStore) -> Version {
+        self.sub_store(store).min_readable_version()
+    }
+
+    /// Returns the target version of a store.
+    pub fn target_version_of(&self, store: LedgerSubStore) -> Version {
+        self.sub_store(store).target_version()
+    }
+
+    /// Sets the target version of a store.
+    pub fn set_target_version_of(&self, store: LedgerSubStore, target_version: Version) {
+        self.sub_store(store)
+            .target_version
+            .store(target_version, Ordering::Relaxed)
+    }
+
+    /// Returns the version to prune a store up to (exclusive) for it to keep `prune_window` as of
+    /// `latest_version`, or `None` if the ledger doesn't go back that far yet.
+    pub fn resolve_target_version(
+        &self,
+        prune_window: PruneWindow,
+        latest_version: Version,
+    ) -> anyhow::Result<Option<Version>> {
+        match prune_window {
+            PruneWindow::Versions(versions) => Ok(latest_version.checked_sub(versions)),
+            PruneWindow::Seconds(seconds) => {
+                let latest_timestamp = match self
+                    .event_store
+                    .get_latest_block_timestamp(latest_version)?
+                {
+                    Some(timestamp) => timestamp,
+                    None => return Ok(None),
+                };
+                let timestamp =
+                    match latest_timestamp.checked_sub(seconds.saturating_mul(1_000_000)) {
+                        Some(timestamp) => timestamp,
+                        None => return Ok(None),
+                    };
+                Ok(self
+                    .event_store
+                    .find_last_version_before_timestamp(timestamp, latest_version)?
+                    .map(|version| version + 1))
+            }
+        }
+    }
+
+    fn overall_min_readable_version(&self) -> Version {
+        self.sub_stores
+            .iter()
+            .map(SubStorePruner::min_readable_version)
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn record_overall_progress(&self) {
+        PRUNER_LEAST_READABLE_VERSION
+            .with_label_values(&["ledger_pruner"])
+            .set(self.overall_min_readable_version() as i64);
+    }
+
+    fn first_version<S: Schema>(&self) -> anyhow::Result<Option<S::Key>> {
+        let mut iter = self.db.iter::<S>(ReadOptions::default())?;
+        iter.seek_to_first();
+        Ok(iter.next().transpose()?.map(|(key, _)| key))
+    }
 }
@@ -1,7 +1,7 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use aptos_config::config::StoragePrunerConfig;
+use aptos_config::config::{LedgerStorePruneWindows, StoragePrunerConfig};
 use std::collections::HashMap;
 use std::sync::mpsc::channel;
 use std::sync::Arc;
@@ -76,12 +76,14 @@ fn test_state_store_pruner() {
         Arc::clone(&aptos_db.state_merkle_db),
         1000,  /* snapshot_size_threshold, does not matter */
         false, /* hack_for_tests */
+        false, /* enable_state_key_history */
     );
     let pruner = StatePrunerManager::new(
         Arc::clone(&aptos_db.state_merkle_db),
         StoragePrunerConfig {
             state_store_prune_window: Some(0),
             ledger_prune_window: Some(0),
+            ledger_store_prune_windows: LedgerStorePruneWindows::default(),
             ledger_pruning_batch_size: prune_batch_size,
             state_store_pruning_batch_size: prune_batch_size,
         },
@@ -170,6 +172,7 @@ fn test_state_store_pruner_partial_version() {
         StoragePrunerConfig {
             state_store_prune_window: Some(0),
             ledger_prune_window: Some(0),
+            ledger_store_prune_windows: LedgerStorePruneWindows::default(),
             ledger_pruning_batch_size: prune_batch_size,
             state_store_pruning_batch_size: prune_batch_size,
         },
@@ -341,6 +344,7 @@ fn test_worker_quit_eagerly() {
             StoragePrunerConfig {
                 state_store_prune_window: Some(1),
                 ledger_prune_window: Some(1),
+                ledger_store_prune_windows: LedgerStorePruneWindows::default(),
                 ledger_pruning_batch_size: 100,
                 state_store_pruning_batch_size: 100,
             },
@@ -3,5 +3,6 @@
 
 #[cfg(test)]
 mod test;
+pub(crate) mod transaction_info_pruner;
 pub(crate) mod transaction_store_pruner;
 pub(crate) mod write_set_pruner;
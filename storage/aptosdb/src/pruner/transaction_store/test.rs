@@ -14,7 +14,7 @@ use aptos_types::{
     transaction::{SignedTransaction, Transaction},
 };
 
-use aptos_config::config::StoragePrunerConfig;
+use aptos_config::config::{LedgerStorePruneWindows, StoragePrunerConfig};
 use aptos_types::{
     transaction::{TransactionInfo, Version},
     write_set::WriteSet,
@@ -56,6 +56,7 @@ fn verify_write_set_pruner(write_sets: Vec<WriteSet>) {
         StoragePrunerConfig {
             state_store_prune_window: Some(0),
             ledger_prune_window: Some(0),
+            ledger_store_prune_windows: LedgerStorePruneWindows::default(),
             ledger_pruning_batch_size: 1,
             state_store_pruning_batch_size: 100,
         },
@@ -102,6 +103,7 @@ fn verify_txn_store_pruner(
         StoragePrunerConfig {
             state_store_prune_window: Some(0),
             ledger_prune_window: Some(0),
+            ledger_store_prune_windows: LedgerStorePruneWindows::default(),
             ledger_pruning_batch_size: 1,
             state_store_pruning_batch_size: 100,
         },
@@ -0,0 +1,32 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+use crate::{pruner::db_sub_pruner::DBSubPruner, TransactionStore};
+use schemadb::SchemaBatch;
+use std::sync::Arc;
+
+#[derive(Debug)]
+pub struct TransactionInfoPruner {
+    transaction_store: Arc<TransactionStore>,
+}
+
+impl DBSubPruner for TransactionInfoPruner {
+    fn prune(
+        &self,
+        db_batch: &mut SchemaBatch,
+        min_readable_version: u64,
+        target_version: u64,
+    ) -> anyhow::Result<()> {
+        self.transaction_store.prune_transaction_info_schema(
+            min_readable_version,
+            target_version,
+            db_batch,
+        )?;
+        Ok(())
+    }
+}
+
+impl TransactionInfoPruner {
+    pub(in crate::pruner) fn new(transaction_store: Arc<TransactionStore>) -> Self {
+        TransactionInfoPruner { transaction_store }
+    }
+}
@@ -26,16 +26,17 @@ impl DBSubPruner for TransactionStorePruner {
             .prune_transaction_by_hash(&candidate_transactions, db_batch)?;
         self.transaction_store
             .prune_transaction_by_account(&candidate_transactions, db_batch)?;
+        self.transaction_store
+            .prune_transaction_by_affected_account(
+                min_readable_version,
+                target_version,
+                db_batch,
+            )?;
         self.transaction_store.prune_transaction_schema(
             min_readable_version,
             target_version,
             db_batch,
         )?;
-        self.transaction_store.prune_transaction_info_schema(
-            min_readable_version,
-            target_version,
-            db_batch,
-        )?;
         Ok(())
     }
 }
@@ -0,0 +1,18 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use proptest::{collection::vec, prelude::*};
+use schemadb::{schema::fuzzing::assert_encode_decode, test_no_panic_decoding};
+
+proptest! {
+    #[test]
+    fn test_encode_decode(
+        version in any::<Version>(),
+        addresses in vec(any::<AccountAddress>(), 0..10),
+    ) {
+        assert_encode_decode::<AffectedAccountsByVersionSchema>(&version, &addresses);
+    }
+}
+
+test_no_panic_decoding!(AffectedAccountsByVersionSchema);
@@ -6,6 +6,7 @@
 //!
 //! All schemas are `pub(crate)` so not shown in rustdoc, refer to the source code to see details.
 
+pub(crate) mod affected_accounts_by_version;
 pub(crate) mod epoch_by_version;
 pub(crate) mod event;
 pub(crate) mod event_accumulator;
@@ -15,10 +16,12 @@ pub(crate) mod jellyfish_merkle_node;
 pub(crate) mod ledger_counters;
 pub(crate) mod ledger_info;
 pub(crate) mod stale_node_index;
+pub(crate) mod state_key_history;
 pub(crate) mod state_value;
 pub(crate) mod transaction;
 pub(crate) mod transaction_accumulator;
 pub(crate) mod transaction_by_account;
+pub(crate) mod transaction_by_affected_account;
 pub(crate) mod transaction_by_hash;
 pub(crate) mod transaction_info;
 pub(crate) mod write_set;
@@ -26,6 +29,7 @@ pub(crate) mod write_set;
 use anyhow::{ensure, Result};
 use schemadb::ColumnFamilyName;
 
+pub const AFFECTED_ACCOUNTS_BY_VERSION_CF_NAME: ColumnFamilyName = "affected_accounts_by_version";
 pub const EPOCH_BY_VERSION_CF_NAME: ColumnFamilyName = "epoch_by_version";
 pub const EVENT_ACCUMULATOR_CF_NAME: ColumnFamilyName = "event_accumulator";
 pub const EVENT_BY_KEY_CF_NAME: ColumnFamilyName = "event_by_key";
@@ -36,11 +40,14 @@ pub const JELLYFISH_MERKLE_NODE_CF_NAME: ColumnFamilyName = "jellyfish_merkle_no
 pub const LEDGER_COUNTERS_CF_NAME: ColumnFamilyName = "ledger_counters";
 pub const LEDGER_INFO_CF_NAME: ColumnFamilyName = "ledger_info";
 pub const STALE_NODE_INDEX_CF_NAME: ColumnFamilyName = "stale_node_index";
+pub const STATE_KEY_HISTORY_CF_NAME: ColumnFamilyName = "state_key_history";
 pub const STATE_VALUE_CF_NAME: ColumnFamilyName = "state_value";
 pub const TABLE_INFO_CF_NAME: ColumnFamilyName = "table_info";
 pub const TRANSACTION_CF_NAME: ColumnFamilyName = "transaction";
 pub const TRANSACTION_ACCUMULATOR_CF_NAME: ColumnFamilyName = "transaction_accumulator";
 pub const TRANSACTION_BY_ACCOUNT_CF_NAME: ColumnFamilyName = "transaction_by_account";
+pub const TRANSACTION_BY_AFFECTED_ACCOUNT_CF_NAME: ColumnFamilyName =
+    "transaction_by_affected_account";
 pub const TRANSACTION_BY_HASH_CF_NAME: ColumnFamilyName = "transaction_by_hash";
 pub const TRANSACTION_INFO_CF_NAME: ColumnFamilyName = "transaction_info";
 pub const WRITE_SET_CF_NAME: ColumnFamilyName = "write_set";
@@ -72,6 +79,9 @@ pub mod fuzzing {
     pub fn fuzz_decode(data: &[u8]) {
         #[allow(unused_must_use)]
         {
+            assert_no_panic_decoding::<
+                super::affected_accounts_by_version::AffectedAccountsByVersionSchema,
+            >(data);
             assert_no_panic_decoding::<super::epoch_by_version::EpochByVersionSchema>(data);
             assert_no_panic_decoding::<super::event::EventSchema>(data);
             assert_no_panic_decoding::<super::event_accumulator::EventAccumulatorSchema>(data);
@@ -83,6 +93,7 @@ pub mod fuzzing {
             assert_no_panic_decoding::<super::ledger_counters::LedgerCountersSchema>(data);
             assert_no_panic_decoding::<super::ledger_info::LedgerInfoSchema>(data);
             assert_no_panic_decoding::<super::stale_node_index::StaleNodeIndexSchema>(data);
+            assert_no_panic_decoding::<super::state_key_history::StateKeyHistorySchema>(data);
             assert_no_panic_decoding::<super::state_value::StateValueSchema>(data);
             assert_no_panic_decoding::<super::transaction::TransactionSchema>(data);
             assert_no_panic_decoding::<super::transaction_accumulator::TransactionAccumulatorSchema>(
@@ -91,6 +102,9 @@ pub mod fuzzing {
             assert_no_panic_decoding::<super::transaction_by_account::TransactionByAccountSchema>(
                 data,
             );
+            assert_no_panic_decoding::<
+                super::transaction_by_affected_account::TransactionByAffectedAccountSchema,
+            >(data);
             assert_no_panic_decoding::<super::transaction_by_hash::TransactionByHashSchema>(data);
             assert_no_panic_decoding::<super::transaction_info::TransactionInfoSchema>(data);
             assert_no_panic_decoding::<super::write_set::WriteSetSchema>(data);
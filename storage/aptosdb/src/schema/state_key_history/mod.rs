@@ -0,0 +1,73 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for an optional index of the versions at which each
+//! state key was changed. With a version one can resort to `StateValueSchema` for the value the key
+//! was set to.
+//!
+//! An Index Key in this data set has 2 pieces of information:
+//!     1. The hash of the state key
+//!     2. The version at which the state key was changed
+//!
+//! ```text
+//! |<------------key------------>|
+//! | state key hash |  version   |
+//! ```
+//!
+//! `version` is serialized in big endian so that records in RocksDB will be in order of its
+//! numeric value.
+
+use crate::schema::{ensure_slice_len_eq, STATE_KEY_HISTORY_CF_NAME};
+use anyhow::Result;
+use aptos_crypto::HashValue;
+use aptos_types::transaction::Version;
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use schemadb::{
+    define_schema,
+    schema::{KeyCodec, SeekKeyCodec, ValueCodec},
+};
+use std::mem::size_of;
+
+define_schema!(StateKeyHistorySchema, Key, (), STATE_KEY_HISTORY_CF_NAME);
+
+type Key = (HashValue, Version);
+
+impl KeyCodec<StateKeyHistorySchema> for Key {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        let (ref state_key_hash, version) = *self;
+
+        let mut encoded = state_key_hash.to_vec();
+        encoded.write_u64::<BigEndian>(version)?;
+
+        Ok(encoded)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, HashValue::LENGTH + size_of::<Version>())?;
+
+        let state_key_hash = HashValue::from_slice(&data[..HashValue::LENGTH])?;
+        let version = (&data[HashValue::LENGTH..]).read_u64::<BigEndian>()?;
+
+        Ok((state_key_hash, version))
+    }
+}
+
+impl ValueCodec<StateKeyHistorySchema> for () {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(Vec::new())
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, 0)?;
+        Ok(())
+    }
+}
+
+impl SeekKeyCodec<StateKeyHistorySchema> for HashValue {
+    fn encode_seek_key(&self) -> Result<Vec<u8>> {
+        Ok(self.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod test;
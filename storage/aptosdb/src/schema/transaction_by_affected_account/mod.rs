@@ -0,0 +1,68 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for an optional transaction index via which the
+//! versions of the transactions that affected `account_address` can be found, whether or not they
+//! were sent by it. With a version one can resort to `TransactionSchema` for the transaction
+//! content.
+//!
+//! A transaction affects its sender, the accounts whose state it writes and the accounts whose
+//! event handles emit its events.
+//!
+//! ```text
+//! |<-------key------->|
+//! | address | txn_ver |
+//! ```
+
+use crate::schema::{ensure_slice_len_eq, TRANSACTION_BY_AFFECTED_ACCOUNT_CF_NAME};
+use anyhow::Result;
+use aptos_types::{account_address::AccountAddress, transaction::Version};
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use schemadb::{
+    define_schema,
+    schema::{KeyCodec, ValueCodec},
+};
+use std::{convert::TryFrom, mem::size_of};
+
+define_schema!(
+    TransactionByAffectedAccountSchema,
+    Key,
+    (),
+    TRANSACTION_BY_AFFECTED_ACCOUNT_CF_NAME
+);
+
+type Key = (AccountAddress, Version);
+
+impl KeyCodec<TransactionByAffectedAccountSchema> for Key {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        let (ref account_address, version) = *self;
+
+        let mut encoded = account_address.to_vec();
+        encoded.write_u64::<BigEndian>(version)?;
+
+        Ok(encoded)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, size_of::<Self>())?;
+
+        let address = AccountAddress::try_from(&data[..AccountAddress::LENGTH])?;
+        let version = (&data[AccountAddress::LENGTH..]).read_u64::<BigEndian>()?;
+
+        Ok((address, version))
+    }
+}
+
+impl ValueCodec<TransactionByAffectedAccountSchema> for () {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(Vec::new())
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, 0)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test;
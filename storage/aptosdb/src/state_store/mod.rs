@@ -36,7 +36,9 @@ use storage_interface::{
 
 use crate::state_store::buffered_state::BufferedState;
 use crate::{
-    change_set::ChangeSet, schema::state_value::StateValueSchema, state_merkle_db::StateMerkleDb,
+    change_set::ChangeSet,
+    schema::{state_key_history::StateKeyHistorySchema, state_value::StateValueSchema},
+    state_merkle_db::StateMerkleDb,
     AptosDbError, LedgerStore, TransactionStore,
 };
 
@@ -68,6 +70,9 @@ pub(crate) struct StateStore {
     // write set stored in ledger_db.
     buffered_state: Mutex<BufferedState>,
     target_snapshot_size: usize,
+    // Whether the versions at which each state key changes are indexed in
+    // `StateKeyHistorySchema`.
+    enable_state_key_history: bool,
 }
 
 impl Deref for StateStore {
@@ -205,6 +210,7 @@ impl StateStore {
         state_merkle_db: Arc<DB>,
         target_snapshot_size: usize,
         hack_for_tests: bool,
+        enable_state_key_history: bool,
     ) -> Self {
         let state_merkle_db = Arc::new(StateMerkleDb::new(state_merkle_db));
         let state_db = Arc::new(StateDb {
@@ -223,6 +229,7 @@ impl StateStore {
             state_db,
             buffered_state,
             target_snapshot_size,
+            enable_state_key_history,
         }
     }
 
@@ -416,9 +423,48 @@ impl StateStore {
                     .map(move |(k, v)| ((k.clone(), first_version + i as Version), v.clone()))
             })
             .collect::<HashMap<_, _>>();
+        if self.enable_state_key_history {
+            add_state_key_history_batch(&mut cs.batch, &kv_batch)?;
+        }
         add_kv_batch(&mut cs.batch, &kv_batch)
     }
 
+    pub fn state_key_history_enabled(&self) -> bool {
+        self.enable_state_key_history
+    }
+
+    /// Returns up to `limit` versions at which `state_key` was changed, from `start_version` to
+    /// `ledger_version`, in ascending order, with the value the key was set to at each of them.
+    ///
+    /// Only changes committed while the history was enabled are indexed.
+    pub fn get_state_value_history(
+        &self,
+        state_key: &StateKey,
+        start_version: Version,
+        limit: usize,
+        ledger_version: Version,
+    ) -> Result<Vec<(Version, StateValue)>> {
+        ensure!(
+            self.enable_state_key_history,
+            "State key history not enabled."
+        );
+        let state_key_hash = state_key.hash();
+        let mut iter = self
+            .ledger_db
+            .iter::<StateKeyHistorySchema>(ReadOptions::default())?;
+        iter.seek(&(state_key_hash, start_version))?;
+
+        let mut history = Vec::new();
+        for res in iter {
+            let ((key_hash, version), ()) = res?;
+            if key_hash != state_key_hash || version > ledger_version || history.len() >= limit {
+                break;
+            }
+            history.push((version, self.expect_value_by_version(state_key, version)?));
+        }
+        Ok(history)
+    }
+
     /// Merklize the results generated by `value_state_sets` to `batch` and return the result root
     /// hashes for each write set.
     #[cfg(test)]
@@ -538,3 +584,11 @@ fn add_kv_batch(batch: &mut SchemaBatch, kv_batch: &StateValueBatch) -> Result<(
     // Add kv_batch
     Ok(())
 }
+
+fn add_state_key_history_batch(batch: &mut SchemaBatch, kv_batch: &StateValueBatch) -> Result<()> {
+    kv_batch
+        .keys()
+        .map(|(k, version)| batch.put::<StateKeyHistorySchema>(&(k.hash(), *version), &()))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(())
+}
@@ -144,6 +144,65 @@ fn test_state_store_reader_writer() {
     verify_value_and_proof(store, key3, Some(&value3), 1, root);
 }
 
+#[test]
+fn test_state_value_history() {
+    let tmp_dir = TempPath::new();
+    let db = AptosDB::new_for_test_with_state_key_history(&tmp_dir);
+    let store = &db.state_store;
+    let key1 = StateKey::Raw(String::from("test_key1").into_bytes());
+    let key2 = StateKey::Raw(String::from("test_key2").into_bytes());
+
+    let value1 = StateValue::from(String::from("test_val1").into_bytes());
+    let value1_update = StateValue::from(String::from("test_val1_update").into_bytes());
+    let value2 = StateValue::from(String::from("test_val2").into_bytes());
+
+    put_value_set(store, vec![(key1.clone(), value1.clone())], 0, None);
+    put_value_set(store, vec![(key2.clone(), value2.clone())], 1, Some(0));
+    put_value_set(
+        store,
+        vec![(key1.clone(), value1_update.clone())],
+        2,
+        Some(1),
+    );
+    put_value_set(store, vec![(key1.clone(), StateValue::empty())], 3, Some(2));
+
+    assert_eq!(
+        store.get_state_value_history(&key1, 0, 10, 3).unwrap(),
+        vec![
+            (0, value1.clone()),
+            (2, value1_update.clone()),
+            (3, StateValue::empty())
+        ]
+    );
+    // Paging through the history.
+    assert_eq!(
+        store.get_state_value_history(&key1, 0, 1, 3).unwrap(),
+        vec![(0, value1)]
+    );
+    assert_eq!(
+        store.get_state_value_history(&key1, 1, 10, 2).unwrap(),
+        vec![(2, value1_update)]
+    );
+    assert_eq!(
+        store.get_state_value_history(&key2, 0, 10, 3).unwrap(),
+        vec![(1, value2)]
+    );
+
+    // Not indexed unless enabled.
+    let tmp_dir = TempPath::new();
+    let db = AptosDB::new_for_test(&tmp_dir);
+    put_value_set(
+        &db.state_store,
+        vec![(key1.clone(), StateValue::empty())],
+        0,
+        None,
+    );
+    assert!(db
+        .state_store
+        .get_state_value_history(&key1, 0, 10, 0)
+        .is_err());
+}
+
 #[test]
 fn test_get_values_by_key_prefix() {
     let tmp_dir = TempPath::new();
@@ -7,7 +7,9 @@ use crate::{
     change_set::ChangeSet,
     errors::AptosDbError,
     schema::{
+        affected_accounts_by_version::AffectedAccountsByVersionSchema,
         transaction::TransactionSchema, transaction_by_account::TransactionByAccountSchema,
+        transaction_by_affected_account::TransactionByAffectedAccountSchema,
         transaction_by_hash::TransactionByHashSchema, write_set::WriteSetSchema,
     },
     transaction_info::TransactionInfoSchema,
@@ -17,12 +19,14 @@ use aptos_crypto::{hash::CryptoHash, HashValue};
 use aptos_types::{
     account_address::AccountAddress,
     block_metadata::BlockMetadata,
+    contract_event::ContractEvent,
     proof::position::Position,
+    state_store::state_key::StateKey,
     transaction::{Transaction, Version},
     write_set::WriteSet,
 };
 use schemadb::{ReadOptions, SchemaBatch, SchemaIterator, DB};
-use std::sync::Arc;
+use std::{collections::BTreeSet, sync::Arc};
 
 #[derive(Clone, Debug)]
 pub struct TransactionStore {
@@ -53,6 +57,31 @@ impl TransactionStore {
         Ok(None)
     }
 
+    /// Gets the versions of up to `limit` transactions that affected `address`, starting from
+    /// `start_version` and up to `ledger_version`, in ascending order.
+    pub fn get_affected_account_transaction_versions(
+        &self,
+        address: AccountAddress,
+        start_version: Version,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<Vec<Version>> {
+        let mut iter = self
+            .db
+            .iter::<TransactionByAffectedAccountSchema>(ReadOptions::default())?;
+        iter.seek(&(address, start_version))?;
+
+        let mut versions = Vec::new();
+        for res in iter {
+            let ((account, version), ()) = res?;
+            if account != address || version > ledger_version || versions.len() as u64 >= limit {
+                break;
+            }
+            versions.push(version);
+        }
+        Ok(versions)
+    }
+
     /// Gets the version of a transaction by its hash.
     pub fn get_transaction_version_by_hash(
         &self,
@@ -240,6 +269,40 @@ impl TransactionStore {
         Ok(())
     }
 
+    /// Index the transaction at `version` by every account it affected: its sender, the accounts
+    /// whose state it wrote and the accounts whose event handles emitted its events.
+    pub fn put_affected_accounts(
+        &self,
+        version: Version,
+        transaction: &Transaction,
+        write_set: &WriteSet,
+        events: &[ContractEvent],
+        cs: &mut ChangeSet,
+    ) -> Result<()> {
+        let mut addresses = BTreeSet::new();
+        if let Transaction::UserTransaction(txn) = transaction {
+            addresses.insert(txn.sender());
+        }
+        for (state_key, _) in write_set {
+            // Table items don't belong to an account.
+            if let StateKey::AccessPath(access_path) = state_key {
+                addresses.insert(access_path.address);
+            }
+        }
+        for event in events {
+            addresses.insert(event.key().get_creator_address());
+        }
+
+        for address in &addresses {
+            cs.batch
+                .put::<TransactionByAffectedAccountSchema>(&(*address, version), &())?;
+        }
+        cs.batch.put::<AffectedAccountsByVersionSchema>(
+            &version,
+            &addresses.into_iter().collect::<Vec<_>>(),
+        )
+    }
+
     /// Get executed transaction vm output given `version`
     pub fn get_write_set(&self, version: Version) -> Result<WriteSet> {
         self.db.get::<WriteSetSchema>(&version)?.ok_or_else(|| {
@@ -330,6 +393,30 @@ impl TransactionStore {
         Ok(())
     }
 
+    /// Prune the transaction by affected account index between a range of version in [begin, end)
+    pub fn prune_transaction_by_affected_account(
+        &self,
+        begin: Version,
+        end: Version,
+        db_batch: &mut SchemaBatch,
+    ) -> anyhow::Result<()> {
+        let mut iter = self
+            .db
+            .iter::<AffectedAccountsByVersionSchema>(ReadOptions::default())?;
+        iter.seek(&begin)?;
+        for res in iter {
+            let (version, addresses) = res?;
+            if version >= end {
+                break;
+            }
+            for address in addresses {
+                db_batch.delete::<TransactionByAffectedAccountSchema>(&(address, version))?;
+            }
+            db_batch.delete::<AffectedAccountsByVersionSchema>(&version)?;
+        }
+        Ok(())
+    }
+
     /// Prune the transaction schema store between a range of version in [begin, end)
     pub fn prune_transaction_schema(
         &self,
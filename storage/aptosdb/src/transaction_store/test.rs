@@ -7,6 +7,7 @@ use aptos_proptest_helpers::Index;
 use aptos_temppath::TempPath;
 use aptos_types::{
     block_metadata::BlockMetadata,
+    contract_event::ContractEvent,
     proptest_types::{AccountInfoUniverse, SignatureCheckedTransactionGen},
     transaction::{SignedTransaction, Transaction},
 };
@@ -248,6 +249,88 @@ proptest! {
 
         prop_assert_eq!(&actual_scan, &expected_scan);
     }
+
+    #[test]
+    fn test_put_get_prune_affected_accounts(
+        universe in any_with::<AccountInfoUniverse>(3),
+        gens_write_sets_and_events in vec(
+            (
+                (any::<Index>(), any::<SignatureCheckedTransactionGen>()),
+                any::<WriteSet>(),
+                vec(any::<ContractEvent>(), 0..3),
+            ),
+            1..10
+        ),
+    ) {
+        let tmp_dir = TempPath::new();
+        let db = AptosDB::new_for_test(&tmp_dir);
+        let store = &db.transaction_store;
+        let (gens, write_sets_and_events): (Vec<_>, Vec<_>) = gens_write_sets_and_events
+            .into_iter()
+            .map(|(gen, write_set, events)| (gen, (write_set, events)))
+            .unzip();
+        let txns = init_store(universe, gens, store);
+
+        let mut cs = ChangeSet::new();
+        let mut expected = BTreeMap::<AccountAddress, Vec<Version>>::new();
+        for (ver, (txn, (write_set, events))) in
+            itertools::zip_eq(txns.iter(), write_sets_and_events.iter()).enumerate()
+        {
+            let ver = ver as Version;
+            store
+                .put_affected_accounts(ver, txn, write_set, events, &mut cs)
+                .unwrap();
+
+            let mut addresses = BTreeSet::new();
+            addresses.insert(txn.as_signed_user_txn().unwrap().sender());
+            for (state_key, _) in write_set {
+                if let StateKey::AccessPath(access_path) = state_key {
+                    addresses.insert(access_path.address);
+                }
+            }
+            for event in events {
+                addresses.insert(event.key().get_creator_address());
+            }
+            for address in addresses {
+                expected.entry(address).or_default().push(ver);
+            }
+        }
+        store.db.write_schemas(cs.batch).unwrap();
+
+        let ledger_version = txns.len() as Version - 1;
+        for (address, versions) in &expected {
+            prop_assert_eq!(
+                &store
+                    .get_affected_account_transaction_versions(*address, 0, 100, ledger_version)
+                    .unwrap(),
+                versions
+            );
+            // Paging through the versions.
+            prop_assert_eq!(
+                store
+                    .get_affected_account_transaction_versions(
+                        *address,
+                        versions[0] + 1,
+                        1,
+                        ledger_version,
+                    )
+                    .unwrap(),
+                versions.iter().skip(1).take(1).cloned().collect::<Vec<_>>()
+            );
+        }
+
+        let mut db_batch = SchemaBatch::new();
+        store
+            .prune_transaction_by_affected_account(0, ledger_version + 1, &mut db_batch)
+            .unwrap();
+        store.db.write_schemas(db_batch).unwrap();
+        for address in expected.keys() {
+            prop_assert!(store
+                .get_affected_account_transaction_versions(*address, 0, 100, ledger_version)
+                .unwrap()
+                .is_empty());
+        }
+    }
 }
 
 fn init_store(
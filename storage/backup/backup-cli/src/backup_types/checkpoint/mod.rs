@@ -0,0 +1,7 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+pub mod restore;
+
+#[cfg(test)]
+mod tests;
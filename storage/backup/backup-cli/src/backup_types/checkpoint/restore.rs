@@ -0,0 +1,195 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::utils::GlobalRestoreOpt;
+use anyhow::{anyhow, ensure, Result};
+use aptos_config::config::{RocksdbConfigs, NO_OP_STORAGE_PRUNER_CONFIG, TARGET_SNAPSHOT_SIZE};
+use aptos_logger::prelude::*;
+use aptos_types::{transaction::Version, waypoint::Waypoint};
+use aptosdb::{AptosDB, LEDGER_DB_NAME, STATE_MERKLE_DB_NAME};
+use aptosdb_indexer::INDEX_DB_NAME;
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+use storage_interface::DbReader;
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+pub struct CheckpointRestoreOpt {
+    #[structopt(
+        long,
+        parse(from_os_str),
+        help = "A checkpoint created by the backup service, holding the ledger DB, the state \
+        merkle DB and, if the node had the indexer enabled, the indexer DB."
+    )]
+    pub checkpoint_dir: PathBuf,
+
+    #[structopt(
+        long = "move",
+        help = "Move the DBs out of the checkpoint instead of copying them, which is much faster \
+        but consumes the checkpoint. Only works when both are on the same file system."
+    )]
+    pub move_files: bool,
+}
+
+/// Bootstraps a DB from a checkpoint, which is a copy of a node's DBs as they were at one point.
+///
+/// Unlike the other restore controllers, this takes the `GlobalRestoreOpt` as is, since the target
+/// DB must not exist yet, while turning it into `GlobalRestoreOptions` opens it.
+pub struct CheckpointRestoreController {
+    checkpoint_dir: PathBuf,
+    move_files: bool,
+    /// `None` on a dry run, in which case the checkpoint is only verified in place.
+    db_dir: Option<PathBuf>,
+    target_version: Version,
+    trusted_waypoints: HashMap<Version, Waypoint>,
+    rocksdb_configs: RocksdbConfigs,
+}
+
+impl CheckpointRestoreController {
+    pub fn new(opt: CheckpointRestoreOpt, global_opt: GlobalRestoreOpt) -> Result<Self> {
+        Ok(Self {
+            checkpoint_dir: opt.checkpoint_dir,
+            move_files: opt.move_files,
+            db_dir: global_opt.db_dir,
+            target_version: global_opt.target_version.unwrap_or(Version::max_value()),
+            trusted_waypoints: global_opt.trusted_waypoints.verify()?,
+            rocksdb_configs: global_opt.rocksdb_opt.into(),
+        })
+    }
+
+    pub async fn run(self) -> Result<()> {
+        let name = self.name();
+        info!("{} started. Checkpoint: {:?}", name, self.checkpoint_dir);
+        tokio::task::spawn_blocking(move || self.run_impl())
+            .await?
+            .map_err(|e| anyhow!("{} failed: {}", name, e))?;
+        info!("{} succeeded.", name);
+        Ok(())
+    }
+}
+
+impl CheckpointRestoreController {
+    fn name(&self) -> String {
+        format!(
+            "checkpoint {}",
+            if self.db_dir.is_some() {
+                "restore"
+            } else {
+                "verify"
+            }
+        )
+    }
+
+    fn run_impl(self) -> Result<()> {
+        for name in [LEDGER_DB_NAME, STATE_MERKLE_DB_NAME] {
+            ensure!(
+                self.checkpoint_dir.join(name).is_dir(),
+                "{} not found in checkpoint {:?}.",
+                name,
+                self.checkpoint_dir,
+            );
+        }
+        // Verify before putting the DBs in place, not to leave a DB behind that doesn't verify.
+        self.verify(&self.checkpoint_dir)?;
+
+        if let Some(db_dir) = &self.db_dir {
+            let names = [LEDGER_DB_NAME, STATE_MERKLE_DB_NAME, INDEX_DB_NAME];
+            for name in names {
+                ensure!(
+                    !db_dir.join(name).exists(),
+                    "{} already exists in {:?}, refusing to overwrite it.",
+                    name,
+                    db_dir,
+                );
+            }
+            fs::create_dir_all(db_dir)?;
+            for name in names {
+                let from = self.checkpoint_dir.join(name);
+                if !from.exists() {
+                    continue;
+                }
+                let to = db_dir.join(name);
+                if self.move_files {
+                    fs::rename(&from, &to)?;
+                } else {
+                    copy_dir(&from, &to)?;
+                }
+                info!("{} {:?} to {:?}.", self.name(), from, to);
+            }
+        }
+        Ok(())
+    }
+
+    /// Opens the DB in `db_dir` and checks it against the target version and trusted waypoints.
+    fn verify(&self, db_dir: &Path) -> Result<()> {
+        let db = AptosDB::open(
+            db_dir,
+            true,                        /* read_only */
+            NO_OP_STORAGE_PRUNER_CONFIG, /* pruner config */
+            self.rocksdb_configs,
+            false,
+            false,
+            false,
+            TARGET_SNAPSHOT_SIZE,
+        )?;
+        let ledger_info = db
+            .get_latest_ledger_info_option()?
+            .ok_or_else(|| anyhow!("Checkpoint has no ledger info."))?;
+        let version = ledger_info.ledger_info().version();
+        ensure!(
+            version <= self.target_version,
+            "Checkpoint is at version {}, which is newer than the target version {}.",
+            version,
+            self.target_version,
+        );
+        ensure!(
+            db.get_accumulator_root_hash(version)?
+                == ledger_info.ledger_info().transaction_accumulator_hash(),
+            "Accumulator root hash at version {} doesn't match the latest ledger info.",
+            version,
+        );
+
+        for (waypoint_version, wp_trusted) in &self.trusted_waypoints {
+            if *waypoint_version > version {
+                warn!(
+                    "Trusted waypoint {} is newer than the checkpoint, skipped.",
+                    wp_trusted,
+                );
+                continue;
+            }
+            let li = db.get_epoch_ending_ledger_info(*waypoint_version)?;
+            let wp_li = Waypoint::new_epoch_boundary(li.ledger_info())?;
+            ensure!(
+                *wp_trusted == wp_li,
+                "Waypoints don't match. In checkpoint: {}, trusted: {}",
+                wp_li,
+                wp_trusted,
+            );
+        }
+        info!(
+            version = version,
+            epoch = ledger_info.ledger_info().epoch(),
+            "Checkpoint verified."
+        );
+        Ok(())
+    }
+}
+
+/// Copies the directory `from` to `to`, which must not exist.
+fn copy_dir(from: &Path, to: &Path) -> Result<()> {
+    fs::create_dir(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let path = entry.path();
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&path, &target)?;
+        } else {
+            fs::copy(&path, &target)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,88 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backup_types::checkpoint::restore::{CheckpointRestoreController, CheckpointRestoreOpt},
+    utils::{
+        test_utils::tmp_db_with_random_content, ConcurrentDownloadsOpt, GlobalRestoreOpt,
+        RocksdbOpt, TrustedWaypointOpt,
+    },
+};
+use aptos_temppath::TempPath;
+use aptos_types::transaction::Version;
+use aptosdb::AptosDB;
+use std::path::Path;
+use storage_interface::DbReader;
+use tokio::runtime::Runtime;
+
+fn restore(
+    rt: &Runtime,
+    checkpoint_dir: &Path,
+    db_dir: Option<&Path>,
+    target_version: Option<Version>,
+    move_files: bool,
+) -> anyhow::Result<()> {
+    rt.block_on(
+        CheckpointRestoreController::new(
+            CheckpointRestoreOpt {
+                checkpoint_dir: checkpoint_dir.to_path_buf(),
+                move_files,
+            },
+            GlobalRestoreOpt {
+                dry_run: db_dir.is_none(),
+                db_dir: db_dir.map(Path::to_path_buf),
+                target_version,
+                trusted_waypoints: TrustedWaypointOpt::default(),
+                rocksdb_opt: RocksdbOpt::default(),
+                concurernt_downloads: ConcurrentDownloadsOpt::default(),
+            },
+        )?
+        .run(),
+    )
+}
+
+#[test]
+fn end_to_end() {
+    let (_src_db_dir, src_db, _blocks) = tmp_db_with_random_content();
+    let checkpoint_dir = TempPath::new();
+    checkpoint_dir.create_as_dir().unwrap();
+    let version = src_db
+        .create_checkpoint(checkpoint_dir.path())
+        .unwrap()
+        .unwrap();
+    let expected_root_hash = src_db.get_accumulator_root_hash(version).unwrap();
+    let rt = Runtime::new().unwrap();
+
+    // Dry run verifies the checkpoint in place.
+    restore(&rt, checkpoint_dir.path(), None, None, false).unwrap();
+    // The checkpoint is newer than the target version.
+    assert!(restore(&rt, checkpoint_dir.path(), None, Some(version - 1), false).is_err());
+
+    let tgt_db_dir = TempPath::new();
+    restore(
+        &rt,
+        checkpoint_dir.path(),
+        Some(tgt_db_dir.path()),
+        Some(version),
+        false,
+    )
+    .unwrap();
+    // Refuses to overwrite the restored DB.
+    assert!(restore(
+        &rt,
+        checkpoint_dir.path(),
+        Some(tgt_db_dir.path()),
+        None,
+        true
+    )
+    .is_err());
+    // The checkpoint is still there after copying it.
+    assert!(checkpoint_dir.path().join(aptosdb::LEDGER_DB_NAME).is_dir());
+
+    let tgt_db = AptosDB::new_for_test(&tgt_db_dir);
+    assert_eq!(tgt_db.get_latest_version_option().unwrap(), Some(version));
+    assert_eq!(
+        tgt_db.get_accumulator_root_hash(version).unwrap(),
+        expected_root_hash
+    );
+}
@@ -0,0 +1,174 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backup_types::indexer::manifest::{IndexerBackup, IndexerChunk},
+    metadata::Metadata,
+    storage::{BackupHandleRef, BackupStorage, FileHandle, ShellSafeName},
+    utils::{
+        backup_service_client::BackupServiceClient, read_record_bytes::ReadRecordBytes,
+        should_cut_chunk, GlobalBackupOpt,
+    },
+};
+use anyhow::{anyhow, Result};
+use aptos_logger::prelude::*;
+use aptos_types::transaction::Version;
+use once_cell::sync::Lazy;
+use std::{convert::TryInto, str::FromStr, sync::Arc};
+use structopt::StructOpt;
+use tokio::io::AsyncWriteExt;
+
+#[derive(StructOpt)]
+pub struct IndexerBackupOpt {}
+
+pub struct IndexerBackupController {
+    max_chunk_size: usize,
+    client: Arc<BackupServiceClient>,
+    storage: Arc<dyn BackupStorage>,
+}
+
+impl IndexerBackupController {
+    pub fn new(
+        _opt: IndexerBackupOpt,
+        global_opt: GlobalBackupOpt,
+        client: Arc<BackupServiceClient>,
+        storage: Arc<dyn BackupStorage>,
+    ) -> Self {
+        Self {
+            max_chunk_size: global_opt.max_chunk_size,
+            client,
+            storage,
+        }
+    }
+
+    /// Backs up the indexer DB, returning the version it's backed up at and the manifest.
+    pub async fn run(self) -> Result<(Version, FileHandle)> {
+        info!("Indexer backup started.");
+        let ret = self
+            .run_impl()
+            .await
+            .map_err(|e| anyhow!("Indexer backup failed: {}", e))?;
+        info!(
+            "Indexer backup succeeded, at version {}. Manifest: {}",
+            ret.0, ret.1
+        );
+        Ok(ret)
+    }
+
+    async fn run_impl(self) -> Result<(Version, FileHandle)> {
+        // The version must be read before the table infos, so that all tables created up to it
+        // are in the backup.
+        let version = self
+            .client
+            .get_indexer_latest_version()
+            .await?
+            .ok_or_else(|| anyhow!("Indexer hasn't processed any transaction."))?;
+        let backup_handle = self
+            .storage
+            .create_backup_with_random_suffix(&Self::backup_name(version))
+            .await?;
+
+        let mut chunks = vec![];
+        let mut table_infos_file = self.client.get_table_infos().await?;
+        let mut chunk_bytes = vec![];
+        let mut chunk_first_idx: usize = 0;
+        let mut current_idx: usize = 0;
+
+        while let Some(record_bytes) = table_infos_file.read_record_bytes().await? {
+            if should_cut_chunk(&chunk_bytes, &record_bytes, self.max_chunk_size) {
+                let chunk = self
+                    .write_chunk(
+                        &backup_handle,
+                        &chunk_bytes,
+                        chunk_first_idx,
+                        current_idx - 1,
+                    )
+                    .await?;
+                chunks.push(chunk);
+                chunk_bytes = vec![];
+                chunk_first_idx = current_idx;
+            }
+
+            current_idx += 1;
+            chunk_bytes.extend(&(record_bytes.len() as u32).to_be_bytes());
+            chunk_bytes.extend(&record_bytes);
+        }
+
+        if !chunk_bytes.is_empty() {
+            let chunk = self
+                .write_chunk(
+                    &backup_handle,
+                    &chunk_bytes,
+                    chunk_first_idx,
+                    current_idx - 1,
+                )
+                .await?;
+            chunks.push(chunk);
+        }
+
+        let manifest_handle = self.write_manifest(&backup_handle, version, chunks).await?;
+        Ok((version, manifest_handle))
+    }
+}
+
+impl IndexerBackupController {
+    fn backup_name(version: Version) -> String {
+        format!("indexer_ver_{}", version)
+    }
+
+    fn manifest_name() -> &'static ShellSafeName {
+        static NAME: Lazy<ShellSafeName> =
+            Lazy::new(|| ShellSafeName::from_str("indexer.manifest").unwrap());
+        &NAME
+    }
+
+    fn chunk_name(first_idx: usize) -> ShellSafeName {
+        format!("{}-.chunk", first_idx).try_into().unwrap()
+    }
+
+    async fn write_chunk(
+        &self,
+        backup_handle: &BackupHandleRef,
+        chunk_bytes: &[u8],
+        first_idx: usize,
+        last_idx: usize,
+    ) -> Result<IndexerChunk> {
+        let (chunk_handle, mut chunk_file) = self
+            .storage
+            .create_for_write(backup_handle, &Self::chunk_name(first_idx))
+            .await?;
+        chunk_file.write_all(chunk_bytes).await?;
+        chunk_file.shutdown().await?;
+
+        Ok(IndexerChunk {
+            first_idx,
+            last_idx,
+            table_infos: chunk_handle,
+        })
+    }
+
+    async fn write_manifest(
+        &self,
+        backup_handle: &BackupHandleRef,
+        version: Version,
+        chunks: Vec<IndexerChunk>,
+    ) -> Result<FileHandle> {
+        let manifest = IndexerBackup { version, chunks };
+
+        let (manifest_handle, mut manifest_file) = self
+            .storage
+            .create_for_write(backup_handle, Self::manifest_name())
+            .await?;
+        manifest_file
+            .write_all(&serde_json::to_vec(&manifest)?)
+            .await?;
+        manifest_file.shutdown().await?;
+
+        let metadata = Metadata::new_indexer_backup(version, manifest_handle.clone());
+        self.storage
+            .save_metadata_line(&metadata.name(), &metadata.to_text_line()?)
+            .await?;
+
+        Ok(manifest_handle)
+    }
+}
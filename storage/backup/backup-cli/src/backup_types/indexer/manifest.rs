@@ -0,0 +1,31 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::storage::FileHandle;
+use aptos_types::transaction::Version;
+use serde::{Deserialize, Serialize};
+
+/// A chunk of an indexer backup manifest, representing table infos [`first_idx`, `last_idx`]
+/// (right side inclusive) over all table infos, ordered by table handle.
+#[derive(Deserialize, Serialize)]
+pub struct IndexerChunk {
+    pub first_idx: usize,
+    pub last_idx: usize,
+    /// Repeated `len(record) + record` where `record` is BCS serialized tuple
+    /// `(TableHandle, TableInfo)`
+    pub table_infos: FileHandle,
+}
+
+/// Indexer DB backup manifest, representing the indexer after it has processed all transactions
+/// up to and including `version`.
+///
+/// Unlike the other backups, this carries no proofs: the table infos are derived from the
+/// transactions, not committed to by the ledger.
+#[derive(Deserialize, Serialize)]
+pub struct IndexerBackup {
+    /// Latest version processed by the indexer when the backup is taken.
+    pub version: Version,
+    /// All table infos in chunks. Table infos of tables created after `version` can be included,
+    /// which does no harm since table infos never change once created.
+    pub chunks: Vec<IndexerChunk>,
+}
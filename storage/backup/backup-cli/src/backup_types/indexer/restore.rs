@@ -0,0 +1,143 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backup_types::indexer::manifest::IndexerBackup,
+    metrics::restore::INDEXER_VERSION,
+    storage::{BackupStorage, FileHandle},
+    utils::{
+        read_record_bytes::ReadRecordBytes, storage_ext::BackupStorageExt, GlobalRestoreOptions,
+        IndexerDbTarget, RestoreRunMode,
+    },
+};
+use anyhow::{anyhow, ensure, Result};
+use aptos_logger::prelude::*;
+use aptos_types::{
+    state_store::table::{TableHandle, TableInfo},
+    transaction::Version,
+};
+use std::sync::Arc;
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+pub struct IndexerRestoreOpt {
+    #[structopt(long = "indexer-manifest")]
+    pub manifest_handle: FileHandle,
+}
+
+pub struct IndexerRestoreController {
+    storage: Arc<dyn BackupStorage>,
+    run_mode: Arc<RestoreRunMode>,
+    manifest_handle: FileHandle,
+    /// Global "target_version" for the entire restore process, if the backup is newer than this,
+    /// nothing will be done, since the indexer would claim to have processed transactions that
+    /// are not restored.
+    target_version: Version,
+    indexer_db: Option<IndexerDbTarget>,
+}
+
+impl IndexerRestoreController {
+    pub fn new(
+        opt: IndexerRestoreOpt,
+        global_opt: GlobalRestoreOptions,
+        storage: Arc<dyn BackupStorage>,
+    ) -> Self {
+        Self {
+            storage,
+            run_mode: global_opt.run_mode,
+            manifest_handle: opt.manifest_handle,
+            target_version: global_opt.target_version,
+            indexer_db: global_opt.indexer_db,
+        }
+    }
+
+    pub async fn run(self) -> Result<()> {
+        let name = self.name();
+        info!("{} started. Manifest: {}", name, self.manifest_handle);
+        self.run_impl()
+            .await
+            .map_err(|e| anyhow!("{} failed: {}", name, e))?;
+        info!("{} succeeded.", name);
+        Ok(())
+    }
+}
+
+impl IndexerRestoreController {
+    fn name(&self) -> String {
+        format!("indexer {}", self.run_mode.name())
+    }
+
+    async fn run_impl(self) -> Result<()> {
+        let manifest: IndexerBackup = self.storage.load_json_file(&self.manifest_handle).await?;
+        if manifest.version > self.target_version {
+            warn!(
+                "Trying to restore indexer at version {}, which is newer than the target version {}, skipping.",
+                manifest.version,
+                self.target_version,
+            );
+            return Ok(());
+        }
+
+        let indexer = match (self.run_mode.as_ref(), &self.indexer_db) {
+            (RestoreRunMode::Restore { .. }, Some(indexer_db)) => {
+                let indexer = indexer_db.open()?;
+                if let Some(latest_version) = indexer.latest_version() {
+                    if latest_version >= manifest.version {
+                        warn!(
+                            "Indexer DB is already at version {}, not older than the backup at version {}, skipping.",
+                            latest_version,
+                            manifest.version,
+                        );
+                        return Ok(());
+                    }
+                }
+                Some(indexer)
+            }
+            _ => None,
+        };
+
+        INDEXER_VERSION.set(manifest.version as i64);
+        let mut next_idx = 0;
+        for chunk in manifest.chunks {
+            ensure!(
+                chunk.first_idx == next_idx,
+                "Indexer backup chunks not continuous, expecting index {}, got {}.",
+                next_idx,
+                chunk.first_idx,
+            );
+            let table_infos = self.read_table_infos(chunk.table_infos).await?;
+            ensure!(
+                table_infos.len() == chunk.last_idx + 1 - chunk.first_idx,
+                "Indexer backup chunk has {} table infos, expecting {}.",
+                table_infos.len(),
+                chunk.last_idx + 1 - chunk.first_idx,
+            );
+            if let Some(indexer) = &indexer {
+                indexer.save_table_infos(&table_infos)?;
+            }
+            next_idx = chunk.last_idx + 1;
+        }
+
+        // Only mark the versions as indexed once all table infos are saved, so that an
+        // interrupted restore can be retried.
+        if let Some(indexer) = &indexer {
+            indexer.finish_restore(manifest.version)?;
+        }
+        Ok(())
+    }
+
+    async fn read_table_infos(
+        &self,
+        file_handle: FileHandle,
+    ) -> Result<Vec<(TableHandle, TableInfo)>> {
+        let mut file = self.storage.open_for_read(&file_handle).await?;
+
+        let mut chunk = vec![];
+
+        while let Some(record_bytes) = file.read_record_bytes().await? {
+            chunk.push(bcs::from_bytes(&record_bytes)?);
+        }
+
+        Ok(chunk)
+    }
+}
@@ -0,0 +1,178 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backup_types::indexer::{
+        backup::{IndexerBackupController, IndexerBackupOpt},
+        manifest::IndexerBackup,
+        restore::{IndexerRestoreController, IndexerRestoreOpt},
+    },
+    storage::{local_fs::LocalFs, BackupStorage, FileHandle},
+    utils::{
+        backup_service_client::BackupServiceClient, storage_ext::BackupStorageExt,
+        test_utils::start_local_backup_service, ConcurrentDownloadsOpt, GlobalBackupOpt,
+        GlobalRestoreOpt, IndexerDbTarget, RocksdbOpt, TrustedWaypointOpt,
+    },
+};
+use aptos_config::config::RocksdbConfig;
+use aptos_temppath::TempPath;
+use aptos_types::state_store::table::{TableHandle, TableInfo};
+use aptosdb::AptosDB;
+use aptosdb_indexer::Indexer;
+use move_deps::move_core_types::language_storage::TypeTag;
+use std::{convert::TryInto, mem::size_of, sync::Arc};
+use tokio::runtime::Runtime;
+
+fn table_infos(num: u128) -> Vec<(TableHandle, TableInfo)> {
+    (0..num)
+        .map(|i| {
+            (
+                TableHandle(i),
+                TableInfo {
+                    key_type: TypeTag::U64,
+                    value_type: if i % 2 == 0 {
+                        TypeTag::Address
+                    } else {
+                        TypeTag::Vector(Box::new(TypeTag::U8))
+                    },
+                },
+            )
+        })
+        .collect()
+}
+
+/// Creates a DB whose indexer has processed up to `version`, knowing about `table_infos`.
+fn tmp_db_with_indexer_content(
+    version: u64,
+    table_infos: &[(TableHandle, TableInfo)],
+) -> (TempPath, Arc<AptosDB>) {
+    let tmpdir = TempPath::new();
+    tmpdir.create_as_dir().unwrap();
+    {
+        let indexer = Indexer::open(tmpdir.path(), RocksdbConfig::default()).unwrap();
+        indexer.save_table_infos(table_infos).unwrap();
+        indexer.finish_restore(version).unwrap();
+    }
+    let db = Arc::new(AptosDB::new_for_test_with_indexer(&tmpdir));
+    (tmpdir, db)
+}
+
+fn restore(
+    rt: &Runtime,
+    store: &Arc<dyn BackupStorage>,
+    manifest_handle: FileHandle,
+    db_dir: &TempPath,
+    target_version: Option<u64>,
+) {
+    rt.block_on(
+        IndexerRestoreController::new(
+            IndexerRestoreOpt { manifest_handle },
+            GlobalRestoreOpt {
+                dry_run: false,
+                db_dir: Some(db_dir.path().to_path_buf()),
+                target_version,
+                trusted_waypoints: TrustedWaypointOpt::default(),
+                rocksdb_opt: RocksdbOpt::default(),
+                concurernt_downloads: ConcurrentDownloadsOpt::default(),
+            }
+            .try_into()
+            .unwrap(),
+            Arc::clone(store),
+        )
+        .run(),
+    )
+    .unwrap();
+}
+
+#[test]
+fn end_to_end() {
+    let table_infos = table_infos(10);
+    let (_src_db_dir, src_db) = tmp_db_with_indexer_content(100, &table_infos);
+    let tgt_db_dir = TempPath::new();
+    tgt_db_dir.create_as_dir().unwrap();
+    let backup_dir = TempPath::new();
+    backup_dir.create_as_dir().unwrap();
+    let store: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(backup_dir.path().to_path_buf()));
+
+    let (rt, port) = start_local_backup_service(src_db);
+    let client = Arc::new(BackupServiceClient::new(format!(
+        "http://localhost:{}",
+        port
+    )));
+
+    // Fit 3 table infos in a chunk
+    let max_chunk_size = 3 * table_infos
+        .iter()
+        .map(|table_info| bcs::to_bytes(table_info).unwrap().len() + size_of::<u32>())
+        .max()
+        .unwrap();
+    let (version, manifest_handle) = rt
+        .block_on(
+            IndexerBackupController::new(
+                IndexerBackupOpt {},
+                GlobalBackupOpt { max_chunk_size },
+                client,
+                Arc::clone(&store),
+            )
+            .run(),
+        )
+        .unwrap();
+    assert_eq!(version, 100);
+    let manifest: IndexerBackup = rt.block_on(store.load_json_file(&manifest_handle)).unwrap();
+    assert_eq!(manifest.version, 100);
+    assert_eq!(
+        manifest
+            .chunks
+            .iter()
+            .map(|chunk| (chunk.first_idx, chunk.last_idx))
+            .collect::<Vec<_>>(),
+        vec![(0, 2), (3, 5), (6, 8), (9, 9)]
+    );
+
+    // Newer than the target version, nothing is restored.
+    restore(&rt, &store, manifest_handle.clone(), &tgt_db_dir, Some(99));
+    let target = IndexerDbTarget::new(tgt_db_dir.path().to_path_buf(), RocksdbConfig::default());
+    {
+        let indexer = target.open().unwrap();
+        assert_eq!(indexer.latest_version(), None);
+        assert_eq!(indexer.get_table_info(TableHandle(0)).unwrap(), None);
+    }
+
+    restore(&rt, &store, manifest_handle, &tgt_db_dir, None);
+    let indexer = target.open().unwrap();
+    assert_eq!(indexer.latest_version(), Some(100));
+    let restored = indexer
+        .get_table_info_iter()
+        .unwrap()
+        .collect::<anyhow::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(restored, table_infos);
+}
+
+#[test]
+fn backup_fails_without_indexer() {
+    let tmpdir = TempPath::new();
+    let src_db = Arc::new(AptosDB::new_for_test(&tmpdir));
+    let backup_dir = TempPath::new();
+    backup_dir.create_as_dir().unwrap();
+    let store: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(backup_dir.path().to_path_buf()));
+
+    let (rt, port) = start_local_backup_service(src_db);
+    let client = Arc::new(BackupServiceClient::new(format!(
+        "http://localhost:{}",
+        port
+    )));
+
+    rt.block_on(
+        IndexerBackupController::new(
+            IndexerBackupOpt {},
+            GlobalBackupOpt {
+                max_chunk_size: 1024,
+            },
+            client,
+            store,
+        )
+        .run(),
+    )
+    .unwrap_err();
+}
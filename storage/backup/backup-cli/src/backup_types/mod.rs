@@ -1,7 +1,9 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod checkpoint;
 pub mod epoch_ending;
+pub mod indexer;
 pub mod state_snapshot;
 pub mod transaction;
 
@@ -5,7 +5,10 @@ use anyhow::Result;
 use aptos_logger::{prelude::*, Level, Logger};
 use aptos_secure_push_metrics::MetricsPusher;
 use backup_cli::{
-    coordinators::verify::VerifyCoordinator,
+    coordinators::{
+        continuous_verify::{ContinuousVerifyCoordinator, ContinuousVerifyOpt},
+        verify::VerifyCoordinator,
+    },
     metadata::cache::MetadataCacheOpt,
     storage::StorageOpt,
     utils::{ConcurrentDownloadsOpt, TrustedWaypointOpt},
@@ -22,6 +25,8 @@ struct Opt {
     storage: StorageOpt,
     #[structopt(flatten)]
     concurrent_downloads: ConcurrentDownloadsOpt,
+    #[structopt(flatten)]
+    continuous_verify_opt: ContinuousVerifyOpt,
 }
 
 #[tokio::main]
@@ -37,6 +42,17 @@ async fn main_impl() -> Result<()> {
     let _mp = MetricsPusher::start();
 
     let opt = Opt::from_args();
+    if opt.continuous_verify_opt.continuous {
+        return ContinuousVerifyCoordinator::new(
+            opt.storage.init_storage().await?,
+            opt.metadata_cache_opt,
+            opt.trusted_waypoints_opt,
+            opt.concurrent_downloads.get(),
+            opt.continuous_verify_opt,
+        )?
+        .run()
+        .await;
+    }
     VerifyCoordinator::new(
         opt.storage.init_storage().await?,
         opt.metadata_cache_opt,
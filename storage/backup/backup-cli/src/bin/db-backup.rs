@@ -11,11 +11,16 @@ use aptos_secure_push_metrics::MetricsPusher;
 use backup_cli::{
     backup_types::{
         epoch_ending::backup::{EpochEndingBackupController, EpochEndingBackupOpt},
+        indexer::backup::{IndexerBackupController, IndexerBackupOpt},
         state_snapshot::backup::{StateSnapshotBackupController, StateSnapshotBackupOpt},
         transaction::backup::{TransactionBackupController, TransactionBackupOpt},
     },
     coordinators::backup::{BackupCoordinator, BackupCoordinatorOpt},
-    metadata::{cache, cache::MetadataCacheOpt},
+    metadata::{
+        cache,
+        cache::MetadataCacheOpt,
+        compaction::{MetadataCompactionController, MetadataCompactionOpt},
+    },
     storage::StorageOpt,
     utils::{
         backup_service_client::{BackupServiceClient, BackupServiceClientOpt},
@@ -38,6 +43,11 @@ enum OneShotCommand {
     Query(OneShotQueryType),
     #[structopt(about = "Do a one shot backup.")]
     Backup(OneShotBackupOpt),
+    #[structopt(
+        about = "Merge the metadata files in the storage into a few compacted ones, deleting \
+        expired backups on the way if a retention policy is given."
+    )]
+    CompactMetadata(OneShotCompactMetadataOpt),
 }
 
 #[derive(StructOpt)]
@@ -69,6 +79,18 @@ struct OneShotQueryBackupStorageStateOpt {
     storage: StorageOpt,
 }
 
+#[derive(StructOpt)]
+struct OneShotCompactMetadataOpt {
+    #[structopt(flatten)]
+    compaction: MetadataCompactionOpt,
+    #[structopt(flatten)]
+    metadata_cache: MetadataCacheOpt,
+    #[structopt(flatten)]
+    concurrent_downloads: ConcurrentDownloadsOpt,
+    #[structopt(subcommand)]
+    storage: StorageOpt,
+}
+
 #[derive(StructOpt)]
 struct OneShotBackupOpt {
     #[structopt(flatten)]
@@ -101,6 +123,12 @@ enum BackupType {
         #[structopt(subcommand)]
         storage: StorageOpt,
     },
+    Indexer {
+        #[structopt(flatten)]
+        opt: IndexerBackupOpt,
+        #[structopt(subcommand)]
+        storage: StorageOpt,
+    },
 }
 
 #[derive(StructOpt)]
@@ -158,6 +186,16 @@ async fn main_impl() -> Result<()> {
                     println!("{}", view.get_storage_state())
                 }
             },
+            OneShotCommand::CompactMetadata(opt) => {
+                MetadataCompactionController::new(
+                    opt.compaction,
+                    opt.metadata_cache,
+                    opt.storage.init_storage().await?,
+                    opt.concurrent_downloads.get(),
+                )
+                .run()
+                .await?;
+            }
             OneShotCommand::Backup(opt) => {
                 let client = Arc::new(BackupServiceClient::new_with_opt(opt.client));
                 let global_opt = opt.global;
@@ -193,6 +231,16 @@ async fn main_impl() -> Result<()> {
                         .run()
                         .await?;
                     }
+                    BackupType::Indexer { opt, storage } => {
+                        IndexerBackupController::new(
+                            opt,
+                            global_opt,
+                            client,
+                            storage.init_storage().await?,
+                        )
+                        .run()
+                        .await?;
+                    }
                 }
             }
         },
@@ -6,7 +6,9 @@ use aptos_logger::{prelude::*, Level, Logger};
 use aptos_secure_push_metrics::MetricsPusher;
 use backup_cli::{
     backup_types::{
+        checkpoint::restore::{CheckpointRestoreController, CheckpointRestoreOpt},
         epoch_ending::restore::{EpochEndingRestoreController, EpochEndingRestoreOpt},
+        indexer::restore::{IndexerRestoreController, IndexerRestoreOpt},
         state_snapshot::restore::{StateSnapshotRestoreController, StateSnapshotRestoreOpt},
         transaction::restore::{TransactionRestoreController, TransactionRestoreOpt},
     },
@@ -46,12 +48,23 @@ enum RestoreType {
         #[structopt(subcommand)]
         storage: StorageOpt,
     },
+    Indexer {
+        #[structopt(flatten)]
+        opt: IndexerRestoreOpt,
+        #[structopt(subcommand)]
+        storage: StorageOpt,
+    },
     Auto {
         #[structopt(flatten)]
         opt: RestoreCoordinatorOpt,
         #[structopt(subcommand)]
         storage: StorageOpt,
     },
+    /// Bootstrap the DB from a checkpoint created by the backup service, instead of from backups.
+    Checkpoint {
+        #[structopt(flatten)]
+        opt: CheckpointRestoreOpt,
+    },
 }
 
 #[tokio::main]
@@ -67,6 +80,16 @@ async fn main_impl() -> Result<()> {
     let _mp = MetricsPusher::start();
 
     let opt = Opt::from_args();
+    // The target DB must not exist before restoring a checkpoint, so it's handled before opening
+    // the DB below.
+    if let RestoreType::Checkpoint {
+        opt: checkpoint_opt,
+    } = opt.restore_type
+    {
+        return CheckpointRestoreController::new(checkpoint_opt, opt.global)?
+            .run()
+            .await;
+    }
     let global_opt: GlobalRestoreOptions = opt.global.clone().try_into()?;
 
     match opt.restore_type {
@@ -95,11 +118,17 @@ async fn main_impl() -> Result<()> {
             .run()
             .await?;
         }
+        RestoreType::Indexer { opt, storage } => {
+            IndexerRestoreController::new(opt, global_opt, storage.init_storage().await?)
+                .run()
+                .await?;
+        }
         RestoreType::Auto { opt, storage } => {
             RestoreCoordinator::new(opt, global_opt, storage.init_storage().await?)
                 .run()
                 .await?;
         }
+        RestoreType::Checkpoint { .. } => unreachable!("Handled above."),
     }
 
     Ok(())
@@ -60,6 +60,8 @@ async fn main_impl() -> Result<()> {
         NO_OP_STORAGE_PRUNER_CONFIG, /* pruner config */
         opt.rocksdb_opt.into(),
         false,
+        false,
+        false,
         TARGET_SNAPSHOT_SIZE,
     )?)
     .get_restore_handler();
@@ -4,13 +4,15 @@
 use crate::{
     backup_types::{
         epoch_ending::backup::{EpochEndingBackupController, EpochEndingBackupOpt},
+        indexer::backup::{IndexerBackupController, IndexerBackupOpt},
         state_snapshot::backup::{StateSnapshotBackupController, StateSnapshotBackupOpt},
         transaction::backup::{TransactionBackupController, TransactionBackupOpt},
     },
     metadata,
     metadata::cache::MetadataCacheOpt,
     metrics::backup::{
-        EPOCH_ENDING_EPOCH, HEARTBEAT_TS, STATE_SNAPSHOT_VERSION, TRANSACTION_VERSION,
+        EPOCH_ENDING_EPOCH, HEARTBEAT_TS, INDEXER_VERSION, STATE_SNAPSHOT_VERSION,
+        TRANSACTION_VERSION,
     },
     storage::BackupStorage,
     utils::{
@@ -46,6 +48,14 @@ pub struct BackupCoordinatorOpt {
     // slower than expected.
     #[structopt(long, default_value = "100000")]
     pub transaction_batch_size: usize,
+    // The indexer DB can be rebuilt by replaying all transactions, so backing it up is only an
+    // optimization for restoring nodes that run the indexer, and is off unless an interval is set.
+    #[structopt(
+        long,
+        help = "Back up the indexer DB of the node every this many versions. The node must have \
+        the indexer enabled. [Defaults to not backing up the indexer DB]"
+    )]
+    pub indexer_backup_interval: Option<usize>,
     #[structopt(flatten)]
     pub concurernt_downloads: ConcurrentDownloadsOpt,
 }
@@ -56,6 +66,10 @@ impl BackupCoordinatorOpt {
             self.state_snapshot_interval > 0 && self.transaction_batch_size > 0,
             "Backup interval and batch size must be greater than 0."
         );
+        ensure!(
+            self.indexer_backup_interval != Some(0),
+            "Indexer backup interval must be greater than 0."
+        );
         ensure!(
             self.state_snapshot_interval % self.transaction_batch_size == 0,
             "State snapshot interval should be N x transaction_batch_size, N >= 1. \
@@ -74,6 +88,7 @@ pub struct BackupCoordinator {
     metadata_cache_opt: MetadataCacheOpt,
     state_snapshot_interval: usize,
     transaction_batch_size: usize,
+    indexer_backup_interval: Option<usize>,
     concurrent_downloads: usize,
 }
 
@@ -92,18 +107,19 @@ impl BackupCoordinator {
             metadata_cache_opt: opt.metadata_cache_opt,
             state_snapshot_interval: opt.state_snapshot_interval,
             transaction_batch_size: opt.transaction_batch_size,
+            indexer_backup_interval: opt.indexer_backup_interval,
             concurrent_downloads: opt.concurernt_downloads.get(),
         }
     }
     pub async fn run(&self) -> Result<()> {
         // Connect to both the local node and the backup storage.
-        let backup_state = metadata::cache::sync_and_load(
+        let metadata_view = metadata::cache::sync_and_load(
             &self.metadata_cache_opt,
             Arc::clone(&self.storage),
             self.concurrent_downloads,
         )
-        .await?
-        .get_storage_state();
+        .await?;
+        let backup_state = metadata_view.get_storage_state();
 
         // On new DbState retrieved:
         // `watch_db_state` informs `backup_epoch_endings` via channel 1,
@@ -149,6 +165,16 @@ impl BackupCoordinator {
             backup_state_snapshots,
             backup_transactions,
         ]);
+        if self.indexer_backup_interval.is_some() {
+            all_work.push(
+                self.backup_work_stream(
+                    metadata_view.latest_indexer_version(),
+                    &rx2,
+                    Self::backup_indexer,
+                )
+                .boxed_local(),
+            );
+        }
 
         loop {
             all_work
@@ -286,6 +312,43 @@ impl BackupCoordinator {
         }
     }
 
+    async fn backup_indexer(
+        &self,
+        last_indexer_version_in_backup: Option<Version>,
+        db_state: DbState,
+    ) -> Result<Option<Version>> {
+        if let Some(version) = last_indexer_version_in_backup {
+            INDEXER_VERSION.set(version as i64);
+        }
+        let interval = self
+            .indexer_backup_interval
+            .expect("Indexer backup not enabled.");
+        if let Some(last) = last_indexer_version_in_backup {
+            // The indexer can lag behind the ledger, so check the indexer itself once the ledger
+            // is far enough ahead.
+            if db_state.committed_version < last + interval as u64 {
+                // wait for the next db_state update
+                return Ok(last_indexer_version_in_backup);
+            }
+            match self.client.get_indexer_latest_version().await? {
+                Some(indexer_version) if indexer_version >= last + interval as u64 => (),
+                // wait for the indexer to catch up
+                _ => return Ok(last_indexer_version_in_backup),
+            }
+        }
+
+        let (version, _manifest) = IndexerBackupController::new(
+            IndexerBackupOpt {},
+            self.global_opt.clone(),
+            Arc::clone(&self.client),
+            Arc::clone(&self.storage),
+        )
+        .run()
+        .await?;
+
+        Ok(Some(version))
+    }
+
     fn backup_work_stream<'a, S, W, Fut>(
         &'a self,
         initial_state: S,
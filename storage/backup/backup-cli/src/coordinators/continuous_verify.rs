@@ -0,0 +1,410 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backup_types::{
+        epoch_ending::restore::{
+            EpochEndingRestoreController, EpochEndingRestoreOpt, EpochHistory,
+        },
+        state_snapshot::restore::{StateSnapshotRestoreController, StateSnapshotRestoreOpt},
+        transaction::restore::TransactionRestoreBatchController,
+    },
+    coordinators::replay_verify::ReplayVerifyCoordinator,
+    metadata,
+    metadata::{cache::MetadataCacheOpt, view::MetadataView},
+    metrics::verify::{
+        VERIFY_COORDINATOR_FAIL_TS, VERIFY_COORDINATOR_START_TS, VERIFY_COORDINATOR_SUCC_TS,
+        VERIFY_FAILED_BACKUPS, VERIFY_PENDING_BACKUPS, VERIFY_REPLAY_FAIL_TS,
+        VERIFY_REPLAY_SUCC_TS, VERIFY_REPLAY_VERSION, VERIFY_VERIFIED_EPOCH,
+        VERIFY_VERIFIED_STATE_SNAPSHOT_VERSION, VERIFY_VERIFIED_TRANSACTION_VERSION,
+    },
+    storage::{BackupStorage, FileHandle},
+    utils::{
+        unix_timestamp_sec, GlobalRestoreOptions, RestoreRunMode, RocksdbOpt, TrustedWaypointOpt,
+    },
+};
+use anyhow::{anyhow, ensure, Result};
+use aptos_config::config::{RocksdbConfigs, NO_OP_STORAGE_PRUNER_CONFIG, TARGET_SNAPSHOT_SIZE};
+use aptos_logger::prelude::*;
+use aptos_temppath::TempPath;
+use aptos_types::{ledger_info::LedgerInfo, transaction::Version};
+use aptosdb::{AptosDB, GetRestoreHandler};
+use rand::Rng;
+use std::{collections::HashSet, path::PathBuf, sync::Arc};
+use structopt::StructOpt;
+use tokio::time::Duration;
+
+#[derive(StructOpt)]
+pub struct ContinuousVerifyOpt {
+    #[structopt(
+        long,
+        help = "Keep running and verify backups as they are written, instead of verifying all \
+        backups once and exiting."
+    )]
+    pub continuous: bool,
+
+    #[structopt(
+        long,
+        default_value = "300",
+        help = "With --continuous, seconds to wait between rounds of verification."
+    )]
+    pub verify_interval_secs: u64,
+
+    // Replaying restores a whole state snapshot to start from, so it's much more expensive than
+    // verifying proofs, hence only a sample of the transactions is replayed.
+    #[structopt(
+        long,
+        default_value = "1",
+        help = "With --continuous, number of random transaction ranges to replay in each round. \
+        Each range starts right after a random verified state snapshot."
+    )]
+    pub replay_samples_per_round: usize,
+
+    #[structopt(
+        long,
+        default_value = "1000",
+        help = "With --continuous, maximum number of transactions replayed in each range."
+    )]
+    pub replay_sample_size: usize,
+
+    #[structopt(
+        long,
+        parse(from_os_str),
+        help = "[Defaults to temporary dir] With --continuous, where to restore the DB for each \
+        replayed range, which is deleted once replayed."
+    )]
+    pub replay_scratch_dir: Option<PathBuf>,
+
+    #[structopt(flatten)]
+    pub rocksdb_opt: RocksdbOpt,
+}
+
+/// Verifies backups as they are written, until killed.
+///
+/// Each round verifies the epoch ending backups written since the last round on top of the epoch
+/// history verified so far, and the state snapshot and transaction backups not yet verified, as
+/// long as they are covered by that history. Backups that fail to verify are retried in the next
+/// round. On top of that, transaction ranges starting right after random verified state snapshots
+/// are replayed on top of them. What's verified is only kept in memory, so everything is verified again after a
+/// restart.
+pub struct ContinuousVerifyCoordinator {
+    storage: Arc<dyn BackupStorage>,
+    metadata_cache_opt: MetadataCacheOpt,
+    trusted_waypoints_opt: TrustedWaypointOpt,
+    concurrent_downloads: usize,
+    verify_interval: Duration,
+    replay_samples_per_round: usize,
+    replay_sample_size: usize,
+    replay_scratch_dir: Option<PathBuf>,
+    rocksdb_configs: RocksdbConfigs,
+    global_opt: GlobalRestoreOptions,
+    /// Epoch ending ledger infos verified so far, since epoch 0.
+    epoch_endings: Vec<LedgerInfo>,
+    /// Manifests of all backups verified so far.
+    verified: HashSet<FileHandle>,
+}
+
+impl ContinuousVerifyCoordinator {
+    pub fn new(
+        storage: Arc<dyn BackupStorage>,
+        metadata_cache_opt: MetadataCacheOpt,
+        trusted_waypoints_opt: TrustedWaypointOpt,
+        concurrent_downloads: usize,
+        opt: ContinuousVerifyOpt,
+    ) -> Result<Self> {
+        ensure!(
+            opt.replay_sample_size > 0,
+            "--replay-sample-size must be positive."
+        );
+        let global_opt = GlobalRestoreOptions {
+            target_version: Version::max_value(),
+            trusted_waypoints: Arc::new(trusted_waypoints_opt.verify()?),
+            run_mode: Arc::new(RestoreRunMode::Verify),
+            concurrent_downloads,
+            indexer_db: None,
+        };
+        Ok(Self {
+            storage,
+            metadata_cache_opt,
+            trusted_waypoints_opt,
+            concurrent_downloads,
+            verify_interval: Duration::from_secs(opt.verify_interval_secs),
+            replay_samples_per_round: opt.replay_samples_per_round,
+            replay_sample_size: opt.replay_sample_size,
+            replay_scratch_dir: opt.replay_scratch_dir,
+            rocksdb_configs: opt.rocksdb_opt.into(),
+            global_opt,
+            epoch_endings: Vec::new(),
+            verified: HashSet::new(),
+        })
+    }
+
+    pub async fn run(mut self) -> Result<()> {
+        info!("Continuous verify coordinator started.");
+        VERIFY_COORDINATOR_START_TS.set(unix_timestamp_sec());
+
+        loop {
+            if let Err(e) = self.run_round().await {
+                error!(
+                    error = ?e,
+                    "Continuous verify round failed."
+                );
+                VERIFY_COORDINATOR_FAIL_TS.set(unix_timestamp_sec());
+            } else {
+                info!("Continuous verify round succeeded.");
+                VERIFY_COORDINATOR_SUCC_TS.set(unix_timestamp_sec());
+            }
+            tokio::time::sleep(self.verify_interval).await;
+        }
+    }
+}
+
+impl ContinuousVerifyCoordinator {
+    /// Verifies what's new in the storage since the last round, and replays the samples.
+    pub(crate) async fn run_round(&mut self) -> Result<()> {
+        let metadata_view = metadata::cache::sync_and_load(
+            &self.metadata_cache_opt,
+            Arc::clone(&self.storage),
+            self.concurrent_downloads,
+        )
+        .await?;
+
+        // A broken chain of epoch ending backups stops the verification of anything newer, since
+        // the signatures can't be verified without it.
+        let epoch_result = self.verify_epoch_endings(&metadata_view).await;
+        let epoch_history = Arc::new(EpochHistory {
+            epoch_endings: self.epoch_endings.clone(),
+        });
+        if let Some(li) = self.epoch_endings.last() {
+            VERIFY_VERIFIED_EPOCH.set(li.epoch() as i64);
+        }
+        // Backups are signed by the ledger info of the epoch they end in, so only those up to the
+        // last verified epoch ending are sure to be verifiable.
+        let max_version = match self.epoch_endings.last() {
+            Some(li) => li.version(),
+            None => {
+                epoch_result?;
+                info!("No epoch ending backups to verify the others with yet.");
+                return Ok(());
+            }
+        };
+
+        let mut num_pending = 0;
+        let mut num_failed = 0;
+        let ver_max = Version::max_value();
+        let state_snapshots = metadata_view.select_state_snapshots(ver_max);
+        for backup in state_snapshots {
+            if self.verified.contains(&backup.manifest) {
+                continue;
+            }
+            if backup.version > max_version {
+                num_pending += 1;
+                continue;
+            }
+            let res = StateSnapshotRestoreController::new(
+                StateSnapshotRestoreOpt {
+                    manifest_handle: backup.manifest.clone(),
+                    version: backup.version,
+                },
+                self.global_opt.clone(),
+                Arc::clone(&self.storage),
+                Some(Arc::clone(&epoch_history)),
+            )
+            .run()
+            .await;
+            if let Err(e) = res {
+                error!(
+                    error = ?e,
+                    manifest = %backup.manifest,
+                    "State snapshot backup failed to verify."
+                );
+                num_failed += 1;
+                continue;
+            }
+            VERIFY_VERIFIED_STATE_SNAPSHOT_VERSION.set(backup.version as i64);
+            self.verified.insert(backup.manifest);
+        }
+
+        // This checks the transaction backups are continuous.
+        let transactions = metadata_view.select_transaction_backups(0, ver_max)?;
+        let mut verified_transaction_version = None;
+        for backup in &transactions {
+            if self.verified.contains(&backup.manifest) {
+                verified_transaction_version = Some(backup.last_version);
+                continue;
+            }
+            if backup.last_version > max_version {
+                num_pending += 1;
+                continue;
+            }
+            let res = TransactionRestoreBatchController::new(
+                self.global_opt.clone(),
+                Arc::clone(&self.storage),
+                vec![backup.manifest.clone()],
+                None, /* replay_from_version */
+                Some(Arc::clone(&epoch_history)),
+            )
+            .run()
+            .await;
+            if let Err(e) = res {
+                error!(
+                    error = ?e,
+                    manifest = %backup.manifest,
+                    "Transaction backup failed to verify."
+                );
+                num_failed += 1;
+                continue;
+            }
+            verified_transaction_version = Some(backup.last_version);
+            self.verified.insert(backup.manifest.clone());
+        }
+        if let Some(version) = verified_transaction_version {
+            VERIFY_VERIFIED_TRANSACTION_VERSION.set(version as i64);
+        }
+        VERIFY_PENDING_BACKUPS.set(num_pending);
+        VERIFY_FAILED_BACKUPS.set(num_failed);
+
+        let replay_result = match (transactions.first(), verified_transaction_version) {
+            (Some(first), Some(last_version)) => {
+                self.replay_samples(&metadata_view, first.first_version, last_version)
+                    .await
+            }
+            _ => Ok(()),
+        };
+
+        epoch_result?;
+        ensure!(num_failed == 0, "{} backups failed to verify.", num_failed);
+        replay_result
+    }
+
+    /// Extends the verified epoch history with the epoch ending backups not verified yet.
+    async fn verify_epoch_endings(&mut self, metadata_view: &MetadataView) -> Result<()> {
+        // This checks the epoch ending backups are continuous.
+        let epoch_endings = metadata_view.select_epoch_ending_backups(Version::max_value())?;
+        for backup in epoch_endings {
+            if self.verified.contains(&backup.manifest) {
+                continue;
+            }
+            let lis = EpochEndingRestoreController::new(
+                EpochEndingRestoreOpt {
+                    manifest_handle: backup.manifest.clone(),
+                },
+                self.global_opt.clone(),
+                Arc::clone(&self.storage),
+            )
+            .run(self.epoch_endings.last())
+            .await?;
+            for li in &lis {
+                ensure!(
+                    li.epoch() == self.epoch_endings.len() as u64,
+                    "Epoch ending backup {} has epoch {}, expecting {}.",
+                    backup.manifest,
+                    li.epoch(),
+                    self.epoch_endings.len(),
+                );
+                ensure!(
+                    li.ends_epoch(),
+                    "LedgerInfo is not one at an epoch ending. epoch: {}",
+                    li.epoch(),
+                );
+                self.epoch_endings.push(li.clone());
+            }
+            self.verified.insert(backup.manifest);
+        }
+        Ok(())
+    }
+
+    /// Replays ranges of the verified transactions, each one into a new DB restored from a random
+    /// verified state snapshot, starting right after it so that the cost is bound by the sample
+    /// size.
+    async fn replay_samples(
+        &self,
+        metadata_view: &MetadataView,
+        first_version: Version,
+        last_version: Version,
+    ) -> Result<()> {
+        // Older transactions can have been expired by the retention policy, in which case the
+        // state snapshots before them can't be replayed from.
+        let state_snapshots = metadata_view
+            .select_state_snapshots(last_version)
+            .into_iter()
+            .filter(|s| {
+                s.version + 1 >= first_version
+                    && s.version < last_version
+                    && self.verified.contains(&s.manifest)
+            })
+            .collect::<Vec<_>>();
+        if state_snapshots.is_empty() {
+            info!("No verified state snapshot to replay transactions from.");
+            return Ok(());
+        }
+
+        let mut num_failed = 0;
+        for _ in 0..self.replay_samples_per_round {
+            let snapshot_index = rand::thread_rng().gen_range(0, state_snapshots.len());
+            let start_version = state_snapshots[snapshot_index].version + 1;
+            let end_version = std::cmp::min(
+                start_version + self.replay_sample_size as Version - 1,
+                last_version,
+            );
+
+            info!(
+                start_version = start_version,
+                end_version = end_version,
+                "Replaying sampled transactions."
+            );
+            if let Err(e) = self.replay(start_version, end_version).await {
+                error!(
+                    error = ?e,
+                    start_version = start_version,
+                    end_version = end_version,
+                    "Sampled transactions failed to replay."
+                );
+                VERIFY_REPLAY_FAIL_TS.set(unix_timestamp_sec());
+                num_failed += 1;
+                continue;
+            }
+            VERIFY_REPLAY_VERSION.set(end_version as i64);
+            VERIFY_REPLAY_SUCC_TS.set(unix_timestamp_sec());
+        }
+
+        ensure!(
+            num_failed == 0,
+            "{} sampled transaction ranges failed to replay.",
+            num_failed,
+        );
+        Ok(())
+    }
+
+    async fn replay(&self, start_version: Version, end_version: Version) -> Result<()> {
+        let db_dir = match &self.replay_scratch_dir {
+            Some(dir) => TempPath::new_with_temp_dir(dir.clone()),
+            None => TempPath::new(),
+        };
+        db_dir.create_as_dir()?;
+        let restore_handler = Arc::new(AptosDB::open(
+            db_dir.path(),
+            false,                       /* read_only */
+            NO_OP_STORAGE_PRUNER_CONFIG, /* pruner config */
+            self.rocksdb_configs,
+            false,
+            false,
+            false,
+            TARGET_SNAPSHOT_SIZE,
+        )?)
+        .get_restore_handler();
+
+        ReplayVerifyCoordinator::new(
+            Arc::clone(&self.storage),
+            self.metadata_cache_opt.clone(),
+            self.trusted_waypoints_opt.clone(),
+            self.concurrent_downloads,
+            restore_handler,
+            start_version,
+            end_version,
+        )?
+        .run()
+        .await
+        .map_err(|e| anyhow!("Replaying {}-{}: {}", start_version, end_version, e))
+    }
+}
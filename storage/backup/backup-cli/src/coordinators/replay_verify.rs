@@ -88,6 +88,15 @@ impl ReplayVerifyCoordinator {
             .unwrap_or(0);
         let transactions = metadata_view
             .select_transaction_backups(replay_transactions_from_version, self.end_version)?;
+        // Older transaction backups can have been expired by the retention policy.
+        if let Some(first_version) = transactions.first().map(|t| t.first_version) {
+            ensure!(
+                first_version <= replay_transactions_from_version,
+                "Transaction backups start at version {}, too late to replay from version {}.",
+                first_version,
+                replay_transactions_from_version,
+            );
+        }
 
         let global_opt = GlobalRestoreOptions {
             target_version: self.end_version,
@@ -96,6 +105,7 @@ impl ReplayVerifyCoordinator {
                 restore_handler: self.restore_handler,
             }),
             concurrent_downloads: self.concurrent_downloads,
+            indexer_db: None,
         };
 
         if let Some(backup) = state_snapshot {
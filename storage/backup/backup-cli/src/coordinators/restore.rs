@@ -4,6 +4,7 @@
 use crate::{
     backup_types::{
         epoch_ending::restore::EpochHistoryRestoreController,
+        indexer::restore::{IndexerRestoreController, IndexerRestoreOpt},
         state_snapshot::restore::{StateSnapshotRestoreController, StateSnapshotRestoreOpt},
         transaction::restore::TransactionRestoreBatchController,
     },
@@ -15,7 +16,7 @@ use crate::{
     storage::BackupStorage,
     utils::{unix_timestamp_sec, GlobalRestoreOptions, RestoreRunMode},
 };
-use anyhow::{bail, Result};
+use anyhow::{bail, ensure, Result};
 use aptos_logger::prelude::*;
 use aptos_types::transaction::Version;
 use std::sync::Arc;
@@ -107,6 +108,13 @@ impl RestoreCoordinator {
             Some(b) => b.version + 1,
             None => 0,
         };
+        // The node catches the indexer up by replaying write sets after the indexer backup, so
+        // the backup must be no older than the state snapshot, after which write sets are
+        // restored.
+        let indexer = metadata_view.select_indexer_backup(
+            state_snapshot.as_ref().map_or(0, |s| s.version),
+            actual_target_version,
+        );
         COORDINATOR_TARGET_VERSION.set(actual_target_version as i64);
         info!("Planned to restore to version {}.", actual_target_version);
 
@@ -127,6 +135,15 @@ impl RestoreCoordinator {
             .into_iter()
             .skip_while(|p| p.last_version < start_version)
             .collect();
+        // Older transaction backups can have been expired by the retention policy.
+        if let Some(first_version) = transactions.first().map(|t| t.first_version) {
+            ensure!(
+                first_version <= replay_transactions_from_version,
+                "Transaction backups start at version {}, too late to replay from version {}.",
+                first_version,
+                replay_transactions_from_version,
+            );
+        }
         if let Some(actual_start_version) = transactions.first().map(|t| t.first_version) {
             if txn_resume_point > 0 {
                 if actual_start_version > txn_resume_point {
@@ -182,8 +199,8 @@ impl RestoreCoordinator {
 
         let txn_manifests = transactions.into_iter().map(|b| b.manifest).collect();
         TransactionRestoreBatchController::new(
-            self.global_opt,
-            self.storage,
+            self.global_opt.clone(),
+            Arc::clone(&self.storage),
             txn_manifests,
             Some(replay_transactions_from_version),
             epoch_history,
@@ -191,6 +208,18 @@ impl RestoreCoordinator {
         .run()
         .await?;
 
+        if let Some(backup) = indexer {
+            IndexerRestoreController::new(
+                IndexerRestoreOpt {
+                    manifest_handle: backup.manifest,
+                },
+                self.global_opt,
+                self.storage,
+            )
+            .run()
+            .await?;
+        }
+
         Ok(())
     }
 }
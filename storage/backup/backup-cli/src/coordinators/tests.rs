@@ -0,0 +1,247 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backup_types::{
+        epoch_ending::backup::{EpochEndingBackupController, EpochEndingBackupOpt},
+        state_snapshot::backup::{StateSnapshotBackupController, StateSnapshotBackupOpt},
+        transaction::backup::{TransactionBackupController, TransactionBackupOpt},
+    },
+    coordinators::continuous_verify::{ContinuousVerifyCoordinator, ContinuousVerifyOpt},
+    metadata::{cache::MetadataCacheOpt, Metadata},
+    metrics::verify::{
+        VERIFY_FAILED_BACKUPS, VERIFY_PENDING_BACKUPS, VERIFY_REPLAY_SUCC_TS, VERIFY_REPLAY_VERSION,
+    },
+    storage::{
+        local_fs::LocalFs, BackupHandle, BackupHandleRef, BackupStorage, FileHandle, FileHandleRef,
+        ShellSafeName, TextLine,
+    },
+    utils::{
+        backup_service_client::BackupServiceClient, test_utils::start_local_backup_service,
+        GlobalBackupOpt, RocksdbOpt, TrustedWaypointOpt,
+    },
+};
+use anyhow::Result;
+use aptos_infallible::Mutex;
+use aptos_temppath::TempPath;
+use aptos_types::transaction::Version;
+use async_trait::async_trait;
+use executor_test_helpers::integration_test_impl::test_execution_with_reconfiguration_impl;
+use std::sync::Arc;
+use storage_interface::DbReader;
+use structopt::StructOpt;
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    time::Duration,
+};
+
+/// Records the files read, to check what's downloaded in each round.
+struct RecordReads {
+    inner: LocalFs,
+    reads: Mutex<Vec<FileHandle>>,
+}
+
+impl RecordReads {
+    fn take_reads(&self) -> Vec<FileHandle> {
+        std::mem::take(&mut *self.reads.lock())
+    }
+}
+
+#[async_trait]
+impl BackupStorage for RecordReads {
+    async fn create_backup(&self, name: &ShellSafeName) -> Result<BackupHandle> {
+        self.inner.create_backup(name).await
+    }
+
+    async fn create_for_write(
+        &self,
+        backup_handle: &BackupHandleRef,
+        name: &ShellSafeName,
+    ) -> Result<(FileHandle, Box<dyn AsyncWrite + Send + Unpin>)> {
+        self.inner.create_for_write(backup_handle, name).await
+    }
+
+    async fn open_for_read(
+        &self,
+        file_handle: &FileHandleRef,
+    ) -> Result<Box<dyn AsyncRead + Send + Unpin>> {
+        self.reads.lock().push(file_handle.to_string());
+        self.inner.open_for_read(file_handle).await
+    }
+
+    async fn save_metadata_line(&self, name: &ShellSafeName, content: &TextLine) -> Result<()> {
+        self.inner.save_metadata_line(name, content).await
+    }
+
+    async fn save_metadata_lines(&self, name: &ShellSafeName, lines: &[TextLine]) -> Result<()> {
+        self.inner.save_metadata_lines(name, lines).await
+    }
+
+    async fn list_metadata_files(&self) -> Result<Vec<FileHandle>> {
+        self.inner.list_metadata_files().await
+    }
+
+    async fn delete_file(&self, file_handle: &FileHandleRef) -> Result<()> {
+        self.inner.delete_file(file_handle).await
+    }
+}
+
+#[test]
+fn continuous_verify() {
+    let src_db = test_execution_with_reconfiguration_impl();
+    let backup_dir = TempPath::new();
+    backup_dir.create_as_dir().unwrap();
+    let recorder = Arc::new(RecordReads {
+        inner: LocalFs::new(backup_dir.path().to_path_buf()),
+        reads: Mutex::new(Vec::new()),
+    });
+    let store: Arc<dyn BackupStorage> = Arc::clone(&recorder) as Arc<dyn BackupStorage>;
+    let cache_dir = TempPath::new();
+
+    let latest_ledger_info = src_db.get_latest_ledger_info().unwrap();
+    let latest_version = latest_ledger_info.ledger_info().version();
+    let latest_epoch = latest_ledger_info.ledger_info().next_block_epoch();
+    let epoch_ending_version = src_db
+        .get_epoch_ending_ledger_infos(0, latest_epoch)
+        .unwrap()
+        .ledger_info_with_sigs
+        .last()
+        .unwrap()
+        .ledger_info()
+        .version();
+    assert!(epoch_ending_version < latest_version);
+
+    let (rt, port) = start_local_backup_service(src_db);
+    let client = Arc::new(BackupServiceClient::new(format!(
+        "http://localhost:{}",
+        port
+    )));
+    let global_backup_opt = || GlobalBackupOpt {
+        max_chunk_size: 1024 * 1024,
+    };
+    let backup_state_snapshot = |version: Version| {
+        rt.block_on(
+            StateSnapshotBackupController::new(
+                StateSnapshotBackupOpt { version },
+                global_backup_opt(),
+                Arc::clone(&client),
+                Arc::clone(&store),
+            )
+            .run(),
+        )
+        .unwrap()
+    };
+    let backup_transactions = |start_version: Version, last_version: Version| {
+        rt.block_on(
+            TransactionBackupController::new(
+                TransactionBackupOpt {
+                    start_version,
+                    num_transactions: (last_version - start_version + 1) as usize,
+                },
+                global_backup_opt(),
+                Arc::clone(&client),
+                Arc::clone(&store),
+            )
+            .run(),
+        )
+        .unwrap()
+    };
+
+    let epoch_ending_manifest = rt
+        .block_on(
+            EpochEndingBackupController::new(
+                EpochEndingBackupOpt {
+                    start_epoch: 0,
+                    end_epoch: latest_epoch,
+                },
+                global_backup_opt(),
+                Arc::clone(&client),
+                Arc::clone(&store),
+            )
+            .run(),
+        )
+        .unwrap();
+    let state_snapshot_manifest = backup_state_snapshot(0);
+    let transaction_manifest = backup_transactions(0, epoch_ending_version);
+    // Not covered by the epoch ending backups.
+    let pending_manifests = vec![
+        backup_state_snapshot(latest_version),
+        backup_transactions(epoch_ending_version + 1, latest_version),
+    ];
+
+    let mut coordinator = ContinuousVerifyCoordinator::new(
+        Arc::clone(&store),
+        MetadataCacheOpt::from_iter(vec![
+            "exe",
+            "--metadata-cache-dir",
+            cache_dir.path().to_str().unwrap(),
+        ]),
+        TrustedWaypointOpt::default(),
+        4, /* concurrent_downloads */
+        ContinuousVerifyOpt {
+            continuous: true,
+            verify_interval_secs: 1,
+            replay_samples_per_round: 1,
+            replay_sample_size: 100,
+            replay_scratch_dir: None,
+            rocksdb_opt: RocksdbOpt::default(),
+        },
+    )
+    .unwrap();
+    rt.block_on(coordinator.run_round()).unwrap();
+    assert_eq!(VERIFY_PENDING_BACKUPS.get(), 2);
+    assert_eq!(VERIFY_FAILED_BACKUPS.get(), 0);
+    // Replayed from the only state snapshot verified, until the last verified transaction.
+    assert_eq!(VERIFY_REPLAY_VERSION.get(), epoch_ending_version as i64);
+    assert!(VERIFY_REPLAY_SUCC_TS.get() > 0);
+    let reads = recorder.take_reads();
+    assert!(reads.contains(&epoch_ending_manifest));
+    for manifest in &pending_manifests {
+        assert!(!reads.contains(manifest));
+    }
+
+    // Backups verified in the last round are not downloaded again, only replayed.
+    rt.block_on(coordinator.run_round()).unwrap();
+    assert_eq!(VERIFY_PENDING_BACKUPS.get(), 2);
+    assert_eq!(VERIFY_FAILED_BACKUPS.get(), 0);
+    let reads = recorder.take_reads();
+    assert!(!reads.contains(&epoch_ending_manifest));
+    for manifest in [&state_snapshot_manifest, &transaction_manifest] {
+        assert_eq!(reads.iter().filter(|f| f == &manifest).count(), 1);
+    }
+    for manifest in &pending_manifests {
+        assert!(!reads.contains(manifest));
+    }
+
+    // A backup that fails to verify fails the round.
+    rt.block_on(
+        store.save_metadata_line(
+            &"missing.meta".parse().unwrap(),
+            &Metadata::new_state_snapshot_backup(1, "missing_manifest".to_string())
+                .to_text_line()
+                .unwrap(),
+        ),
+    )
+    .unwrap();
+    assert!(rt.block_on(coordinator.run_round()).is_err());
+    assert_eq!(VERIFY_PENDING_BACKUPS.get(), 2);
+    assert_eq!(VERIFY_FAILED_BACKUPS.get(), 1);
+
+    // A gap in the transaction backups breaks the chain.
+    rt.block_on(
+        store.save_metadata_line(
+            &"gap.meta".parse().unwrap(),
+            &Metadata::new_transaction_backup(
+                latest_version + 2,
+                latest_version + 2,
+                "gap_manifest".to_string(),
+            )
+            .to_text_line()
+            .unwrap(),
+        ),
+    )
+    .unwrap();
+    assert!(rt.block_on(coordinator.run_round()).is_err());
+
+    rt.shutdown_timeout(Duration::from_secs(1));
+}
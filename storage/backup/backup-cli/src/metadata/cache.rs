@@ -33,7 +33,7 @@ static TEMP_METADATA_CACHE_DIR: Lazy<TempPath> = Lazy::new(|| {
     dir
 });
 
-#[derive(StructOpt)]
+#[derive(Clone, StructOpt)]
 pub struct MetadataCacheOpt {
     #[structopt(
         long = "metadata-cache-dir",
@@ -61,6 +61,18 @@ pub async fn sync_and_load(
     storage: Arc<dyn BackupStorage>,
     concurrent_downloads: usize,
 ) -> Result<MetadataView> {
+    let (_file_handles, metadata_vec) =
+        sync_and_load_entries(opt, storage, concurrent_downloads).await?;
+    Ok(metadata_vec.into())
+}
+
+/// Like `sync_and_load`, but returns the entries as they are, together with the remote metadata
+/// files they were loaded from.
+pub(crate) async fn sync_and_load_entries(
+    opt: &MetadataCacheOpt,
+    storage: Arc<dyn BackupStorage>,
+    concurrent_downloads: usize,
+) -> Result<(Vec<FileHandle>, Vec<Metadata>)> {
     let timer = Instant::now();
     let cache_dir = opt.cache_dir();
     create_dir_all(&cache_dir).await.err_notes(&cache_dir)?; // create if not present already
@@ -165,7 +177,10 @@ pub async fn sync_and_load(
         "Metadata cache loaded in {:.2} seconds.",
         timer.elapsed().as_secs_f64()
     );
-    Ok(metadata_vec.into())
+    Ok((
+        remote_file_handle_by_hash.into_values().collect(),
+        metadata_vec,
+    ))
 }
 
 trait FileHandleHash {
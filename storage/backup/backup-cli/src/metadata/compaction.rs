@@ -0,0 +1,276 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backup_types::{
+        state_snapshot::manifest::StateSnapshotBackup, transaction::manifest::TransactionBackup,
+    },
+    metadata::{
+        cache::{sync_and_load_entries, MetadataCacheOpt},
+        ExpiredBackupMeta, Metadata,
+    },
+    storage::{BackupStorage, FileHandle, ShellSafeName},
+    utils::{storage_ext::BackupStorageExt, stream::StreamX},
+};
+use anyhow::{anyhow, ensure, Result};
+use aptos_infallible::duration_since_epoch;
+use aptos_logger::prelude::*;
+use aptos_types::transaction::Version;
+use itertools::Itertools;
+use std::{collections::HashSet, convert::TryInto, sync::Arc};
+use structopt::StructOpt;
+use tokio_stream::StreamExt;
+
+#[derive(StructOpt)]
+pub struct MetadataCompactionOpt {
+    #[structopt(
+        long,
+        default_value = "100000",
+        help = "Maximum number of metadata entries in each compacted metadata file."
+    )]
+    pub max_entries_per_file: usize,
+
+    #[structopt(
+        long,
+        help = "[Defaults to keeping all] Keep this many of the latest state snapshot backups, \
+        and delete the older ones."
+    )]
+    pub keep_state_snapshots: Option<usize>,
+
+    #[structopt(
+        long,
+        help = "[Defaults to keeping all] Delete transaction backups that end before this \
+        version. Transaction backups are kept regardless from the oldest state snapshot backup \
+        kept onwards, so that each one can be restored and replayed onwards."
+    )]
+    pub keep_transactions_from_version: Option<Version>,
+
+    #[structopt(
+        long,
+        help = "Only log what would be compacted and deleted, without changing the storage."
+    )]
+    pub dry_run: bool,
+}
+
+/// Merges all metadata files in the storage into a few compacted ones, and expires old backups by
+/// the retention policy on the way.
+///
+/// The compacted metadata files are saved before the old ones are deleted, and the files of the
+/// expired backups are deleted last, so that the metadata never refers to deleted files, however
+/// the process is interrupted. The compacted metadata records the files of the expired backups
+/// until the next compaction, which deletes them again before dropping the records, so that none
+/// is left behind if deleting them was interrupted. Duplicated metadata entries left behind by an
+/// interruption are ignored.
+pub struct MetadataCompactionController {
+    opt: MetadataCompactionOpt,
+    metadata_cache_opt: MetadataCacheOpt,
+    storage: Arc<dyn BackupStorage>,
+    concurrency: usize,
+}
+
+impl MetadataCompactionController {
+    pub fn new(
+        opt: MetadataCompactionOpt,
+        metadata_cache_opt: MetadataCacheOpt,
+        storage: Arc<dyn BackupStorage>,
+        concurrency: usize,
+    ) -> Self {
+        Self {
+            opt,
+            metadata_cache_opt,
+            storage,
+            concurrency,
+        }
+    }
+
+    pub async fn run(self) -> Result<()> {
+        info!("Metadata compaction started.");
+        self.run_impl()
+            .await
+            .map_err(|e| anyhow!("Metadata compaction failed: {}", e))?;
+        info!("Metadata compaction succeeded.");
+        Ok(())
+    }
+}
+
+impl MetadataCompactionController {
+    async fn run_impl(self) -> Result<()> {
+        ensure!(
+            self.opt.max_entries_per_file > 0,
+            "--max-entries-per-file must be positive."
+        );
+        ensure!(
+            self.opt.keep_state_snapshots != Some(0),
+            "--keep-state-snapshots must be positive."
+        );
+
+        let (metadata_files, metadata_vec) = sync_and_load_entries(
+            &self.metadata_cache_opt,
+            Arc::clone(&self.storage),
+            self.concurrency,
+        )
+        .await?;
+        let num_entries = metadata_vec.len();
+        let mut expired_earlier = Vec::new();
+        let mut backups = Vec::new();
+        for entry in metadata_vec.into_iter().sorted().dedup() {
+            match entry {
+                Metadata::ExpiredBackup(e) => expired_earlier.push(e),
+                _ => backups.push(entry),
+            }
+        }
+        // A backup recorded as expired stays so, even if it's still listed by metadata files that
+        // an interrupted compaction didn't get to delete, and whatever the retention policy is now.
+        let expired_manifests = expired_earlier
+            .iter()
+            .map(|e| e.manifest.clone())
+            .collect::<HashSet<_>>();
+        backups.retain(|entry| match entry {
+            Metadata::StateSnapshotBackup(s) => !expired_manifests.contains(&s.manifest),
+            Metadata::TransactionBackup(t) => !expired_manifests.contains(&t.manifest),
+            _ => true,
+        });
+        let (kept, expired) = self.apply_retention(backups);
+        let newly_expired = self.expire(&expired).await?;
+        let compacted_entries = kept
+            .into_iter()
+            .chain(newly_expired.iter().cloned().map(Metadata::ExpiredBackup))
+            .collect::<Vec<_>>();
+        let compacted = compacted_entries
+            .chunks(self.opt.max_entries_per_file)
+            .collect::<Vec<_>>();
+        info!(
+            num_metadata_files = metadata_files.len(),
+            num_entries = num_entries,
+            num_entries_kept = compacted_entries.len() - newly_expired.len(),
+            num_entries_expired = newly_expired.len(),
+            num_entries_expired_earlier = expired_earlier.len(),
+            num_compacted_files = compacted.len(),
+            "Metadata loaded."
+        );
+        if newly_expired.is_empty()
+            && expired_earlier.is_empty()
+            && metadata_files.len() <= compacted.len()
+        {
+            info!("Metadata already compacted, nothing to do.");
+            return Ok(());
+        }
+        if self.opt.dry_run {
+            for entry in &expired {
+                info!(
+                    "Would expire {}.",
+                    entry.to_text_line()?.as_ref().trim_end()
+                );
+            }
+            info!(
+                "Dry run, would have deleted {} metadata files, {} files of backups expired \
+                 earlier and {} backup files.",
+                metadata_files.len(),
+                expired_earlier.iter().map(|e| e.files.len()).sum::<usize>(),
+                newly_expired.iter().map(|e| e.files.len()).sum::<usize>(),
+            );
+            return Ok(());
+        }
+
+        // Done before the records are dropped with the old metadata files.
+        self.delete_files(expired_earlier.into_iter().flat_map(|e| e.files).collect())
+            .await?;
+        info!("Files of backups expired earlier deleted.");
+        let timestamp = duration_since_epoch().as_millis();
+        for (i, chunk) in compacted.into_iter().enumerate() {
+            let name: ShellSafeName = format!("compacted_{}_{}.meta", timestamp, i).try_into()?;
+            let lines = chunk
+                .iter()
+                .map(Metadata::to_text_line)
+                .collect::<Result<Vec<_>>>()?;
+            self.storage.save_metadata_lines(&name, &lines).await?;
+            info!("Saved compacted metadata file {}.", name.as_str());
+        }
+        self.delete_files(metadata_files).await?;
+        info!("Old metadata files deleted.");
+        self.delete_files(newly_expired.into_iter().flat_map(|e| e.files).collect())
+            .await?;
+        info!("Files of expired backups deleted.");
+
+        Ok(())
+    }
+
+    /// Splits sorted and deduplicated entries into those to keep and those to expire.
+    fn apply_retention(&self, entries: Vec<Metadata>) -> (Vec<Metadata>, Vec<Metadata>) {
+        let mut state_snapshot_versions = entries
+            .iter()
+            .filter_map(|e| match e {
+                Metadata::StateSnapshotBackup(s) => Some(s.version),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        state_snapshot_versions.sort_unstable_by(|a, b| b.cmp(a));
+        state_snapshot_versions.dedup();
+        let min_state_snapshot_version = match self.opt.keep_state_snapshots {
+            Some(n) => state_snapshot_versions.get(n - 1).copied(),
+            None => state_snapshot_versions.last().copied(),
+        }
+        .unwrap_or(0);
+        let min_transaction_version = match self.opt.keep_transactions_from_version {
+            Some(version) => std::cmp::min(version, min_state_snapshot_version),
+            None => 0,
+        };
+
+        entries.into_iter().partition(|e| match e {
+            Metadata::StateSnapshotBackup(s) => s.version >= min_state_snapshot_version,
+            Metadata::TransactionBackup(t) => t.last_version >= min_transaction_version,
+            Metadata::EpochEndingBackup(_)
+            | Metadata::IndexerBackup(_)
+            | Metadata::ExpiredBackup(_) => true,
+        })
+    }
+
+    /// Lists the files of the expired backups, manifests included.
+    async fn expire(&self, entries: &[Metadata]) -> Result<Vec<ExpiredBackupMeta>> {
+        let mut expired = Vec::new();
+        for entry in entries {
+            let mut files = Vec::new();
+            let manifest = match entry {
+                Metadata::StateSnapshotBackup(s) => {
+                    let manifest: StateSnapshotBackup =
+                        self.storage.load_json_file(&s.manifest).await?;
+                    for chunk in manifest.chunks {
+                        files.push(chunk.blobs);
+                        files.push(chunk.proof);
+                    }
+                    files.push(manifest.proof);
+                    s.manifest.clone()
+                }
+                Metadata::TransactionBackup(t) => {
+                    let manifest: TransactionBackup =
+                        self.storage.load_json_file(&t.manifest).await?;
+                    for chunk in manifest.chunks {
+                        files.push(chunk.transactions);
+                        files.push(chunk.proof);
+                    }
+                    t.manifest.clone()
+                }
+                Metadata::EpochEndingBackup(_)
+                | Metadata::IndexerBackup(_)
+                | Metadata::ExpiredBackup(_) => {
+                    unreachable!("Never expired.")
+                }
+            };
+            files.push(manifest.clone());
+            expired.push(ExpiredBackupMeta { manifest, files });
+        }
+        Ok(expired)
+    }
+
+    async fn delete_files(&self, file_handles: Vec<FileHandle>) -> Result<()> {
+        let futs = file_handles.into_iter().map(|file_handle| {
+            let storage = Arc::clone(&self.storage);
+            async move { storage.delete_file(&file_handle).await }
+        });
+        futures::stream::iter(futs)
+            .buffered_x(self.concurrency * 2, self.concurrency)
+            .collect::<Result<Vec<_>>>()
+            .await?;
+        Ok(())
+    }
+}
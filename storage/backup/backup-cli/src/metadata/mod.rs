@@ -2,20 +2,26 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod cache;
+pub mod compaction;
 pub mod view;
 
+#[cfg(test)]
+mod tests;
+
 use crate::storage::{FileHandle, ShellSafeName, TextLine};
 use anyhow::Result;
 use aptos_types::transaction::Version;
 use serde::{Deserialize, Serialize};
 use std::convert::TryInto;
 
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, Eq, PartialEq, Ord, PartialOrd)]
 #[allow(clippy::enum_variant_names)] // to introduce: BackupperId, etc
 pub(crate) enum Metadata {
     EpochEndingBackup(EpochEndingBackupMeta),
     StateSnapshotBackup(StateSnapshotBackupMeta),
     TransactionBackup(TransactionBackupMeta),
+    IndexerBackup(IndexerBackupMeta),
+    ExpiredBackup(ExpiredBackupMeta),
 }
 
 impl Metadata {
@@ -51,6 +57,10 @@ impl Metadata {
         })
     }
 
+    pub fn new_indexer_backup(version: Version, manifest: FileHandle) -> Self {
+        Self::IndexerBackup(IndexerBackupMeta { version, manifest })
+    }
+
     pub fn name(&self) -> ShellSafeName {
         match self {
             Self::EpochEndingBackup(e) => {
@@ -60,6 +70,9 @@ impl Metadata {
             Self::TransactionBackup(t) => {
                 format!("transaction_{}-{}.meta", t.first_version, t.last_version,)
             }
+            Self::IndexerBackup(i) => format!("indexer_ver_{}.meta", i.version),
+            // Only ever saved in compacted metadata files.
+            Self::ExpiredBackup(_) => "expired_backup.meta".to_string(),
         }
         .try_into()
         .unwrap()
@@ -91,3 +104,18 @@ pub struct TransactionBackupMeta {
     pub last_version: Version,
     pub manifest: FileHandle,
 }
+
+#[derive(Clone, Deserialize, Serialize, Eq, PartialEq, Ord, PartialOrd)]
+pub struct IndexerBackupMeta {
+    pub version: Version,
+    pub manifest: FileHandle,
+}
+
+/// A backup expired by metadata compaction, recorded in the compacted metadata until its files
+/// are deleted, so that an interrupted compaction deletes them when run again.
+#[derive(Clone, Deserialize, Serialize, Eq, PartialEq, Ord, PartialOrd)]
+pub struct ExpiredBackupMeta {
+    pub manifest: FileHandle,
+    /// All the files of the backup, the manifest included.
+    pub files: Vec<FileHandle>,
+}
@@ -0,0 +1,377 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backup_types::{
+        state_snapshot::manifest::{StateSnapshotBackup, StateSnapshotChunk},
+        transaction::manifest::{TransactionBackup, TransactionChunk},
+    },
+    metadata::{
+        cache::{sync_and_load, sync_and_load_entries, MetadataCacheOpt},
+        compaction::{MetadataCompactionController, MetadataCompactionOpt},
+        Metadata,
+    },
+    storage::{
+        local_fs::LocalFs, BackupHandle, BackupHandleRef, BackupStorage, FileHandle, FileHandleRef,
+        ShellSafeName, TextLine,
+    },
+    utils::storage_ext::BackupStorageExt,
+};
+use anyhow::{ensure, Result};
+use aptos_crypto::HashValue;
+use aptos_temppath::TempPath;
+use aptos_types::transaction::Version;
+use async_trait::async_trait;
+use std::sync::Arc;
+use structopt::StructOpt;
+use tokio::{
+    io::{AsyncRead, AsyncWrite, AsyncWriteExt},
+    runtime::Runtime,
+};
+
+async fn write_file(
+    store: &Arc<dyn BackupStorage>,
+    backup_handle: &str,
+    name: &str,
+    content: &[u8],
+) -> FileHandle {
+    let (file_handle, mut file) = store
+        .create_for_write(backup_handle, &name.parse().unwrap())
+        .await
+        .unwrap();
+    file.write_all(content).await.unwrap();
+    file.shutdown().await.unwrap();
+    file_handle
+}
+
+async fn save_metadata(store: &Arc<dyn BackupStorage>, metadata: Metadata) {
+    store
+        .save_metadata_line(&metadata.name(), &metadata.to_text_line().unwrap())
+        .await
+        .unwrap();
+}
+
+/// Writes a transaction backup, returning all its files, the manifest first.
+async fn write_transaction_backup(
+    store: &Arc<dyn BackupStorage>,
+    first_version: Version,
+    last_version: Version,
+) -> Vec<FileHandle> {
+    let backup_handle = store
+        .create_backup_with_random_suffix("transaction_test")
+        .await
+        .unwrap();
+    let transactions = write_file(store, &backup_handle, "txns.chunk", b"txns").await;
+    let proof = write_file(store, &backup_handle, "txns.proof", b"proof").await;
+    let manifest = TransactionBackup {
+        first_version,
+        last_version,
+        chunks: vec![TransactionChunk {
+            first_version,
+            last_version,
+            transactions: transactions.clone(),
+            proof: proof.clone(),
+        }],
+    };
+    let manifest_handle = write_file(
+        store,
+        &backup_handle,
+        "transaction.manifest",
+        &serde_json::to_vec(&manifest).unwrap(),
+    )
+    .await;
+    save_metadata(
+        store,
+        Metadata::new_transaction_backup(first_version, last_version, manifest_handle.clone()),
+    )
+    .await;
+    vec![manifest_handle, transactions, proof]
+}
+
+/// Writes a state snapshot backup, returning all its files, the manifest first.
+async fn write_state_snapshot_backup(
+    store: &Arc<dyn BackupStorage>,
+    version: Version,
+) -> Vec<FileHandle> {
+    let backup_handle = store
+        .create_backup_with_random_suffix("state_snapshot_test")
+        .await
+        .unwrap();
+    let blobs = write_file(store, &backup_handle, "0-.chunk", b"blobs").await;
+    let chunk_proof = write_file(store, &backup_handle, "0-.proof", b"proof").await;
+    let proof = write_file(store, &backup_handle, "state.proof", b"proof").await;
+    let manifest = StateSnapshotBackup {
+        version,
+        root_hash: HashValue::zero(),
+        chunks: vec![StateSnapshotChunk {
+            first_idx: 0,
+            last_idx: 0,
+            first_key: HashValue::zero(),
+            last_key: HashValue::zero(),
+            blobs: blobs.clone(),
+            proof: chunk_proof.clone(),
+        }],
+        proof: proof.clone(),
+    };
+    let manifest_handle = write_file(
+        store,
+        &backup_handle,
+        "state.manifest",
+        &serde_json::to_vec(&manifest).unwrap(),
+    )
+    .await;
+    save_metadata(
+        store,
+        Metadata::new_state_snapshot_backup(version, manifest_handle.clone()),
+    )
+    .await;
+    vec![manifest_handle, blobs, chunk_proof, proof]
+}
+
+async fn all_exist(store: &Arc<dyn BackupStorage>, file_handles: &[FileHandle]) -> bool {
+    for file_handle in file_handles {
+        if store.read_all(file_handle).await.is_err() {
+            return false;
+        }
+    }
+    true
+}
+
+async fn none_exists(store: &Arc<dyn BackupStorage>, file_handles: &[FileHandle]) -> bool {
+    for file_handle in file_handles {
+        if store.read_all(file_handle).await.is_ok() {
+            return false;
+        }
+    }
+    true
+}
+
+fn compaction_opt(
+    keep_state_snapshots: Option<usize>,
+    keep_transactions_from_version: Option<Version>,
+    dry_run: bool,
+) -> MetadataCompactionOpt {
+    MetadataCompactionOpt {
+        max_entries_per_file: 3,
+        keep_state_snapshots,
+        keep_transactions_from_version,
+        dry_run,
+    }
+}
+
+fn num_expired(entries: &[Metadata]) -> usize {
+    entries
+        .iter()
+        .filter(|e| matches!(e, Metadata::ExpiredBackup(_)))
+        .count()
+}
+
+#[test]
+fn compaction_and_retention() {
+    let backup_dir = TempPath::new();
+    backup_dir.create_as_dir().unwrap();
+    let cache_dir = TempPath::new();
+    let cache_opt = || {
+        MetadataCacheOpt::from_iter(vec![
+            "exe",
+            "--metadata-cache-dir",
+            cache_dir.path().to_str().unwrap(),
+        ])
+    };
+    let store: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(backup_dir.path().to_path_buf()));
+    let rt = Runtime::new().unwrap();
+    let compact = |opt| {
+        rt.block_on(
+            MetadataCompactionController::new(opt, cache_opt(), Arc::clone(&store), 2).run(),
+        )
+        .unwrap()
+    };
+    let load = || {
+        rt.block_on(sync_and_load_entries(&cache_opt(), Arc::clone(&store), 2))
+            .unwrap()
+    };
+
+    let mut txn_files = Vec::new();
+    let mut snapshot_files = Vec::new();
+    rt.block_on(async {
+        for i in 0..4 {
+            txn_files.push(write_transaction_backup(&store, i * 10, i * 10 + 9).await);
+            snapshot_files.push(write_state_snapshot_backup(&store, i * 10 + 5).await);
+        }
+    });
+    let (metadata_files, mut entries) = load();
+    assert_eq!(metadata_files.len(), 8);
+    entries.sort();
+
+    // A dry run changes nothing.
+    compact(compaction_opt(Some(1), Some(100), true));
+    assert_eq!(load().0.len(), 8);
+
+    // Without retention, the entries are only merged, into files of 3 entries at most.
+    compact(compaction_opt(None, None, false));
+    let (metadata_files, mut compacted_entries) = load();
+    assert_eq!(metadata_files.len(), 3);
+    compacted_entries.sort();
+    assert!(compacted_entries == entries);
+    rt.block_on(async {
+        for files in txn_files.iter().chain(&snapshot_files) {
+            assert!(all_exist(&store, files).await);
+        }
+    });
+
+    // Keeps the snapshots at 25 and 35, and transactions from 20 on despite asking for 30 on, so
+    // that the one at 25 can be replayed onwards. The 4 expired backups are recorded along.
+    compact(compaction_opt(Some(2), Some(30), false));
+    let (metadata_files, entries) = load();
+    assert_eq!(metadata_files.len(), 3);
+    assert_eq!(num_expired(&entries), 4);
+    rt.block_on(async {
+        for (i, files) in txn_files.iter().zip(&snapshot_files).enumerate() {
+            let (txn_files, snapshot_files) = files;
+            assert_eq!(all_exist(&store, txn_files).await, i >= 2);
+            assert_eq!(none_exists(&store, txn_files).await, i < 2);
+            assert_eq!(all_exist(&store, snapshot_files).await, i >= 2);
+            assert_eq!(none_exists(&store, snapshot_files).await, i < 2);
+        }
+    });
+    let view = rt
+        .block_on(sync_and_load(&cache_opt(), Arc::clone(&store), 2))
+        .unwrap();
+    let transactions = view
+        .select_transaction_backups(0, Version::max_value())
+        .unwrap();
+    assert_eq!(
+        transactions
+            .iter()
+            .map(|t| (t.first_version, t.last_version))
+            .collect::<Vec<_>>(),
+        vec![(20, 29), (30, 39)],
+    );
+    assert_eq!(
+        view.select_state_snapshot(Version::max_value())
+            .unwrap()
+            .map(|s| s.version),
+        Some(35),
+    );
+    assert!(view.select_state_snapshot(24).unwrap().is_none());
+
+    // The next compaction drops the records of the expired backups.
+    compact(compaction_opt(Some(2), Some(30), false));
+    let (mut metadata_files, entries) = load();
+    assert_eq!(metadata_files.len(), 2);
+    assert_eq!(num_expired(&entries), 0);
+    metadata_files.sort();
+
+    // Nothing left to do.
+    compact(compaction_opt(Some(2), Some(30), false));
+    let (mut unchanged_metadata_files, _) = load();
+    unchanged_metadata_files.sort();
+    assert_eq!(unchanged_metadata_files, metadata_files);
+}
+
+/// Fails to delete anything but metadata files, as if compaction was interrupted right after
+/// deleting the old metadata files.
+struct FailToDeleteBackupFiles(LocalFs);
+
+#[async_trait]
+impl BackupStorage for FailToDeleteBackupFiles {
+    async fn create_backup(&self, name: &ShellSafeName) -> Result<BackupHandle> {
+        self.0.create_backup(name).await
+    }
+
+    async fn create_for_write(
+        &self,
+        backup_handle: &BackupHandleRef,
+        name: &ShellSafeName,
+    ) -> Result<(FileHandle, Box<dyn AsyncWrite + Send + Unpin>)> {
+        self.0.create_for_write(backup_handle, name).await
+    }
+
+    async fn open_for_read(
+        &self,
+        file_handle: &FileHandleRef,
+    ) -> Result<Box<dyn AsyncRead + Send + Unpin>> {
+        self.0.open_for_read(file_handle).await
+    }
+
+    async fn save_metadata_line(&self, name: &ShellSafeName, content: &TextLine) -> Result<()> {
+        self.0.save_metadata_line(name, content).await
+    }
+
+    async fn save_metadata_lines(&self, name: &ShellSafeName, lines: &[TextLine]) -> Result<()> {
+        self.0.save_metadata_lines(name, lines).await
+    }
+
+    async fn list_metadata_files(&self) -> Result<Vec<FileHandle>> {
+        self.0.list_metadata_files().await
+    }
+
+    async fn delete_file(&self, file_handle: &FileHandleRef) -> Result<()> {
+        ensure!(
+            file_handle.starts_with("metadata/"),
+            "Failed to delete {}.",
+            file_handle
+        );
+        self.0.delete_file(file_handle).await
+    }
+}
+
+#[test]
+fn compaction_interrupted() {
+    let backup_dir = TempPath::new();
+    backup_dir.create_as_dir().unwrap();
+    let cache_dir = TempPath::new();
+    let cache_opt = || {
+        MetadataCacheOpt::from_iter(vec![
+            "exe",
+            "--metadata-cache-dir",
+            cache_dir.path().to_str().unwrap(),
+        ])
+    };
+    let store: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(backup_dir.path().to_path_buf()));
+    let failing_store: Arc<dyn BackupStorage> = Arc::new(FailToDeleteBackupFiles(LocalFs::new(
+        backup_dir.path().to_path_buf(),
+    )));
+    let rt = Runtime::new().unwrap();
+    let compact = |opt, store: &Arc<dyn BackupStorage>| {
+        rt.block_on(MetadataCompactionController::new(opt, cache_opt(), Arc::clone(store), 2).run())
+    };
+
+    let mut snapshot_files = Vec::new();
+    rt.block_on(async {
+        for i in 0..3 {
+            snapshot_files.push(write_state_snapshot_backup(&store, i * 10).await);
+        }
+    });
+
+    compact(compaction_opt(Some(1), None, false), &failing_store).unwrap_err();
+    let view = rt
+        .block_on(sync_and_load(&cache_opt(), Arc::clone(&store), 2))
+        .unwrap();
+    assert_eq!(
+        view.select_state_snapshots(Version::max_value())
+            .iter()
+            .map(|s| s.version)
+            .collect::<Vec<_>>(),
+        vec![20],
+    );
+    rt.block_on(async {
+        for files in &snapshot_files {
+            assert!(all_exist(&store, files).await);
+        }
+    });
+
+    // Finishes deleting the expired backups, even though they'd be kept by the policy now.
+    compact(compaction_opt(None, None, false), &store).unwrap();
+    rt.block_on(async {
+        for (i, files) in snapshot_files.iter().enumerate() {
+            assert_eq!(none_exists(&store, files).await, i < 2);
+            assert_eq!(all_exist(&store, files).await, i == 2);
+        }
+    });
+    let (_, entries) = rt
+        .block_on(sync_and_load_entries(&cache_opt(), Arc::clone(&store), 2))
+        .unwrap();
+    assert_eq!(num_expired(&entries), 0);
+    assert_eq!(entries.len(), 1);
+}
@@ -2,7 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::metadata::{
-    EpochEndingBackupMeta, Metadata, StateSnapshotBackupMeta, TransactionBackupMeta,
+    EpochEndingBackupMeta, IndexerBackupMeta, Metadata, StateSnapshotBackupMeta,
+    TransactionBackupMeta,
 };
 use anyhow::{anyhow, ensure, Result};
 use aptos_types::transaction::Version;
@@ -13,6 +14,7 @@ pub struct MetadataView {
     epoch_ending_backups: Vec<EpochEndingBackupMeta>,
     state_snapshot_backups: Vec<StateSnapshotBackupMeta>,
     transaction_backups: Vec<TransactionBackupMeta>,
+    indexer_backups: Vec<IndexerBackupMeta>,
 }
 
 impl MetadataView {
@@ -47,31 +49,63 @@ impl MetadataView {
             .map(Clone::clone))
     }
 
+    /// Selects all state snapshots at or before `target_version`, the oldest first.
+    pub fn select_state_snapshots(&self, target_version: Version) -> Vec<StateSnapshotBackupMeta> {
+        self.state_snapshot_backups
+            .iter()
+            .sorted()
+            .filter(|m| m.version <= target_version)
+            .cloned()
+            .collect()
+    }
+
+    pub fn latest_indexer_version(&self) -> Option<Version> {
+        self.indexer_backups.iter().map(|i| i.version).max()
+    }
+
+    /// Selects the latest indexer backup in the range [`min_version`, `target_version`].
+    pub fn select_indexer_backup(
+        &self,
+        min_version: Version,
+        target_version: Version,
+    ) -> Option<IndexerBackupMeta> {
+        self.indexer_backups
+            .iter()
+            .sorted()
+            .rev()
+            .find(|m| m.version >= min_version && m.version <= target_version)
+            .map(Clone::clone)
+    }
+
     pub fn select_transaction_backups(
         &self,
         start_version: Version,
         target_version: Version,
     ) -> Result<Vec<TransactionBackupMeta>> {
         // This can be more flexible, but for now we assume and check backups are continuous in
-        // range (which is always true when we backup from a single backup coordinator)
-        let mut next_ver = 0;
+        // range (which is always true when we backup from a single backup coordinator). Backups
+        // before the first one can have been expired by the retention policy, so it's up to the
+        // caller to check the first one starts early enough.
+        let mut next_ver = None;
         let mut res = Vec::new();
         for backup in self.transaction_backups.iter().sorted() {
             if backup.first_version > target_version {
                 break;
             }
-            ensure!(
-                backup.first_version == next_ver,
-                "Transactioon backup ranges not continuous, expecting version {}, got {}.",
-                next_ver,
-                backup.first_version,
-            );
+            if let Some(next_ver) = next_ver {
+                ensure!(
+                    backup.first_version == next_ver,
+                    "Transactioon backup ranges not continuous, expecting version {}, got {}.",
+                    next_ver,
+                    backup.first_version,
+                );
+            }
 
             if backup.last_version >= start_version {
                 res.push(backup.clone());
             }
 
-            next_ver = backup.last_version + 1;
+            next_ver = Some(backup.last_version + 1);
         }
 
         Ok(res)
@@ -110,12 +144,18 @@ impl From<Vec<Metadata>> for MetadataView {
         let mut epoch_ending_backups = Vec::new();
         let mut state_snapshot_backups = Vec::new();
         let mut transaction_backups = Vec::new();
+        let mut indexer_backups = Vec::new();
 
-        for meta in metadata_vec {
+        // The same entry can show up more than once, in the metadata file saved by the backup and
+        // in a compacted one, if compaction didn't get to delete the former.
+        for meta in metadata_vec.into_iter().sorted().dedup() {
             match meta {
                 Metadata::EpochEndingBackup(e) => epoch_ending_backups.push(e),
                 Metadata::StateSnapshotBackup(s) => state_snapshot_backups.push(s),
                 Metadata::TransactionBackup(t) => transaction_backups.push(t),
+                Metadata::IndexerBackup(i) => indexer_backups.push(i),
+                // Not a backup anymore, its files are being deleted.
+                Metadata::ExpiredBackup(_) => (),
             }
         }
 
@@ -123,6 +163,7 @@ impl From<Vec<Metadata>> for MetadataView {
             epoch_ending_backups,
             state_snapshot_backups,
             transaction_backups,
+            indexer_backups,
         }
     }
 }
@@ -28,6 +28,14 @@ pub static STATE_SNAPSHOT_VERSION: Lazy<IntGauge> = Lazy::new(|| {
     .unwrap()
 });
 
+pub static INDEXER_VERSION: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_db_backup_coordinator_indexer_version",
+        "The version of the latest indexer DB backup taken."
+    )
+    .unwrap()
+});
+
 pub static TRANSACTION_VERSION: Lazy<IntGauge> = Lazy::new(|| {
     register_int_gauge!(
         "aptos_db_backup_coordinator_transaction_version",
@@ -28,6 +28,14 @@ pub static EPOCH_ENDING_VERSION: Lazy<IntGauge> = Lazy::new(|| {
     .unwrap()
 });
 
+pub static INDEXER_VERSION: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_db_restore_indexer_version",
+        "The version that an indexer DB backup restores to."
+    )
+    .unwrap()
+});
+
 pub static STATE_SNAPSHOT_VERSION: Lazy<IntGauge> = Lazy::new(|| {
     register_int_gauge!(
         "aptos_db_restore_state_snapshot_version",
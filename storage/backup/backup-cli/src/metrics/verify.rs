@@ -75,3 +75,68 @@ pub static VERIFY_COORDINATOR_FAIL_TS: Lazy<IntGauge> = Lazy::new(|| {
     )
     .unwrap()
 });
+
+pub static VERIFY_VERIFIED_EPOCH: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_db_backup_verify_verified_epoch",
+        "Latest epoch ending verified by the continuous verify coordinator."
+    )
+    .unwrap()
+});
+
+pub static VERIFY_VERIFIED_STATE_SNAPSHOT_VERSION: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_db_backup_verify_verified_state_snapshot_version",
+        "Version of the latest state snapshot verified by the continuous verify coordinator."
+    )
+    .unwrap()
+});
+
+pub static VERIFY_VERIFIED_TRANSACTION_VERSION: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_db_backup_verify_verified_transaction_version",
+        "Last version of the latest transaction backup verified by the continuous verify \
+        coordinator."
+    )
+    .unwrap()
+});
+
+pub static VERIFY_PENDING_BACKUPS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_db_backup_verify_pending_backups",
+        "Number of backups waiting for the epoch ending backups to be verified with."
+    )
+    .unwrap()
+});
+
+pub static VERIFY_FAILED_BACKUPS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_db_backup_verify_failed_backups",
+        "Number of backups that failed to verify in the last round of continuous verification."
+    )
+    .unwrap()
+});
+
+pub static VERIFY_REPLAY_VERSION: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_db_backup_verify_replay_version",
+        "Last version of the latest sampled transaction range replayed successfully."
+    )
+    .unwrap()
+});
+
+pub static VERIFY_REPLAY_SUCC_TS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_db_backup_verify_replay_succeed_timestamp_s",
+        "Timestamp when a sampled transaction range was replayed successfully."
+    )
+    .unwrap()
+});
+
+pub static VERIFY_REPLAY_FAIL_TS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_db_backup_verify_replay_fail_timestamp_s",
+        "Timestamp when a sampled transaction range failed to replay."
+    )
+    .unwrap()
+});
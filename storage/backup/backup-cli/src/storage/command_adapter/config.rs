@@ -62,11 +62,18 @@ pub struct Commands {
     /// Command line to save a line of metadata
     /// input env vars:
     ///     $FILE_NAME
-    /// stdin will be fed with a line of text with a trailing newline.
+    /// stdin will be fed with a line of text with a trailing newline, or multiple such lines
+    /// when compacting metadata.
     pub save_metadata_line: String,
     /// Command line to list all existing metadata file handles.
     /// expected stdout to stream out lines of file handles.
     pub list_metadata_files: String,
+    /// Command line to delete a file, only needed to compact metadata and to expire backups.
+    /// It must succeed if the file doesn't exist, so that an interrupted deletion can be retried.
+    /// input env vars:
+    ///     $FILE_HANDLE, of a file created for write, or of a metadata file
+    #[serde(default)]
+    pub delete_file: Option<String>,
 }
 
 #[derive(Clone, Default, Deserialize)]
@@ -3,6 +3,7 @@
 
 pub mod command_adapter;
 pub mod local_fs;
+pub mod s3;
 
 #[cfg(test)]
 mod test_util;
@@ -12,6 +13,7 @@ mod tests;
 use crate::storage::{
     command_adapter::{CommandAdapter, CommandAdapterOpt},
     local_fs::{LocalFs, LocalFsOpt},
+    s3::{S3Opt, S3},
 };
 use anyhow::{ensure, Result};
 use async_trait::async_trait;
@@ -160,6 +162,9 @@ pub trait BackupStorage: Send + Sync {
     /// is straightforward and acceptable.
     /// See `list_metadata_files`.
     async fn save_metadata_line(&self, name: &ShellSafeName, content: &TextLine) -> Result<()>;
+    /// Asks to save multiple metadata entries in one file, like `save_metadata_line` does with one.
+    /// This is used to compact metadata, so the name is always new.
+    async fn save_metadata_lines(&self, name: &ShellSafeName, lines: &[TextLine]) -> Result<()>;
     /// The backup system always asks for all metadata files and cache and build index on top of
     /// the content of them. This means:
     ///   1. The storage is free to reorganise the metadata files, like combining multiple ones to
@@ -167,6 +172,10 @@ pub trait BackupStorage: Send + Sync {
     ///   2. But the cache does expect the content stays the same for a file handle, so when
     /// reorganising metadata files, give them new unique names.
     async fn list_metadata_files(&self) -> Result<Vec<FileHandle>>;
+    /// Deletes a file, either one returned by `create_for_write`, or a metadata file returned by
+    /// `list_metadata_files`. This is used to compact metadata and to expire old backups.
+    /// Deleting a file that doesn't exist succeeds, so that an interrupted deletion can be retried.
+    async fn delete_file(&self, file_handle: &FileHandleRef) -> Result<()>;
 }
 
 #[derive(StructOpt)]
@@ -175,6 +184,8 @@ pub enum StorageOpt {
     LocalFs(LocalFsOpt),
     #[structopt(about = "Select the CommandAdapter backup store.")]
     CommandAdapter(CommandAdapterOpt),
+    #[structopt(about = "Select the S3 backup store, for AWS S3 or an S3 compatible store.")]
+    S3(S3Opt),
 }
 
 impl StorageOpt {
@@ -182,6 +193,7 @@ impl StorageOpt {
         Ok(match self {
             StorageOpt::LocalFs(opt) => Arc::new(LocalFs::new_with_opt(opt)),
             StorageOpt::CommandAdapter(opt) => Arc::new(CommandAdapter::new_with_opt(opt).await?),
+            StorageOpt::S3(opt) => Arc::new(S3::new_with_opt(opt)?),
         })
     }
 }
@@ -0,0 +1,522 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{anyhow, ensure, Result};
+use aptos_logger::prelude::*;
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac, NewMac};
+use md5::Md5;
+use rand::Rng;
+use reqwest::{Method, Response, StatusCode, Url};
+use sha2::{Digest, Sha256};
+use std::{cmp::min, time::Duration};
+
+/// Credentials to sign requests with, see
+/// https://docs.aws.amazon.com/general/latest/gr/sigv4_signing.html
+pub(super) struct Credentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+}
+
+/// Speaks the part of the S3 REST API the backup storage needs, signing requests with AWS
+/// Signature Version 4 and retrying those failing with server side or network errors, including
+/// timeouts.
+pub(super) struct S3Client {
+    http: reqwest::Client,
+    endpoint: Url,
+    bucket: String,
+    region: String,
+    path_style: bool,
+    credentials: Credentials,
+    max_retries: usize,
+}
+
+impl S3Client {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        endpoint: &str,
+        bucket: String,
+        region: String,
+        path_style: bool,
+        credentials: Credentials,
+        max_retries: usize,
+        connect_timeout: Duration,
+        request_timeout: Duration,
+    ) -> Result<Self> {
+        let endpoint = Url::parse(endpoint)?;
+        ensure!(
+            endpoint.host_str().is_some(),
+            "Endpoint {} has no host.",
+            endpoint,
+        );
+        Ok(Self {
+            http: reqwest::Client::builder()
+                .connect_timeout(connect_timeout)
+                .timeout(request_timeout)
+                .build()?,
+            endpoint,
+            bucket,
+            region,
+            path_style,
+            credentials,
+            max_retries,
+        })
+    }
+
+    pub async fn put_object(&self, key: &str, body: Bytes) -> Result<()> {
+        self.send(Method::PUT, key, &[], &[], body).await?;
+        Ok(())
+    }
+
+    /// Returns the size and the ETag of an object, failing if it doesn't exist.
+    pub async fn head_object(&self, key: &str) -> Result<(u64, String)> {
+        let response = self.send(Method::HEAD, key, &[], &[], Bytes::new()).await?;
+        // Not `Response::content_length()`, which is that of the empty body of the response.
+        let size = response
+            .headers()
+            .get("content-length")
+            .ok_or_else(|| anyhow!("No Content-Length for {}.", key))?
+            .to_str()?
+            .parse()?;
+        Ok((size, etag(&response)?))
+    }
+
+    /// Gets the `range` of an object, as long as it's still the version identified by `etag`.
+    pub async fn get_object_range(
+        &self,
+        key: &str,
+        etag: &str,
+        range: (u64, u64),
+    ) -> Result<Bytes> {
+        let headers = [
+            ("if-match", etag.to_string()),
+            ("range", format!("bytes={}-{}", range.0, range.1 - 1)),
+        ];
+        let bytes = self
+            .send(Method::GET, key, &[], &headers, Bytes::new())
+            .await?
+            .bytes()
+            .await?;
+        ensure!(
+            bytes.len() as u64 == range.1 - range.0,
+            "Got {} bytes of {} in range {}-{}.",
+            bytes.len(),
+            key,
+            range.0,
+            range.1,
+        );
+        Ok(bytes)
+    }
+
+    pub async fn delete_object(&self, key: &str) -> Result<()> {
+        self.send(Method::DELETE, key, &[], &[], Bytes::new())
+            .await?;
+        Ok(())
+    }
+
+    /// Lists the keys of all objects starting with `prefix`.
+    pub async fn list_objects(&self, prefix: &str) -> Result<Vec<String>> {
+        let mut keys = Vec::new();
+        let mut continuation_token = None;
+        loop {
+            let mut query = vec![
+                ("list-type", "2".to_string()),
+                ("prefix", prefix.to_string()),
+            ];
+            if let Some(token) = continuation_token.take() {
+                query.push(("continuation-token", token));
+            }
+            let xml = self
+                .send(Method::GET, "", &query, &[], Bytes::new())
+                .await?
+                .text()
+                .await?;
+            keys.extend(xml_values(&xml, "Key"));
+            if xml_values(&xml, "IsTruncated").next().as_deref() != Some("true") {
+                return Ok(keys);
+            }
+            continuation_token = Some(
+                xml_values(&xml, "NextContinuationToken")
+                    .next()
+                    .ok_or_else(|| anyhow!("Listing {} truncated without a token.", prefix))?,
+            );
+        }
+    }
+
+    /// Starts a multipart upload, returning its ID.
+    pub async fn create_multipart_upload(&self, key: &str) -> Result<String> {
+        let xml = self
+            .send(
+                Method::POST,
+                key,
+                &[("uploads", String::new())],
+                &[],
+                Bytes::new(),
+            )
+            .await?
+            .text()
+            .await?;
+        xml_values(&xml, "UploadId")
+            .next()
+            .ok_or_else(|| anyhow!("No UploadId for {}.", key))
+    }
+
+    /// Uploads a part of a multipart upload, returning its ETag.
+    pub async fn upload_part(
+        &self,
+        key: &str,
+        upload_id: &str,
+        part_number: usize,
+        body: Bytes,
+    ) -> Result<String> {
+        let query = [
+            ("partNumber", part_number.to_string()),
+            ("uploadId", upload_id.to_string()),
+        ];
+        let response = self.send(Method::PUT, key, &query, &[], body).await?;
+        etag(&response)
+    }
+
+    /// Completes a multipart upload with the ETags of its parts, numbered from 1 on.
+    pub async fn complete_multipart_upload(
+        &self,
+        key: &str,
+        upload_id: &str,
+        etags: &[String],
+    ) -> Result<()> {
+        let mut body = String::from("<CompleteMultipartUpload>");
+        for (i, etag) in etags.iter().enumerate() {
+            body.push_str(&format!(
+                "<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>",
+                i + 1,
+                xml_escape(etag),
+            ));
+        }
+        body.push_str("</CompleteMultipartUpload>");
+        let res = self
+            .send(
+                Method::POST,
+                key,
+                &[("uploadId", upload_id.to_string())],
+                &[],
+                body.into(),
+            )
+            .await;
+        let xml = match res {
+            Ok(response) => response.text().await?,
+            // The upload is gone when retrying after the response of a successful completion got
+            // lost, in which case the object tells whether it was completed with these parts.
+            Err(e) if e.to_string().contains("NoSuchUpload") => {
+                let expected_etag = multipart_etag(etags);
+                return match self.head_object(key).await {
+                    Ok((_size, etag)) if Some(&etag) == expected_etag.as_ref() => Ok(()),
+                    _ => Err(e),
+                };
+            }
+            Err(e) => return Err(e),
+        };
+        // This can fail after the response status is sent.
+        ensure!(
+            !xml.contains("<Error>"),
+            "Failed to complete uploading {}: {}",
+            key,
+            xml,
+        );
+        Ok(())
+    }
+
+    pub async fn abort_multipart_upload(&self, key: &str, upload_id: &str) -> Result<()> {
+        self.send(
+            Method::DELETE,
+            key,
+            &[("uploadId", upload_id.to_string())],
+            &[],
+            Bytes::new(),
+        )
+        .await?;
+        Ok(())
+    }
+
+    #[cfg(test)]
+    pub async fn create_bucket(&self) -> Result<()> {
+        match self.send(Method::PUT, "", &[], &[], Bytes::new()).await {
+            Err(e) if e.to_string().contains("BucketAlreadyOwnedByYou") => Ok(()),
+            res => res.map(|_| ()),
+        }
+    }
+
+    /// Sends a signed request for the object `key`, or the bucket if `key` is empty, retrying with
+    /// exponential backoff on server side and network errors.
+    async fn send(
+        &self,
+        method: Method,
+        key: &str,
+        query: &[(&str, String)],
+        headers: &[(&str, String)],
+        body: Bytes,
+    ) -> Result<Response> {
+        let mut attempt = 0;
+        loop {
+            let err = match self
+                .send_once(method.clone(), key, query, headers, body.clone())
+                .await
+            {
+                Ok(response) if response.status().is_success() => return Ok(response),
+                Ok(response) => {
+                    let status = response.status();
+                    let err = anyhow!(
+                        "S3 {} {} failed with status {}: {}",
+                        method,
+                        key,
+                        status,
+                        response.text().await.unwrap_or_default(),
+                    );
+                    if !is_retryable(status) {
+                        return Err(err);
+                    }
+                    err
+                }
+                Err(e) => anyhow!("S3 {} {} failed: {}", method, key, e),
+            };
+            if attempt >= self.max_retries {
+                return Err(err);
+            }
+            attempt += 1;
+            // Randomized, so that requests failing together, like the parts of a file, are not
+            // retried together.
+            let max_backoff_ms = 100 * 2u64.pow(min(attempt, 8) as u32);
+            let backoff = Duration::from_millis(
+                rand::thread_rng().gen_range(max_backoff_ms / 2, max_backoff_ms + 1),
+            );
+            warn!(
+                error = %err,
+                attempt = attempt,
+                "S3 request failed, retrying in {:?}.",
+                backoff
+            );
+            tokio::time::sleep(backoff).await;
+        }
+    }
+
+    async fn send_once(
+        &self,
+        method: Method,
+        key: &str,
+        query: &[(&str, String)],
+        headers: &[(&str, String)],
+        body: Bytes,
+    ) -> Result<Response> {
+        let endpoint_host = match self.endpoint.port() {
+            Some(port) => format!("{}:{}", self.endpoint.host_str().unwrap(), port),
+            None => self.endpoint.host_str().unwrap().to_string(),
+        };
+        let (host, path) = if self.path_style {
+            if key.is_empty() {
+                (endpoint_host, format!("/{}", self.bucket))
+            } else {
+                (endpoint_host, format!("/{}/{}", self.bucket, key))
+            }
+        } else {
+            (
+                format!("{}.{}", self.bucket, endpoint_host),
+                format!("/{}", key),
+            )
+        };
+
+        let mut query = query.to_vec();
+        query.sort();
+        let canonical_query = query
+            .iter()
+            .map(|(k, v)| format!("{}={}", uri_encode(k, true), uri_encode(v, true)))
+            .collect::<Vec<_>>()
+            .join("&");
+        let canonical_path = uri_encode(&path, false);
+
+        let now = Utc::now();
+        let mut all_headers = headers
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect::<Vec<_>>();
+        all_headers.push((
+            "x-amz-content-sha256".to_string(),
+            hex::encode(Sha256::digest(&body)),
+        ));
+        all_headers.push((
+            "x-amz-date".to_string(),
+            now.format("%Y%m%dT%H%M%SZ").to_string(),
+        ));
+        if let Some(token) = &self.credentials.session_token {
+            all_headers.push(("x-amz-security-token".to_string(), token.clone()));
+        }
+        let authorization = sign(
+            &self.credentials,
+            &self.region,
+            now,
+            method.as_str(),
+            &canonical_path,
+            &canonical_query,
+            &host,
+            &all_headers,
+        );
+
+        let mut url = format!("{}://{}{}", self.endpoint.scheme(), host, canonical_path);
+        if !canonical_query.is_empty() {
+            url.push('?');
+            url.push_str(&canonical_query);
+        }
+        let mut request = self.http.request(method, &url);
+        for (k, v) in &all_headers {
+            request = request.header(k.as_str(), v.as_str());
+        }
+        Ok(request
+            .header("authorization", authorization)
+            .body(body)
+            .send()
+            .await?)
+    }
+}
+
+fn is_retryable(status: StatusCode) -> bool {
+    status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+}
+
+fn etag(response: &Response) -> Result<String> {
+    Ok(response
+        .headers()
+        .get("etag")
+        .ok_or_else(|| anyhow!("No ETag in response."))?
+        .to_str()?
+        .to_string())
+}
+
+/// The ETag of an object completed from parts with `part_etags`, which is the MD5 of their MD5s
+/// followed by the number of parts, as long as the ETags of the parts are their MD5s, which is the
+/// case unless they are encrypted with KMS or customer provided keys.
+pub(super) fn multipart_etag(part_etags: &[String]) -> Option<String> {
+    let mut md5s = Vec::with_capacity(part_etags.len() * 16);
+    for etag in part_etags {
+        md5s.extend(hex::decode(etag.trim_matches('"')).ok()?);
+    }
+    Some(format!(
+        "\"{}-{}\"",
+        hex::encode(Md5::digest(&md5s)),
+        part_etags.len()
+    ))
+}
+
+/// Returns the Authorization header value for a request. `headers` must include
+/// `x-amz-content-sha256` and `x-amz-date`, which has to be `now`, and not include `host`, which
+/// is signed as well.
+#[allow(clippy::too_many_arguments)]
+pub(super) fn sign(
+    credentials: &Credentials,
+    region: &str,
+    now: DateTime<Utc>,
+    method: &str,
+    canonical_path: &str,
+    canonical_query: &str,
+    host: &str,
+    headers: &[(String, String)],
+) -> String {
+    let date = now.format("%Y%m%d").to_string();
+    let mut headers = headers
+        .iter()
+        .map(|(k, v)| (k.to_lowercase(), v.trim().to_string()))
+        .chain(std::iter::once(("host".to_string(), host.to_string())))
+        .collect::<Vec<_>>();
+    headers.sort();
+    let canonical_headers = headers
+        .iter()
+        .map(|(k, v)| format!("{}:{}\n", k, v))
+        .collect::<String>();
+    let signed_headers = headers
+        .iter()
+        .map(|(k, _)| k.as_str())
+        .collect::<Vec<_>>()
+        .join(";");
+    let payload_hash = headers
+        .iter()
+        .find(|(k, _)| k == "x-amz-content-sha256")
+        .map(|(_, v)| v.as_str())
+        .unwrap_or_default();
+    let canonical_request = format!(
+        "{}\n{}\n{}\n{}\n{}\n{}",
+        method, canonical_path, canonical_query, canonical_headers, signed_headers, payload_hash,
+    );
+
+    let scope = format!("{}/{}/s3/aws4_request", date, region);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        now.format("%Y%m%dT%H%M%SZ"),
+        scope,
+        hex::encode(Sha256::digest(canonical_request.as_bytes())),
+    );
+    let signing_key = [region, "s3", "aws4_request"].iter().fold(
+        hmac_sha256(
+            format!("AWS4{}", credentials.secret_access_key).as_bytes(),
+            date.as_bytes(),
+        ),
+        |key, data| hmac_sha256(&key, data.as_bytes()),
+    );
+    format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        credentials.access_key_id,
+        scope,
+        signed_headers,
+        hex::encode(hmac_sha256(&signing_key, string_to_sign.as_bytes())),
+    )
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_varkey(key).expect("HMAC takes keys of any size.");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// URI encodes as S3 expects, which is all but the unreserved characters, and '/' unless
+/// `encode_slash`.
+pub(super) fn uri_encode(value: &str, encode_slash: bool) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            b'/' if !encode_slash => encoded.push('/'),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// Values of all elements named `tag` in a flat XML response, which is all S3 responds with.
+pub(super) fn xml_values<'a>(xml: &'a str, tag: &str) -> impl Iterator<Item = String> + 'a {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+    let mut rest = xml;
+    std::iter::from_fn(move || {
+        let start = rest.find(&open)? + open.len();
+        let end = start + rest[start..].find(&close)?;
+        let value = xml_unescape(&rest[start..end]);
+        rest = &rest[end + close.len()..];
+        Some(value)
+    })
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn xml_unescape(value: &str) -> String {
+    value
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
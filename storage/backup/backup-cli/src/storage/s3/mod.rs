@@ -0,0 +1,418 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+mod client;
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    storage::{
+        s3::client::{Credentials, S3Client},
+        BackupHandle, BackupHandleRef, BackupStorage, FileHandle, FileHandleRef, ShellSafeName,
+        TextLine,
+    },
+    utils::{error_notes::ErrorNotes, stream::StreamX},
+};
+use anyhow::{anyhow, ensure, Result};
+use aptos_logger::prelude::*;
+use async_trait::async_trait;
+use bytes::{Bytes, BytesMut};
+use futures::{
+    stream::{self, TryStreamExt},
+    task::{Context, Poll},
+    FutureExt, StreamExt,
+};
+use std::{pin::Pin, sync::Arc, time::Duration};
+use structopt::StructOpt;
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, DuplexStream},
+    sync::oneshot,
+    task::JoinHandle,
+};
+use tokio_util::compat::FuturesAsyncReadCompatExt;
+
+#[derive(StructOpt)]
+pub struct S3Opt {
+    #[structopt(long = "bucket", help = "Bucket to store the backups in.")]
+    pub bucket: String,
+
+    #[structopt(
+        long = "prefix",
+        default_value = "",
+        help = "Prefix of the keys of all objects stored, like a folder in the bucket."
+    )]
+    pub prefix: String,
+
+    #[structopt(long = "region", env = "AWS_REGION", default_value = "us-east-1")]
+    pub region: String,
+
+    #[structopt(
+        long = "endpoint",
+        help = "[Defaults to the AWS S3 endpoint of the region] URL of the S3 compatible API, \
+        like http://localhost:9000 for a local MinIO."
+    )]
+    pub endpoint: Option<String>,
+
+    #[structopt(
+        long = "path-style",
+        help = "Address the bucket in the path of the URLs instead of in the host name, which \
+        most S3 compatible stores like MinIO need."
+    )]
+    pub path_style: bool,
+
+    #[structopt(
+        long = "access-key-id",
+        env = "AWS_ACCESS_KEY_ID",
+        hide_env_values = true
+    )]
+    pub access_key_id: String,
+
+    #[structopt(
+        long = "secret-access-key",
+        env = "AWS_SECRET_ACCESS_KEY",
+        hide_env_values = true
+    )]
+    pub secret_access_key: String,
+
+    #[structopt(
+        long = "session-token",
+        env = "AWS_SESSION_TOKEN",
+        hide_env_values = true
+    )]
+    pub session_token: Option<String>,
+
+    // S3 doesn't take parts smaller than 5MiB other than the last one, or more than 10000 parts,
+    // so the default allows files up to 160GiB.
+    #[structopt(
+        long = "part-size-mb",
+        default_value = "16",
+        help = "Files are uploaded and downloaded in parts of this many MiB. Each file being \
+        uploaded or downloaded holds up to --concurrent-parts + 2 parts in memory."
+    )]
+    pub part_size_mb: usize,
+
+    #[structopt(
+        long = "concurrent-parts",
+        default_value = "4",
+        help = "Maximum number of parts of each file uploaded or downloaded at the same time."
+    )]
+    pub concurrent_parts: usize,
+
+    #[structopt(
+        long = "max-retries",
+        default_value = "5",
+        help = "Maximum number of times to retry a request failing with a server side or network \
+        error."
+    )]
+    pub max_retries: usize,
+
+    #[structopt(
+        long = "connect-timeout-secs",
+        default_value = "10",
+        help = "Seconds to wait for connecting to the endpoint before failing a request."
+    )]
+    pub connect_timeout_secs: u64,
+
+    #[structopt(
+        long = "request-timeout-secs",
+        default_value = "300",
+        help = "Seconds to wait for a request to complete, including transferring a part, before \
+        failing it."
+    )]
+    pub request_timeout_secs: u64,
+}
+
+/// A BackupStorage that stores files as objects in a bucket, through the S3 API, which is spoken
+/// by AWS S3 and compatible object stores, like MinIO, GCS in its interoperability mode, or those
+/// of OCI and Scaleway.
+///
+/// Files are uploaded in parts, with a single request if small enough and a multipart upload
+/// otherwise, and downloaded with a ranged request per part, a few of them at a time.
+pub struct S3 {
+    client: Arc<S3Client>,
+    prefix: String,
+    part_size: usize,
+    concurrent_parts: usize,
+}
+
+impl S3 {
+    const METADATA_DIR: &'static str = "metadata";
+
+    pub fn new_with_opt(opt: S3Opt) -> Result<Self> {
+        ensure!(
+            opt.part_size_mb >= 5,
+            "--part-size-mb must be at least 5, the minimum S3 takes."
+        );
+        ensure!(
+            opt.concurrent_parts > 0,
+            "--concurrent-parts must be positive."
+        );
+        ensure!(
+            opt.connect_timeout_secs > 0 && opt.request_timeout_secs > 0,
+            "--connect-timeout-secs and --request-timeout-secs must be positive."
+        );
+        let endpoint = opt
+            .endpoint
+            .unwrap_or_else(|| format!("https://s3.{}.amazonaws.com", opt.region));
+        let client = S3Client::new(
+            &endpoint,
+            opt.bucket,
+            opt.region,
+            opt.path_style,
+            Credentials {
+                access_key_id: opt.access_key_id,
+                secret_access_key: opt.secret_access_key,
+                session_token: opt.session_token,
+            },
+            opt.max_retries,
+            Duration::from_secs(opt.connect_timeout_secs),
+            Duration::from_secs(opt.request_timeout_secs),
+        )?;
+        Ok(Self {
+            client: Arc::new(client),
+            prefix: opt.prefix.trim_matches('/').to_string(),
+            part_size: opt.part_size_mb << 20,
+            concurrent_parts: opt.concurrent_parts,
+        })
+    }
+
+    fn key(&self, file_handle: &FileHandleRef) -> String {
+        if self.prefix.is_empty() {
+            file_handle.to_string()
+        } else {
+            format!("{}/{}", self.prefix, file_handle)
+        }
+    }
+}
+
+#[async_trait]
+impl BackupStorage for S3 {
+    async fn create_backup(&self, name: &ShellSafeName) -> Result<BackupHandle> {
+        // There are no folders to create in a bucket.
+        Ok(name.to_string())
+    }
+
+    async fn create_for_write(
+        &self,
+        backup_handle: &BackupHandleRef,
+        name: &ShellSafeName,
+    ) -> Result<(FileHandle, Box<dyn AsyncWrite + Send + Unpin>)> {
+        let file_handle = format!("{}/{}", backup_handle, name.as_str());
+        let (sink, source) = tokio::io::duplex(self.part_size);
+        let (commit_tx, commit_rx) = oneshot::channel();
+        let upload = tokio::spawn(upload(
+            Arc::clone(&self.client),
+            self.key(&file_handle),
+            source,
+            commit_rx,
+            self.part_size,
+            self.concurrent_parts,
+        ));
+        Ok((
+            file_handle,
+            Box::new(ObjectSink {
+                sink,
+                commit_tx: Some(commit_tx),
+                upload: Some(upload),
+            }),
+        ))
+    }
+
+    async fn open_for_read(
+        &self,
+        file_handle: &FileHandleRef,
+    ) -> Result<Box<dyn AsyncRead + Send + Unpin>> {
+        let key = self.key(file_handle);
+        let (size, etag) = self.client.head_object(&key).await.err_notes(file_handle)?;
+        let part_size = self.part_size as u64;
+        let client = Arc::clone(&self.client);
+        let parts = (0..size).step_by(self.part_size).map(move |start| {
+            let client = Arc::clone(&client);
+            let key = key.clone();
+            let etag = etag.clone();
+            let end = std::cmp::min(start + part_size, size);
+            async move { client.get_object_range(&key, &etag, (start, end)).await }
+        });
+        Ok(Box::new(
+            stream::iter(parts)
+                .buffered_x(self.concurrent_parts, self.concurrent_parts)
+                .map_err(|e| futures::io::Error::new(futures::io::ErrorKind::Other, e))
+                .into_async_read()
+                .compat(),
+        ))
+    }
+
+    async fn save_metadata_line(&self, name: &ShellSafeName, content: &TextLine) -> Result<()> {
+        self.save_metadata_lines(name, std::slice::from_ref(content))
+            .await
+    }
+
+    async fn save_metadata_lines(&self, name: &ShellSafeName, lines: &[TextLine]) -> Result<()> {
+        let content = lines.iter().map(|line| line.as_ref()).collect::<String>();
+        let file_handle = format!("{}/{}", Self::METADATA_DIR, name.as_str());
+        self.client
+            .put_object(&self.key(&file_handle), content.into())
+            .await
+            .err_notes(name)
+    }
+
+    async fn list_metadata_files(&self) -> Result<Vec<FileHandle>> {
+        let prefix = self.key(&format!("{}/", Self::METADATA_DIR));
+        let keys = self.client.list_objects(&prefix).await?;
+        let root_len = prefix.len() - Self::METADATA_DIR.len() - 1;
+        Ok(keys
+            .into_iter()
+            .map(|key| key[root_len..].to_string())
+            .collect())
+    }
+
+    async fn delete_file(&self, file_handle: &FileHandleRef) -> Result<()> {
+        // S3 deletes objects that don't exist just fine.
+        self.client
+            .delete_object(&self.key(file_handle))
+            .await
+            .err_notes(file_handle)
+    }
+}
+
+/// Reads up to `part_size` bytes, less only at the end of `source`.
+async fn read_part(source: &mut DuplexStream, part_size: usize) -> Result<Bytes> {
+    let mut part = BytesMut::with_capacity(part_size);
+    while part.len() < part_size {
+        if source.read_buf(&mut part).await? == 0 {
+            break;
+        }
+    }
+    Ok(part.freeze())
+}
+
+/// Uploads what's written to the other end of `source`, once the write is committed through
+/// `commit_rx`, in a single request if it fits in one part, and with a multipart upload otherwise.
+async fn upload(
+    client: Arc<S3Client>,
+    key: String,
+    mut source: DuplexStream,
+    commit_rx: oneshot::Receiver<()>,
+    part_size: usize,
+    concurrent_parts: usize,
+) -> Result<()> {
+    let first_part = read_part(&mut source, part_size).await?;
+    if first_part.len() < part_size {
+        commit_rx
+            .await
+            .map_err(|_| anyhow!("Writing {} was abandoned.", key))?;
+        return client.put_object(&key, first_part).await;
+    }
+
+    let upload_id = client.create_multipart_upload(&key).await?;
+    let res = upload_parts(
+        &client,
+        &key,
+        &upload_id,
+        source,
+        first_part,
+        commit_rx,
+        part_size,
+        concurrent_parts,
+    )
+    .await;
+    if res.is_err() {
+        if let Err(e) = client.abort_multipart_upload(&key, &upload_id).await {
+            warn!(error = %e, key = %key, "Failed to abort multipart upload.");
+        }
+    }
+    res
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn upload_parts(
+    client: &Arc<S3Client>,
+    key: &str,
+    upload_id: &str,
+    source: DuplexStream,
+    first_part: Bytes,
+    commit_rx: oneshot::Receiver<()>,
+    part_size: usize,
+    concurrent_parts: usize,
+) -> Result<()> {
+    let parts = stream::try_unfold(
+        (source, Some(first_part)),
+        move |(mut source, first_part)| async move {
+            let part = match first_part {
+                Some(part) => part,
+                None => read_part(&mut source, part_size).await?,
+            };
+            Ok::<_, anyhow::Error>(if part.is_empty() {
+                None
+            } else {
+                Some((part, (source, None)))
+            })
+        },
+    );
+    let etags = parts
+        .enumerate()
+        .map(|(i, part)| {
+            let client = Arc::clone(client);
+            let key = key.to_string();
+            let upload_id = upload_id.to_string();
+            async move { client.upload_part(&key, &upload_id, i + 1, part?).await }
+        })
+        .buffered_x(concurrent_parts, concurrent_parts)
+        .try_collect::<Vec<_>>()
+        .await?;
+    commit_rx
+        .await
+        .map_err(|_| anyhow!("Writing {} was abandoned.", key))?;
+    client
+        .complete_multipart_upload(key, upload_id, &etags)
+        .await
+}
+
+/// The writing end of a file being uploaded in the background, which is only stored once shut
+/// down, and not at all if dropped before that.
+struct ObjectSink {
+    sink: DuplexStream,
+    commit_tx: Option<oneshot::Sender<()>>,
+    upload: Option<JoinHandle<Result<()>>>,
+}
+
+impl AsyncWrite for ObjectSink {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize, tokio::io::Error>> {
+        Pin::new(&mut self.sink).poll_write(cx, buf)
+    }
+
+    fn poll_flush(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), tokio::io::Error>> {
+        Pin::new(&mut self.sink).poll_flush(cx)
+    }
+
+    fn poll_shutdown(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), tokio::io::Error>> {
+        if let Some(commit_tx) = self.commit_tx.take() {
+            // Errors mean the upload failed already, which is reported below.
+            let _ = commit_tx.send(());
+        }
+        futures::ready!(Pin::new(&mut self.sink).poll_shutdown(cx))?;
+
+        let upload = match self.upload.as_mut() {
+            Some(upload) => upload,
+            None => return Poll::Ready(Ok(())),
+        };
+        let res = futures::ready!(upload.poll_unpin(cx));
+        self.upload = None;
+        Poll::Ready(
+            res.map_err(anyhow::Error::from)
+                .and_then(|res| res)
+                .map_err(|e| tokio::io::Error::new(tokio::io::ErrorKind::Other, e)),
+        )
+    }
+}
@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    storage::{BackupStorage, ShellSafeName, TextLine},
+    storage::{BackupStorage, FileHandleRef, ShellSafeName, TextLine},
     utils::PathToString,
 };
 use anyhow::Result;
@@ -11,7 +11,7 @@ use proptest::{
     collection::{hash_map, vec},
     prelude::*,
 };
-use std::{collections::HashMap, path::Path};
+use std::{collections::HashMap, path::Path, str::FromStr};
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
 fn to_file_name(backup_name: &str, file_name: &str) -> String {
@@ -102,3 +102,62 @@ pub fn arb_metadata_files() -> impl Strategy<Value = Vec<(ShellSafeName, TextLin
         .prop_map(HashMap::into_iter)
         .prop_map(Iterator::collect)
 }
+
+pub async fn read_file(store: &dyn BackupStorage, file_handle: &FileHandleRef) -> Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    store
+        .open_for_read(file_handle)
+        .await?
+        .read_to_end(&mut buf)
+        .await?;
+    Ok(buf)
+}
+
+pub async fn test_delete_files_impl(
+    store: Box<dyn BackupStorage>,
+    backups: HashMap<ShellSafeName, HashMap<ShellSafeName, Vec<u8>>>,
+    input: Vec<(ShellSafeName, TextLine)>,
+) {
+    // Keep the first file of each backup, and delete the others.
+    for (backup_name, files) in &backups {
+        let backup_handle = store.create_backup(backup_name).await.unwrap();
+        let mut kept = None;
+        for (name, content) in files {
+            let (handle, mut file) = store.create_for_write(&backup_handle, name).await.unwrap();
+            file.write_all(content).await.unwrap();
+            file.shutdown().await.unwrap();
+            if kept.is_none() {
+                kept = Some((handle, content));
+            } else {
+                store.delete_file(&handle).await.unwrap();
+                assert!(read_file(store.as_ref(), &handle).await.is_err());
+                // Deleting it again succeeds.
+                store.delete_file(&handle).await.unwrap();
+            }
+        }
+        let (handle, content) = kept.unwrap();
+        assert_eq!(&read_file(store.as_ref(), &handle).await.unwrap(), content);
+    }
+
+    let lines = input
+        .into_iter()
+        .map(|(_name, content)| content)
+        .sorted()
+        .collect::<Vec<_>>();
+    store
+        .save_metadata_lines(&ShellSafeName::from_str("compacted.meta").unwrap(), &lines)
+        .await
+        .unwrap();
+    let file_handles = store.list_metadata_files().await.unwrap();
+    assert_eq!(file_handles.len(), 1);
+    let read_back = String::from_utf8(read_file(store.as_ref(), &file_handles[0]).await.unwrap())
+        .unwrap()
+        .lines()
+        .map(TextLine::new)
+        .collect::<Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(read_back, lines);
+
+    store.delete_file(&file_handles[0]).await.unwrap();
+    assert!(store.list_metadata_files().await.unwrap().is_empty());
+}
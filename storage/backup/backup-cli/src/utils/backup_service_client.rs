@@ -120,4 +120,17 @@ impl BackupServiceClient {
         ))
         .await
     }
+
+    pub async fn get_indexer_latest_version(&self) -> Result<Option<Version>> {
+        let mut buf = Vec::new();
+        self.get("indexer_latest_version")
+            .await?
+            .read_to_end(&mut buf)
+            .await?;
+        Ok(bcs::from_bytes(&buf)?)
+    }
+
+    pub async fn get_table_infos(&self) -> Result<impl AsyncRead> {
+        self.get("table_infos").await
+    }
 }
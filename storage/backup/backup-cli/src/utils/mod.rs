@@ -25,6 +25,7 @@ use aptos_types::{
     waypoint::Waypoint,
 };
 use aptosdb::{backup::restore_handler::RestoreHandler, AptosDB, GetRestoreHandler};
+use aptosdb_indexer::Indexer;
 use std::{
     collections::HashMap,
     convert::TryFrom,
@@ -187,12 +188,35 @@ impl RestoreRunMode {
     }
 }
 
+/// The indexer DB in the target DB dir, which is only opened when restoring an indexer backup, so
+/// that restoring nodes without the indexer doesn't create one.
+#[derive(Clone)]
+pub struct IndexerDbTarget {
+    db_dir: PathBuf,
+    rocksdb_config: RocksdbConfig,
+}
+
+impl IndexerDbTarget {
+    pub fn new(db_dir: PathBuf, rocksdb_config: RocksdbConfig) -> Self {
+        Self {
+            db_dir,
+            rocksdb_config,
+        }
+    }
+
+    pub fn open(&self) -> Result<Indexer> {
+        Indexer::open(&self.db_dir, self.rocksdb_config)
+    }
+}
+
 #[derive(Clone)]
 pub struct GlobalRestoreOptions {
     pub target_version: Version,
     pub trusted_waypoints: Arc<HashMap<Version, Waypoint>>,
     pub run_mode: Arc<RestoreRunMode>,
     pub concurrent_downloads: usize,
+    /// Where indexer backups are restored to, `None` on a dry run.
+    pub indexer_db: Option<IndexerDbTarget>,
 }
 
 impl TryFrom<GlobalRestoreOpt> for GlobalRestoreOptions {
@@ -201,12 +225,15 @@ impl TryFrom<GlobalRestoreOpt> for GlobalRestoreOptions {
     fn try_from(opt: GlobalRestoreOpt) -> Result<Self> {
         let target_version = opt.target_version.unwrap_or(Version::max_value());
         let concurrent_downloads = opt.concurernt_downloads.get();
+        let rocksdb_configs: RocksdbConfigs = opt.rocksdb_opt.into();
         let run_mode = if let Some(db_dir) = &opt.db_dir {
             let restore_handler = Arc::new(AptosDB::open(
                 db_dir,
                 false,                       /* read_only */
                 NO_OP_STORAGE_PRUNER_CONFIG, /* pruner config */
-                opt.rocksdb_opt.into(),
+                rocksdb_configs,
+                false,
+                false,
                 false,
                 TARGET_SNAPSHOT_SIZE,
             )?)
@@ -220,6 +247,9 @@ impl TryFrom<GlobalRestoreOpt> for GlobalRestoreOptions {
             trusted_waypoints: Arc::new(opt.trusted_waypoints.verify()?),
             run_mode: Arc::new(run_mode),
             concurrent_downloads,
+            indexer_db: opt
+                .db_dir
+                .map(|db_dir| IndexerDbTarget::new(db_dir, rocksdb_configs.index_db_config)),
         })
     }
 }
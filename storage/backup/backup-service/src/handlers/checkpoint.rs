@@ -0,0 +1,91 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::handlers::utils::unwrap_or_500;
+use anyhow::{ensure, Result};
+use aptos_config::config::BackupServiceCheckpointConfig;
+use aptosdb::AptosDB;
+use serde_json::json;
+use std::{
+    fs,
+    path::PathBuf,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use warp::{http::StatusCode, reply::Reply};
+
+/// Creates checkpoints of the DBs for the `checkpoint` endpoint.
+#[derive(Clone)]
+pub(crate) struct Checkpointer {
+    db: Arc<AptosDB>,
+    dir: PathBuf,
+    auth_token: String,
+}
+
+impl Checkpointer {
+    pub fn new(db: Arc<AptosDB>, config: BackupServiceCheckpointConfig) -> Result<Self> {
+        let auth_token = fs::read_to_string(&config.auth_token_file)?
+            .trim()
+            .to_string();
+        ensure!(
+            !auth_token.is_empty(),
+            "Checkpoint auth token file {:?} is empty.",
+            config.auth_token_file,
+        );
+        Ok(Self {
+            db,
+            dir: config.dir,
+            auth_token,
+        })
+    }
+
+    fn is_authorized(&self, authorization: Option<&str>) -> bool {
+        let token = match authorization.and_then(|value| value.strip_prefix("Bearer ")) {
+            Some(token) => token.as_bytes(),
+            None => return false,
+        };
+        // Compare in constant time, not to give away how much of the token is right.
+        let expected = self.auth_token.as_bytes();
+        token.len() == expected.len()
+            && token
+                .iter()
+                .zip(expected)
+                .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+                == 0
+    }
+
+    /// Creates a checkpoint in a new directory, and replies with its path and latest version.
+    fn create_checkpoint(&self) -> Result<Box<dyn Reply>> {
+        let timestamp_ms = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
+        let path = self.dir.join(format!("checkpoint_{}", timestamp_ms));
+        ensure!(!path.exists(), "Checkpoint {:?} already exists.", path);
+        fs::create_dir_all(&path)?;
+        let version = self.db.create_checkpoint(&path)?;
+        Ok(Box::new(warp::reply::json(&json!({
+            "path": path,
+            "version": version,
+        }))))
+    }
+}
+
+/// Handles `POST checkpoint`, replying 404 when checkpoints aren't enabled and 401 when the
+/// request doesn't carry the right token.
+pub(super) async fn handle_checkpoint(
+    checkpointer: Option<Checkpointer>,
+    authorization: Option<String>,
+) -> Box<dyn Reply> {
+    let checkpointer = match checkpointer {
+        Some(checkpointer) => checkpointer,
+        None => return Box::new(StatusCode::NOT_FOUND),
+    };
+    if !checkpointer.is_authorized(authorization.as_deref()) {
+        return Box::new(StatusCode::UNAUTHORIZED);
+    }
+    // Creating a checkpoint flushes the DBs and holds off commits, so keep it off the runtime's
+    // worker threads.
+    let result = tokio::task::spawn_blocking(move || checkpointer.create_checkpoint())
+        .await
+        .map_err(anyhow::Error::from)
+        .and_then(|result| result);
+    unwrap_or_500(result)
+}
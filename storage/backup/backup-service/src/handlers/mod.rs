@@ -1,15 +1,20 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
+pub(crate) mod checkpoint;
 mod utils;
 
-use crate::handlers::utils::{
-    handle_rejection, reply_with_async_channel_writer, reply_with_bcs_bytes,
-    send_size_prefixed_bcs_bytes, unwrap_or_500, LATENCY_HISTOGRAM,
+use crate::handlers::{
+    checkpoint::{handle_checkpoint, Checkpointer},
+    utils::{
+        handle_rejection, reply_with_async_channel_writer, reply_with_bcs_bytes,
+        send_size_prefixed_bcs_bytes, unwrap_or_500, LATENCY_HISTOGRAM,
+    },
 };
 use aptos_crypto::hash::HashValue;
 use aptos_types::transaction::Version;
 use aptosdb::backup::backup_handler::BackupHandler;
+use std::convert::Infallible;
 use warp::{filters::BoxedFilter, reply::Reply, Filter};
 
 static DB_STATE: &str = "db_state";
@@ -19,8 +24,14 @@ static STATE_ROOT_PROOF: &str = "state_root_proof";
 static EPOCH_ENDING_LEDGER_INFOS: &str = "epoch_ending_ledger_infos";
 static TRANSACTIONS: &str = "transactions";
 static TRANSACTION_RANGE_PROOF: &str = "transaction_range_proof";
+static INDEXER_LATEST_VERSION: &str = "indexer_latest_version";
+static TABLE_INFOS: &str = "table_infos";
+static CHECKPOINT: &str = "checkpoint";
 
-pub(crate) fn get_routes(backup_handler: BackupHandler) -> BoxedFilter<(impl Reply,)> {
+pub(crate) fn get_routes(
+    backup_handler: BackupHandler,
+    checkpointer: Option<Checkpointer>,
+) -> BoxedFilter<(impl Reply,)> {
     // GET db_state
     let bh = backup_handler.clone();
     let db_state = warp::path::end()
@@ -96,7 +107,7 @@ pub(crate) fn get_routes(backup_handler: BackupHandler) -> BoxedFilter<(impl Rep
         .recover(handle_rejection);
 
     // GET transaction_range_proof/<first_version>/<last_version>
-    let bh = backup_handler;
+    let bh = backup_handler.clone();
     let transaction_range_proof = warp::path!(Version / Version)
         .map(move |first_version, last_version| {
             reply_with_bcs_bytes(
@@ -107,6 +118,25 @@ pub(crate) fn get_routes(backup_handler: BackupHandler) -> BoxedFilter<(impl Rep
         .map(unwrap_or_500)
         .recover(handle_rejection);
 
+    // GET indexer_latest_version
+    let bh = backup_handler.clone();
+    let indexer_latest_version = warp::path::end()
+        .map(move || {
+            reply_with_bcs_bytes(INDEXER_LATEST_VERSION, &bh.get_indexer_latest_version()?)
+        })
+        .map(unwrap_or_500)
+        .recover(handle_rejection);
+
+    // GET table_infos
+    let bh = backup_handler;
+    let table_infos = warp::path::end()
+        .map(move || {
+            reply_with_async_channel_writer(&bh, TABLE_INFOS, |bh, sender| async move {
+                send_size_prefixed_bcs_bytes(bh.get_table_info_iter(), sender).await
+            })
+        })
+        .recover(handle_rejection);
+
     // Route by endpoint name.
     let routes = warp::any()
         .and(warp::path(DB_STATE).and(db_state))
@@ -115,11 +145,25 @@ pub(crate) fn get_routes(backup_handler: BackupHandler) -> BoxedFilter<(impl Rep
         .or(warp::path(STATE_ROOT_PROOF).and(state_root_proof))
         .or(warp::path(EPOCH_ENDING_LEDGER_INFOS).and(epoch_ending_ledger_infos))
         .or(warp::path(TRANSACTIONS).and(transactions))
-        .or(warp::path(TRANSACTION_RANGE_PROOF).and(transaction_range_proof));
+        .or(warp::path(TRANSACTION_RANGE_PROOF).and(transaction_range_proof))
+        .or(warp::path(INDEXER_LATEST_VERSION).and(indexer_latest_version))
+        .or(warp::path(TABLE_INFOS).and(table_infos));
+
+    // POST checkpoint
+    // Matched by path before method, so that other paths are still answered with a 404.
+    let checkpoint = warp::path(CHECKPOINT)
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(warp::header::optional::<String>("authorization"))
+        .and_then(move |authorization| {
+            let checkpointer = checkpointer.clone();
+            async move { Ok::<_, Infallible>(handle_checkpoint(checkpointer, authorization).await) }
+        });
 
-    // Serve all routes for GET only.
+    // Serve all routes but checkpoint for GET only.
     warp::get()
         .and(routes)
+        .or(checkpoint)
         .with(warp::log::custom(|info| {
             let endpoint = info.path().split('/').nth(1).unwrap_or("-");
             LATENCY_HISTOGRAM
@@ -3,15 +3,25 @@
 
 mod handlers;
 
-use crate::handlers::get_routes;
+use crate::handlers::{checkpoint::Checkpointer, get_routes};
+use aptos_config::config::BackupServiceCheckpointConfig;
 use aptos_logger::prelude::*;
 use aptosdb::AptosDB;
 use std::{net::SocketAddr, sync::Arc};
 use tokio::runtime::{Builder, Runtime};
 
-pub fn start_backup_service(address: SocketAddr, db: Arc<AptosDB>) -> Runtime {
+/// Starts the backup service, which also creates checkpoints of the DBs on request when
+/// `checkpoint_config` is given.
+pub fn start_backup_service(
+    address: SocketAddr,
+    db: Arc<AptosDB>,
+    checkpoint_config: Option<BackupServiceCheckpointConfig>,
+) -> Runtime {
     let backup_handler = db.get_backup_handler();
-    let routes = get_routes(backup_handler);
+    let checkpointer = checkpoint_config.map(|config| {
+        Checkpointer::new(db, config).expect("[backup] failed to set up checkpoints")
+    });
+    let routes = get_routes(backup_handler, checkpointer);
 
     let runtime = Builder::new_multi_thread()
         .thread_name("backup")
@@ -39,7 +49,7 @@ mod tests {
     use aptos_config::utils::get_available_port;
     use aptos_crypto::hash::HashValue;
     use aptos_temppath::TempPath;
-    use reqwest::blocking::get;
+    use reqwest::blocking::{get, Client};
     use std::net::{IpAddr, Ipv4Addr};
 
     /// 404 - endpoint not found
@@ -52,7 +62,11 @@ mod tests {
         let tmpdir = TempPath::new();
         let db = Arc::new(AptosDB::new_for_test(&tmpdir));
         let port = get_available_port();
-        let _rt = start_backup_service(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port), db);
+        let _rt = start_backup_service(
+            SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port),
+            db,
+            None,
+        );
 
         // Endpoint doesn't exist.
         let resp = get(&format!("http://127.0.0.1:{}/", port)).unwrap();
@@ -94,4 +108,63 @@ mod tests {
         assert_eq!(resp.content_length(), None);
         assert!(resp.bytes().is_err());
     }
+
+    #[test]
+    fn checkpoint() {
+        let tmpdir = TempPath::new();
+        let db = Arc::new(AptosDB::new_for_test(&tmpdir));
+        let checkpoint_dir = TempPath::new();
+        checkpoint_dir.create_as_dir().unwrap();
+        let auth_token_file = checkpoint_dir.path().join("auth_token");
+        std::fs::write(&auth_token_file, "secret\n").unwrap();
+        let port = get_available_port();
+        let _rt = start_backup_service(
+            SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port),
+            db,
+            Some(BackupServiceCheckpointConfig {
+                dir: checkpoint_dir.path().to_path_buf(),
+                auth_token_file,
+            }),
+        );
+        let url = format!("http://127.0.0.1:{}/checkpoint", port);
+        let client = Client::new();
+
+        // Only POST is served.
+        let resp = get(&url).unwrap();
+        assert_eq!(resp.status(), 405);
+
+        // Token missing or wrong.
+        let resp = client.post(&url).send().unwrap();
+        assert_eq!(resp.status(), 401);
+        let resp = client.post(&url).bearer_auth("secreT").send().unwrap();
+        assert_eq!(resp.status(), 401);
+
+        let resp = client.post(&url).bearer_auth("secret").send().unwrap();
+        assert_eq!(resp.status(), 200);
+        let body: serde_json::Value = resp.json().unwrap();
+        let path = std::path::PathBuf::from(body["path"].as_str().unwrap());
+        assert!(path.starts_with(checkpoint_dir.path()));
+        assert!(path.join("ledger_db").is_dir());
+        assert!(path.join("state_merkle_db").is_dir());
+        assert!(body["version"].is_null());
+    }
+
+    #[test]
+    fn checkpoint_disabled() {
+        let tmpdir = TempPath::new();
+        let db = Arc::new(AptosDB::new_for_test(&tmpdir));
+        let port = get_available_port();
+        let _rt = start_backup_service(
+            SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port),
+            db,
+            None,
+        );
+
+        let resp = Client::new()
+            .post(&format!("http://127.0.0.1:{}/checkpoint", port))
+            .bearer_auth("secret")
+            .send()
+            .unwrap();
+        assert_eq!(resp.status(), 404);
+    }
 }
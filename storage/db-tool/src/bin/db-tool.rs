@@ -0,0 +1,10 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use db_tool::Cmd;
+use structopt::StructOpt;
+
+fn main() -> Result<()> {
+    Cmd::from_args().run()
+}
@@ -0,0 +1,91 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::DbDir;
+use anyhow::{ensure, Result};
+use aptos_jellyfish_merkle::node_type::NodeKey;
+use aptos_types::transaction::Version;
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+pub struct Cmd {
+    #[structopt(flatten)]
+    db_dir: DbDir,
+
+    /// The first `stale_since_version` to check.
+    #[structopt(long, default_value = "0")]
+    start_version: Version,
+
+    /// Exclusive. Defaults to checking up to the end of the index.
+    #[structopt(long)]
+    end_version: Option<Version>,
+
+    /// How many inconsistencies to print before giving up.
+    #[structopt(long, default_value = "100")]
+    max_errors: usize,
+}
+
+impl Cmd {
+    /// Checks each entry of the stale node index in the range: the node it refers to must have
+    /// been written before it became stale, must still exist, as the pruner deletes the node
+    /// together with its index entry, and a tree root must exist at the version it became stale
+    /// at, which is the version of the update that replaced it.
+    pub fn run(self) -> Result<()> {
+        let handler = self.db_dir.open()?;
+        let end_version = self.end_version.unwrap_or(Version::max_value());
+
+        let mut num_checked = 0;
+        let mut errors = Vec::new();
+        let mut last_root_checked = None;
+        for res in handler.get_stale_node_index_iter(self.start_version)? {
+            let index = res?;
+            let stale_since_version = index.stale_since_version;
+            if stale_since_version >= end_version {
+                break;
+            }
+            num_checked += 1;
+
+            let node_key = &index.node_key;
+            if node_key.version() >= stale_since_version {
+                errors.push(format!(
+                    "Node {:?} is stale since version {}, not after it was written.",
+                    node_key, stale_since_version,
+                ));
+            }
+            if handler.get_jmt_node(node_key)?.is_none() {
+                errors.push(format!(
+                    "Node {:?} stale since version {} is missing.",
+                    node_key, stale_since_version,
+                ));
+            }
+            if last_root_checked != Some(stale_since_version) {
+                last_root_checked = Some(stale_since_version);
+                let root_key = NodeKey::new_empty_path(stale_since_version);
+                if handler.get_jmt_node(&root_key)?.is_none() {
+                    errors.push(format!(
+                        "Nodes are stale since version {}, which has no tree root.",
+                        stale_since_version,
+                    ));
+                }
+            }
+            if errors.len() >= self.max_errors {
+                break;
+            }
+        }
+
+        for error in &errors {
+            println!("{}", error);
+        }
+        ensure!(
+            errors.is_empty(),
+            "Stale node index is inconsistent, {} errors found in {} entries checked.",
+            errors.len(),
+            num_checked,
+        );
+        println!(
+            "Stale node index is consistent, {} entries checked.",
+            num_checked
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,156 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::DbDir;
+use anyhow::{bail, ensure, Error, Result};
+use aptos_jellyfish_merkle::node_type::NodeKey;
+use aptos_types::{state_store::state_key::StateKey, transaction::Version};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::str::FromStr;
+use structopt::StructOpt;
+
+type Node = aptos_jellyfish_merkle::node_type::Node<StateKey>;
+
+#[derive(StructOpt)]
+pub struct Cmd {
+    #[structopt(flatten)]
+    db_dir: DbDir,
+
+    /// One of `transaction`, `transaction-info`, `event`, `write-set` and `jmt-node`.
+    #[structopt(long)]
+    schema: DumpSchema,
+
+    #[structopt(long)]
+    start_version: Version,
+
+    /// Exclusive.
+    #[structopt(long)]
+    end_version: Version,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum DumpSchema {
+    Transaction,
+    TransactionInfo,
+    Event,
+    WriteSet,
+    JmtNode,
+}
+
+impl FromStr for DumpSchema {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "transaction" => DumpSchema::Transaction,
+            "transaction-info" => DumpSchema::TransactionInfo,
+            "event" => DumpSchema::Event,
+            "write-set" => DumpSchema::WriteSet,
+            "jmt-node" => DumpSchema::JmtNode,
+            _ => bail!("Unknown schema to dump: {}", s),
+        })
+    }
+}
+
+impl Cmd {
+    pub fn run(self) -> Result<()> {
+        ensure!(
+            self.start_version <= self.end_version,
+            "start_version {} is after end_version {}",
+            self.start_version,
+            self.end_version,
+        );
+        let handler = self.db_dir.open()?;
+        let (start, end) = (self.start_version, self.end_version);
+
+        match self.schema {
+            DumpSchema::Transaction => dump_entries(
+                handler.get_transaction_iter(start)?,
+                |version| *version < end,
+                |version| json!({ "version": version }),
+            ),
+            DumpSchema::TransactionInfo => dump_entries(
+                handler.get_transaction_info_iter(start)?,
+                |version| *version < end,
+                |version| json!({ "version": version }),
+            ),
+            DumpSchema::Event => dump_entries(
+                handler.get_event_iter(start)?,
+                |(version, _index)| *version < end,
+                |(version, index)| json!({ "version": version, "index": index }),
+            ),
+            DumpSchema::WriteSet => dump_entries(
+                handler.get_write_set_iter(start)?,
+                |version| *version < end,
+                |version| json!({ "version": version }),
+            ),
+            DumpSchema::JmtNode => {
+                for res in handler.get_jmt_node_iter(start)? {
+                    let (node_key, node) = res?;
+                    if node_key.version() >= end {
+                        break;
+                    }
+                    println!("{}", describe_node(&node_key, &node)?);
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Prints the entries for as long as `in_range` holds, as JSON lines of the key, as described by
+/// `describe_key`, and the value.
+fn dump_entries<K, V: Serialize>(
+    iter: impl Iterator<Item = Result<(K, V)>>,
+    in_range: impl Fn(&K) -> bool,
+    describe_key: impl Fn(&K) -> Value,
+) -> Result<()> {
+    for res in iter {
+        let (key, value) = res?;
+        if !in_range(&key) {
+            break;
+        }
+        println!(
+            "{}",
+            json!({ "key": describe_key(&key), "value": serde_json::to_value(&value)? })
+        );
+    }
+    Ok(())
+}
+
+/// Nodes aren't serializable as they are, so they are described field by field.
+fn describe_node(node_key: &NodeKey, node: &Node) -> Result<Value> {
+    let key = json!({
+        "version": node_key.version(),
+        "nibble_path": format!("{:?}", node_key.nibble_path()),
+    });
+    let value = match node {
+        Node::Internal(internal) => json!({
+            "type": "internal",
+            "hash": internal.hash(),
+            "leaf_count": internal.leaf_count(),
+            "children": internal
+                .children_sorted()
+                .map(|(nibble, child)| {
+                    json!({
+                        "nibble": u8::from(*nibble),
+                        "hash": child.hash,
+                        "version": child.version,
+                        "leaf_count": child.leaf_count(),
+                        "is_leaf": child.is_leaf(),
+                    })
+                })
+                .collect::<Vec<_>>(),
+        }),
+        Node::Leaf(leaf) => json!({
+            "type": "leaf",
+            "hash": leaf.hash(),
+            "account_key": leaf.account_key(),
+            "value_hash": leaf.value_hash(),
+            "state_key": serde_json::to_value(&leaf.value_index().0)?,
+            "value_version": leaf.value_index().1,
+        }),
+    };
+    Ok(json!({ "key": key, "value": value }))
+}
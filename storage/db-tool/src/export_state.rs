@@ -0,0 +1,612 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::DbDir;
+use anyhow::{ensure, format_err, Context, Result};
+use aptos_config::config::{RocksdbConfigs, NO_OP_STORAGE_PRUNER_CONFIG, TARGET_SNAPSHOT_SIZE};
+use aptos_crypto::HashValue;
+use aptos_types::{
+    access_path::Path,
+    state_store::{state_key::StateKey, table::TableHandle},
+    transaction::Version,
+};
+use aptos_vm::data_cache::AsMoveResolver;
+use aptosdb::AptosDB;
+use aptosdb_indexer::Indexer;
+use move_deps::{
+    move_core_types::language_storage::{StructTag, CORE_CODE_ADDRESS},
+    move_resource_viewer::{AnnotatedMoveStruct, AnnotatedMoveValue, MoveValueAnnotator},
+};
+use serde_json::{json, Map, Value};
+use std::{
+    collections::{HashMap, HashSet},
+    convert::TryInto,
+    fs::{self, File, OpenOptions},
+    path::{Path as FsPath, PathBuf},
+    sync::Arc,
+};
+use storage_interface::{state_view::DbStateView, DbReader};
+use structopt::StructOpt;
+
+/// File names longer than this are truncated, and suffixed with a hash of the type to keep them
+/// apart.
+const MAX_FILE_STEM_LEN: usize = 200;
+
+#[derive(StructOpt)]
+pub struct Cmd {
+    #[structopt(flatten)]
+    db_dir: DbDir,
+
+    /// Version of the state snapshot to export. Defaults to the latest snapshot.
+    #[structopt(long)]
+    version: Option<Version>,
+
+    /// Directory to write the CSV files into, which is created if needed.
+    #[structopt(long, parse(from_os_str))]
+    output_dir: PathBuf,
+
+    /// Number of state values read from the DB at a time.
+    #[structopt(long, default_value = "10000")]
+    chunk_size: usize,
+
+    /// Most CSV files kept open at a time. The least recently written is closed to open another,
+    /// and appended to if written again.
+    #[structopt(long, default_value = "256")]
+    max_open_files: usize,
+}
+
+impl Cmd {
+    /// Exports the resources and table items of a state snapshot as CSV, one file per type.
+    ///
+    /// Resources go to `resources/<type>.csv` with `address`, `type` and `value` columns, and
+    /// table items to `table_items/<value type>.csv` with `handle`, `key_type`, `value_type`, `key`
+    /// and `value` columns. Values are decoded to JSON the way the API shows them. Table items
+    /// are decoded with the table infos of the indexer DB, and those of tables it doesn't know
+    /// about go to `table_items/unknown.csv`, hex encoded. Modules are not exported.
+    pub fn run(self) -> Result<()> {
+        ensure!(self.chunk_size > 0, "chunk_size must be positive.");
+        ensure!(self.max_open_files > 0, "max_open_files must be positive.");
+        let db: Arc<dyn DbReader> = Arc::new(AptosDB::open(
+            &self.db_dir.db_dir,
+            true, /* readonly */
+            NO_OP_STORAGE_PRUNER_CONFIG,
+            RocksdbConfigs::default(),
+            false, /* indexer */
+            false, /* state key history */
+            false, /* affected account index */
+            TARGET_SNAPSHOT_SIZE,
+        )?);
+        let indexer = match Indexer::open_readonly(
+            &self.db_dir.db_dir,
+            RocksdbConfigs::default().index_db_config,
+        ) {
+            Ok(indexer) => Some(indexer),
+            Err(err) => {
+                eprintln!(
+                    "No indexer DB to decode table items with, they are exported hex encoded: {}",
+                    err
+                );
+                None
+            }
+        };
+
+        let version = match self.version {
+            Some(version) => {
+                ensure!(
+                    db.get_state_snapshot_before(version + 1)?
+                        .map(|(snapshot_version, _)| snapshot_version)
+                        == Some(version),
+                    "No state snapshot at version {}.",
+                    version,
+                );
+                version
+            }
+            None => db
+                .get_state_snapshot_before(Version::max_value())?
+                .map(|(version, _)| version)
+                .ok_or_else(|| format_err!("DB has no state snapshot."))?,
+        };
+
+        let state_view = DbStateView {
+            db: Arc::clone(&db),
+            version: Some(version),
+        };
+        let resolver = state_view.as_move_resolver();
+        let annotator = MoveValueAnnotator::new(&resolver);
+        let mut writers = CsvWriters::new(&self.output_dir, self.max_open_files)?;
+        let mut num_modules = 0;
+
+        let num_values = db.get_state_leaf_count(version)?;
+        let mut next_index = 0;
+        while next_index < num_values {
+            let chunk =
+                db.get_state_value_chunk_with_proof(version, next_index, self.chunk_size)?;
+            let is_last_chunk = chunk.is_last_chunk();
+            ensure!(
+                !chunk.raw_values.is_empty() || is_last_chunk,
+                "Got an empty chunk of state values at index {} of version {}, expected {}.",
+                next_index,
+                version,
+                num_values,
+            );
+            next_index += chunk.raw_values.len();
+            for (state_key, state_value) in chunk.raw_values {
+                let bytes = match state_value.maybe_bytes {
+                    Some(bytes) => bytes,
+                    None => continue,
+                };
+                match &state_key {
+                    StateKey::AccessPath(access_path) => {
+                        let path: Path = (&access_path.path).try_into()?;
+                        match path {
+                            Path::Code(_) => num_modules += 1,
+                            Path::Resource(struct_tag) => {
+                                let resource =
+                                    annotator.view_resource(&struct_tag, &bytes).with_context(
+                                        || format!("Failed to decode resource {:?}", state_key),
+                                    )?;
+                                writers.write_resource(
+                                    access_path.address.to_hex_literal(),
+                                    struct_tag,
+                                    move_struct_to_json(resource)?,
+                                )?;
+                            }
+                        }
+                    }
+                    StateKey::TableItem { handle, key } => {
+                        let table_info = match &indexer {
+                            Some(indexer) => indexer.get_table_info(*handle)?,
+                            None => None,
+                        };
+                        match table_info {
+                            Some(table_info) => {
+                                let key_value = annotator
+                                    .view_value(&table_info.key_type, key)
+                                    .and_then(move_value_to_json)
+                                    .with_context(|| {
+                                        format!("Failed to decode key of {:?}", state_key)
+                                    })?;
+                                let value = annotator
+                                    .view_value(&table_info.value_type, &bytes)
+                                    .and_then(move_value_to_json)
+                                    .with_context(|| {
+                                        format!("Failed to decode value of {:?}", state_key)
+                                    })?;
+                                writers.write_table_item(
+                                    *handle,
+                                    table_info.key_type.to_string(),
+                                    table_info.value_type.to_string(),
+                                    key_value,
+                                    value,
+                                )?;
+                            }
+                            None => writers.write_unknown_table_item(*handle, key, &bytes)?,
+                        }
+                    }
+                    StateKey::Raw(_) => (),
+                }
+            }
+            ensure!(
+                is_last_chunk == (next_index >= num_values),
+                "Got {} state values at version {}, expected {}.",
+                next_index,
+                version,
+                num_values,
+            );
+        }
+        writers.flush()?;
+
+        println!(
+            "Exported state at version {}: {} resources, {} table items ({} undecoded) to {} \
+             files, {} modules skipped.",
+            version,
+            writers.num_resources,
+            writers.num_table_items,
+            writers.num_unknown_table_items,
+            writers.files.len(),
+            num_modules,
+        );
+        Ok(())
+    }
+}
+
+/// The CSV files being written, by path, of which at most `max_open_files` are open at a time.
+struct CsvWriters {
+    resources_dir: PathBuf,
+    table_items_dir: PathBuf,
+    /// The files created so far, which are appended to when reopened.
+    files: HashSet<PathBuf>,
+    /// The open files, with the time they were last written at, counted in writes.
+    writers: HashMap<PathBuf, (u64, csv::Writer<File>)>,
+    max_open_files: usize,
+    num_writes: u64,
+    num_resources: usize,
+    num_table_items: usize,
+    num_unknown_table_items: usize,
+}
+
+impl CsvWriters {
+    fn new(output_dir: &FsPath, max_open_files: usize) -> Result<Self> {
+        let resources_dir = output_dir.join("resources");
+        let table_items_dir = output_dir.join("table_items");
+        fs::create_dir_all(&resources_dir)?;
+        fs::create_dir_all(&table_items_dir)?;
+        Ok(Self {
+            resources_dir,
+            table_items_dir,
+            files: HashSet::new(),
+            writers: HashMap::new(),
+            max_open_files,
+            num_writes: 0,
+            num_resources: 0,
+            num_table_items: 0,
+            num_unknown_table_items: 0,
+        })
+    }
+
+    fn write_resource(
+        &mut self,
+        address: String,
+        struct_tag: StructTag,
+        value: Value,
+    ) -> Result<()> {
+        let typ = struct_tag.to_string();
+        let path = self.resources_dir.join(file_name(&typ));
+        self.writer(path, &["address", "type", "value"])?
+            .write_record(&[address, typ, value.to_string()])?;
+        self.num_resources += 1;
+        Ok(())
+    }
+
+    fn write_table_item(
+        &mut self,
+        handle: TableHandle,
+        key_type: String,
+        value_type: String,
+        key: Value,
+        value: Value,
+    ) -> Result<()> {
+        let path = self.table_items_dir.join(file_name(&value_type));
+        self.writer(path, &["handle", "key_type", "value_type", "key", "value"])?
+            .write_record(&[
+                handle.0.to_string(),
+                key_type,
+                value_type,
+                key.to_string(),
+                value.to_string(),
+            ])?;
+        self.num_table_items += 1;
+        Ok(())
+    }
+
+    fn write_unknown_table_item(
+        &mut self,
+        handle: TableHandle,
+        key: &[u8],
+        value: &[u8],
+    ) -> Result<()> {
+        let path = self.table_items_dir.join("unknown.csv");
+        self.writer(path, &["handle", "key", "value"])?
+            .write_record(&[
+                handle.0.to_string(),
+                format!("0x{}", hex::encode(key)),
+                format!("0x{}", hex::encode(value)),
+            ])?;
+        self.num_table_items += 1;
+        self.num_unknown_table_items += 1;
+        Ok(())
+    }
+
+    /// Returns the writer of the file at `path`, creating it with `header` if needed, or
+    /// reopening it if it was closed to keep the number of open files down.
+    fn writer(&mut self, path: PathBuf, header: &[&str]) -> Result<&mut csv::Writer<File>> {
+        self.num_writes += 1;
+        if !self.writers.contains_key(&path) {
+            if self.writers.len() >= self.max_open_files {
+                self.close_least_recently_written()?;
+            }
+            let writer = if self.files.contains(&path) {
+                csv::Writer::from_writer(OpenOptions::new().append(true).open(&path)?)
+            } else {
+                let mut writer = csv::Writer::from_path(&path)?;
+                writer.write_record(header)?;
+                self.files.insert(path.clone());
+                writer
+            };
+            self.writers.insert(path.clone(), (self.num_writes, writer));
+        }
+        let (last_written, writer) = self.writers.get_mut(&path).expect("Inserted above.");
+        *last_written = self.num_writes;
+        Ok(writer)
+    }
+
+    fn close_least_recently_written(&mut self) -> Result<()> {
+        let path = self
+            .writers
+            .iter()
+            .min_by_key(|(_, (last_written, _))| *last_written)
+            .map(|(path, _)| path.clone());
+        if let Some(path) = path {
+            let (_, mut writer) = self.writers.remove(&path).expect("Found above.");
+            writer.flush()?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        for (_, writer) in self.writers.values_mut() {
+            writer.flush()?;
+        }
+        Ok(())
+    }
+}
+
+/// Names the file of a type, keeping only characters that are safe in file names.
+fn file_name(typ: &str) -> String {
+    let mut stem: String = typ
+        .replace("::", ".")
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if stem.len() > MAX_FILE_STEM_LEN {
+        stem.truncate(MAX_FILE_STEM_LEN);
+        let hash = HashValue::sha3_256_of(typ.as_bytes()).to_hex();
+        stem = format!("{}_{}", stem, &hash[..8]);
+    }
+    format!("{}.csv", stem)
+}
+
+/// Converts a value to JSON the way the API shows it: integers wider than 32 bits and bytes as
+/// strings, and `0x1::string::String` as a string.
+fn move_value_to_json(value: AnnotatedMoveValue) -> Result<Value> {
+    Ok(match value {
+        AnnotatedMoveValue::U8(v) => json!(v),
+        AnnotatedMoveValue::U64(v) => json!(v.to_string()),
+        AnnotatedMoveValue::U128(v) => json!(v.to_string()),
+        AnnotatedMoveValue::Bool(v) => json!(v),
+        AnnotatedMoveValue::Address(v) => json!(v.to_hex_literal()),
+        AnnotatedMoveValue::Vector(_, vals) => Value::Array(
+            vals.into_iter()
+                .map(move_value_to_json)
+                .collect::<Result<_>>()?,
+        ),
+        AnnotatedMoveValue::Bytes(v) => json!(format!("0x{}", hex::encode(v))),
+        AnnotatedMoveValue::Struct(v) => move_struct_to_json(v)?,
+    })
+}
+
+fn move_struct_to_json(value: AnnotatedMoveStruct) -> Result<Value> {
+    let is_utf8_string = value.type_.address == CORE_CODE_ADDRESS
+        && value.type_.module.as_str() == "string"
+        && value.type_.name.as_str() == "String";
+    if is_utf8_string {
+        if let Some((_, AnnotatedMoveValue::Bytes(bytes))) = value.value.into_iter().next() {
+            return Ok(json!(String::from_utf8(bytes)?));
+        }
+        return Err(format_err!(
+            "expect string::String, but failed to decode struct value"
+        ));
+    }
+
+    let mut fields = Map::new();
+    for (name, field) in value.value {
+        fields.insert(name.to_string(), move_value_to_json(field)?);
+    }
+    Ok(Value::Object(fields))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_temppath::TempPath;
+    use aptos_types::account_config::AccountResource;
+    use aptos_vm::AptosVM;
+    use executor_test_helpers::bootstrap_genesis;
+    use move_deps::{
+        move_binary_format::file_format::AbilitySet,
+        move_core_types::{
+            account_address::AccountAddress, identifier::Identifier, language_storage::TypeTag,
+            move_resource::MoveStructType,
+        },
+    };
+    use storage_interface::DbReaderWriter;
+
+    fn export(db_dir: &TempPath, chunk_size: usize, max_open_files: usize) -> Result<TempPath> {
+        let output_dir = TempPath::new();
+        Cmd {
+            db_dir: DbDir {
+                db_dir: db_dir.path().to_path_buf(),
+            },
+            version: None,
+            output_dir: output_dir.path().to_path_buf(),
+            chunk_size,
+            max_open_files,
+        }
+        .run()?;
+        Ok(output_dir)
+    }
+
+    /// Reads the header and the records of a CSV file.
+    fn read_csv(path: &FsPath) -> (Vec<String>, Vec<Vec<String>>) {
+        let mut reader = csv::Reader::from_path(path).unwrap();
+        let header = reader.headers().unwrap().iter().map(String::from).collect();
+        let records = reader
+            .records()
+            .map(|record| record.unwrap().iter().map(String::from).collect())
+            .collect();
+        (header, records)
+    }
+
+    /// Reads the CSV files of a directory, by file name.
+    fn read_csv_dir(dir: &FsPath) -> HashMap<String, (Vec<String>, Vec<Vec<String>>)> {
+        fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| {
+                let path = entry.unwrap().path();
+                let name = path.file_name().unwrap().to_str().unwrap().to_string();
+                (name, read_csv(&path))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_export_state() {
+        let db_dir = TempPath::new();
+        {
+            let (_db, db_rw) = DbReaderWriter::wrap(AptosDB::new_for_test_with_indexer(&db_dir));
+            bootstrap_genesis::<AptosVM>(&db_rw, &vm_genesis::test_genesis_transaction()).unwrap();
+        }
+
+        export(&db_dir, 0, 256).unwrap_err();
+        export(&db_dir, 10000, 0).unwrap_err();
+        let output_dir = export(&db_dir, 10000, 256).unwrap();
+
+        // Each resource is in the file named after its type.
+        let resources = read_csv_dir(&output_dir.path().join("resources"));
+        assert!(!resources.is_empty());
+        for (name, (header, records)) in &resources {
+            assert_eq!(header, &["address", "type", "value"]);
+            assert!(!records.is_empty());
+            for record in records {
+                assert_eq!(name, &file_name(&record[1]));
+                serde_json::from_str::<Value>(&record[2]).unwrap();
+            }
+        }
+        let (_, accounts) = &resources[&file_name(&AccountResource::struct_tag().to_string())];
+        let core_account = accounts.iter().find(|record| record[0] == "0x1").unwrap();
+        let value: Value = serde_json::from_str(&core_account[2]).unwrap();
+        assert_eq!(value["sequence_number"], json!("0"));
+
+        // Each table item is in the file named after its value type, unless it can't be decoded.
+        let table_items = read_csv_dir(&output_dir.path().join("table_items"));
+        for (name, (header, records)) in &table_items {
+            if name == "unknown.csv" {
+                assert_eq!(header, &["handle", "key", "value"]);
+                continue;
+            }
+            assert_eq!(
+                header,
+                &["handle", "key_type", "value_type", "key", "value"]
+            );
+            for record in records {
+                assert_eq!(name, &file_name(&record[2]));
+                serde_json::from_str::<Value>(&record[3]).unwrap();
+                serde_json::from_str::<Value>(&record[4]).unwrap();
+            }
+        }
+
+        // Reading the state in small chunks, or closing and reopening files to keep only one
+        // open at a time, exports the same.
+        for (chunk_size, max_open_files) in vec![(7, 256), (10000, 1)] {
+            let output_dir = export(&db_dir, chunk_size, max_open_files).unwrap();
+            assert_eq!(
+                read_csv_dir(&output_dir.path().join("resources")),
+                resources
+            );
+            assert_eq!(
+                read_csv_dir(&output_dir.path().join("table_items")),
+                table_items
+            );
+        }
+    }
+
+    #[test]
+    fn test_file_name() {
+        assert_eq!(
+            file_name("0x1::account::Account"),
+            "0x1.account.Account.csv"
+        );
+        assert_eq!(
+            file_name("0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>"),
+            "0x1.coin.CoinStore_0x1.aptos_coin.AptosCoin_.csv"
+        );
+        assert_eq!(file_name("vector<u8>"), "vector_u8_.csv");
+
+        // Long names are truncated, and kept apart by a hash of the type.
+        let long_type = |name: &str| format!("0x1::m::S<{}, {}>", "u64, ".repeat(50), name);
+        let (a, b) = (file_name(&long_type("A")), file_name(&long_type("B")));
+        assert_eq!(a.len(), MAX_FILE_STEM_LEN + 1 + 8 + ".csv".len());
+        assert_eq!(a[..MAX_FILE_STEM_LEN], b[..MAX_FILE_STEM_LEN]);
+        assert_ne!(a, b);
+    }
+
+    fn annotated_move_struct(
+        module: &str,
+        name: &str,
+        value: Vec<(&str, AnnotatedMoveValue)>,
+    ) -> AnnotatedMoveStruct {
+        AnnotatedMoveStruct {
+            abilities: AbilitySet::EMPTY,
+            type_: StructTag {
+                address: CORE_CODE_ADDRESS,
+                module: Identifier::new(module).unwrap(),
+                name: Identifier::new(name).unwrap(),
+                type_params: vec![],
+            },
+            value: value
+                .into_iter()
+                .map(|(field, value)| (Identifier::new(field).unwrap(), value))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_move_value_to_json() {
+        use AnnotatedMoveValue::*;
+
+        let string = |bytes: &[u8]| {
+            Struct(annotated_move_struct(
+                "string",
+                "String",
+                vec![("bytes", Bytes(bytes.to_vec()))],
+            ))
+        };
+        let value = Struct(annotated_move_struct(
+            "m",
+            "Values",
+            vec![
+                ("u8", U8(7)),
+                ("u64", U64(u64::max_value())),
+                ("u128", U128(7)),
+                ("bool", Bool(true)),
+                (
+                    "address",
+                    Address(AccountAddress::from_hex_literal("0xdd").unwrap()),
+                ),
+                ("vector", Vector(TypeTag::U128, vec![U128(1), U128(2)])),
+                ("bytes", Bytes(vec![9, 10])),
+                ("string", string(b"hello")),
+                (
+                    "nested",
+                    Struct(annotated_move_struct(
+                        "m",
+                        "Nested",
+                        vec![("b", Bool(false))],
+                    )),
+                ),
+            ],
+        ));
+        assert_eq!(
+            move_value_to_json(value).unwrap(),
+            json!({
+                "u8": 7,
+                "u64": "18446744073709551615",
+                "u128": "7",
+                "bool": true,
+                "address": "0xdd",
+                "vector": ["1", "2"],
+                "bytes": "0x090a",
+                "string": "hello",
+                "nested": { "b": false },
+            })
+        );
+
+        move_value_to_json(string(&[0xff])).unwrap_err();
+    }
+}
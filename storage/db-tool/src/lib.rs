@@ -0,0 +1,70 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! Offline inspection and consistency checks of an AptosDB directory.
+//!
+//! The DBs are opened read-only through `aptosdb`'s `DebugHandler`, so that a DB that fails to
+//! open as usual can still be looked into.
+
+mod check_stale_node_index;
+mod dump;
+mod export_state;
+mod print_progress;
+mod verify_roots;
+
+use anyhow::Result;
+use aptosdb::debug_handler::DebugHandler;
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+#[structopt(
+    name = "db-tool",
+    about = "Inspect an AptosDB directory and check its consistency."
+)]
+pub enum Cmd {
+    /// Print the latest version, the state snapshots and how far each store has been pruned.
+    PrintProgress(print_progress::Cmd),
+    /// Dump the entries of a schema in a range of versions as JSON, one entry per line.
+    Dump(dump::Cmd),
+    /// Verify that the accumulator roots and the state roots match the transaction infos in a
+    /// range of versions.
+    VerifyRoots(verify_roots::Cmd),
+    /// Check that the stale node index in a range of versions refers to existing nodes.
+    CheckStaleNodeIndex(check_stale_node_index::Cmd),
+    /// Export the resources and table items of a state snapshot as CSV files, one per type.
+    ExportState(export_state::Cmd),
+}
+
+impl Cmd {
+    pub fn run(self) -> Result<()> {
+        match self {
+            Cmd::PrintProgress(cmd) => cmd.run(),
+            Cmd::Dump(cmd) => cmd.run(),
+            Cmd::VerifyRoots(cmd) => cmd.run(),
+            Cmd::CheckStaleNodeIndex(cmd) => cmd.run(),
+            Cmd::ExportState(cmd) => cmd.run(),
+        }
+    }
+}
+
+#[derive(StructOpt)]
+struct DbDir {
+    /// The directory holding the ledger DB and the state merkle DB.
+    #[structopt(long, parse(from_os_str))]
+    db_dir: PathBuf,
+}
+
+impl DbDir {
+    fn open(&self) -> Result<DebugHandler> {
+        DebugHandler::open(&self.db_dir, true /* readonly */)
+    }
+}
+
+/// Returns the first key of a store, which tells how far it has been pruned.
+fn first_key<K, V>(mut iter: impl Iterator<Item = Result<(K, V)>>) -> Result<Option<K>> {
+    Ok(iter.next().transpose()?.map(|(key, _)| key))
+}
+
+#[cfg(test)]
+mod tests;
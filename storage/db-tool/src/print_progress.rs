@@ -0,0 +1,94 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{first_key, DbDir};
+use anyhow::Result;
+use aptos_types::transaction::Version;
+use serde::Serialize;
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+pub struct Cmd {
+    #[structopt(flatten)]
+    db_dir: DbDir,
+}
+
+/// The versions the DB has data for, store by store.
+#[derive(Debug, Serialize)]
+struct Progress {
+    ledger: LedgerProgress,
+    state: StateProgress,
+    /// The first version each of the stores pruned by the ledger pruner still has.
+    ledger_pruner: LedgerPrunerProgress,
+}
+
+#[derive(Debug, Serialize)]
+struct LedgerProgress {
+    /// The latest version with a transaction info, which can be ahead of the latest ledger info
+    /// while the node is syncing.
+    latest_version: Option<Version>,
+    latest_ledger_info_version: Option<Version>,
+    latest_ledger_info_epoch: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+struct StateProgress {
+    latest_snapshot_version: Option<Version>,
+    /// The first version whose state tree is still fully readable, as tracked by the state pruner.
+    min_readable_version: Version,
+}
+
+#[derive(Debug, Serialize)]
+struct LedgerPrunerProgress {
+    transaction: Option<Version>,
+    transaction_info: Option<Version>,
+    event: Option<Version>,
+    write_set: Option<Version>,
+}
+
+impl Cmd {
+    pub fn run(self) -> Result<()> {
+        let handler = self.db_dir.open()?;
+
+        let latest_ledger_info = handler.get_latest_ledger_info_option();
+        let ledger = LedgerProgress {
+            latest_version: handler
+                .get_latest_transaction_info_option()?
+                .map(|(version, _)| version),
+            latest_ledger_info_version: latest_ledger_info
+                .as_ref()
+                .map(|li| li.ledger_info().version()),
+            latest_ledger_info_epoch: latest_ledger_info
+                .as_ref()
+                .map(|li| li.ledger_info().epoch()),
+        };
+
+        // Same as what the state pruner starts from: nodes stale since a version are pruned
+        // together with their index, so the first index left tells how far it got.
+        let min_readable_version = handler
+            .get_stale_node_index_iter(0)?
+            .next()
+            .transpose()?
+            .map_or(0, |index| index.stale_since_version.saturating_sub(1));
+        let state = StateProgress {
+            latest_snapshot_version: handler
+                .get_state_snapshot_version_before(Version::max_value())?,
+            min_readable_version,
+        };
+
+        let ledger_pruner = LedgerPrunerProgress {
+            transaction: first_key(handler.get_transaction_iter(0)?)?,
+            transaction_info: first_key(handler.get_transaction_info_iter(0)?)?,
+            event: first_key(handler.get_event_iter(0)?)?.map(|(version, _index)| version),
+            write_set: first_key(handler.get_write_set_iter(0)?)?,
+        };
+
+        let progress = Progress {
+            ledger,
+            state,
+            ledger_pruner,
+        };
+        println!("{}", serde_json::to_string_pretty(&progress)?);
+        Ok(())
+    }
+}
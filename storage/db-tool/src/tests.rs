@@ -0,0 +1,109 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::Cmd;
+use anyhow::Result;
+use aptos_jellyfish_merkle::{node_type::NodeKey, StaleNodeIndex};
+use aptos_proptest_helpers::ValueGenerator;
+use aptos_temppath::TempPath;
+use aptos_types::{nibble::nibble_path::NibblePath, transaction::Version};
+use aptosdb::{
+    debug_handler::DebugHandler,
+    test_helper::{arb_blocks_to_commit, update_in_memory_state},
+    AptosDB,
+};
+use storage_interface::DbWriter;
+use structopt::StructOpt;
+
+/// Creates a DB with random content, and closes it so that the commands can open it.
+fn tmp_db_with_random_content() -> TempPath {
+    let tmpdir = TempPath::new();
+    let db = AptosDB::new_for_test(&tmpdir);
+    let mut cur_ver: Version = 0;
+    let mut in_memory_state = db.buffered_state().lock().current_state().clone();
+    let blocks = ValueGenerator::new().generate(arb_blocks_to_commit());
+    for (txns_to_commit, ledger_info_with_sigs) in &blocks {
+        update_in_memory_state(&mut in_memory_state, txns_to_commit.as_slice());
+        db.save_transactions(
+            txns_to_commit,
+            cur_ver, /* first_version */
+            cur_ver.checked_sub(1),
+            Some(ledger_info_with_sigs),
+            true, /* sync_commit */
+            in_memory_state.clone(),
+        )
+        .unwrap();
+        cur_ver += txns_to_commit.len() as u64;
+    }
+    tmpdir
+}
+
+fn run(db_dir: &TempPath, args: &[&str]) -> Result<()> {
+    let db_dir = db_dir.path().to_str().unwrap();
+    Cmd::from_iter(
+        ["db-tool", args[0], "--db-dir", db_dir]
+            .iter()
+            .chain(&args[1..]),
+    )
+    .run()
+}
+
+fn latest_snapshot_version(db_dir: &TempPath) -> Version {
+    DebugHandler::open(db_dir.path(), true /* readonly */)
+        .unwrap()
+        .get_state_snapshot_version_before(Version::max_value())
+        .unwrap()
+        .unwrap()
+}
+
+#[test]
+fn test_verify_roots() {
+    let db_dir = tmp_db_with_random_content();
+    run(&db_dir, &["verify-roots", "--start-version", "0"]).unwrap();
+
+    // Replace the root of the latest snapshot with another node of the tree.
+    let version = latest_snapshot_version(&db_dir);
+    {
+        let handler = DebugHandler::open(db_dir.path(), false /* readonly */).unwrap();
+        let root_key = NodeKey::new_empty_path(version);
+        let root = handler.get_jmt_node(&root_key).unwrap().unwrap();
+        let other_node = handler
+            .get_jmt_node_iter(0)
+            .unwrap()
+            .map(|res| res.unwrap().1)
+            .find(|node| node.hash() != root.hash())
+            .unwrap();
+        handler.put_jmt_node(&root_key, &other_node).unwrap();
+    }
+
+    let err = run(&db_dir, &["verify-roots", "--start-version", "0"]).unwrap_err();
+    assert!(err.to_string().contains("State root mismatch"), "{}", err);
+}
+
+#[test]
+fn test_check_stale_node_index() {
+    let db_dir = tmp_db_with_random_content();
+    run(&db_dir, &["check-stale-node-index"]).unwrap();
+
+    // An index entry whose node doesn't exist.
+    let version = latest_snapshot_version(&db_dir);
+    DebugHandler::open(db_dir.path(), false /* readonly */)
+        .unwrap()
+        .put_stale_node_index(&StaleNodeIndex {
+            stale_since_version: version,
+            node_key: NodeKey::new(0, NibblePath::new_even(vec![0xff; 32])),
+        })
+        .unwrap();
+
+    run(&db_dir, &["check-stale-node-index"]).unwrap_err();
+    // The entry is out of the range checked.
+    run(
+        &db_dir,
+        &[
+            "check-stale-node-index",
+            "--end-version",
+            &version.to_string(),
+        ],
+    )
+    .unwrap();
+}
@@ -0,0 +1,204 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{first_key, DbDir};
+use anyhow::{ensure, format_err, Result};
+use aptos_crypto::hash::{CryptoHash, EventAccumulatorHasher};
+use aptos_types::{
+    proof::accumulator::InMemoryAccumulator,
+    transaction::{TransactionInfo, Version},
+};
+use aptosdb::debug_handler::DebugHandler;
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+pub struct Cmd {
+    #[structopt(flatten)]
+    db_dir: DbDir,
+
+    #[structopt(long)]
+    start_version: Version,
+
+    /// Exclusive. Defaults to one after the latest version.
+    #[structopt(long)]
+    end_version: Option<Version>,
+}
+
+impl Cmd {
+    /// Checks each version of the range against its transaction info: the transaction, events and
+    /// write set hash to what it says, the state tree root matches its state checkpoint hash, and
+    /// it's a leaf of the transaction accumulator. Then checks the accumulator roots against the
+    /// ledger infos in the range. Data already pruned is skipped.
+    pub fn run(self) -> Result<()> {
+        let handler = self.db_dir.open()?;
+        let latest_version = handler
+            .get_latest_transaction_info_option()?
+            .map(|(version, _)| version)
+            .ok_or_else(|| format_err!("DB has no transactions."))?;
+        let end_version = self.end_version.unwrap_or(latest_version + 1);
+        ensure!(
+            self.start_version < end_version && end_version <= latest_version + 1,
+            "Invalid range [{}, {}), the latest version is {}",
+            self.start_version,
+            end_version,
+            latest_version,
+        );
+
+        let first_txn_info_version = first_key(handler.get_transaction_info_iter(0)?)?.unwrap_or(0);
+        ensure!(
+            self.start_version >= first_txn_info_version,
+            "Transaction infos before version {} have been pruned.",
+            first_txn_info_version,
+        );
+        let first_txn_version = first_key(handler.get_transaction_iter(0)?)?.unwrap_or(0);
+        let first_event_version =
+            first_key(handler.get_event_iter(0)?)?.map_or(0, |(version, _)| version);
+        let first_write_set_version = first_key(handler.get_write_set_iter(0)?)?.unwrap_or(0);
+
+        // Proofs are towards the root of the last version of the range.
+        let ledger_version = end_version - 1;
+        let root_hash = handler.get_accumulator_root_hash(ledger_version)?;
+        let mut num_state_roots = 0;
+        for version in self.start_version..end_version {
+            let txn_info = handler.get_transaction_info(version)?;
+            handler
+                .get_transaction_proof(version, ledger_version)?
+                .verify(root_hash, txn_info.hash(), version)
+                .map_err(|err| {
+                    format_err!(
+                        "Transaction info at version {} not in the accumulator: {}",
+                        version,
+                        err
+                    )
+                })?;
+            if version >= first_txn_version {
+                verify_transaction(&handler, version, &txn_info)?;
+            }
+            if version >= first_event_version {
+                verify_events(&handler, version, &txn_info)?;
+            }
+            if version >= first_write_set_version {
+                verify_write_set(&handler, version, &txn_info)?;
+            }
+            if verify_state_root(&handler, version, &txn_info)? {
+                num_state_roots += 1;
+            }
+        }
+
+        let num_ledger_infos = verify_ledger_infos(&handler, self.start_version, end_version)?;
+        println!(
+            "Verified versions [{}, {}): {} state roots and {} ledger infos checked.",
+            self.start_version, end_version, num_state_roots, num_ledger_infos,
+        );
+        Ok(())
+    }
+}
+
+fn verify_transaction(
+    handler: &DebugHandler,
+    version: Version,
+    txn_info: &TransactionInfo,
+) -> Result<()> {
+    let txn = handler.get_transaction(version)?;
+    ensure!(
+        txn.hash() == txn_info.transaction_hash(),
+        "Transaction hash mismatch at version {}: {} in the transaction info, {} computed.",
+        version,
+        txn_info.transaction_hash(),
+        txn.hash(),
+    );
+    Ok(())
+}
+
+fn verify_events(
+    handler: &DebugHandler,
+    version: Version,
+    txn_info: &TransactionInfo,
+) -> Result<()> {
+    let event_hashes: Vec<_> = handler
+        .get_events_by_version(version)?
+        .iter()
+        .map(CryptoHash::hash)
+        .collect();
+    let event_root_hash =
+        InMemoryAccumulator::<EventAccumulatorHasher>::from_leaves(&event_hashes).root_hash();
+    ensure!(
+        event_root_hash == txn_info.event_root_hash(),
+        "Event root hash mismatch at version {}: {} in the transaction info, {} computed.",
+        version,
+        txn_info.event_root_hash(),
+        event_root_hash,
+    );
+    Ok(())
+}
+
+fn verify_write_set(
+    handler: &DebugHandler,
+    version: Version,
+    txn_info: &TransactionInfo,
+) -> Result<()> {
+    let write_set = handler.get_write_set(version)?;
+    ensure!(
+        CryptoHash::hash(&write_set) == txn_info.state_change_hash(),
+        "State change hash mismatch at version {}: {} in the transaction info, {} computed.",
+        version,
+        txn_info.state_change_hash(),
+        CryptoHash::hash(&write_set),
+    );
+    Ok(())
+}
+
+/// Returns whether there was a state tree root to check, which is only the case for versions
+/// with a state checkpoint that was persisted as a snapshot and not pruned since.
+fn verify_state_root(
+    handler: &DebugHandler,
+    version: Version,
+    txn_info: &TransactionInfo,
+) -> Result<bool> {
+    let state_checkpoint_hash = match txn_info.state_checkpoint_hash() {
+        Some(hash) => hash,
+        None => return Ok(false),
+    };
+    if handler.get_state_snapshot_version_before(version + 1)? != Some(version) {
+        return Ok(false);
+    }
+    let root_hash = handler.get_state_root_hash(version)?;
+    ensure!(
+        root_hash == state_checkpoint_hash,
+        "State root mismatch at version {}: {} in the transaction info, {} in the state tree.",
+        version,
+        state_checkpoint_hash,
+        root_hash,
+    );
+    Ok(true)
+}
+
+/// Checks the transaction accumulator root of each ledger info in the range, and returns how
+/// many there were.
+fn verify_ledger_infos(
+    handler: &DebugHandler,
+    start_version: Version,
+    end_version: Version,
+) -> Result<usize> {
+    let mut num_ledger_infos = 0;
+    for res in handler.get_ledger_info_iter()? {
+        let ledger_info_with_sigs = res?;
+        let ledger_info = ledger_info_with_sigs.ledger_info();
+        let version = ledger_info.version();
+        if version < start_version || version >= end_version {
+            continue;
+        }
+        let root_hash = handler.get_accumulator_root_hash(version)?;
+        ensure!(
+            root_hash == ledger_info.transaction_accumulator_hash(),
+            "Accumulator root mismatch at version {} (epoch {}): {} in the ledger info, {} in \
+             the accumulator.",
+            version,
+            ledger_info.epoch(),
+            ledger_info.transaction_accumulator_hash(),
+            root_hash,
+        );
+        num_ledger_infos += 1;
+    }
+    Ok(num_ledger_infos)
+}
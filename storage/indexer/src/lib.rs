@@ -5,7 +5,8 @@ mod db;
 mod metadata;
 mod schema;
 
-use crate::db::INDEX_DB_NAME;
+pub use crate::db::INDEX_DB_NAME;
+
 use crate::metadata::{Metadata, MetadataTag};
 use crate::schema::column_families;
 use crate::schema::indexer_metadata::IndexerMetadataSchema;
@@ -25,7 +26,7 @@ use move_deps::move_core_types::identifier::IdentStr;
 use move_deps::move_core_types::language_storage::{StructTag, TypeTag};
 use move_deps::move_resource_viewer::{AnnotatedMoveValue, MoveValueAnnotator};
 use schemadb::db_options::gen_rocksdb_options;
-use schemadb::{SchemaBatch, DB};
+use schemadb::{ReadOptions, SchemaBatch, DB};
 use std::collections::HashMap;
 use std::convert::TryInto;
 use std::sync::atomic::Ordering;
@@ -55,6 +56,27 @@ impl Indexer {
             &gen_rocksdb_options(&rocksdb_config, false),
         )?;
 
+        Self::new(db)
+    }
+
+    /// Opens the indexer DB read-only, failing if it doesn't exist.
+    pub fn open_readonly(
+        db_root_path: impl AsRef<std::path::Path>,
+        rocksdb_config: RocksdbConfig,
+    ) -> Result<Self> {
+        let db_path = db_root_path.as_ref().join(INDEX_DB_NAME);
+
+        let db = DB::open_cf_readonly(
+            &gen_rocksdb_options(&rocksdb_config, true),
+            db_path,
+            "index_db_ro",
+            column_families(),
+        )?;
+
+        Self::new(db)
+    }
+
+    fn new(db: DB) -> Result<Self> {
         let next_version = db
             .get::<IndexerMetadataSchema>(&MetadataTag::LatestVersion)?
             .map_or(0, |meta| match meta {
@@ -67,6 +89,12 @@ impl Indexer {
         })
     }
 
+    /// Creates a checkpoint of the indexer DB under `db_root_path`, where `open` would find it.
+    pub fn create_checkpoint(&self, db_root_path: impl AsRef<std::path::Path>) -> Result<()> {
+        self.db
+            .create_checkpoint(db_root_path.as_ref().join(INDEX_DB_NAME))
+    }
+
     pub fn index(
         &self,
         db_reader: Arc<dyn DbReader>,
@@ -130,9 +158,47 @@ impl Indexer {
         self.next_version.load(Ordering::Relaxed)
     }
 
+    /// Returns the last version indexed, if any.
+    pub fn latest_version(&self) -> Option<Version> {
+        self.next_version().checked_sub(1)
+    }
+
     pub fn get_table_info(&self, handle: TableHandle) -> Result<Option<TableInfo>> {
         self.db.get::<TableInfoSchema>(&handle)
     }
+
+    /// Gets an iterator over the info of all tables indexed, ordered by handle.
+    pub fn get_table_info_iter(
+        &self,
+    ) -> Result<impl Iterator<Item = Result<(TableHandle, TableInfo)>> + '_> {
+        let mut iter = self.db.iter::<TableInfoSchema>(ReadOptions::default())?;
+        iter.seek_to_first();
+        Ok(iter)
+    }
+
+    /// Saves table infos restored from a backup.
+    ///
+    /// Call `finish_restore` once all of them are saved, so indexing resumes after the backup.
+    pub fn save_table_infos(&self, table_infos: &[(TableHandle, TableInfo)]) -> Result<()> {
+        let mut batch = SchemaBatch::new();
+        for (handle, info) in table_infos {
+            batch.put::<TableInfoSchema>(handle, info)?;
+        }
+        self.db.write_schemas(batch)
+    }
+
+    /// Marks all versions up to `latest_version` as indexed, after restoring their table infos.
+    pub fn finish_restore(&self, latest_version: Version) -> Result<()> {
+        let mut batch = SchemaBatch::new();
+        batch.put::<IndexerMetadataSchema>(
+            &MetadataTag::LatestVersion,
+            &Metadata::LatestVersion(latest_version),
+        )?;
+        self.db.write_schemas(batch)?;
+        self.next_version
+            .store(latest_version + 1, Ordering::Relaxed);
+        Ok(())
+    }
 }
 
 struct TableInfoParser<'a> {
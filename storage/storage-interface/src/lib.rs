@@ -318,6 +318,27 @@ pub trait DbReader: Send + Sync {
         unimplemented!()
     }
 
+    /// Returns the list of transactions that affected an account with `address`, whether or not
+    /// they were sent by it, starting at version `start_version`. Will return no more than `limit`
+    /// transactions. Will ignore transactions with `txn.version > ledger_version`. Optionally
+    /// fetch events for each transaction when `include_events` is `true`. Only available with the
+    /// affected account index enabled.
+    fn get_affected_account_transactions(
+        &self,
+        address: AccountAddress,
+        start_version: Version,
+        limit: u64,
+        include_events: bool,
+        ledger_version: Version,
+    ) -> Result<Vec<TransactionWithProof>> {
+        unimplemented!()
+    }
+
+    /// Returns whether the affected account index has been enabled or not
+    fn affected_account_index_enabled(&self) -> bool {
+        unimplemented!()
+    }
+
     /// Returns proof of new state for a given ledger info with signatures relative to version known
     /// to client
     fn get_state_proof_with_ledger_info(
@@ -458,6 +479,18 @@ pub trait DbReader: Send + Sync {
         unimplemented!()
     }
 
+    /// Get the oldest version at which state can still be read, state of any
+    /// version before it has been pruned.
+    fn get_min_readable_state_version(&self) -> Result<Version> {
+        unimplemented!()
+    }
+
+    /// Get the oldest version of which all ledger data (the transaction, its info, events and
+    /// write set) can still be read, ledger data of any version before it has been pruned.
+    fn get_min_readable_ledger_version(&self) -> Result<Version> {
+        unimplemented!()
+    }
+
     /// Get table info from the internal indexer.
     fn get_table_info(&self, handle: TableHandle) -> Result<TableInfo> {
         unimplemented!()
@@ -467,6 +500,24 @@ pub trait DbReader: Send + Sync {
     fn indexer_enabled(&self) -> bool {
         unimplemented!()
     }
+
+    /// Returns up to `limit` versions at which `state_key` was changed, starting from
+    /// `start_version` and up to `ledger_version`, with the value the key was set to at each of
+    /// them. Only available with the state key history enabled.
+    fn get_state_value_history(
+        &self,
+        state_key: &StateKey,
+        start_version: Version,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<Vec<(Version, StateValue)>> {
+        unimplemented!()
+    }
+
+    /// Returns whether the state key history has been enabled or not
+    fn state_key_history_enabled(&self) -> bool {
+        unimplemented!()
+    }
 }
 
 impl MoveStorage for &dyn DbReader {
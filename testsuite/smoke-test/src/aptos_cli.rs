@@ -6,11 +6,14 @@ use crate::test_utils::reconfig;
 use aptos::common::types::{GasOptions, DEFAULT_GAS_UNIT_PRICE, DEFAULT_MAX_GAS};
 use aptos::{account::create::DEFAULT_FUNDED_COINS, test::CliTestFramework};
 use aptos_crypto::ed25519::Ed25519PrivateKey;
-use aptos_crypto::{bls12381, x25519};
+use aptos_crypto::{bls12381, x25519, PrivateKey};
 use aptos_genesis::config::HostAndPort;
 use aptos_keygen::KeyGen;
 use aptos_rest_client::Transaction;
+use aptos_temppath::TempPath;
 use aptos_types::network_address::DnsName;
+use aptos_types::transaction::{authenticator::TransactionAuthenticator, SignedTransaction};
+use cached_framework_packages::aptos_stdlib;
 use forge::{NodeExt, Swarm};
 use std::convert::TryFrom;
 use std::sync::Arc;
@@ -68,6 +71,142 @@ async fn test_account_flow() {
     );
 }
 
+#[tokio::test]
+async fn test_offline_transaction_flow() {
+    let (_swarm, cli, _faucet) = SwarmBuilder::new_local(1)
+        .with_aptos()
+        .build_with_cli(2)
+        .await;
+    let temp_dir = TempPath::new();
+    temp_dir.create_as_dir().unwrap();
+    let unsigned_txn_file = temp_dir.path().join("transfer.txn");
+    let signed_txn_file = temp_dir.path().join("transfer.signed.txn");
+    let transfer_amount = 100;
+
+    let built = cli
+        .build_transfer_coins(cli.account_id(0), 1, transfer_amount, &unsigned_txn_file)
+        .await
+        .unwrap();
+    assert_eq!(built.sender, cli.account_id(0));
+    assert!(built.summary_file.exists());
+
+    // Only the sender can sign it
+    assert!(cli
+        .sign_transaction(1, &unsigned_txn_file, &signed_txn_file)
+        .await
+        .is_err());
+    let signed = cli
+        .sign_transaction(0, &unsigned_txn_file, &signed_txn_file)
+        .await
+        .unwrap();
+
+    let signed_txn: SignedTransaction =
+        bcs::from_bytes(&std::fs::read(&signed_txn_file).unwrap()).unwrap();
+    assert_eq!(signed_txn.sender(), cli.account_id(0));
+    assert_eq!(signed_txn.sequence_number(), built.sequence_number);
+    assert_eq!(signed_txn.clone().committed_hash(), signed.hash);
+    assert_eq!(
+        signed_txn.payload(),
+        &aptos_stdlib::aptos_coin_transfer(cli.account_id(1), transfer_amount)
+    );
+    match signed_txn.authenticator() {
+        TransactionAuthenticator::Ed25519 { public_key, .. } => {
+            assert_eq!(public_key, cli.private_key(0).public_key())
+        }
+        authenticator => panic!("Unexpected authenticator {:?}", authenticator),
+    }
+    signed_txn.check_signature().unwrap();
+
+    cli.submit_transaction(&signed_txn_file).await.unwrap();
+    assert_eq!(
+        DEFAULT_FUNDED_COINS + transfer_amount,
+        cli.wait_for_balance(1, DEFAULT_FUNDED_COINS + transfer_amount)
+            .await
+            .unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_multisig_transaction_flow() {
+    let (_swarm, mut cli, _faucet) = SwarmBuilder::new_local(1)
+        .with_aptos()
+        .build_with_cli(2)
+        .await;
+    let mut keygen = KeyGen::from_os_rng();
+    let owners: Vec<_> = (0..3)
+        .map(|_| cli.add_private_key(keygen.generate_ed25519_private_key()))
+        .collect();
+    let temp_dir = TempPath::new();
+    temp_dir.create_as_dir().unwrap();
+    let multisig_public_key_file = temp_dir.path().join("multisig.pub");
+    let unsigned_txn_file = temp_dir.path().join("transfer.txn");
+    let signed_txn_file = temp_dir.path().join("transfer.signed.txn");
+    let transfer_amount = 100;
+
+    let multisig = cli
+        .create_multisig_account(0, &owners, 2, &multisig_public_key_file)
+        .await
+        .unwrap();
+    assert_eq!(multisig.threshold, 2);
+    assert_eq!(multisig.num_public_keys, 3);
+    cli.fund_address(multisig.account, None).await.unwrap();
+
+    cli.build_transfer_coins(multisig.account, 1, transfer_amount, &unsigned_txn_file)
+        .await
+        .unwrap();
+    let mut partial_signature_files = vec![];
+    for owner in [owners[0], owners[2]] {
+        let partial_signature_file = temp_dir.path().join(format!("{}.sig", owner));
+        let partial_signature = cli
+            .sign_multisig_transaction(
+                owner,
+                &unsigned_txn_file,
+                &multisig_public_key_file,
+                &partial_signature_file,
+            )
+            .await
+            .unwrap();
+        assert_eq!(partial_signature.sender, multisig.account);
+        partial_signature_files.push(partial_signature_file);
+    }
+
+    // One signature isn't enough
+    assert!(cli
+        .combine_multisig_transaction(
+            &unsigned_txn_file,
+            &multisig_public_key_file,
+            partial_signature_files[..1].to_vec(),
+            &signed_txn_file,
+        )
+        .await
+        .is_err());
+    let signed = cli
+        .combine_multisig_transaction(
+            &unsigned_txn_file,
+            &multisig_public_key_file,
+            partial_signature_files,
+            &signed_txn_file,
+        )
+        .await
+        .unwrap();
+    assert_eq!(signed.sender, multisig.account);
+
+    let signed_txn: SignedTransaction =
+        bcs::from_bytes(&std::fs::read(&signed_txn_file).unwrap()).unwrap();
+    assert!(matches!(
+        signed_txn.authenticator(),
+        TransactionAuthenticator::MultiEd25519 { .. }
+    ));
+
+    cli.submit_transaction(&signed_txn_file).await.unwrap();
+    assert_eq!(
+        DEFAULT_FUNDED_COINS + transfer_amount,
+        cli.wait_for_balance(1, DEFAULT_FUNDED_COINS + transfer_amount)
+            .await
+            .unwrap()
+    );
+}
+
 #[tokio::test]
 async fn test_show_validator_set() {
     let (swarm, cli, _faucet) = SwarmBuilder::new_local(1)